@@ -127,6 +127,8 @@ async fn handle_check_existence(msg: &JsValue) -> Result<JsValue, String> {
             map
         },
         pagination: None,
+        sort: None,
+        facets: Default::default(),
     };
     let resp = req_post_json_with_headers(&log, &base_url, &headers, req).await?;
     let rows = match resp.rows {
@@ -1,17 +1,28 @@
+export * from "./sub/BuiltinGenerator.ts";
+export * from "./sub/BuiltinGeneratorVideoWebm.ts";
+export * from "./sub/CalendarPeriod.ts";
 export * from "./sub/Chain.ts";
 export * from "./sub/ChainHead.ts";
 export * from "./sub/ChainRoot.ts";
+export * from "./sub/ChartType.ts";
 export * from "./sub/ClientViewParam.ts";
+export * from "./sub/CommandGenerator.ts";
 export * from "./sub/Config.ts";
 export * from "./sub/ConfigIamGrants.ts";
 export * from "./sub/ConfigIamGrantsLimited.ts";
+export * from "./sub/DataRowsBulkAction.ts";
+export * from "./sub/DataRowsBulkActionForm.ts";
 export * from "./sub/DataRowsLayout.ts";
+export * from "./sub/DataRowsLayoutCalendar.ts";
 export * from "./sub/DataRowsLayoutTable.ts";
+export * from "./sub/DataRowsLayoutTimeline.ts";
 export * from "./sub/DataRowsLayoutUnaligned.ts";
+export * from "./sub/DataRowsSelection.ts";
 export * from "./sub/Direction.ts";
 export * from "./sub/FdapConfig.ts";
 export * from "./sub/FieldOrLiteral.ts";
 export * from "./sub/FieldOrLiteralString.ts";
+export * from "./sub/FileStorageConfig.ts";
 export * from "./sub/FilterExpr.ts";
 export * from "./sub/FilterExprExistance.ts";
 export * from "./sub/FilterExprExistsType.ts";
@@ -33,6 +44,9 @@ export * from "./sub/FormFieldType.ts";
 export * from "./sub/FormId.ts";
 export * from "./sub/FormLink.ts";
 export * from "./sub/FormOutput.ts";
+export * from "./sub/GeneratorConfig.ts";
+export * from "./sub/GeneratorKind.ts";
+export * from "./sub/GeneratorWorkersConfig.ts";
 export * from "./sub/GlobalConfig.ts";
 export * from "./sub/InputOrInline.ts";
 export * from "./sub/InputOrInlineText.ts";
@@ -48,8 +62,10 @@ export * from "./sub/MenuItemSection.ts";
 export * from "./sub/MoveDirection.ts";
 export * from "./sub/OidcConfig.ts";
 export * from "./sub/Orientation.ts";
+export * from "./sub/PlayedFilter.ts";
 export * from "./sub/Query.ts";
 export * from "./sub/QueryOrField.ts";
+export * from "./sub/S3FileStorageConfig.ts";
 export * from "./sub/ServerConfigMenuItem.ts";
 export * from "./sub/ServerConfigMenuItemDetail.ts";
 export * from "./sub/SortDir.ts";
@@ -59,22 +75,35 @@ export * from "./sub/StepMove.ts";
 export * from "./sub/StepRecurse.ts";
 export * from "./sub/StrValue.ts";
 export * from "./sub/TextSizeMode.ts";
+export * from "./sub/TimelineGroup.ts";
 export * from "./sub/TransAlign.ts";
 export * from "./sub/UserConfig.ts";
 export * from "./sub/UserIdentityId.ts";
 export * from "./sub/UsersConfig.ts";
 export * from "./sub/Value.ts";
 export * from "./sub/View.ts";
+export * from "./sub/ViewFacet.ts";
 export * from "./sub/ViewId.ts";
 export * from "./sub/ViewLink.ts";
+export * from "./sub/ViewSort.ts";
 export * from "./sub/Widget.ts";
+export * from "./sub/WidgetChart.ts";
 export * from "./sub/WidgetColor.ts";
+export * from "./sub/WidgetCondition.ts";
+export * from "./sub/WidgetConditionEquals.ts";
+export * from "./sub/WidgetConditionMime.ts";
 export * from "./sub/WidgetDataRows.ts";
 export * from "./sub/WidgetDate.ts";
 export * from "./sub/WidgetDatetime.ts";
+export * from "./sub/WidgetEdit.ts";
+export * from "./sub/WidgetEditInput.ts";
+export * from "./sub/WidgetIf.ts";
 export * from "./sub/WidgetLayout.ts";
+export * from "./sub/WidgetMarkdown.ts";
 export * from "./sub/WidgetMedia.ts";
 export * from "./sub/WidgetPlayButton.ts";
 export * from "./sub/WidgetRootDataRows.ts";
+export * from "./sub/WidgetSwitch.ts";
+export * from "./sub/WidgetSwitchCase.ts";
 export * from "./sub/WidgetText.ts";
 export * from "./sub/WidgetTime.ts";
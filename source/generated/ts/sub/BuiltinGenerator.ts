@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuiltinGeneratorVideoWebm } from "./BuiltinGeneratorVideoWebm";
+
+export type BuiltinGenerator = "video_subtitles" | { "video_webm": BuiltinGeneratorVideoWebm } | "audio_webm" | "epub_html" | "comic_dir" | "fulltext";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type BuiltinGeneratorVideoWebm = { 
+/**
+ * Constant quality factor for the vp9 encode, lower is higher quality (and
+ * larger). Defaults to 30.
+ */
+crf?: number | null, };
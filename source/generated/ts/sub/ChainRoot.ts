@@ -1,5 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlayedFilter } from "./PlayedFilter";
 import type { StrValue } from "./StrValue";
 import type { Value } from "./Value";
 
-export type ChainRoot = { "value": Value } | { "search": StrValue };
+export type ChainRoot = { "value": Value } | { "search": StrValue } | { "played": PlayedFilter };
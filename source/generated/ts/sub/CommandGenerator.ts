@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CommandGenerator = { 
+/**
+ * An identifier for the generated file, used when requesting it. To provide a
+ * replacement file (i.e. a transcode) used when the original can't be played,
+ * use `mime_` followed by the output mimetype with non-alphanumeric characters
+ * replaced with `_`, like `mime_audio_webm`.
+ */
+gentype: string, 
+/**
+ * The mimetype of the generated file, sent when serving it.
+ */
+mimetype: string, 
+/**
+ * The program and arguments to run. In each element, `{source}` is replaced
+ * with the path of the original file and `{dest}` with the path the output must
+ * be written to. The command is run in an empty temporary directory.
+ */
+command: Array<string>, 
+/**
+ * Create `{dest}` as an empty directory before running the command, for
+ * generators that produce multiple files.
+ */
+dest_dir?: boolean, };
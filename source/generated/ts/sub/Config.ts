@@ -1,5 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { FdapConfig } from "./FdapConfig";
+import type { FileStorageConfig } from "./FileStorageConfig";
+import type { GeneratorConfig } from "./GeneratorConfig";
+import type { GeneratorWorkersConfig } from "./GeneratorWorkersConfig";
 import type { GlobalConfig } from "./GlobalConfig";
 import type { MaybeFdap } from "./MaybeFdap";
 import type { OidcConfig } from "./OidcConfig";
@@ -38,6 +41,31 @@ users?: MaybeFdap<UsersConfig> | null,
  * must configure how to access the FDAP server here.
  */
 fdap?: FdapConfig | null, 
+/**
+ * Generators for derived files (transcodes, extracted subtitles, etc). These are
+ * run in order for each file. If not specified, a default set of builtin
+ * generators is used; if specified it replaces the defaults, so include any
+ * builtin generators you want to keep.
+ */
+generators?: Array<GeneratorConfig> | null, 
+/**
+ * How many files are processed in parallel by the generators. Files are
+ * processed separately for slow and non-slow generators, so a long video
+ * transcode doesn't hold up quick generation of other files.
+ */
+generator_workers?: GeneratorWorkersConfig, 
+/**
+ * Where to store uploaded files. Defaults to local (`live/files` in the
+ * persistent dir).
+ */
+storage?: FileStorageConfig, 
+/**
+ * Where to store generated files. These can all be re-created, so this can be
+ * less durable than `storage`. Defaults to local (`genfiles` in the cache dir).
+ * Must not overlap `storage` (same directory, or same bucket with nested
+ * prefixes).
+ */
+generated_storage?: FileStorageConfig, 
 /**
  * Everything else.
  */
@@ -17,4 +17,9 @@ views?: Array<ViewId>,
 /**
  * Give the user access to all these forms.
  */
-forms?: Array<FormId>, };
+forms?: Array<FormId>, 
+/**
+ * Let the user make the edits configured in these views (changing editable
+ * values, bulk deleting). This doesn't give access to the views themselves.
+ */
+view_edits?: Array<ViewId>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataRowsBulkActionForm } from "./DataRowsBulkActionForm";
+
+export type DataRowsBulkAction = "edit" | "add_to_list" | { "form": DataRowsBulkActionForm } | "offline" | "delete";
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormId } from "./FormId";
+
+export type DataRowsBulkActionForm = { form_id: FormId, 
+/**
+ * The button text, also used as the form page title.
+ */
+name: string, 
+/**
+ * The form parameter that receives the selected nodes. Outputs using this
+ * parameter are created once for each selected node.
+ */
+parameter: string, };
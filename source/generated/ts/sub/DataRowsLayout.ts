@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataRowsLayoutCalendar } from "./DataRowsLayoutCalendar";
 import type { DataRowsLayoutTable } from "./DataRowsLayoutTable";
+import type { DataRowsLayoutTimeline } from "./DataRowsLayoutTimeline";
 import type { DataRowsLayoutUnaligned } from "./DataRowsLayoutUnaligned";
 
-export type DataRowsLayout = { "unaligned": DataRowsLayoutUnaligned } | { "table": DataRowsLayoutTable };
+export type DataRowsLayout = { "unaligned": DataRowsLayoutUnaligned } | { "table": DataRowsLayoutTable } | { "calendar": DataRowsLayoutCalendar } | { "timeline": DataRowsLayoutTimeline };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CalendarPeriod } from "./CalendarPeriod";
+import type { Widget } from "./Widget";
+
+export type DataRowsLayoutCalendar = { 
+/**
+ * The field with each row's date or datetime (ISO 8601, like `2024-05-30` or
+ * `2024-05-30T10:00:00Z`). Datetimes are placed on the date as written,
+ * regardless of time zone. Rows without a date are not shown.
+ */
+date_field: string, 
+/**
+ * Show a grid per month, or a row of days per week. Only periods with rows are
+ * shown.
+ */
+period?: CalendarPeriod, 
+/**
+ * The widget for each row, placed in the row's day.
+ */
+widget: Widget, con_size_max?: string | null, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Orientation } from "./Orientation";
+import type { TimelineGroup } from "./TimelineGroup";
+import type { Widget } from "./Widget";
+
+export type DataRowsLayoutTimeline = { 
+/**
+ * The field with each row's date or datetime, as in `calendar`. Rows without a
+ * date are not shown.
+ */
+date_field: string, 
+/**
+ * Rows are shown under a heading for each day (week, etc.) that has rows.
+ */
+group?: TimelineGroup, 
+/**
+ * Show the latest rows first.
+ */
+descending?: boolean, gap?: string | null, 
+/**
+ * The converse direction is the direction of time. If unspecified, keep the
+ * parent widget's orientation.
+ */
+orientation?: Orientation | null, widget: Widget, con_scroll?: boolean, con_size_max?: string | null, trans_size_max?: string | null, };
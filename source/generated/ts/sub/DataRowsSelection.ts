@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataRowsBulkAction } from "./DataRowsBulkAction";
+
+export type DataRowsSelection = { 
+/**
+ * The field with the node each row represents.
+ */
+node_field: string, 
+/**
+ * Actions offered for the selected rows.
+ */
+actions: Array<DataRowsBulkAction>, };
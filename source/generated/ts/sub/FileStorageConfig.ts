@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { S3FileStorageConfig } from "./S3FileStorageConfig";
+
+export type FileStorageConfig = "local" | { "s3": S3FileStorageConfig };
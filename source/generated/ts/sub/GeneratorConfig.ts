@@ -0,0 +1,29 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GeneratorKind } from "./GeneratorKind";
+
+export type GeneratorConfig = { 
+/**
+ * Run the generator for files with these mimetypes. Each is either a full
+ * mimetype (`audio/mpeg`), a type with any subtype (`audio/*`), or `*` to match
+ * everything.
+ */
+mimetypes: Array<string>, 
+/**
+ * Don't run the generator for files with these mimetypes, even if they match
+ * `mimetypes`. Same format as `mimetypes`.
+ */
+exclude_mimetypes?: Array<string>, 
+/**
+ * Slow generators are run after all other generators during the startup scan
+ * so they don't hold up quick results.
+ */
+slow?: boolean, 
+/**
+ * The maximum number of files this generator can process at the same time.
+ * Defaults to 1.
+ */
+concurrency?: number | null, 
+/**
+ * What to run.
+ */
+kind: GeneratorKind, };
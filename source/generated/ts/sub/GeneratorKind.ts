@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuiltinGenerator } from "./BuiltinGenerator";
+import type { CommandGenerator } from "./CommandGenerator";
+
+export type GeneratorKind = { "builtin": BuiltinGenerator } | { "command": CommandGenerator };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GeneratorWorkersConfig = { 
+/**
+ * How many files to run non-slow generators for at once. Defaults to 4.
+ */
+fast?: number | null, 
+/**
+ * How many files to run slow generators for at once. Defaults to 1.
+ */
+slow?: number | null, };
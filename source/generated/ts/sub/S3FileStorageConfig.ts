@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type S3FileStorageConfig = { 
+/**
+ * Url of the S3-compatible service, like `https://s3.us-west-1.amazonaws.com`.
+ */
+endpoint: string, region: string, bucket: string, 
+/**
+ * Store files under this key prefix in the bucket.
+ */
+prefix?: string, access_key: string, secret_key: string, 
+/**
+ * Address the bucket as `endpoint/bucket` rather than `bucket.endpoint`. Most
+ * self-hosted services (Minio, Garage, etc) need this.
+ */
+path_style?: boolean, 
+/**
+ * Redirect file downloads to pre-signed urls rather than proxying them through
+ * Sunwet. The bucket must allow cross-origin requests from the Sunwet domain.
+ */
+redirect?: boolean, };
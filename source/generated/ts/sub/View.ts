@@ -2,6 +2,8 @@
 import type { ClientViewParam } from "./ClientViewParam";
 import type { Query } from "./Query";
 import type { TrackEndMode } from "./TrackEndMode";
+import type { ViewFacet } from "./ViewFacet";
+import type { ViewSort } from "./ViewSort";
 import type { WidgetRootDataRows } from "./WidgetRootDataRows";
 
 export type View = { 
@@ -23,4 +25,16 @@ display: WidgetRootDataRows,
  * What happens when a track reaches the end: advance to next track (default) or
  * loop.
  */
-track_end_mode?: TrackEndMode, };
+track_end_mode?: TrackEndMode, 
+/**
+ * Alternative orders for the rows of the root query the user can choose between.
+ * The root query's own sort is used when none is chosen. Requires the root data
+ * to be a query with a record suffix.
+ */
+sorts?: Array<ViewSort>, 
+/**
+ * Fields of the root query the user can narrow the rows by. Each facet is shown
+ * with the available values and their row counts. Requires the root data to be a
+ * query with a record suffix.
+ */
+facets?: Array<ViewFacet>, };
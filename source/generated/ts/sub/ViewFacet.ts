@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ViewFacet = { 
+/**
+ * The label for the facet's control.
+ */
+name: string, 
+/**
+ * A field of the root query's records. Choosing a value narrows the rows to
+ * those where the field has that value (or, for arrays, contains it).
+ */
+field: string, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SortQuery } from "./SortQuery";
+
+export type ViewSort = { 
+/**
+ * Text to show in the sort selector.
+ */
+name: string, 
+/**
+ * Replaces the sort of the root query when selected.
+ */
+sort: SortQuery, };
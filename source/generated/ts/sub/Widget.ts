@@ -1,15 +1,19 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WidgetChart } from "./WidgetChart";
 import type { WidgetColor } from "./WidgetColor";
 import type { WidgetDataRows } from "./WidgetDataRows";
 import type { WidgetDate } from "./WidgetDate";
 import type { WidgetDatetime } from "./WidgetDatetime";
 import type { WidgetIcon } from "./WidgetIcon";
+import type { WidgetIf } from "./WidgetIf";
 import type { WidgetLayout } from "./WidgetLayout";
+import type { WidgetMarkdown } from "./WidgetMarkdown";
 import type { WidgetMedia } from "./WidgetMedia";
 import type { WidgetNode } from "./WidgetNode";
 import type { WidgetPlayButton } from "./WidgetPlayButton";
+import type { WidgetSwitch } from "./WidgetSwitch";
 import type { WidgetTable } from "./WidgetTable";
 import type { WidgetText } from "./WidgetText";
 import type { WidgetTime } from "./WidgetTime";
 
-export type Widget = { "layout": WidgetLayout } | { "data_rows": WidgetDataRows } | { "table": WidgetTable } | { "text": WidgetText } | { "date": WidgetDate } | { "time": WidgetTime } | { "datetime": WidgetDatetime } | { "color": WidgetColor } | { "media": WidgetMedia } | { "icon": WidgetIcon } | { "play_button": WidgetPlayButton } | "space" | { "node": WidgetNode };
+export type Widget = { "layout": WidgetLayout } | { "data_rows": WidgetDataRows } | { "table": WidgetTable } | { "text": WidgetText } | { "markdown": WidgetMarkdown } | { "date": WidgetDate } | { "time": WidgetTime } | { "datetime": WidgetDatetime } | { "color": WidgetColor } | { "media": WidgetMedia } | { "icon": WidgetIcon } | { "play_button": WidgetPlayButton } | "space" | { "node": WidgetNode } | { "if": WidgetIf } | { "switch": WidgetSwitch } | { "chart": WidgetChart };
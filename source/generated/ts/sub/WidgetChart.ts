@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChartType } from "./ChartType";
+import type { QueryOrField } from "./QueryOrField";
+import type { TransAlign } from "./TransAlign";
+
+export type WidgetChart = { 
+/**
+ * Where to get the rows for the chart.
+ */
+data: QueryOrField, chart_type: ChartType, 
+/**
+ * The field with each row's category (bar, line, pie) or number (histogram).
+ * Categories are shown in the order they first appear in the rows.
+ */
+x_field: string, 
+/**
+ * The field with each row's number. Numbers for rows with the same category are
+ * summed. If not specified, rows are counted.
+ */
+y_field?: string | null, 
+/**
+ * Number of buckets for histograms. Defaults to 10, at most 1000.
+ */
+buckets?: number | null, 
+/**
+ * Defaults to 100%.
+ */
+width?: string | null, height?: string | null, 
+/**
+ * The color of bars and lines. Pie charts use a fixed palette.
+ */
+color?: string | null, trans_align?: TransAlign, };
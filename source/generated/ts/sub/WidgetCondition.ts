@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldOrLiteral } from "./FieldOrLiteral";
+import type { WidgetConditionEquals } from "./WidgetConditionEquals";
+import type { WidgetConditionMime } from "./WidgetConditionMime";
+
+export type WidgetCondition = { "exists": FieldOrLiteral } | { "equals": WidgetConditionEquals } | { "mime": WidgetConditionMime } | { "not": WidgetCondition } | { "all": Array<WidgetCondition> } | { "any": Array<WidgetCondition> };
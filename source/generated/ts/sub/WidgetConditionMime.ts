@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldOrLiteral } from "./FieldOrLiteral";
+
+export type WidgetConditionMime = { 
+/**
+ * A file node.
+ */
+data: FieldOrLiteral, 
+/**
+ * Ex: `image/` or `video/webm`.
+ */
+prefix: string, };
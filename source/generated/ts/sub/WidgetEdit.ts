@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WidgetEditInput } from "./WidgetEditInput";
+
+/**
+ * Allow changing the value in the view. The relation `subject predicate old value`
+ * is replaced with `subject predicate new value` (if there was no old value, the
+ * relation is just added).
+ *
+ * Edits are accepted from logged in users with edit access to the view (admins,
+ * or `view_edits` in the user's grants), for predicates marked editable somewhere
+ * in the view.
+ */
+export type WidgetEdit = { 
+/**
+ * The field with the node that has the relation being edited.
+ */
+subject_field: string, 
+/**
+ * The predicate of the relation being edited.
+ */
+predicate: string, input?: WidgetEditInput, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Widget } from "./Widget";
+import type { WidgetCondition } from "./WidgetCondition";
+
+export type WidgetIf = { condition: WidgetCondition, 
+/**
+ * Shown if the condition is true.
+ */
+then: Widget, 
+/**
+ * Shown if the condition is false. If not specified, nothing is shown.
+ */
+otherwise?: Widget | null, };
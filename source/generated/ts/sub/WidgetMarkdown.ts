@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldOrLiteralString } from "./FieldOrLiteralString";
+import type { TransAlign } from "./TransAlign";
+
+/**
+ * Render a string as CommonMark. Raw html is shown as text.
+ *
+ * Links and images can refer to sunwet data:
+ *
+ * * `node:ID` links to the node view of the string node `ID`
+ *
+ * * `sha256:HASH` links to the file, or as an image (`![alt](sha256:HASH)`) shows
+ * the file inline (audio and video are shown with controls if the file's mime
+ * type is known from the query)
+ */
+export type WidgetMarkdown = { data: FieldOrLiteralString, font_size?: string | null, color?: string | null, 
+/**
+ * Max width of the rendered text. Longer lines are wrapped.
+ */
+con_size_max?: string | null, trans_align?: TransAlign, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataRowsSelection } from "./DataRowsSelection";
 import type { QueryOrField } from "./QueryOrField";
 import type { Widget } from "./Widget";
 
@@ -27,4 +28,8 @@ element_height?: string | null,
  * When the element body is clicked, this expansion is toggled on the next row.
  * It's always 100% width.
  */
-element_expansion?: Widget | null, };
+element_expansion?: Widget | null, 
+/**
+ * Allow selecting rows and applying actions to all selected rows at once.
+ */
+selection?: DataRowsSelection | null, };
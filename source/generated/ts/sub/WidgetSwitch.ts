@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Widget } from "./Widget";
+import type { WidgetSwitchCase } from "./WidgetSwitchCase";
+
+export type WidgetSwitch = { 
+/**
+ * The widget of the first case whose condition is true is shown.
+ */
+cases: Array<WidgetSwitchCase>, 
+/**
+ * Shown if no case matches. If not specified, nothing is shown.
+ */
+default?: Widget | null, };
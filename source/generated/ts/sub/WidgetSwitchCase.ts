@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Widget } from "./Widget";
+import type { WidgetCondition } from "./WidgetCondition";
+
+export type WidgetSwitchCase = { condition: WidgetCondition, widget: Widget, };
@@ -4,5 +4,10 @@ import type { Link } from "./Link";
 import type { Orientation } from "./Orientation";
 import type { TextSizeMode } from "./TextSizeMode";
 import type { TransAlign } from "./TransAlign";
+import type { WidgetEdit } from "./WidgetEdit";
 
-export type WidgetText = { data: FieldOrLiteralString, prefix?: string, suffix?: string, font_size?: string | null, color?: string | null, con_size_mode?: TextSizeMode, con_size_max?: string | null, orientation: Orientation, trans_align?: TransAlign, link?: Link | null, };
+export type WidgetText = { data: FieldOrLiteralString, prefix?: string, suffix?: string, font_size?: string | null, color?: string | null, con_size_mode?: TextSizeMode, con_size_max?: string | null, orientation: Orientation, trans_align?: TransAlign, link?: Link | null, 
+/**
+ * Make the text editable. `data` must be a field with the current value.
+ */
+edit?: WidgetEdit | null, };
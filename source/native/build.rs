@@ -14,14 +14,17 @@ fn main() {
         filehash_type_path: "crate::interface::triple::DbFileHash",
         access_source_type_path: "crate::server::access::DbAccessSourceId",
     };
-    let latest = buildlib::dbv3::build(db_build_input.clone());
+    let latest = buildlib::dbv6::build(db_build_input.clone());
     match generate(GenerateArgs {
         db_name: None,
         versions: vec![
             (0usize, buildlib::dbv0::build(db_build_input.clone()).0),
             (1usize, buildlib::dbv1::build(db_build_input.clone()).0),
             (2usize, buildlib::dbv2::build(db_build_input.clone()).0),
-            (3usize, latest.0),
+            (3usize, buildlib::dbv3::build(db_build_input.clone()).0),
+            (4usize, buildlib::dbv4::build(db_build_input.clone()).0),
+            (5usize, buildlib::dbv5::build(db_build_input.clone()).0),
+            (6usize, latest.0),
         ],
         queries: latest.1,
     }) {
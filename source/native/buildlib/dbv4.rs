@@ -0,0 +1,146 @@
+use {
+    crate::buildlib::BuildDbInput,
+    good_ormning::sqlite::{
+        schema::field::{field_bool, field_i64, field_str, field_utctime_ms_chrono},
+        types::type_str,
+        Query, Version,
+    },
+};
+
+pub fn build(input: BuildDbInput) -> (Version, Vec<Query>) {
+    let version = Version::new();
+    let queries = vec![];
+
+    let node_type = version
+        .custom_type("node")
+        .rust_type(input.node_type_path)
+        .base_type(type_str().build());
+    let filehash_type = version
+        .custom_type("filehash")
+        .rust_type(input.filehash_type_path)
+        .base_type(type_str().build());
+    let access_source_type = version
+        .custom_type("access_source")
+        .rust_type(input.access_source_type_path)
+        .base_type(type_str().build());
+
+    // Subjobj (deduplicated node values, with integer id for normalization)
+    {
+        let t = version.table("subjobj");
+        let id = t.field("id", field_i64().build());
+        let value = t.field("value", node_type.field_type());
+        t.primary_key("subjobj_pk", &[&id]);
+        t.unique_index("subjobj_value", &[&value]);
+    }
+
+    // Predicate (deduplicated predicates, with integer id for normalization)
+    {
+        let t = version.table("predicate");
+        let id = t.field("id", field_i64().build());
+        let value = t.field("value", field_str().build());
+        t.primary_key("predicate_pk", &[&id]);
+        t.unique_index("predicate_value", &[&value]);
+    }
+
+    // Triple snapshot (current state, normalized with integer references)
+    {
+        let t = version.table("triple_snapshot");
+        let subject = t.field("subject", field_i64().build());
+        let predicate = t.field("predicate", field_i64().build());
+        let object = t.field("object", field_i64().build());
+        let _commit = t.field("commit_", field_utctime_ms_chrono().build());
+        t.primary_key("triple_snapshot_pk", &[&subject, &predicate, &object]);
+        t.unique_index(
+            "triple_snapshot_obj_pred_subj",
+            &[&object, &predicate, &subject],
+        );
+        t.index("triple_snapshot_pred_subj", &[&predicate, &subject]);
+        t.index("triple_snapshot_pred_obj", &[&predicate, &object]);
+    }
+
+    // Triple (history table, renamed from triple2, normalized with integer references)
+    {
+        let t = version.table("triple").renamed_from("triple2");
+        let subject = t.field("subject", field_i64().build());
+        let predicate = t.field("predicate", field_i64().build());
+        let object = t.field("object", field_i64().build());
+        let commit = t.field("commit_", field_utctime_ms_chrono().build());
+        let exist = t.field("exists", field_bool().build());
+        t.primary_key(
+            "triple_pk",
+            &[&subject, &predicate, &object, &commit],
+        ).renamed_from("triple2_pk");
+        t.unique_index(
+            "triple_index_obj_pred_subj",
+            &[&object, &predicate, &subject, &commit],
+        ).renamed_from("triple2_index_obj_pred_subj");
+        t.index(
+            "triple_index_pred_subj",
+            &[&predicate, &subject, &commit],
+        ).renamed_from("triple2_index_pred_subj");
+        t.index(
+            "triple_index_pred_obj",
+            &[&predicate, &object, &commit],
+        ).renamed_from("triple2_index_pred_obj");
+        t.index("triple_commit_exists", &[&commit, &exist]).renamed_from("triple2_commit_exists");
+    }
+
+    // Commits
+    {
+        let t = version.table("commit");
+        let event_stamp = t.field("idtimestamp", field_utctime_ms_chrono().build());
+        let _desc = t.field("description", field_str().build());
+        t.primary_key("commit_timestamp", &[&event_stamp]);
+    }
+
+    // Metadata (file mime types; fulltext for FTS)
+    {
+        let t = version.table("meta");
+        let node = t.field("node", node_type.field_type());
+        let _mimetype = t.field("mimetype", field_str().opt().build());
+        let _fulltext = t.field("fulltext", field_str().build());
+        t.primary_key("meta_node", &[&node]);
+    }
+
+    // Generated
+    {
+        let t = version.table("generated");
+        let node = t.field("node", node_type.field_type());
+        let gentype = t.field("gentype", field_str().build());
+        let _mimetype = t.field("mimetype", field_str().build());
+        t.primary_key("generated_pk", &[&node, &gentype]);
+    }
+
+    // File access
+    {
+        let t = version.table("file_access");
+        let file = t.field("file", filehash_type.field_type());
+        let access_source = t.field("access_source", access_source_type.field_type());
+        let spec_hash = t.field("spec_hash", field_i64().build());
+        t.primary_key("file_access_pk", &[&file, &access_source, &spec_hash]);
+    }
+
+    // Generation queue
+    {
+        let t = version.table("generate_queue");
+        let file = t.field("file", filehash_type.field_type());
+        let slow = t.field("slow", field_bool().build());
+        let priority = t.field("priority", field_i64().build());
+        let _attempts = t.field("attempts", field_i64().build());
+        let next_attempt = t.field("next_attempt", field_utctime_ms_chrono().build());
+        let running = t.field("running", field_bool().build());
+        let failed = t.field("failed", field_bool().build());
+        let _last_error = t.field("last_error", field_str().opt().build());
+
+        // Bumped when the job is replaced, so a worker finishing an older version
+        // doesn't remove it
+        let _version = t.field("version", field_i64().build());
+        t.primary_key("generate_queue_pk", &[&file, &slow]);
+        t.index(
+            "generate_queue_next",
+            &[&slow, &failed, &running, &priority, &next_attempt],
+        );
+    }
+
+    return (version.build(), queries);
+}
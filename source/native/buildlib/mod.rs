@@ -2,6 +2,9 @@ pub mod dbv0;
 pub mod dbv1;
 pub mod dbv2;
 pub mod dbv3;
+pub mod dbv4;
+pub mod dbv5;
+pub mod dbv6;
 
 #[derive(Clone)]
 pub struct BuildDbInput {
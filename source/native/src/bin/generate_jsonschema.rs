@@ -34,6 +34,14 @@ fn main() {
         root.join("cli_commit.schema.json"),
         serde_json::to_vec_pretty(&schema_for!(shared::interface::cli::CliCommit)).unwrap(),
     ).unwrap();
+    write(
+        root.join("cli_import_dir.schema.json"),
+        serde_json::to_vec_pretty(&schema_for!(shared::interface::cli::CliImportDir)).unwrap(),
+    ).unwrap();
+    write(
+        root.join("cli_rdf_mapping.schema.json"),
+        serde_json::to_vec_pretty(&schema_for!(shared::interface::cli::CliRdfMapping)).unwrap(),
+    ).unwrap();
 
     // Api
     write(
@@ -19,6 +19,12 @@ enum Command {
     /// commit JSON appropriate for use in `commit` (additionally downloading all
     /// files, replacing the file nodes with local references).
     Export(client::ExportCommand),
+    /// Like `export`, but write the relations as RDF (N-Triples, Turtle, or
+    /// JSON-LD) without downloading files.
+    RdfExport(client::rdf::RdfExportCommand),
+    /// Convert an RDF file (N-Triples, Turtle, or JSON-LD) to a CLI commit JSON for
+    /// use in `commit`.
+    RdfImport(client::rdf::RdfImportCommand),
     /// Compile a query into JSON to use in config or the API.
     ///
     /// Specify a query either inline on the command line, or as a file.
@@ -28,21 +34,40 @@ enum Command {
     /// This takes a CLI commit JSON, prepares and sends an API JSON commit payload
     /// (replacing files with hashes), then uploads all files.
     Commit(client::commit::CommitCommand),
+    /// Compare a CLI commit JSON with the data on the server without committing
+    /// anything. Shows which entities are new or already exist, and which triples
+    /// are already present.
+    DiffCommit(client::commit::DiffCommitCommand),
     /// Prepare a CLI commit JSON from media files in a directory using their tags.
     PrepareMediaImportCommit(client::media_import::PrepareImportCommitCommand),
+    /// Watch a directory, and prepare and commit media placed there automatically.
+    /// Imported items are moved to a done or failed directory with a report.
+    WatchImport(client::media_import::watch::WatchImportCommand),
     /// Move all triples centered around one node to another node, eliminating the
     /// first node.
     MergeNodes(client::MergeNodesCommand),
     /// Delete all triples centered around one node.
     DeleteNodes(client::DeleteNodesCommand),
+    /// Copy nodes (and optionally linked entities, like an album's tracks) to new
+    /// nodes with fresh ids and the same relations.
+    DuplicateNodes(client::DuplicateNodesCommand),
     /// Show commit history.
     History(client::HistoryCommand),
     /// Show all triples involving a given node.
     GetNode(client::GetNodeCommand),
     /// Run various data checks and show a report. Could take a long time.
     Check(client::CheckCommand),
+    /// Show which derived files have been generated for a file and any pending or
+    /// failed generation.
+    GenerationStatus(client::GenerationStatusCommand),
     /// Run the Sunwet server.
     RunServer(server::Args),
+    /// Write the whole graph (with commit history) and files to an archive, for
+    /// moving to a new server or migrating storage. The server should be stopped.
+    Dump(server::dump::DumpArgs),
+    /// Load a dump created with `dump` into a new, empty instance. Generated files
+    /// are recreated when the server starts.
+    Load(server::dump::LoadArgs),
 }
 
 #[derive(Aargvark)]
@@ -59,21 +84,36 @@ async fn main1() -> Result<(), loga::Error> {
         Command::Export(c) => {
             client::handle_export(c).await?;
         },
+        Command::RdfExport(c) => {
+            client::rdf::handle_rdf_export(c).await?;
+        },
+        Command::RdfImport(c) => {
+            client::rdf::handle_rdf_import(c)?;
+        },
         Command::CompileQuery(c) => {
             client::handle_compile_query(c)?;
         },
         Command::Commit(c) => {
             client::commit::handle_commit(c).await?;
         },
+        Command::DiffCommit(c) => {
+            client::commit::handle_diff_commit(c).await?;
+        },
         Command::PrepareMediaImportCommit(c) => {
             client::media_import::handle_prepare_media_import_commit(c).await?;
         },
+        Command::WatchImport(c) => {
+            client::media_import::watch::handle_watch_import(c).await?;
+        },
         Command::DeleteNodes(c) => {
             client::handle_delete_nodes(c).await?;
         },
         Command::MergeNodes(c) => {
             client::handle_merge_nodes_command(c).await?;
         },
+        Command::DuplicateNodes(c) => {
+            client::handle_duplicate_nodes_command(c).await?;
+        },
         Command::History(c) => {
             client::handle_history(c).await?;
         },
@@ -83,9 +123,18 @@ async fn main1() -> Result<(), loga::Error> {
         Command::Check(c) => {
             client::handle_check(c).await?;
         },
+        Command::GenerationStatus(c) => {
+            client::handle_generation_status(c).await?;
+        },
         Command::RunServer(config) => {
             server::main(config).await?;
         },
+        Command::Dump(c) => {
+            server::dump::handle_dump(c).await?;
+        },
+        Command::Load(c) => {
+            server::dump::handle_load(c).await?;
+        },
     }
     return Ok(());
 }
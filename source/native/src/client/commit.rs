@@ -7,6 +7,7 @@ use {
         client::req::{
             http_limits,
             req,
+            req_simple,
             server_headers,
             server_url,
         },
@@ -26,19 +27,26 @@ use {
         ea,
     },
     mime_guess::MimeGuess,
+    serde::Serialize,
     shared::interface::{
         cli::{
             CliCommit,
             CliNode,
+            CliTriple,
         },
+        ont::PREDICATE_NAME,
         triple::Node,
         wire::{
             CommitFile,
             HEADER_OFFSET,
             ReqCommit,
             ReqCommitFree,
+            ReqGetTriplesAround,
             ReqUploadFinish,
+            ReqUploadStatus,
             Triple,
+            UPLOAD_CHUNK_SIZE,
+            upload_chunk_checksum,
         },
     },
     std::{
@@ -70,6 +78,157 @@ use {
     },
 };
 
+/// Give up resuming an upload after this many failed status requests, with the
+/// delay doubling after each.
+const UPLOAD_STATUS_MAX_ATTEMPTS: u32 = 6;
+
+/// Upload paths in a commit are relative to the commit file
+fn commit_base_dir(log: &Log, source: &aargvark::traits_impls::Source) -> Result<PathBuf, loga::Error> {
+    match source {
+        aargvark::traits_impls::Source::Stdin => return Ok(
+            current_dir().stack_context(&log, "Error determining current dir for relative path normalization")?,
+        ),
+        aargvark::traits_impls::Source::File(p) => return Ok(
+            p
+                .canonicalize()
+                .stack_context(&log, "Error getting normalized commit path")?
+                .parent()
+                .unwrap()
+                .to_path_buf(),
+        ),
+    }
+}
+
+#[derive(Serialize)]
+pub struct DiffEntity {
+    pub id: Node,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CommitDiff {
+    /// Entities (subjects of added triples) that don't exist on the server
+    pub new_entities: Vec<DiffEntity>,
+    /// Entities that already exist on the server, which the commit will modify
+    pub matched_entities: Vec<DiffEntity>,
+    /// Added triples that aren't on the server yet
+    pub new_triples: Vec<CliTriple>,
+    /// Added triples that are already on the server (no-ops)
+    pub existing_triples: Vec<CliTriple>,
+}
+
+/// Compare a commit's added triples with the data on the server without changing
+/// anything.
+pub async fn diff_commit(log: &Log, base_dir: &Path, commit: &CliCommit) -> Result<CommitDiff, loga::Error> {
+    let mut uploads = HashMap::new();
+
+    async fn resolve_node(
+        log: &Log,
+        uploads: &mut HashMap<PathBuf, Node>,
+        base_dir: &Path,
+        n: &CliNode,
+    ) -> Result<Node, loga::Error> {
+        match n {
+            CliNode::File(v) => return Ok(Node::File(v.clone())),
+            CliNode::Value(v) => return Ok(Node::Value(v.clone())),
+            CliNode::Upload(v) => {
+                let path = base_dir.join(v);
+                if let Some(n) = uploads.get(&path) {
+                    return Ok(n.clone());
+                }
+                let n = Node::File(hash_file_sha256(log, &path).await?);
+                uploads.insert(path, n.clone());
+                return Ok(n);
+            },
+        }
+    }
+
+    let mut add = vec![];
+    let mut subjects = vec![];
+    let mut file_subjects = vec![];
+    let mut seen_subjects = HashSet::new();
+    let mut names = HashMap::new();
+    for (i, t) in commit.add.iter().enumerate() {
+        let subject =
+            resolve_node(log, &mut uploads, base_dir, &t.subject)
+                .await
+                .context(format!("Failed to process subject in add triple {}", i))?;
+        let object =
+            resolve_node(log, &mut uploads, base_dir, &t.object)
+                .await
+                .context(format!("Failed to process object in add triple {}", i))?;
+        if let (PREDICATE_NAME, Node::Value(serde_json::Value::String(name))) = (t.predicate.as_str(), &object) {
+            names.entry(subject.clone()).or_insert_with(|| name.clone());
+        }
+        if seen_subjects.insert(subject.clone()) {
+            match &subject {
+                Node::Value(_) => subjects.push(subject.clone()),
+                Node::File(_) => file_subjects.push(subject.clone()),
+            }
+        }
+        add.push((t, Triple {
+            subject: subject,
+            predicate: t.predicate.clone(),
+            object: object,
+        }));
+    }
+    let mut existing = HashSet::new();
+    let mut present = HashSet::new();
+
+    // File subjects aren't reported as entities, but their triples may already exist
+    let query_nodes = subjects.iter().chain(file_subjects.iter()).cloned().collect::<Vec<_>>();
+    for chunk in query_nodes.chunks(100) {
+        for t in req_simple(log, ReqGetTriplesAround { nodes: chunk.to_vec() }).await? {
+            present.insert(t.subject.clone());
+            present.insert(t.object.clone());
+            existing.insert(t);
+        }
+    }
+    let mut out = CommitDiff {
+        new_entities: vec![],
+        matched_entities: vec![],
+        new_triples: vec![],
+        existing_triples: vec![],
+    };
+    for subject in subjects {
+        let entity = DiffEntity {
+            name: names.get(&subject).cloned(),
+            id: subject.clone(),
+        };
+        if present.contains(&subject) {
+            out.matched_entities.push(entity);
+        } else {
+            out.new_entities.push(entity);
+        }
+    }
+    for (cli_triple, triple) in add {
+        if existing.contains(&triple) {
+            out.existing_triples.push(cli_triple.clone());
+        } else {
+            out.new_triples.push(cli_triple.clone());
+        }
+    }
+    return Ok(out);
+}
+
+#[derive(Aargvark)]
+pub struct DiffCommitCommand {
+    debug: Option<()>,
+    commit: AargvarkJson<CliCommit>,
+}
+
+pub async fn handle_diff_commit(c: DiffCommitCommand) -> Result<(), loga::Error> {
+    let log = Log::new_root(if c.debug.is_some() {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    let base_dir = commit_base_dir(&log, &c.commit.source)?;
+    let diff = diff_commit(&log, &base_dir, &c.commit.value).await?;
+    println!("{}", serde_json::to_string_pretty(&diff).unwrap());
+    return Ok(());
+}
+
 #[derive(Aargvark)]
 pub struct CommitCommand {
     debug: Option<()>,
@@ -84,18 +243,19 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
     } else {
         loga::INFO
     });
-    let base_dir = match c.commit.source {
-        aargvark::traits_impls::Source::Stdin => current_dir().stack_context(
-            &log,
-            "Error determining current dir for relative path normalization",
-        )?,
-        aargvark::traits_impls::Source::File(p) => p
-            .canonicalize()
-            .stack_context(&log, "Error getting normalized commit path")?
-            .parent()
-            .unwrap()
-            .to_path_buf(),
-    };
+    let base_dir = commit_base_dir(&log, &c.commit.source)?;
+    return commit(&log, &base_dir, c.commit.value, c.comment).await;
+}
+
+/// Send the commit, then upload any files the server doesn't have yet. Upload
+/// paths are relative to `base_dir`.
+pub async fn commit(
+    log: &Log,
+    base_dir: &Path,
+    cli_commit: CliCommit,
+    comment: Option<String>,
+) -> Result<(), loga::Error> {
+    let base_dir = base_dir.to_path_buf();
 
     // Hash files
     async fn process_file(
@@ -136,11 +296,11 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
 
     let mut files = HashMap::new();
     let limit = Arc::new(Semaphore::new(16));
-    for t in &c.commit.value.add {
+    for t in &cli_commit.add {
         process_file(&base_dir, &limit, &log, &mut files, &t.subject).await;
         process_file(&base_dir, &limit, &log, &mut files, &t.object).await;
     }
-    for t in &c.commit.value.remove {
+    for t in &cli_commit.remove {
         process_file(&base_dir, &limit, &log, &mut files, &t.subject).await;
         process_file(&base_dir, &limit, &log, &mut files, &t.object).await;
     }
@@ -163,7 +323,7 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
 
     // # Build commit info
     let mut commit = ReqCommitFree {
-        comment: c.comment.unwrap_or_else(|| format!("Commit via CLI")),
+        comment: comment.unwrap_or_else(|| format!("Commit via CLI")),
         add: vec![],
         remove: vec![],
         files: vec![],
@@ -188,7 +348,7 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
     }
 
     log.log(loga::INFO, "Processing commit");
-    for (i, t) in c.commit.value.add.into_iter().enumerate() {
+    for (i, t) in cli_commit.add.into_iter().enumerate() {
         let s =
             process_node(&mut commit, &files, &base_dir, t.subject)
                 .await
@@ -203,7 +363,7 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
             object: o,
         });
     }
-    for (i, t) in c.commit.value.remove.into_iter().enumerate() {
+    for (i, t) in cli_commit.remove.into_iter().enumerate() {
         let s =
             process_node(&mut commit, &files, &base_dir, t.subject)
                 .await
@@ -254,17 +414,50 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
             continue;
         }
         let log = log.fork(ea!(state = "upload", file = p.to_string_lossy()));
-        log.log(loga::INFO, "Uploading file");
-        const CHUNK_SIZE: u64 = 1024 * 1024 * 8;
-        let chunks = info.size.div_ceil(CHUNK_SIZE);
+
+        // Skip chunks received in an earlier interrupted upload
+        let mut attempts = 0;
+        let received = loop {
+            match req(&log, &mut conn, &headers, &url, ReqUploadStatus(info.hash.clone())).await {
+                Ok(resp) => break resp
+                    .chunks
+                    .into_iter()
+                    .map(|c| ((c.offset, c.size), c.checksum))
+                    .collect::<HashMap<_, _>>(),
+                Err(e) => {
+                    attempts += 1;
+                    if attempts >= UPLOAD_STATUS_MAX_ATTEMPTS {
+                        return Err(
+                            e.stack_context_with(&log, "Error getting upload status", ea!(attempts = attempts)),
+                        );
+                    }
+                    log.log_err(loga::WARN, e.stack_context(&log, "Error getting upload status, retrying"));
+                    sleep(Duration::from_secs(1 << attempts)).await;
+                    conn = reconnect(&log, &url).await;
+                },
+            }
+        };
+        if received.is_empty() {
+            log.log(loga::INFO, "Uploading file");
+        } else {
+            log.log(loga::INFO, "Resuming file upload");
+        }
+        let chunks = info.size.div_ceil(UPLOAD_CHUNK_SIZE);
         let mut f = File::open(&p).await.stack_context(&log, "Failed to open file for upload")?;
         for i in 0 .. chunks {
-            f.seek(SeekFrom::Start(i * CHUNK_SIZE)).await.stack_context(&log, "Failed to seek to next chunk")?;
+            f
+                .seek(SeekFrom::Start(i * UPLOAD_CHUNK_SIZE))
+                .await
+                .stack_context(&log, "Failed to seek to next chunk")?;
             let mut chunk = vec![];
-            let chunk_start = i * CHUNK_SIZE;
-            let chunk_size = (info.size - chunk_start).min(CHUNK_SIZE);
+            let chunk_start = i * UPLOAD_CHUNK_SIZE;
+            let chunk_size = (info.size - chunk_start).min(UPLOAD_CHUNK_SIZE);
             chunk.resize(chunk_size as usize, 0);
             f.read_exact(&mut chunk).await.stack_context(&log, "Error reading chunk from source file")?;
+            if received.get(&(chunk_start, chunk_size)).map(|c| c.as_str()) ==
+                Some(upload_chunk_checksum(&chunk).as_str()) {
+                continue;
+            }
             let url = url.join(format!("file/{}", info.hash.to_string()));
             let headers = {
                 let mut headers = headers.clone();
@@ -284,7 +477,7 @@ pub async fn handle_commit(c: CommitCommand) -> Result<(), loga::Error> {
                                 "Error uploading chunk",
                                 ea!(
                                     chunk = format!("{}/{}", i + 1, chunks),
-                                    range = format!("{}..{}B", i * CHUNK_SIZE, chunk_start + chunk_size)
+                                    range = format!("{}..{}B", i * UPLOAD_CHUNK_SIZE, chunk_start + chunk_size)
                                 ),
                             ),
                         );
@@ -1,5 +1,12 @@
 use {
     loga::ResultContext,
+    shared::{
+        interface::cli::CliImportDir,
+        stringpattern::{
+            Pattern,
+            PatternPart,
+        },
+    },
     sha2::{
         Digest,
         Sha256,
@@ -24,6 +31,7 @@ pub enum GatherMedia {
     Book,
 }
 
+#[derive(Clone)]
 pub struct Gather {
     pub album_name: Option<String>,
     pub album_artist: BTreeSet<String>,
@@ -34,6 +42,18 @@ pub struct Gather {
     pub track_superindex: Option<f64>,
     pub track_language: Option<String>,
     pub track_cover: HashMap<usize, PathBuf>,
+    pub track_genre: BTreeSet<String>,
+    pub track_composer: Vec<String>,
+    pub track_isrc: Option<String>,
+    /// `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`
+    pub track_date: Option<String>,
+    pub track_musicbrainz_id: Option<String>,
+    /// In the same order as `track_artist`, if known
+    pub track_artist_musicbrainz_id: Vec<String>,
+    pub album_label: Option<String>,
+    pub album_superindex_total: Option<f64>,
+    pub album_musicbrainz_id: Option<String>,
+    pub album_artist_musicbrainz_id: Vec<String>,
 }
 
 impl Gather {
@@ -48,10 +68,142 @@ impl Gather {
             track_superindex: Default::default(),
             track_language: Default::default(),
             track_cover: Default::default(),
+            track_genre: Default::default(),
+            track_composer: Default::default(),
+            track_isrc: Default::default(),
+            track_date: Default::default(),
+            track_musicbrainz_id: Default::default(),
+            track_artist_musicbrainz_id: Default::default(),
+            album_label: Default::default(),
+            album_superindex_total: Default::default(),
+            album_musicbrainz_id: Default::default(),
+            album_artist_musicbrainz_id: Default::default(),
         };
     }
 }
 
+/// Normalize a tag date (ex: `2001-02-03T04:05:06`, `2001.02`, `2001`) to
+/// `YYYY[-MM[-DD]]`, or `None` if it doesn't start with a year.
+pub fn normalize_date(v: &str) -> Option<String> {
+    let parts =
+        v
+            .trim()
+            .split(|c: char| !c.is_ascii_digit())
+            .take(3)
+            .take_while(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+    let year = parts.first()?;
+    if year.len() != 4 {
+        return None;
+    }
+    let mut out = year.to_string();
+    for p in &parts[1..] {
+        if p.len() != 2 {
+            break;
+        }
+        out.push('-');
+        out.push_str(p);
+    }
+    return Some(out);
+}
+
+/// The year from a date normalized with `normalize_date`.
+pub fn date_year(v: &str) -> Option<f64> {
+    return v.get(0 .. 4)?.parse::<f64>().ok();
+}
+
+/// Fill in missing fields from the first pattern that matches the end of the path
+/// (relative to the import root, without extension). Returns true if a pattern
+/// matched.
+pub fn apply_path_patterns(g: &mut Gather, rel_path: &Path, patterns: &[Pattern]) -> bool {
+    let rel_path = rel_path.with_extension("");
+    let segments = rel_path.iter().map(|s| s.to_string_lossy()).collect::<Vec<_>>();
+    for pattern in patterns {
+        let pattern_segments = pattern.parts.iter().map(|p| match p {
+            PatternPart::Lit(l) => l.matches('/').count(),
+            PatternPart::Field(_) => 0,
+        }).sum::<usize>() + 1;
+        if pattern_segments > segments.len() {
+            continue;
+        }
+        let text = segments[segments.len() - pattern_segments..].join("/");
+        let Some(fields) = pattern.extract(&text) else {
+            continue;
+        };
+        let mut index = None;
+        let mut disc = None;
+        if let Some(v) = fields.get("index") {
+            let Ok(v) = v.trim().parse::<f64>() else {
+                continue;
+            };
+            index = Some(v);
+        }
+        if let Some(v) = fields.get("disc") {
+            let Ok(v) = v.trim().parse::<f64>() else {
+                continue;
+            };
+            disc = Some(v);
+        }
+        for (k, v) in fields {
+            let v = v.trim().to_string();
+            match k.as_str() {
+                "artist" => if g.track_artist.is_empty() {
+                    g.track_artist.push(v);
+                },
+                "album_artist" => if g.album_artist.is_empty() {
+                    g.album_artist.insert(v);
+                },
+                "album" => if g.album_name.is_none() {
+                    g.album_name = Some(v);
+                },
+                "name" => if g.track_name.is_none() {
+                    g.track_name = Some(v);
+                },
+                "lang" => if g.track_language.is_none() {
+                    g.track_language = Some(v);
+                },
+                "date" => if g.track_date.is_none() {
+                    g.track_date = normalize_date(&v);
+                },
+                _ => { },
+            }
+        }
+        if g.track_index.is_none() {
+            g.track_index = index;
+        }
+        if g.track_superindex.is_none() {
+            g.track_superindex = disc;
+        }
+        return true;
+    }
+    return false;
+}
+
+/// Replace gathered metadata with values from a directory's import settings.
+pub fn apply_dir_overrides(g: &mut Gather, o: &CliImportDir) {
+    if let Some(v) = &o.album {
+        g.album_name = Some(v.clone());
+    }
+    if let Some(v) = &o.album_artist {
+        g.album_artist = v.iter().cloned().collect();
+    }
+    if let Some(v) = &o.artist {
+        g.track_artist = v.clone();
+    }
+    if let Some(v) = o.disc {
+        g.track_superindex = Some(v);
+    }
+    if let Some(v) = &o.lang {
+        g.track_language = Some(v.clone());
+    }
+    if let Some(v) = &o.date {
+        g.track_date = normalize_date(v);
+    }
+    if let Some(v) = &o.genre {
+        g.track_genre = v.iter().cloned().collect();
+    }
+}
+
 pub fn prep_cover(sunwet_dir: &Path, mime: &str, data: &[u8]) -> Result<Option<PathBuf>, loga::Error> {
     let suffix = match mime {
         "image/jpeg" => "jpg",
@@ -11,13 +11,22 @@ use {
         path::Path,
         str::FromStr,
     },
-    super::gather::{
-        Gather,
-        GatherMedia,
-        prep_cover,
+    super::{
+        gather::{
+            Gather,
+            GatherMedia,
+            normalize_date,
+            prep_cover,
+        },
+        gather_ffprobe,
     },
 };
 
+/// Multiple ids may be in one tag separated by `/` or `;`
+fn split_musicbrainz_ids(v: &str) -> Vec<String> {
+    return v.split(['/', ';']).map(|x| x.trim()).filter(|x| !x.is_empty()).map(|x| x.to_string()).collect();
+}
+
 pub fn gather(sunwet_dir: &Path, path: &Path, e: &OsStr) -> Result<Gather, loga::Error> {
     let mut g = Gather::new(GatherMedia::Audio);
     let mut info =
@@ -30,10 +39,15 @@ pub fn gather(sunwet_dir: &Path, path: &Path, e: &OsStr) -> Result<Gather, loga:
             &Default::default(),
         ) {
             Ok(i) => i,
+            Err(symphonia::core::errors::Error::Unsupported(_)) => {
+                // Wavpack, monkey's audio, etc.
+                return gather_ffprobe::gather(path, GatherMedia::Audio);
+            },
             Err(e) => {
                 return Err(e.context("Unable to read audio file"));
             },
         };
+    let mut original_date = None;
     let mut parse_metadata = |metadata: &symphonia::core::meta::MetadataRevision| {
         for tag in metadata.tags() {
             match tag.std_key {
@@ -49,13 +63,65 @@ pub fn gather(sunwet_dir: &Path, path: &Path, e: &OsStr) -> Result<Gather, loga:
                     },
                     symphonia::core::meta::StandardTagKey::DiscNumber => {
                         let v = tag.value.to_string();
-                        let v = v.split("/").next().unwrap();
+                        let mut v = v.split("/");
+                        let number = v.next().unwrap();
                         g.track_superindex =
                             Some(
                                 f64::from_str(
-                                    &v,
-                                ).context_with("Error converting disc number to float", ea!(text = v))?,
+                                    &number,
+                                ).context_with("Error converting disc number to float", ea!(text = number))?,
                             );
+                        if let Some(total) = v.next().and_then(|t| f64::from_str(t.trim()).ok()) {
+                            g.album_superindex_total = Some(total);
+                        }
+                    },
+                    symphonia::core::meta::StandardTagKey::DiscTotal => {
+                        let v = tag.value.to_string();
+                        g.album_superindex_total =
+                            Some(
+                                f64::from_str(
+                                    v.trim(),
+                                ).context_with("Error converting disc total to float", ea!(text = v))?,
+                            );
+                    },
+                    symphonia::core::meta::StandardTagKey::Genre => {
+                        for v in tag.value.to_string().split([';', '\0']) {
+                            let v = v.trim();
+                            if v.is_empty() {
+                                continue;
+                            }
+                            g.track_genre.insert(v.to_string());
+                        }
+                    },
+                    symphonia::core::meta::StandardTagKey::Composer => {
+                        g.track_composer.push(tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::Date |
+                    symphonia::core::meta::StandardTagKey::ReleaseDate => {
+                        if let Some(v) = normalize_date(&tag.value.to_string()) {
+                            g.track_date = Some(v);
+                        }
+                    },
+                    symphonia::core::meta::StandardTagKey::OriginalDate => {
+                        original_date = normalize_date(&tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::Label => {
+                        g.album_label = Some(tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::IdentIsrc => {
+                        g.track_isrc = Some(tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::MusicBrainzAlbumId => {
+                        g.album_musicbrainz_id = Some(tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::MusicBrainzRecordingId => {
+                        g.track_musicbrainz_id = Some(tag.value.to_string());
+                    },
+                    symphonia::core::meta::StandardTagKey::MusicBrainzArtistId => {
+                        g.track_artist_musicbrainz_id.extend(split_musicbrainz_ids(&tag.value.to_string()));
+                    },
+                    symphonia::core::meta::StandardTagKey::MusicBrainzAlbumArtistId => {
+                        g.album_artist_musicbrainz_id.extend(split_musicbrainz_ids(&tag.value.to_string()));
                     },
                     symphonia::core::meta::StandardTagKey::TrackNumber => {
                         let v = tag.value.to_string();
@@ -118,5 +184,8 @@ pub fn gather(sunwet_dir: &Path, path: &Path, e: &OsStr) -> Result<Gather, loga:
     if let Some(metadata) = info.format.metadata().current() {
         parse_metadata(metadata)?;
     }
+    if g.track_date.is_none() {
+        g.track_date = original_date;
+    }
     return Ok(g);
 }
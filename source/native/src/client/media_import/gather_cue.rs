@@ -0,0 +1,318 @@
+use {
+    loga::{
+        DebugDisplay,
+        Log,
+        ResultContext,
+        ea,
+    },
+    sha2::{
+        Digest,
+        Sha256,
+    },
+    std::{
+        collections::HashMap,
+        fs::{
+            create_dir_all,
+            read,
+            read_dir,
+            rename,
+        },
+        os::unix::ffi::OsStrExt,
+        path::{
+            Path,
+            PathBuf,
+        },
+        process::Command,
+        str::FromStr,
+    },
+    super::{
+        gather::{
+            Gather,
+            GatherMedia,
+            normalize_date,
+        },
+        gather_audio,
+    },
+};
+
+#[derive(Default)]
+pub(super) struct CueTrack {
+    pub number: f64,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub songwriter: Option<String>,
+    pub isrc: Option<String>,
+    /// Seconds, from `INDEX 01`
+    pub start: Option<f64>,
+}
+
+pub(super) struct CueFile {
+    pub name: String,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Default)]
+pub(super) struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub songwriter: Option<String>,
+    /// Upper case `REM` key -> value
+    pub rem: HashMap<String, String>,
+    pub files: Vec<CueFile>,
+}
+
+/// Split off the first (possibly quoted) argument
+fn arg(line: &str) -> (String, &str) {
+    let line = line.trim_start();
+    if let Some(rest) = line.strip_prefix('"') {
+        match rest.split_once('"') {
+            Some((v, rest)) => return (v.to_string(), rest),
+            None => return (rest.to_string(), ""),
+        }
+    }
+    match line.split_once(char::is_whitespace) {
+        Some((v, rest)) => return (v.to_string(), rest),
+        None => return (line.to_string(), ""),
+    }
+}
+
+/// `MM:SS:FF` with 75 frames per second
+fn parse_time(v: &str) -> Option<f64> {
+    let mut parts = v.split(':');
+    let m = f64::from_str(parts.next()?).ok()?;
+    let s = f64::from_str(parts.next()?).ok()?;
+    let f = f64::from_str(parts.next()?).ok()?;
+    return Some(m * 60. + s + f / 75.);
+}
+
+pub(super) fn parse(text: &str) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let (command, rest) = arg(line);
+        let (value, rest) = arg(rest);
+        let value = Some(value).filter(|v| !v.is_empty());
+        let in_track = sheet.files.last().is_some_and(|f| !f.tracks.is_empty());
+        match (command.to_ascii_uppercase().as_str(), in_track) {
+            ("REM", _) => {
+                if let Some(key) = value {
+                    sheet.rem.insert(key.to_ascii_uppercase(), arg(rest).0);
+                }
+            },
+            ("FILE", _) => {
+                if let Some(name) = value {
+                    sheet.files.push(CueFile {
+                        name: name,
+                        tracks: vec![],
+                    });
+                }
+            },
+            ("TRACK", _) => {
+                let Some(file) = sheet.files.last_mut() else {
+                    continue;
+                };
+                let Some(number) = value.and_then(|v| f64::from_str(&v).ok()) else {
+                    continue;
+                };
+                file.tracks.push(CueTrack {
+                    number: number,
+                    ..Default::default()
+                });
+            },
+            ("TITLE", false) => sheet.title = value,
+            ("PERFORMER", false) => sheet.performer = value,
+            ("SONGWRITER", false) => sheet.songwriter = value,
+            (command, true) => {
+                let track = sheet.files.last_mut().unwrap().tracks.last_mut().unwrap();
+                match command {
+                    "TITLE" => track.title = value,
+                    "PERFORMER" => track.performer = value,
+                    "SONGWRITER" => track.songwriter = value,
+                    "ISRC" => track.isrc = value,
+                    "INDEX" => {
+                        if value.as_deref().and_then(|v| u32::from_str(v).ok()) == Some(1) {
+                            track.start = parse_time(&arg(rest).0);
+                        }
+                    },
+                    _ => { },
+                }
+            },
+            _ => { },
+        }
+    }
+    return sheet;
+}
+
+fn is_lossless(p: &[u8]) -> bool {
+    match p {
+        b"flac" | b"ape" | b"wav" | b"wv" => true,
+        _ => false,
+    }
+}
+
+/// Cue sheets often reference a file with a different extension (ex: the `.wav`
+/// the rip was made from, later compressed to `.flac`)
+fn find_source(dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = dir.join(name);
+    if path.exists() {
+        return Some(path);
+    }
+    let stem = path.file_stem()?.to_os_string();
+    for entry in read_dir(dir).ok()? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let candidate = entry.path();
+        if candidate.file_stem() == Some(&stem) &&
+            is_lossless(candidate.extension().unwrap_or_default().as_bytes()) {
+            return Some(candidate);
+        }
+    }
+    return None;
+}
+
+fn split(source: &Path, start: f64, end: Option<f64>, dest: &Path) -> Result<(), loga::Error> {
+    if dest.exists() {
+        return Ok(());
+    }
+    let partial = dest.with_extension("partial");
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args(&["-v", "error", "-y"]);
+    cmd.arg("-i").arg(source);
+    cmd.arg("-ss").arg(format!("{:.6}", start));
+    if let Some(end) = end {
+        cmd.arg("-to").arg(format!("{:.6}", end));
+    }
+    cmd.args(&["-map", "0:a:0", "-map_metadata", "-1", "-c:a", "flac", "-f", "flac"]);
+    cmd.arg(&partial);
+    let output = cmd.output().context_with("Error running ffmpeg", ea!(command = cmd.dbg_str()))?;
+    if !output.status.success() {
+        return Err(loga::err_with("Error splitting track", ea!(command = cmd.dbg_str(), output = output.dbg_str())));
+    }
+    rename(&partial, dest).context_with("Error moving split track into place", ea!(path = dest.dbg_str()))?;
+    return Ok(());
+}
+
+pub struct GatherCueTrack {
+    /// The split track file (in the sunwet dir)
+    pub path: PathBuf,
+    /// The audio file the track was split from
+    pub source: PathBuf,
+    pub gather: Gather,
+}
+
+pub struct GatherCue {
+    /// The audio files the cue sheet describes, which should not be imported
+    /// separately
+    pub sources: Vec<PathBuf>,
+    pub tracks: Vec<GatherCueTrack>,
+}
+
+/// Split single-file album rips described by a cue sheet into per-track files.
+/// Returns `None` if the cue sheet doesn't describe a multi-track lossless file
+/// (ex: a per-track rip with a cue sheet), in which case it should be treated as
+/// a document.
+pub fn gather(log: &Log, sunwet_dir: &Path, path: &Path) -> Result<Option<GatherCue>, loga::Error> {
+    let sheet = parse(&String::from_utf8_lossy(&read(path).context("Error reading cue sheet")?));
+    if !sheet.files.iter().any(|f| f.tracks.len() > 1) {
+        return Ok(None);
+    }
+    let dir = path.parent().unwrap();
+    let mut out = GatherCue {
+        sources: vec![],
+        tracks: vec![],
+    };
+    let mut sources = vec![];
+    for file in &sheet.files {
+        let Some(source) = find_source(dir, &file.name) else {
+            log.log_with(
+                loga::WARN,
+                "Cue sheet references missing file, skipping cue sheet",
+                ea!(path = path.dbg_str(), file = file.name),
+            );
+            return Ok(None);
+        };
+        if !is_lossless(source.extension().unwrap_or_default().as_bytes()) {
+            log.log_with(
+                loga::WARN,
+                "Cue sheet references file that isn't lossless, skipping cue sheet",
+                ea!(path = path.dbg_str(), file = source.dbg_str()),
+            );
+            return Ok(None);
+        }
+        sources.push(source);
+    }
+
+    // Split tracks into a dir unique to the cue sheet and its contents
+    let split_dir = sunwet_dir.join("cue").join(hex::encode(&{
+        let mut hash = Sha256::new();
+        hash.update(path.as_os_str().as_bytes());
+        for source in &sources {
+            hash.update(source.metadata()?.len().to_le_bytes());
+        }
+        hash.finalize()
+    }[..8]));
+    create_dir_all(&split_dir).context("Error creating cue split dir")?;
+    for (file, source) in sheet.files.iter().zip(sources) {
+        // Covers, genre, etc. from the file's own tags
+        let base = match gather_audio::gather(sunwet_dir, &source, source.extension().unwrap_or_default()) {
+            Ok(g) => g,
+            Err(e) => {
+                log.log_err(
+                    loga::DEBUG,
+                    e.context_with("Error reading tags of cue sheet source file", ea!(path = source.dbg_str())),
+                );
+                Gather::new(GatherMedia::Audio)
+            },
+        };
+        for (i, track) in file.tracks.iter().enumerate() {
+            let Some(start) = track.start else {
+                log.log_with(
+                    loga::WARN,
+                    "Cue sheet track has no start index, skipping track",
+                    ea!(path = path.dbg_str(), track = track.number),
+                );
+                continue;
+            };
+            let end = file.tracks.get(i + 1).and_then(|t| t.start);
+            let dest = split_dir.join(format!("{:02}.flac", track.number));
+            split(&source, start, end, &dest)?;
+            let mut g = base.clone();
+            g.track_index = Some(track.number);
+            g.track_name = track.title.clone();
+            g.track_isrc = track.isrc.clone();
+            g.track_musicbrainz_id = None;
+            if let Some(v) = &sheet.title {
+                g.album_name = Some(v.clone());
+            }
+            if let Some(v) = &sheet.performer {
+                g.album_artist = [v.clone()].into_iter().collect();
+            }
+            if let Some(v) = track.performer.as_ref().or(sheet.performer.as_ref()) {
+                g.track_artist = vec![v.clone()];
+                g.track_artist_musicbrainz_id = vec![];
+            }
+            if let Some(v) = track.songwriter.as_ref().or(sheet.songwriter.as_ref()) {
+                g.track_composer = vec![v.clone()];
+            }
+            if let Some(v) = sheet.rem.get("GENRE") {
+                g.track_genre = [v.clone()].into_iter().collect();
+            }
+            if let Some(v) = sheet.rem.get("DATE").and_then(|v| normalize_date(v)) {
+                g.track_date = Some(v);
+            }
+            if let Some(v) = sheet.rem.get("DISCNUMBER").and_then(|v| f64::from_str(v).ok()) {
+                g.track_superindex = Some(v);
+            }
+            if let Some(v) = sheet.rem.get("TOTALDISCS").and_then(|v| f64::from_str(v).ok()) {
+                g.album_superindex_total = Some(v);
+            }
+            out.tracks.push(GatherCueTrack {
+                path: dest,
+                source: source.clone(),
+                gather: g,
+            });
+        }
+        out.sources.push(source);
+    }
+    return Ok(Some(out));
+}
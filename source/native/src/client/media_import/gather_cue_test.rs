@@ -0,0 +1,75 @@
+#![cfg(test)]
+
+use {
+    super::gather_cue::parse,
+    pretty_assertions::assert_eq,
+};
+
+#[test]
+fn test_cue_index_timing() {
+    let sheet = parse(concat!(
+        "FILE \"album.wav\" WAVE\n",
+        "  TRACK 01 AUDIO\n",
+        "    INDEX 01 00:00:00\n",
+        "  TRACK 02 AUDIO\n",
+        "    INDEX 00 03:58:10\n",
+        "    INDEX 01 04:00:15\n",
+        "  TRACK 03 AUDIO\n",
+    ));
+    assert_eq!(sheet.files.len(), 1);
+    let tracks = &sheet.files[0].tracks;
+    assert_eq!(tracks.iter().map(|t| t.number).collect::<Vec<_>>(), vec![1., 2., 3.]);
+    assert_eq!(tracks[0].start, Some(0.));
+
+    // Pregap (`INDEX 00`) is ignored, frames are 1/75 s
+    assert_eq!(tracks[1].start, Some(4. * 60. + 0. + 15. / 75.));
+    assert_eq!(tracks[2].start, None);
+}
+
+#[test]
+fn test_cue_multiple_files() {
+    let sheet = parse(concat!(
+        "\u{feff}REM GENRE Rock\n",
+        "REM DATE 1999\n",
+        "FILE \"disc 1.flac\" WAVE\n",
+        "  TRACK 01 AUDIO\n",
+        "    INDEX 01 00:00:00\n",
+        "  TRACK 02 AUDIO\n",
+        "    INDEX 01 02:00:00\n",
+        "FILE \"disc 2.flac\" WAVE\n",
+        "  TRACK 03 AUDIO\n",
+        "    INDEX 01 00:00:00\n",
+    ));
+    assert_eq!(sheet.rem.get("GENRE").map(|v| v.as_str()), Some("Rock"));
+    assert_eq!(sheet.rem.get("DATE").map(|v| v.as_str()), Some("1999"));
+    assert_eq!(sheet.files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["disc 1.flac", "disc 2.flac"]);
+    assert_eq!(sheet.files[0].tracks.len(), 2);
+    assert_eq!(sheet.files[1].tracks.len(), 1);
+    assert_eq!(sheet.files[1].tracks[0].number, 3.);
+    assert_eq!(sheet.files[1].tracks[0].start, Some(0.));
+}
+
+#[test]
+fn test_cue_quoted_titles() {
+    let sheet = parse(concat!(
+        "PERFORMER \"The Band\"\n",
+        "TITLE \"An Album: Part 1\"\n",
+        "FILE \"album.flac\" WAVE\n",
+        "  TRACK 01 AUDIO\n",
+        "    TITLE \"First Song (Live)\"\n",
+        "    PERFORMER Solo\n",
+        "    ISRC USABC9900001\n",
+        "    INDEX 01 00:00:00\n",
+        "  TRACK 02 AUDIO\n",
+        "    TITLE \"Unterminated\n",
+        "    INDEX 01 03:00:00\n",
+    ));
+    assert_eq!(sheet.performer.as_deref(), Some("The Band"));
+    assert_eq!(sheet.title.as_deref(), Some("An Album: Part 1"));
+    let tracks = &sheet.files[0].tracks;
+    assert_eq!(tracks[0].title.as_deref(), Some("First Song (Live)"));
+    assert_eq!(tracks[0].performer.as_deref(), Some("Solo"));
+    assert_eq!(tracks[0].isrc.as_deref(), Some("USABC9900001"));
+    assert_eq!(tracks[1].title.as_deref(), Some("Unterminated"));
+    assert_eq!(tracks[1].start, Some(180.));
+}
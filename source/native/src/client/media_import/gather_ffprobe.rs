@@ -0,0 +1,126 @@
+use {
+    loga::{
+        DebugDisplay,
+        ResultContext,
+        ea,
+    },
+    serde::Deserialize,
+    std::{
+        collections::HashMap,
+        path::Path,
+        process::{
+            Command,
+            Stdio,
+        },
+        str::FromStr,
+    },
+    super::gather::{
+        Gather,
+        GatherMedia,
+        normalize_date,
+    },
+};
+
+#[derive(Deserialize)]
+struct FfprobeFormat {
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct FfprobeOutput {
+    format: FfprobeFormat,
+}
+
+/// `3` or `3/10`
+fn parse_number(v: &str) -> Option<(f64, Option<f64>)> {
+    let mut v = v.split("/");
+    let number = f64::from_str(v.next()?.trim()).ok()?;
+    let total = v.next().and_then(|t| f64::from_str(t.trim()).ok());
+    return Some((number, total));
+}
+
+/// Get metadata from container-level tags using `ffprobe`, for formats without
+/// a more specific gatherer (mp4, avi, wavpack, etc).
+pub fn gather(path: &Path, media: GatherMedia) -> Result<Gather, loga::Error> {
+    let mut g = Gather::new(media);
+    let mut cmd = Command::new("ffprobe");
+    cmd.stdin(Stdio::null());
+    cmd.args(&["-v", "quiet"]);
+    cmd.args(&["-print_format", "json"]);
+    cmd.arg("-show_format");
+    cmd.arg(path);
+    let output = cmd.output().context_with("Error running ffprobe", ea!(command = cmd.dbg_str()))?;
+    if !output.status.success() {
+        return Err(loga::err_with("Ffprobe failed", ea!(command = cmd.dbg_str(), output = output.dbg_str())));
+    }
+    let output =
+        serde_json::from_slice::<FfprobeOutput>(&output.stdout).context("Error parsing ffprobe output json")?;
+
+    // Tag key case varies by container
+    let tags =
+        output
+            .format
+            .tags
+            .into_iter()
+            .map(|(k, v)| (k.to_ascii_lowercase(), v.trim().to_string()))
+            .filter(|(_, v)| !v.is_empty())
+            .collect::<HashMap<_, _>>();
+    if let Some(v) = tags.get("title") {
+        g.track_name = Some(v.clone());
+    }
+    if let Some(v) = tags.get("artist") {
+        g.track_artist.push(v.clone());
+    }
+    if let Some(v) = tags.get("album_artist").or_else(|| tags.get("album artist")) {
+        g.album_artist.insert(v.clone());
+    }
+    if let Some(v) = tags.get("composer") {
+        g.track_composer.push(v.clone());
+    }
+    if let Some(v) = tags.get("genre") {
+        for v in v.split([';', '\0']) {
+            let v = v.trim();
+            if v.is_empty() {
+                continue;
+            }
+            g.track_genre.insert(v.to_string());
+        }
+    }
+    if let Some(v) = tags.get("date").or_else(|| tags.get("year")).or_else(|| tags.get("creation_time")) {
+        g.track_date = normalize_date(v);
+    }
+    if let Some(v) = tags.get("language") {
+        g.track_language = Some(v.clone());
+    }
+    if let Some(v) = tags.get("isrc") {
+        g.track_isrc = Some(v.clone());
+    }
+    if let Some(v) = tags.get("label").or_else(|| tags.get("publisher")) {
+        g.album_label = Some(v.clone());
+    }
+
+    // TV episodes (iTunes-style mp4 tags) are grouped by show, with the season as
+    // the superindex
+    if let Some(show) = tags.get("show") {
+        g.album_name = Some(show.clone());
+        if let Some(v) = tags.get("episode_sort").and_then(|v| parse_number(v)) {
+            g.track_index = Some(v.0);
+        }
+        if let Some(v) = tags.get("season_number").and_then(|v| parse_number(v)) {
+            g.track_superindex = Some(v.0);
+        }
+    } else {
+        if let Some(v) = tags.get("album") {
+            g.album_name = Some(v.clone());
+        }
+        if let Some(v) = tags.get("track").or_else(|| tags.get("tracknumber")).and_then(|v| parse_number(v)) {
+            g.track_index = Some(v.0);
+        }
+        if let Some(v) = tags.get("disc").or_else(|| tags.get("discnumber")).and_then(|v| parse_number(v)) {
+            g.track_superindex = Some(v.0);
+            g.album_superindex_total = v.1;
+        }
+    }
+    return Ok(g);
+}
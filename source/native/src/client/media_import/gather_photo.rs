@@ -0,0 +1,145 @@
+use {
+    flowcontrol::shed,
+    loga::{
+        DebugDisplay,
+        ErrContext,
+        ResultContext,
+        ea,
+    },
+    std::{
+        fs::File,
+        io::BufReader,
+        path::Path,
+    },
+};
+
+#[derive(Default)]
+pub struct GatherPhoto {
+    /// `YYYY-MM-DDTHH:MM:SS`, with a utc offset suffix if known
+    pub capture_time: Option<String>,
+    pub camera: Option<String>,
+    /// EXIF orientation, 1-8
+    pub orientation: Option<f64>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+}
+
+fn field_ascii(exif: &exif::Exif, tag: exif::Tag) -> Option<String> {
+    let field = exif.get_field(tag, exif::In::PRIMARY)?;
+    let exif::Value::Ascii(v) = &field.value else {
+        return None;
+    };
+    let v = String::from_utf8_lossy(v.first()?).trim().trim_end_matches('\0').trim().to_string();
+    if v.is_empty() {
+        return None;
+    }
+    return Some(v);
+}
+
+/// Degrees, minutes, seconds to decimal degrees, negated if the reference is in
+/// `negative_ref` (south, west)
+fn field_coordinate(exif: &exif::Exif, tag: exif::Tag, ref_tag: exif::Tag, negative_ref: &str) -> Option<f64> {
+    let field = exif.get_field(tag, exif::In::PRIMARY)?;
+    let exif::Value::Rational(v) = &field.value else {
+        return None;
+    };
+    let mut out = 0.;
+    for (i, part) in v.iter().take(3).enumerate() {
+        out += part.to_f64() / 60f64.powi(i as i32);
+    }
+    if !out.is_finite() {
+        return None;
+    }
+    if field_ascii(exif, ref_tag).as_deref() == Some(negative_ref) {
+        out = -out;
+    }
+    return Some(out);
+}
+
+pub fn gather(path: &Path) -> Result<GatherPhoto, loga::Error> {
+    let mut g = GatherPhoto::default();
+    let f = File::open(path).context_with("Error opening image", ea!(path = path.dbg_str()))?;
+    let exif = match exif::Reader::new().read_from_container(&mut BufReader::new(f)) {
+        Ok(e) => e,
+        Err(exif::Error::NotFound(_)) => {
+            return Ok(g);
+        },
+        Err(e) => {
+            return Err(e.context_with("Error reading image EXIF data", ea!(path = path.dbg_str())));
+        },
+    };
+    if let Some(raw) =
+        field_ascii(&exif, exif::Tag::DateTimeOriginal).or_else(|| field_ascii(&exif, exif::Tag::DateTime)) {
+        if let Ok(mut t) = exif::DateTime::from_ascii(raw.as_bytes()) {
+            if let Some(offset) = field_ascii(&exif, exif::Tag::OffsetTimeOriginal) {
+                _ = t.parse_offset(offset.as_bytes());
+            }
+            let mut out =
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    t.year,
+                    t.month,
+                    t.day,
+                    t.hour,
+                    t.minute,
+                    t.second
+                );
+            if let Some(offset) = t.offset {
+                out.push_str(
+                    &format!(
+                        "{}{:02}:{:02}",
+                        if offset < 0 {
+                            "-"
+                        } else {
+                            "+"
+                        },
+                        offset.abs() / 60,
+                        offset.abs() % 60
+                    ),
+                );
+            }
+            g.capture_time = Some(out);
+        }
+    }
+    g.camera = match (field_ascii(&exif, exif::Tag::Make), field_ascii(&exif, exif::Tag::Model)) {
+        (Some(make), Some(model)) => {
+            // Models often already include the make
+            if model.to_ascii_lowercase().starts_with(&make.to_ascii_lowercase()) {
+                Some(model)
+            } else {
+                Some(format!("{} {}", make, model))
+            }
+        },
+        (make, model) => model.or(make),
+    };
+    g.orientation =
+        exif
+            .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+            .and_then(|f| f.value.get_uint(0))
+            .map(|v| v as f64);
+    g.latitude = field_coordinate(&exif, exif::Tag::GPSLatitude, exif::Tag::GPSLatitudeRef, "S");
+    g.longitude = field_coordinate(&exif, exif::Tag::GPSLongitude, exif::Tag::GPSLongitudeRef, "W");
+    shed!{
+        let Some(field) = exif.get_field(exif::Tag::GPSAltitude, exif::In::PRIMARY) else {
+            break;
+        };
+        let exif::Value::Rational(v) = &field.value else {
+            break;
+        };
+        let Some(altitude) = v.first().map(|x| x.to_f64()).filter(|x| x.is_finite()) else {
+            break;
+        };
+        let below_sea =
+            exif
+                .get_field(exif::Tag::GPSAltitudeRef, exif::In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0)) ==
+                Some(1);
+        g.altitude = Some(if below_sea {
+            -altitude
+        } else {
+            altitude
+        });
+    }
+    return Ok(g);
+}
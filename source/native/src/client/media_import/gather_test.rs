@@ -0,0 +1,36 @@
+#![cfg(test)]
+
+use {
+    super::gather::normalize_date,
+    pretty_assertions::assert_eq,
+};
+
+#[test]
+fn test_normalize_date() {
+    for (input, want) in [
+        // Accepted formats
+        ("2001", Some("2001")),
+        ("2001-02", Some("2001-02")),
+        ("2001-02-03", Some("2001-02-03")),
+        ("2001.02.03", Some("2001-02-03")),
+        ("2001/02/03", Some("2001-02-03")),
+        ("2001-02-03T04:05:06", Some("2001-02-03")),
+        ("2001-02-03 04:05:06", Some("2001-02-03")),
+        (" 2001-02-03\n", Some("2001-02-03")),
+        // Partial dates keep the leading well-formed parts
+        ("2001-", Some("2001")),
+        ("2001-2-3", Some("2001")),
+        ("2001-02-3", Some("2001-02")),
+        ("2001--03", Some("2001")),
+        // Invalid
+        ("", None),
+        ("   ", None),
+        ("unknown", None),
+        ("March 2001", None),
+        ("01-02-2001", None),
+        ("20010203", None),
+        ("99", None),
+    ] {
+        assert_eq!(normalize_date(input).as_deref(), want, "input {:?}", input);
+    }
+}
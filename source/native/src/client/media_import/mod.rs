@@ -1,19 +1,38 @@
 pub mod gather;
 pub mod gather_audio;
 pub mod gather_comic;
+pub mod gather_cue;
+pub mod gather_cue_test;
 pub mod gather_epub;
+pub mod gather_ffprobe;
+pub mod gather_photo;
+pub mod gather_test;
 pub mod gather_video;
+pub mod watch;
 
 use {
     aargvark::Aargvark,
     by_address::ByAddress,
     chrono::Utc,
-    crate::client::req::{
-        ENV_SUNWET,
-        self,
+    crate::{
+        client::{
+            commit::{
+                CommitDiff,
+                diff_commit,
+            },
+            req::{
+                ENV_SUNWET,
+                self,
+            },
+        },
+        server::filesutil::hash_file_sha256,
     },
     flowcontrol::shed,
-    gather::GatherMedia,
+    gather::{
+        GatherMedia,
+        date_year,
+    },
+    gather_photo::GatherPhoto,
     loga::{
         DebugDisplay,
         ErrContext,
@@ -21,10 +40,12 @@ use {
         ResultContext,
         ea,
     },
+    serde::Serialize,
     shared::{
         interface::{
             cli::{
                 CliCommit,
+                CliImportDir,
                 CliNode,
                 CliTriple,
             },
@@ -38,17 +59,31 @@ use {
                 OBJ_MEDIA_IMAGE,
                 OBJ_MEDIA_VIDEO,
                 PREDICATE_ADD_TIMESTAMP,
+                PREDICATE_ALTITUDE,
                 PREDICATE_ARTIST,
+                PREDICATE_CAMERA,
+                PREDICATE_CAPTURE_TIMESTAMP,
+                PREDICATE_COMPOSER,
                 PREDICATE_COVER,
                 PREDICATE_DOC,
                 PREDICATE_FILE,
+                PREDICATE_GENRE,
                 PREDICATE_INDEX,
                 PREDICATE_IS,
+                PREDICATE_ISRC,
+                PREDICATE_LABEL,
                 PREDICATE_LANG,
+                PREDICATE_LATITUDE,
+                PREDICATE_LONGITUDE,
                 PREDICATE_MEDIA,
+                PREDICATE_MUSICBRAINZ_ID,
                 PREDICATE_NAME,
+                PREDICATE_ORIENTATION,
+                PREDICATE_RELEASE_DATE,
                 PREDICATE_SUPERINDEX,
+                PREDICATE_SUPERINDEX_TOTAL,
                 PREDICATE_TRACK,
+                PREDICATE_YEAR,
             },
             query::{
                 ChainHead,
@@ -73,12 +108,14 @@ use {
             },
             triple::Node,
             wire::{
+                ReqGetTriplesAround,
                 ReqQuery,
                 RespQueryRows,
                 TreeNode,
             },
         },
         query_parser::compile_query,
+        stringpattern::Pattern,
     },
     std::{
         cell::RefCell,
@@ -93,6 +130,7 @@ use {
         fs::{
             File,
             create_dir_all,
+            read,
             write,
         },
         io,
@@ -101,33 +139,54 @@ use {
             Path,
             PathBuf,
         },
+        process::Command,
         rc::Rc,
     },
+    tempfile::tempdir,
     uuid::Uuid,
     walkdir::WalkDir,
 };
 
+/// An import entity matched multiple existing entities.
+#[derive(Serialize)]
+pub struct AmbiguousMatch {
+    pub query: Query,
+    pub parameters: HashMap<String, Node>,
+    pub candidates: Vec<Node>,
+    /// The new id used instead
+    pub id: Node,
+}
+
+/// Matches entities being imported with existing entities on the server.
+#[derive(Default)]
+pub struct IdResolver {
+    query_cache: HashMap<&'static str, Query>,
+    /// If set, ambiguous matches are recorded here and a new id is used rather than
+    /// failing.
+    pub ambiguous: Option<Vec<AmbiguousMatch>>,
+}
+
+fn new_id() -> Node {
+    return Node::Value(serde_json::Value::String(Uuid::new_v4().hyphenated().to_string()));
+}
+
 pub async fn node_id(
     log: &Log,
-    query_cache: &mut HashMap<&'static str, Query>,
+    resolver: &mut IdResolver,
     query: &'static str,
     parameters: HashMap<String, Node>,
 ) -> Result<Node, loga::Error> {
-    let query = match query_cache.entry(query) {
+    let query = match resolver.query_cache.entry(query) {
         Entry::Occupied(en) => en.get().clone(),
         Entry::Vacant(en) => en.insert(compile_query(query).map_err(loga::err)?).clone(),
     };
-    return node_id_direct(log, query, parameters).await;
+    return node_id_direct(log, resolver, query, parameters).await;
 }
 
-pub async fn node_id_artist(
-    log: &Log,
-    query_cache: &mut HashMap<&'static str, Query>,
-    name: &str,
-) -> Result<Node, loga::Error> {
+pub async fn node_id_artist(log: &Log, resolver: &mut IdResolver, name: &str) -> Result<Node, loga::Error> {
     return node_id(
         log,
-        query_cache,
+        resolver,
         r#"$artist_name -< "sunwet/1/name" ?( -> "sunwet/1/is" == "sunwet/1/artist" ) { => id }"#,
         [(format!("artist_name"), Node::from_str(name))].into_iter().collect(),
     ).await;
@@ -135,69 +194,72 @@ pub async fn node_id_artist(
 
 pub async fn node_id_direct(
     log: &Log,
+    resolver: &mut IdResolver,
     query: Query,
     parameters: HashMap<String, Node>,
 ) -> Result<Node, loga::Error> {
     if env::var_os(ENV_SUNWET).is_none() {
-        return Ok(Node::Value(serde_json::Value::String(Uuid::new_v4().hyphenated().to_string())));
+        return Ok(new_id());
     }
     let resp = req::req_simple(&log, ReqQuery {
         query: query.clone(),
         parameters: parameters.clone(),
         pagination: None,
     }).await?.rows;
+    let mut candidates = vec![];
     match resp {
         RespQueryRows::Scalar(rows) => {
-            let mut rows_iter = rows.iter();
-            let Some(first) = rows_iter.next() else {
-                return Ok(Node::Value(serde_json::Value::String(Uuid::new_v4().hyphenated().to_string())));
-            };
-            if rows_iter.next().is_some() {
-                return Err(
-                    loga::err_with(
-                        "Imported node id can't be matched, multiple potential existing nodes found",
-                        ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
-                    ),
-                );
-            }
-            return Ok(first.clone());
+            candidates = rows;
         },
         RespQueryRows::Record(rows) => {
-            let mut rows_iter = rows.iter();
-            let Some(first) = rows_iter.next() else {
-                return Ok(Node::Value(serde_json::Value::String(Uuid::new_v4().hyphenated().to_string())));
-            };
-            if rows_iter.next().is_some() {
-                return Err(
-                    loga::err_with(
-                        "Imported node id can't be matched, multiple potential existing nodes found",
-                        ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
-                    ),
-                );
+            for row in &rows {
+                let Some(id) = row.get("id") else {
+                    return Err(
+                        loga::err_with(
+                            "Assertion! Returned record missing [id] field",
+                            ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
+                        ),
+                    );
+                };
+                let TreeNode::Scalar(id) = id else {
+                    return Err(
+                        loga::err_with(
+                            "Assertion! Found id is not a scalar node (is array or record; bad query)",
+                            ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
+                        ),
+                    );
+                };
+                candidates.push(id.clone());
             }
-            let Some(id) = first.get("id") else {
-                return Err(
-                    loga::err_with(
-                        "Assertion! Returned record missing [id] field",
-                        ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
-                    ),
-                );
-            };
-            let TreeNode::Scalar(id) = id else {
-                return Err(
-                    loga::err_with(
-                        "Assertion! Found id is not a scalar node (is array or record; bad query)",
-                        ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = rows.dbg_str()),
-                    ),
-                );
-            };
-            return Ok(id.clone());
         },
     }
+    if candidates.len() > 1 {
+        let Some(ambiguous) = &mut resolver.ambiguous else {
+            return Err(
+                loga::err_with(
+                    "Imported node id can't be matched, multiple potential existing nodes found",
+                    ea!(query = query.dbg_str(), params = parameters.dbg_str(), res = candidates.dbg_str()),
+                ),
+            );
+        };
+        let id = new_id();
+        ambiguous.push(AmbiguousMatch {
+            query: query,
+            parameters: parameters,
+            candidates: candidates,
+            id: id.clone(),
+        });
+        return Ok(id);
+    }
+    match candidates.into_iter().next() {
+        Some(id) => return Ok(id),
+        None => return Ok(new_id()),
+    }
 }
 
+/// Paths outside the root (temporary files in dry runs) are left absolute.
 pub fn node_upload(root: &Path, p: &Path) -> CliNode {
-    return CliNode::Upload(p.strip_prefix(root).unwrap().to_path_buf());
+    return CliNode::Upload(p.strip_prefix(root).unwrap_or(p).to_path_buf());
 }
 
 pub fn node_value_str(v: &str) -> CliNode {
@@ -264,18 +326,122 @@ fn is_image(p: &[u8]) -> bool {
 
 fn is_audio(p: &[u8]) -> bool {
     match p {
-        b"mp3" | b"m4a" | b"aac" | b"ogg" | b"flac" | b"alac" => true,
+        b"mp3" | b"m4a" | b"aac" | b"ogg" | b"opus" | b"flac" | b"alac" | b"wav" | b"wv" | b"ape" => true,
         _ => false,
     }
 }
 
 fn is_video(p: &[u8]) -> bool {
     match p {
-        b"mkv" => true,
+        b"mkv" | b"webm" | b"mp4" | b"m4v" | b"mov" | b"avi" => true,
+        _ => false,
+    }
+}
+
+/// Matroska-based, tags can be read with `mkvextract`
+fn is_matroska(p: &[u8]) -> bool {
+    match p {
+        b"mkv" | b"webm" => true,
         _ => false,
     }
 }
 
+enum ArchiveType {
+    Zip,
+    /// Optionally compressed, extracted with `tar`
+    Tar,
+    /// Anything else `7zz` can extract
+    SevenZip,
+}
+
+/// Returns the archive type and the file name with the archive extension(s)
+/// removed.
+fn archive_type(path: &Path) -> Option<(ArchiveType, String)> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let lower = name.to_ascii_lowercase();
+    for (suffix, type_) in [
+        (".zip", ArchiveType::Zip),
+        (".tar", ArchiveType::Tar),
+        (".tar.gz", ArchiveType::Tar),
+        (".tgz", ArchiveType::Tar),
+        (".tar.bz2", ArchiveType::Tar),
+        (".tbz2", ArchiveType::Tar),
+        (".tar.xz", ArchiveType::Tar),
+        (".txz", ArchiveType::Tar),
+        (".tar.zst", ArchiveType::Tar),
+        (".7z", ArchiveType::SevenZip),
+        (".rar", ArchiveType::SevenZip),
+    ] {
+        if lower.ends_with(suffix) {
+            return Some((type_, name[..name.len() - suffix.len()].to_string()));
+        }
+    }
+    return None;
+}
+
+fn extract_archive(log: &Log, type_: ArchiveType, source: &Path, dest: &Path) -> Result<(), loga::Error> {
+    match type_ {
+        ArchiveType::Zip => {
+            let mut zip =
+                zip::ZipArchive::new(
+                    File::open(source).stack_context(&log, "Error opening file")?,
+                ).stack_context(&log, "Error opening file as zip archive")?;
+            for i in 0 .. zip.len() {
+                match (|| {
+                    let mut file = zip.by_index(i)?;
+                    let log = Log::new().fork(ea!(archive_path = file.name()));
+                    let mut out =
+                        File::create(
+                            dest.join(file.enclosed_name().stack_context(&log, "File has invalid name")?),
+                        ).stack_context(&log, "Error creating file in output directory")?;
+                    io::copy(&mut file, &mut out).stack_context(&log, "Error extracting contents")?;
+                    return Ok(()) as Result<(), loga::Error>;
+                })() {
+                    Ok(_) => (),
+                    Err(e) => {
+                        log.log_err(
+                            loga::WARN,
+                            e.context_with("Error extracting file from archive, skipping", ea!(index = i)),
+                        );
+                    },
+                }
+            }
+        },
+        ArchiveType::Tar => {
+            let mut cmd = Command::new("tar");
+            cmd.arg("-x");
+            cmd.arg("-f").arg(source);
+            cmd.arg("-C").arg(dest);
+            let output = cmd.output().context_with("Error running tar", ea!(command = cmd.dbg_str()))?;
+            if !output.status.success() {
+                return Err(
+                    loga::err_with(
+                        "Error extracting tar archive",
+                        ea!(command = cmd.dbg_str(), output = output.dbg_str()),
+                    ),
+                );
+            }
+        },
+        ArchiveType::SevenZip => {
+            let mut cmd = Command::new("7zz");
+            cmd.arg("x");
+            cmd.arg("-y");
+            cmd.arg(format!("-o{}", dest.to_string_lossy()));
+            cmd.arg(source);
+            let output = cmd.output().context_with("Error running 7zz", ea!(command = cmd.dbg_str()))?;
+            if !output.status.success() {
+                return Err(
+                    loga::err_with(
+                        "Error extracting archive",
+                        ea!(command = cmd.dbg_str(), output = output.dbg_str()),
+                    ),
+                );
+            }
+        },
+    }
+    return Ok(());
+}
+
 fn is_comic(p: &[u8]) -> bool {
     match p {
         b"cbz" | b"cbr" | b"cb7" => true,
@@ -414,16 +580,66 @@ fn query_album_track(album: Node, superindex: Option<f64>, index: Option<f64>, n
     };
 }
 
-async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
+const IMPORT_DIR_SETTINGS: &str = "sunwet_import.json";
+
+/// Find and parse the import settings for `dir`, from the dir or its nearest
+/// ancestor within `root_dir`.
+fn import_dir_settings(
+    cache: &mut HashMap<PathBuf, Option<Rc<(CliImportDir, Vec<Pattern>)>>>,
+    root_dir: &Path,
+    dir: &Path,
+) -> Result<Option<Rc<(CliImportDir, Vec<Pattern>)>>, loga::Error> {
+    if let Some(found) = cache.get(dir) {
+        return Ok(found.clone());
+    }
+    let path = dir.join(IMPORT_DIR_SETTINGS);
+    let found = if path.exists() {
+        let settings =
+            serde_json::from_slice::<CliImportDir>(
+                &read(&path).context_with("Error reading import dir settings", ea!(path = path.dbg_str()))?,
+            ).context_with("Error parsing import dir settings", ea!(path = path.dbg_str()))?;
+        let patterns = settings.patterns.iter().map(|p| Pattern::from(p.as_str())).collect::<Vec<_>>();
+        Some(Rc::new((settings, patterns)))
+    } else if dir == root_dir {
+        None
+    } else {
+        match dir.parent() {
+            Some(parent) if parent.starts_with(root_dir) => import_dir_settings(cache, root_dir, parent)?,
+            _ => None,
+        }
+    };
+    cache.insert(dir.to_path_buf(), found.clone());
+    return Ok(found);
+}
+
+async fn import_dir(
+    log: &Log,
+    root_dir: &PathBuf,
+    photos: Option<PhotoGrouping>,
+    dry_run: bool,
+    patterns: &[String],
+) -> Result<(), loga::Error> {
     let sunwet_out_meta_dir = root_dir.join("sunwet");
     let sunwet_out_meta = root_dir.join("sunwet.json");
-    create_dir_all(&sunwet_out_meta_dir).context("Error making sunwet dir")?;
+
+    // Extracted covers and split tracks go in the sunwet dir, or a temp dir that's
+    // deleted afterwards for dry runs so nothing in the import dir is touched
+    let sunwet_temp_dir;
+    let sunwet_write_dir;
+    if dry_run {
+        sunwet_temp_dir = tempdir().context("Error making temp dir for dry run")?;
+        sunwet_write_dir = sunwet_temp_dir.path().to_path_buf();
+    } else {
+        create_dir_all(&sunwet_out_meta_dir).context("Error making sunwet dir")?;
+        sunwet_write_dir = sunwet_out_meta_dir.clone();
+    }
     let timestamp = node_value_str(&Utc::now().to_rfc3339());
 
     // Gather metadata from tracks, prepare dir-associated data
     struct GatherArtist {
         id: Node,
         name: String,
+        musicbrainz_id: Option<String>,
     }
 
     struct GatherTrack {
@@ -434,6 +650,10 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
         artist: Vec<Rc<RefCell<GatherArtist>>>,
         name: Option<String>,
         lang: Option<String>,
+        genre: BTreeSet<String>,
+        composer: Vec<Rc<RefCell<GatherArtist>>>,
+        isrc: Option<String>,
+        musicbrainz_id: Option<String>,
         // Precedence -> hash -> prevalence in tracks
         covers: BTreeMap<usize, HashMap<PathBuf, usize>>,
     }
@@ -442,6 +662,11 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
         name: String,
         artist: BTreeSet<ByAddress<Rc<RefCell<GatherArtist>>>>,
         tracks: Vec<Rc<RefCell<GatherTrack>>>,
+        genre: BTreeSet<String>,
+        date: Option<String>,
+        label: Option<String>,
+        superindex_total: Option<f64>,
+        musicbrainz_id: Option<String>,
         // Precedence -> hash -> prevalence in tracks
         covers: BTreeMap<usize, HashMap<PathBuf, usize>>,
         documents: Vec<PathBuf>,
@@ -462,10 +687,37 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
         lang: Option<String>,
     }
 
-    let mut query_cache = HashMap::new();
+    let mut resolver = IdResolver::default();
+    if dry_run {
+        resolver.ambiguous = Some(vec![]);
+    }
     let mut albums = HashMap::<AlbumKey, Rc<RefCell<GatherAlbum>>>::new();
     let mut artists = HashMap::<String, Rc<RefCell<GatherArtist>>>::new();
     let mut leftover_files = vec![];
+    let patterns = patterns.iter().map(|p| Pattern::from(p.as_str())).collect::<Vec<_>>();
+
+    // Dir -> settings from `sunwet_import.json` in the dir or nearest ancestor
+    let mut dir_settings = HashMap::<PathBuf, Option<Rc<(CliImportDir, Vec<Pattern>)>>>::new();
+
+    // List files, splitting single-file albums with cue sheets into tracks
+    struct ImportFile {
+        path: PathBuf,
+        /// The dir the file is associated with for settings, documents and covers. For
+        /// split tracks this is the cue sheet's dir.
+        dir: PathBuf,
+        /// The path metadata patterns are matched against. For split tracks this is the
+        /// audio file the track was split from.
+        pattern_path: PathBuf,
+    }
+
+    impl ImportFile {
+        fn path(&self) -> &Path {
+            return &self.path;
+        }
+    }
+
+    let mut files = vec![];
+    let mut cue_sources = HashSet::new();
     for file in WalkDir::new(&root_dir) {
         let file = match file {
             Ok(f) => f,
@@ -474,7 +726,8 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
                 continue;
             },
         };
-        if file.path().starts_with(&sunwet_out_meta_dir) || file.path() == sunwet_out_meta {
+        if file.path().starts_with(&sunwet_out_meta_dir) || file.path() == sunwet_out_meta ||
+            file.file_name() == IMPORT_DIR_SETTINGS {
             continue;
         }
         let meta = file.metadata()?;
@@ -484,21 +737,62 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
         if file.path().file_name().is_none() || file.path().file_name().unwrap().as_bytes().starts_with(b".") {
             continue;
         }
+        let dir = file.path().parent().unwrap().to_path_buf();
+        if file.path().extension().unwrap_or_default().as_bytes() == b"cue" {
+            if let Some(cue) =
+                gather_cue::gather(
+                    &log,
+                    &sunwet_write_dir,
+                    file.path(),
+                ).context_with("Error processing cue sheet", ea!(path = file.path().dbg_str()))? {
+                cue_sources.extend(cue.sources);
+                for track in cue.tracks {
+                    files.push((ImportFile {
+                        path: track.path,
+                        dir: dir.clone(),
+                        pattern_path: track.source,
+                    }, Some(track.gather)));
+                }
+                continue;
+            }
+        }
+        files.push((ImportFile {
+            path: file.path().to_path_buf(),
+            dir: dir,
+            pattern_path: file.path().to_path_buf(),
+        }, None));
+    }
+    files.retain(|(f, _)| !cue_sources.contains(&f.path));
+    for (file, gathered) in files {
         let e = file.path().extension().unwrap_or_default();
         let g;
-        if is_audio(e.as_bytes()) {
-            g = gather_audio::gather(&sunwet_out_meta_dir, file.path(), e);
-        } else if is_video(e.as_bytes()) {
+        if let Some(gathered) = gathered {
+            g = Ok(gathered);
+        } else if is_audio(e.as_bytes()) {
+            g = gather_audio::gather(&sunwet_write_dir, file.path(), e);
+        } else if is_matroska(e.as_bytes()) {
             g = gather_video::gather(file.path());
+        } else if is_video(e.as_bytes()) {
+            g = gather_ffprobe::gather(file.path(), GatherMedia::Video);
         } else if is_comic(e.as_bytes()) {
-            g = gather_comic::gather(&sunwet_out_meta_dir, file.path());
+            g = gather_comic::gather(&sunwet_write_dir, file.path());
         } else if is_epub(e.as_bytes()) {
-            g = gather_epub::gather(&sunwet_out_meta_dir, file.path());
+            g = gather_epub::gather(&sunwet_write_dir, file.path());
         } else {
             leftover_files.push(file);
             continue;
         }
-        let g = g.context_with("Error gathering meta for file", ea!(path = file.path().dbg_str()))?;
+        let mut g = g.context_with("Error gathering meta for file", ea!(path = file.path().dbg_str()))?;
+
+        // Fill in missing meta from the path, apply dir overrides
+        let settings = import_dir_settings(&mut dir_settings, root_dir, &file.dir)?;
+        let rel_path = file.pattern_path.strip_prefix(root_dir).unwrap_or(&file.pattern_path);
+        if !settings.as_ref().is_some_and(|s| gather::apply_path_patterns(&mut g, rel_path, &s.1)) {
+            gather::apply_path_patterns(&mut g, rel_path, &patterns);
+        }
+        if let Some(settings) = &settings {
+            gather::apply_dir_overrides(&mut g, &settings.0);
+        }
 
         // Sanity check minimum meta
         if g.track_name.is_none() && g.track_index.is_none() {
@@ -519,10 +813,17 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             let artist = match artists.entry(artist_name.clone()) {
                 Entry::Occupied(e) => e.get().clone(),
                 Entry::Vacant(e) => e.insert(Rc::new(RefCell::new(GatherArtist {
-                    id: node_id_artist(&log, &mut query_cache, artist_name).await?,
+                    id: node_id_artist(&log, &mut resolver, artist_name).await?,
                     name: artist_name.clone(),
+                    musicbrainz_id: None,
                 }))).clone(),
             };
+            if g.album_artist.len() == 1 && g.album_artist_musicbrainz_id.len() == 1 {
+                artist
+                    .borrow_mut()
+                    .musicbrainz_id
+                    .get_or_insert_with(|| g.album_artist_musicbrainz_id[0].clone());
+            }
             album_artist2.insert(ByAddress(artist));
         }
         let Some(album_name) = g.album_name.or_else(|| g.track_name.clone()) else {
@@ -539,31 +840,71 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
                 name: album_name,
                 artist: album_artist2,
                 tracks: Default::default(),
+                genre: Default::default(),
+                date: Default::default(),
+                label: Default::default(),
+                superindex_total: Default::default(),
+                musicbrainz_id: Default::default(),
                 covers: Default::default(),
                 documents: Default::default(),
             }))).clone(),
         };
+        {
+            let mut album = album.borrow_mut();
+            album.genre.extend(g.track_genre.iter().cloned());
+            if album.date.is_none() {
+                album.date = g.track_date.clone();
+            }
+            if album.label.is_none() {
+                album.label = g.album_label.clone();
+            }
+            if album.superindex_total.is_none() {
+                album.superindex_total = g.album_superindex_total;
+            }
+            if album.musicbrainz_id.is_none() {
+                album.musicbrainz_id = g.album_musicbrainz_id.clone();
+            }
+        }
         for (priority, cover) in &g.track_cover {
             *album.borrow_mut().covers.entry(*priority).or_default().entry(cover.clone()).or_default() += 1;
         }
         dir_associations
-            .entry(file.path().parent().unwrap().to_path_buf())
+            .entry(file.dir.clone())
             .or_insert(DirAssociations::default())
             .album
             .insert(ByAddress(album.clone()));
 
         // Assemble track
         let mut track_artist2 = vec![];
-        for artist_name in &g.track_artist {
+        for (i, artist_name) in g.track_artist.iter().enumerate() {
             let artist = match artists.entry(artist_name.clone()) {
                 Entry::Occupied(e) => e.get().clone(),
                 Entry::Vacant(e) => e.insert(Rc::new(RefCell::new(GatherArtist {
-                    id: node_id_artist(&log, &mut query_cache, &artist_name).await?,
+                    id: node_id_artist(&log, &mut resolver, &artist_name).await?,
                     name: artist_name.clone(),
+                    musicbrainz_id: None,
                 }))).clone(),
             };
+            if g.track_artist_musicbrainz_id.len() == g.track_artist.len() {
+                artist
+                    .borrow_mut()
+                    .musicbrainz_id
+                    .get_or_insert_with(|| g.track_artist_musicbrainz_id[i].clone());
+            }
             track_artist2.push(artist);
         }
+        let mut track_composer = vec![];
+        for artist_name in &g.track_composer {
+            let artist = match artists.entry(artist_name.clone()) {
+                Entry::Occupied(e) => e.get().clone(),
+                Entry::Vacant(e) => e.insert(Rc::new(RefCell::new(GatherArtist {
+                    id: node_id_artist(&log, &mut resolver, &artist_name).await?,
+                    name: artist_name.clone(),
+                    musicbrainz_id: None,
+                }))).clone(),
+            };
+            track_composer.push(artist);
+        }
         let track = Rc::new(RefCell::new(GatherTrack {
             type_: g.track_type,
             index: g.track_index,
@@ -572,6 +913,10 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             artist: track_artist2,
             name: g.track_name,
             lang: g.track_language,
+            genre: g.track_genre,
+            composer: track_composer,
+            isrc: g.track_isrc,
+            musicbrainz_id: g.track_musicbrainz_id,
             covers: Default::default(),
         }));
         for (priority, cover) in &g.track_cover {
@@ -581,8 +926,13 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
     }
 
     // Gather non-track data (docs, covers) and associate with common dir albums
+    let mut standalone_images = vec![];
     for file in leftover_files {
-        let Some(assoc) = dir_associations.get(file.path().parent().unwrap()) else {
+        let Some(assoc) = dir_associations.get(&file.dir) else {
+            if photos.is_some() && is_image(file.path().extension().unwrap_or_default().as_bytes()) {
+                standalone_images.push(file.path().to_path_buf());
+                continue;
+            }
             log.log_with(
                 loga::WARN,
                 "Skipping document in dir with no album association",
@@ -624,6 +974,9 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
         triples.push(triple(&node_node(&artist.id), PREDICATE_IS, &obj_is_artist()));
         triples.push(triple(&node_node(&artist.id), PREDICATE_NAME, &node_value_str(&artist.name)));
         triples.push(triple(&node_node(&artist.id), PREDICATE_ADD_TIMESTAMP, &timestamp));
+        if let Some(id) = &artist.musicbrainz_id {
+            triples.push(triple(&node_node(&artist.id), PREDICATE_MUSICBRAINZ_ID, &node_value_str(id)));
+        }
     }
     for album in albums.values() {
         let album = album.borrow();
@@ -637,7 +990,7 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             Some(a) => {
                 node_id(
                     &log,
-                    &mut query_cache,
+                    &mut resolver,
                     concat!(
                         r#"$artist_id -< "sunwet/1/artist" "#,
                         r#"  &( "#,
@@ -657,7 +1010,7 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             None => {
                 node_id(
                     &log,
-                    &mut query_cache,
+                    &mut resolver,
                     concat!(
                         r#"$album_name -< "sunwet/1/name" "#,
                         r#"  &( "#,
@@ -687,6 +1040,24 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             triples.push(triple(&node_node(&album_id), PREDICATE_ARTIST, &node_node(&artist.borrow().id)));
         }
         triples.push(triple(&node_node(&album_id), PREDICATE_ADD_TIMESTAMP, &timestamp));
+        for genre in &album.genre {
+            triples.push(triple(&node_node(&album_id), PREDICATE_GENRE, &node_value_str(genre)));
+        }
+        if let Some(date) = &album.date {
+            triples.push(triple(&node_node(&album_id), PREDICATE_RELEASE_DATE, &node_value_str(date)));
+            if let Some(year) = date_year(date) {
+                triples.push(triple(&node_node(&album_id), PREDICATE_YEAR, &node_value_f64(year)));
+            }
+        }
+        if let Some(label) = &album.label {
+            triples.push(triple(&node_node(&album_id), PREDICATE_LABEL, &node_value_str(label)));
+        }
+        if let Some(total) = album.superindex_total {
+            triples.push(triple(&node_node(&album_id), PREDICATE_SUPERINDEX_TOTAL, &node_value_f64(total)));
+        }
+        if let Some(id) = &album.musicbrainz_id {
+            triples.push(triple(&node_node(&album_id), PREDICATE_MUSICBRAINZ_ID, &node_value_str(id)));
+        }
         shed!{
             'found _;
             for covers in album.covers.values() {
@@ -703,6 +1074,7 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             let track_id =
                 node_id_direct(
                     &log,
+                    &mut resolver,
                     query_album_track(album_id.clone(), track.superindex, track.index, &track.name),
                     Default::default(),
                 ).await?;
@@ -718,6 +1090,20 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             for artist in &track.artist {
                 triples.push(triple(&node_node(&track_id), PREDICATE_ARTIST, &node_node(&artist.borrow().id)));
             }
+            for genre in &track.genre {
+                triples.push(triple(&node_node(&track_id), PREDICATE_GENRE, &node_value_str(genre)));
+            }
+            for composer in &track.composer {
+                triples.push(
+                    triple(&node_node(&track_id), PREDICATE_COMPOSER, &node_node(&composer.borrow().id)),
+                );
+            }
+            if let Some(isrc) = &track.isrc {
+                triples.push(triple(&node_node(&track_id), PREDICATE_ISRC, &node_value_str(isrc)));
+            }
+            if let Some(id) = &track.musicbrainz_id {
+                triples.push(triple(&node_node(&track_id), PREDICATE_MUSICBRAINZ_ID, &node_value_str(id)));
+            }
             triples.push(triple(&node_node(&track_id), PREDICATE_ADD_TIMESTAMP, &timestamp));
             triples.push(triple(&node_node(&track_id), PREDICATE_FILE, &node_upload(&root_dir, &track.file)));
             triples.push(triple(&node_node(&album_id), PREDICATE_TRACK, &node_node(&track_id)));
@@ -738,7 +1124,7 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             let doc_id =
                 node_id(
                     &log,
-                    &mut query_cache,
+                    &mut resolver,
                     concat!(
                         r#"$document_name -< "sunwet/1/name" "#,
                         r#"  ?( -> "sunwet/1/is" == "sunwet/1/document" ) "#,
@@ -752,13 +1138,170 @@ async fn import_dir(log: &Log, root_dir: &PathBuf) -> Result<(), loga::Error> {
             triples.push(triple(&node_node(&album_id), PREDICATE_DOC, &node_node(&doc_id)));
         }
     }
-    write(sunwet_out_meta, serde_json::to_string_pretty(&CliCommit {
+
+    // Group standalone images into photo albums
+    let mut remove = vec![];
+    if let Some(grouping) = photos {
+        let mut photo_indexes = vec![];
+        let mut photo_albums = BTreeMap::<String, Vec<(PathBuf, GatherPhoto)>>::new();
+        for path in standalone_images {
+            let photo = match gather_photo::gather(&path) {
+                Ok(p) => p,
+                Err(e) => {
+                    log.log_err(loga::WARN, e.context("Error reading photo metadata, importing without"));
+                    GatherPhoto::default()
+                },
+            };
+            let album_name = match grouping {
+                PhotoGrouping::Dir => {
+                    let dir = path.parent().unwrap();
+                    let name = dir.strip_prefix(root_dir).unwrap().to_string_lossy().to_string();
+                    if name.is_empty() {
+                        root_dir
+                            .file_name()
+                            .map(|x| x.to_string_lossy().to_string())
+                            .unwrap_or_else(|| format!("Photos"))
+                    } else {
+                        name
+                    }
+                },
+                PhotoGrouping::Date => photo
+                    .capture_time
+                    .as_ref()
+                    .and_then(|t| t.get(0 .. 10))
+                    .unwrap_or("Undated")
+                    .to_string(),
+            };
+            photo_albums.entry(album_name).or_default().push((path, photo));
+        }
+        for (album_name, mut album_photos) in photo_albums {
+            album_photos.sort_by(|a, b| (&a.1.capture_time, &a.0).cmp(&(&b.1.capture_time, &b.0)));
+            let album_id =
+                node_id(
+                    &log,
+                    &mut resolver,
+                    concat!(
+                        r#"$album_name -< "sunwet/1/name" "#,
+                        r#"  &( "#,
+                        r#"    ?(-> "sunwet/1/media" == $album_media) "#,
+                        r#"    ?(-> "sunwet/1/is" == "sunwet/1/album") "#,
+                        r#"  )"#,
+                        r#"  { => id } "#,
+                    ),
+                    [
+                        //. .
+                        (format!("album_media"), Node::from_str(OBJ_MEDIA_IMAGE)),
+                        (format!("album_name"), Node::from_str(&album_name)),
+                    ].into_iter().collect(),
+                ).await?;
+            triples.push(triple(&node_node(&album_id), PREDICATE_IS, &obj_is_album()));
+            triples.push(triple(&node_node(&album_id), PREDICATE_MEDIA, &obj_media_image()));
+            triples.push(triple(&node_node(&album_id), PREDICATE_NAME, &node_value_str(&album_name)));
+            triples.push(triple(&node_node(&album_id), PREDICATE_ADD_TIMESTAMP, &timestamp));
+            if let Some((cover, _)) = album_photos.first() {
+                triples.push(triple(&node_node(&album_id), PREDICATE_COVER, &node_upload(root_dir, cover)));
+            }
+            for (i, (path, photo)) in album_photos.iter().enumerate() {
+                let name = String::from_utf8_lossy(path.file_name().unwrap_or_default().as_bytes()).to_string();
+
+                // Match by file contents rather than name or index: names aren't unique
+                // within date-grouped albums, and indexes change as photos are added
+                let photo_id =
+                    node_id(
+                        &log,
+                        &mut resolver,
+                        concat!(
+                            r#"$photo_file -< "sunwet/1/file" "#,
+                            r#"  ?(-> "sunwet/1/media" == "sunwet/1/image") "#,
+                            r#"  { => id } "#,
+                        ),
+                        [(format!("photo_file"), Node::File(hash_file_sha256(&log, path).await?))]
+                            .into_iter()
+                            .collect(),
+                    ).await?;
+                let photo_node = node_node(&photo_id);
+                let index = (i + 1) as f64;
+                triples.push(triple(&photo_node, PREDICATE_MEDIA, &obj_media_image()));
+                triples.push(triple(&photo_node, PREDICATE_NAME, &node_value_str(&name)));
+                triples.push(triple(&photo_node, PREDICATE_INDEX, &node_value_f64(index)));
+                photo_indexes.push((photo_id.clone(), index));
+                triples.push(triple(&photo_node, PREDICATE_ADD_TIMESTAMP, &timestamp));
+                triples.push(triple(&photo_node, PREDICATE_FILE, &node_upload(&root_dir, path)));
+                if let Some(t) = &photo.capture_time {
+                    triples.push(triple(&photo_node, PREDICATE_CAPTURE_TIMESTAMP, &node_value_str(t)));
+                }
+                if let Some(camera) = &photo.camera {
+                    triples.push(triple(&photo_node, PREDICATE_CAMERA, &node_value_str(camera)));
+                }
+                if let Some(v) = photo.orientation {
+                    triples.push(triple(&photo_node, PREDICATE_ORIENTATION, &node_value_f64(v)));
+                }
+                if let (Some(lat), Some(lon)) = (photo.latitude, photo.longitude) {
+                    triples.push(triple(&photo_node, PREDICATE_LATITUDE, &node_value_f64(lat)));
+                    triples.push(triple(&photo_node, PREDICATE_LONGITUDE, &node_value_f64(lon)));
+                    if let Some(alt) = photo.altitude {
+                        triples.push(triple(&photo_node, PREDICATE_ALTITUDE, &node_value_f64(alt)));
+                    }
+                }
+                triples.push(triple(&node_node(&album_id), PREDICATE_TRACK, &photo_node));
+            }
+        }
+
+        // Photos are re-indexed when photos are added to the album, so replace any
+        // previous index
+        if env::var_os(ENV_SUNWET).is_some() {
+            let new_indexes = photo_indexes.iter().cloned().collect::<HashMap<_, _>>();
+            for chunk in photo_indexes.chunks(100) {
+                for t in req::req_simple(&log, ReqGetTriplesAround {
+                    nodes: chunk.iter().map(|(id, _)| id.clone()).collect(),
+                }).await? {
+                    if t.predicate != PREDICATE_INDEX {
+                        continue;
+                    }
+                    let Some(new_index) = new_indexes.get(&t.subject) else {
+                        continue;
+                    };
+                    if let Node::Value(serde_json::Value::Number(old_index)) = &t.object {
+                        if old_index.as_f64() == Some(*new_index) {
+                            continue;
+                        }
+                    }
+                    remove.push(triple(&node_node(&t.subject), PREDICATE_INDEX, &node_node(&t.object)));
+                }
+            }
+        }
+    }
+    let commit = CliCommit {
         add: triples,
-        remove: vec![],
-    }).unwrap()).context("Error writing sunwet.json")?;
+        remove: remove,
+    };
+    if dry_run {
+        #[derive(Serialize)]
+        struct DryRun {
+            #[serde(flatten)]
+            diff: CommitDiff,
+            ambiguous_matches: Vec<AmbiguousMatch>,
+        }
+
+        let diff = diff_commit(&log, &root_dir, &commit).await?;
+        println!("{}", serde_json::to_string_pretty(&DryRun {
+            diff: diff,
+            ambiguous_matches: resolver.ambiguous.unwrap_or_default(),
+        }).unwrap());
+        return Ok(());
+    }
+    write(sunwet_out_meta, serde_json::to_string_pretty(&commit).unwrap()).context("Error writing sunwet.json")?;
     return Ok(());
 }
 
+#[derive(Aargvark, Clone, Copy)]
+pub enum PhotoGrouping {
+    /// One album per directory, named after the directory.
+    Dir,
+    /// One album per day the photos were taken (`YYYY-MM-DD`).
+    Date,
+}
+
 /// Turn a directory/archive file into a sunwet commit directory ready for upload.
 #[derive(Aargvark)]
 pub struct PrepareImportCommitCommand {
@@ -768,10 +1311,29 @@ pub struct PrepareImportCommitCommand {
     /// source directory or, if an archive, a directory with the name of the archive
     /// with the extension removed.
     dest: Option<PathBuf>,
+    /// Import images that aren't in a directory with other media as photos (with
+    /// EXIF metadata), grouped into albums this way. Otherwise they're skipped.
+    photos: Option<PhotoGrouping>,
+    /// Instead of writing the commit, compare it with the data on the server and
+    /// show new and matched entities, triples that already exist, and imported
+    /// entities that matched multiple existing entities. Nothing is written to the
+    /// source or destination; archives are extracted to a temporary directory.
+    dry_run: Option<()>,
+    /// Patterns for getting metadata missing from tags from file paths, like
+    /// `{artist}/{album}/{index} - {name}`. Each pattern is matched against the end
+    /// of the path (without extension) and the first match is used. Patterns in
+    /// `sunwet_import.json` files are tried first.
+    pattern: Option<Vec<String>>,
 }
 
 pub async fn handle_prepare_media_import_commit(args: PrepareImportCommitCommand) -> Result<(), loga::Error> {
     let log = loga::Log::new_root(loga::INFO);
+    prepare_import_commit(&log, args).await?;
+    return Ok(());
+}
+
+/// Returns the directory containing the prepared commit (`sunwet.json`).
+pub async fn prepare_import_commit(log: &Log, args: PrepareImportCommitCommand) -> Result<PathBuf, loga::Error> {
     let source_meta =
         args
             .source
@@ -784,9 +1346,15 @@ pub async fn handle_prepare_media_import_commit(args: PrepareImportCommitCommand
                 is_doc(e.as_bytes()) ||
                 is_comic(e.as_bytes()) | is_epub(e.as_bytes()) {
                 let log = Log::new().fork(ea!(file = args.source.dbg_str()));
-                let dest = match args.dest {
-                    Some(d) => d,
-                    None => args.source.with_extension(""),
+                let temp_dest;
+                let dest = if args.dry_run.is_some() {
+                    temp_dest = tempdir().context("Error making temp dir for dry run")?;
+                    temp_dest.path().to_path_buf()
+                } else {
+                    match args.dest {
+                        Some(d) => d,
+                        None => args.source.with_extension(""),
+                    }
                 };
                 create_dir_all(&dest)?;
                 let mut out =
@@ -795,38 +1363,35 @@ pub async fn handle_prepare_media_import_commit(args: PrepareImportCommitCommand
                     ).stack_context(&log, "Error creating file in output directory")?;
                 let mut source = File::open(&args.source).context("Error opening file")?;
                 io::copy(&mut source, &mut out).stack_context(&log, "Error extracting contents")?;
-                import_dir(&log, &dest).await?;
-            } else if e.as_bytes() == b"zip" {
-                let dest = match args.dest {
-                    Some(d) => d,
-                    None => args.source.with_extension(""),
+                import_dir(
+                    &log,
+                    &dest,
+                    args.photos,
+                    args.dry_run.is_some(),
+                    args.pattern.as_deref().unwrap_or_default(),
+                ).await?;
+                return Ok(dest);
+            } else if let Some((type_, stem)) = archive_type(&args.source) {
+                let temp_dest;
+                let dest = if args.dry_run.is_some() {
+                    temp_dest = tempdir().context("Error making temp dir for dry run")?;
+                    temp_dest.path().to_path_buf()
+                } else {
+                    match args.dest {
+                        Some(d) => d,
+                        None => args.source.with_file_name(stem),
+                    }
                 };
                 create_dir_all(&dest)?;
-                let mut zip =
-                    zip::ZipArchive::new(
-                        File::open(args.source).stack_context(&log, "Error opening file")?,
-                    ).stack_context(&log, "Error opening file as zip archive")?;
-                for i in 0 .. zip.len() {
-                    match (|| {
-                        let mut file = zip.by_index(i)?;
-                        let log = Log::new().fork(ea!(archive_path = file.name()));
-                        let mut out =
-                            File::create(
-                                dest.join(file.enclosed_name().stack_context(&log, "File has invalid name")?),
-                            ).stack_context(&log, "Error creating file in output directory")?;
-                        io::copy(&mut file, &mut out).stack_context(&log, "Error extracting contents")?;
-                        return Ok(()) as Result<(), loga::Error>;
-                    })() {
-                        Ok(_) => (),
-                        Err(e) => {
-                            log.log_err(
-                                loga::WARN,
-                                e.context_with("Error extracting file from archive, skipping", ea!(index = i)),
-                            );
-                        },
-                    }
-                }
-                import_dir(&log, &dest).await?;
+                extract_archive(&log, type_, &args.source, &dest)?;
+                import_dir(
+                    &log,
+                    &dest,
+                    args.photos,
+                    args.dry_run.is_some(),
+                    args.pattern.as_deref().unwrap_or_default(),
+                ).await?;
+                return Ok(dest);
             } else {
                 return Err(loga::err("Unsupported source file type"));
             }
@@ -836,7 +1401,14 @@ pub async fn handle_prepare_media_import_commit(args: PrepareImportCommitCommand
     } else if source_meta.is_dir() {
         let dest = args.dest.as_ref().unwrap_or(&args.source);
         let log = log.fork(ea!(dest = dest.to_string_lossy()));
-        import_dir(&log, dest).await?;
+        import_dir(
+            &log,
+            dest,
+            args.photos,
+            args.dry_run.is_some(),
+            args.pattern.as_deref().unwrap_or_default(),
+        ).await?;
+        return Ok(dest.clone());
     } else {
         return Err(
             loga::err_with(
@@ -845,5 +1417,4 @@ pub async fn handle_prepare_media_import_commit(args: PrepareImportCommitCommand
             ),
         );
     }
-    return Ok(());
 }
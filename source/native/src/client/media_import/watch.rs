@@ -0,0 +1,272 @@
+use {
+    aargvark::Aargvark,
+    chrono::Utc,
+    crate::client::commit::commit,
+    futures::StreamExt,
+    inotify::{
+        Inotify,
+        WatchMask,
+    },
+    loga::{
+        DebugDisplay,
+        Log,
+        ResultContext,
+        ea,
+    },
+    serde::Serialize,
+    shared::interface::cli::CliCommit,
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+            hash_map::Entry,
+        },
+        fs::{
+            create_dir_all,
+            read,
+            read_dir,
+            remove_dir_all,
+            rename,
+            write,
+        },
+        os::unix::ffi::OsStrExt,
+        path::{
+            Path,
+            PathBuf,
+        },
+        time::{
+            Duration,
+            Instant,
+            SystemTime,
+        },
+    },
+    super::{
+        PhotoGrouping,
+        PrepareImportCommitCommand,
+        prepare_import_commit,
+    },
+    tokio::{
+        select,
+        time::sleep,
+    },
+    walkdir::WalkDir,
+};
+
+/// Watch a directory and import anything placed there.
+#[derive(Aargvark)]
+pub struct WatchImportCommand {
+    debug: Option<()>,
+    /// The directory to watch. Each directory or archive placed here is imported as
+    /// a unit, like with `prepare-media-import-commit` followed by `commit`.
+    inbox: PathBuf,
+    /// Where to move imported items. Defaults to `.done` in the inbox.
+    done: Option<PathBuf>,
+    /// Where to move items that failed to import. Defaults to `.failed` in the
+    /// inbox.
+    failed: Option<PathBuf>,
+    /// Wait until an item hasn't changed for this many seconds before importing it.
+    /// Defaults to 30.
+    settle: Option<u64>,
+    /// Import standalone images as photos, see `prepare-media-import-commit`.
+    photos: Option<PhotoGrouping>,
+    /// Patterns for getting missing metadata from file paths, see
+    /// `prepare-media-import-commit`.
+    pattern: Option<Vec<String>>,
+}
+
+/// Changes when anything in an item is added, removed, or written.
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct ItemSignature {
+    files: u64,
+    bytes: u64,
+    modified: Option<SystemTime>,
+}
+
+fn item_signature(path: &Path) -> ItemSignature {
+    let mut out = ItemSignature {
+        files: 0,
+        bytes: 0,
+        modified: None,
+    };
+    for entry in WalkDir::new(path) {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        out.files += 1;
+        out.bytes += meta.len();
+        if let Ok(modified) = meta.modified() {
+            out.modified = out.modified.max(Some(modified));
+        }
+    }
+    return out;
+}
+
+#[derive(Serialize)]
+struct ImportReport {
+    item: String,
+    time: String,
+    /// The number of triples added, if successful
+    triples: Option<usize>,
+    error: Option<String>,
+}
+
+async fn import_item(
+    log: &Log,
+    item: &Path,
+    work_dir: &Path,
+    photos: Option<PhotoGrouping>,
+    pattern: Option<Vec<String>>,
+) -> Result<usize, loga::Error> {
+    // Directories are prepared in place, archives and single files are extracted to
+    // the work dir
+    let dest = if item.is_dir() {
+        None
+    } else {
+        Some(work_dir.join(item.file_stem().unwrap_or_default()))
+    };
+    let commit_dir = prepare_import_commit(log, PrepareImportCommitCommand {
+        source: item.to_path_buf(),
+        dest: dest,
+        photos: photos,
+        dry_run: None,
+        pattern: pattern,
+    }).await?;
+    let commit_path = commit_dir.join("sunwet.json");
+    let cli_commit =
+        serde_json::from_slice::<CliCommit>(
+            &read(&commit_path).context_with("Error reading prepared commit", ea!(path = commit_path.dbg_str()))?,
+        ).context_with("Error parsing prepared commit", ea!(path = commit_path.dbg_str()))?;
+    let triples = cli_commit.add.len();
+    commit(
+        log,
+        &commit_dir,
+        cli_commit,
+        Some(format!("Watch import of [{}]", item.file_name().unwrap_or_default().to_string_lossy())),
+    ).await?;
+    return Ok(triples);
+}
+
+/// Move the item to the directory with a report. Adds a suffix if an item with
+/// the same name was already moved there.
+fn file_item(item: &Path, dest_dir: &Path, report: &ImportReport) -> Result<(), loga::Error> {
+    create_dir_all(dest_dir).context_with("Error creating directory", ea!(path = dest_dir.dbg_str()))?;
+    let name = item.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut dest = dest_dir.join(&name);
+    if dest.exists() {
+        dest = dest_dir.join(format!("{}.{}", name, Utc::now().timestamp()));
+    }
+    rename(item, &dest).context_with("Error moving item", ea!(from = item.dbg_str(), to = dest.dbg_str()))?;
+    let report_path = dest.with_file_name(format!("{}.report.json", dest.file_name().unwrap().to_string_lossy()));
+    write(&report_path, serde_json::to_string_pretty(report).unwrap())
+        .context_with("Error writing import report", ea!(path = report_path.dbg_str()))?;
+    return Ok(());
+}
+
+pub async fn handle_watch_import(args: WatchImportCommand) -> Result<(), loga::Error> {
+    let log = Log::new_root(if args.debug.is_some() {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    let done_dir = args.done.unwrap_or_else(|| args.inbox.join(".done"));
+    let failed_dir = args.failed.unwrap_or_else(|| args.inbox.join(".failed"));
+    let work_dir = args.inbox.join(".work");
+    let settle = Duration::from_secs(args.settle.unwrap_or(30));
+    let inotify = Inotify::init().context("Error initializing inotify")?;
+    inotify
+        .watches()
+        .add(
+            &args.inbox,
+            WatchMask::CREATE | WatchMask::MODIFY | WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO |
+                WatchMask::MOVED_FROM |
+                WatchMask::DELETE,
+        )
+        .context_with("Error watching inbox", ea!(path = args.inbox.dbg_str()))?;
+    let mut events = inotify.into_event_stream([0u8; 4096]).context("Error reading inotify events")?;
+
+    // Inotify only reports changes directly in the inbox, so items are polled while
+    // waiting for them to settle
+    let mut pending = HashMap::<PathBuf, (ItemSignature, Instant)>::new();
+    let mut stuck = HashSet::<PathBuf>::new();
+    log.log_with(loga::INFO, "Watching for new items", ea!(inbox = args.inbox.dbg_str()));
+    loop {
+        let now = Instant::now();
+        for entry in read_dir(&args.inbox).context_with("Error listing inbox", ea!(path = args.inbox.dbg_str()))? {
+            let entry = entry.context("Error reading inbox entry")?;
+            if entry.file_name().as_bytes().starts_with(b".") {
+                continue;
+            }
+            let path = entry.path();
+            if stuck.contains(&path) {
+                continue;
+            }
+            let signature = item_signature(&path);
+            match pending.entry(path) {
+                Entry::Occupied(mut e) => {
+                    if e.get().0 != signature {
+                        *e.get_mut() = (signature, now);
+                    }
+                },
+                Entry::Vacant(e) => {
+                    e.insert((signature, now));
+                },
+            }
+        }
+        pending.retain(|path, _| path.exists());
+        let mut settled =
+            pending
+                .iter()
+                .filter(|(_, (_, changed))| now.duration_since(*changed) >= settle)
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+        settled.sort();
+        for item in settled {
+            pending.remove(&item);
+            let log = log.fork(ea!(item = item.dbg_str()));
+            log.log(loga::INFO, "Importing");
+            let res = import_item(&log, &item, &work_dir, args.photos, args.pattern.clone()).await;
+            let mut report = ImportReport {
+                item: item.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                time: Utc::now().to_rfc3339(),
+                triples: None,
+                error: None,
+            };
+            let dest_dir = match res {
+                Ok(triples) => {
+                    log.log(loga::INFO, "Import succeeded");
+                    report.triples = Some(triples);
+                    &done_dir
+                },
+                Err(e) => {
+                    report.error = Some(e.to_string());
+                    log.log_err(loga::WARN, e.context("Import failed"));
+                    &failed_dir
+                },
+            };
+            if work_dir.exists() {
+                if let Err(e) = remove_dir_all(&work_dir) {
+                    log.log_err(loga::WARN, loga::err(e).context("Error cleaning up work dir"));
+                }
+            }
+            if let Err(e) = file_item(&item, dest_dir, &report) {
+                log.log_err(loga::WARN, e.context("Error moving item out of inbox, ignoring it from now on"));
+                stuck.insert(item);
+            }
+        }
+
+        // Wait for changes
+        if pending.is_empty() {
+            events.next().await;
+        } else {
+            select!{
+                _ = events.next() => {
+                },
+                _ = sleep(Duration::from_secs(2)) => {
+                }
+            }
+        }
+    }
+}
@@ -49,12 +49,16 @@ use {
                 CliTriple,
             },
             query::Query,
-            triple::Node,
+            triple::{
+                FileHash,
+                Node,
+            },
             wire::{
                 ReqCheckGet,
                 ReqCheckStart,
                 ReqCommit,
                 ReqCommitFree,
+                ReqGenerationStatus,
                 ReqGetTriplesAround,
                 ReqHistory,
                 ReqHistoryFilter,
@@ -72,6 +76,7 @@ use {
             HashSet,
         },
         path::PathBuf,
+        str::FromStr,
         time::Duration,
         usize,
     },
@@ -88,6 +93,8 @@ use {
 
 pub mod commit;
 pub mod media_import;
+pub mod rdf;
+pub mod rdf_test;
 pub mod req;
 
 pub struct AargvarkStrNode(pub Node);
@@ -171,6 +178,44 @@ pub struct ExportCommand {
     exclude: Option<HashSet<String>>,
 }
 
+/// Get all triples around the nodes from the export source.
+pub async fn export_triples(
+    log: &Log,
+    source: ExportCommandSource,
+    parameters: &HashMap<String, AargvarkStrNode>,
+) -> Result<Vec<Triple>, loga::Error> {
+    fn check_query(q: &Query) -> Result<(), loga::Error> {
+        if q.suffix.is_some() {
+            return Err(loga::err("The export query has a struct, it must be struct-less (no `{}`)"));
+        }
+        return Ok(());
+    }
+
+    let RespQueryRows::Scalar(nodes) = (match source {
+        ExportCommandSource::QueryFile(s) => {
+            check_query(&s.value)?;
+            req::req_simple(log, ReqQuery {
+                query: s.value,
+                parameters: parameters.iter().map(|(k, v)| (k.clone(), v.0.clone())).collect(),
+                pagination: None,
+            }).await?.rows
+        },
+        ExportCommandSource::Inline(s) => {
+            let query = compile_query(&s).map_err(loga::err)?;
+            check_query(&query)?;
+            req::req_simple(log, ReqQuery {
+                query: query,
+                parameters: parameters.iter().map(|(k, v)| (k.clone(), v.0.clone())).collect(),
+                pagination: None,
+            }).await?.rows
+        },
+        ExportCommandSource::ResultFile(s) => s.value,
+    }) else {
+        return Err(loga::err("The list of nodes has structured elements, the input list must be plain nodes."));
+    };
+    return Ok(req::req_simple(log, ReqGetTriplesAround { nodes: nodes }).await?);
+}
+
 pub async fn handle_export(c: ExportCommand) -> Result<(), loga::Error> {
     let log = Log::new_root(if c.debug.is_some() {
         loga::DEBUG
@@ -186,36 +231,7 @@ pub async fn handle_export(c: ExportCommand) -> Result<(), loga::Error> {
                 .context_with("Error reading existing triples", ea!(path = triples_path.dbg_str()))?,
         ).context_with("Error reading existing triples", ea!(path = triples_path.dbg_str()))?
     } else {
-        fn check_query(q: &Query) -> Result<(), loga::Error> {
-            if q.suffix.is_some() {
-                return Err(loga::err("The export query has a struct, it must be struct-less (no `{}`)"));
-            }
-            return Ok(());
-        }
-
-        let RespQueryRows::Scalar(nodes) = (match c.source {
-            ExportCommandSource::QueryFile(s) => {
-                check_query(&s.value)?;
-                req::req_simple(&log, ReqQuery {
-                    query: s.value,
-                    parameters: c.parameters.iter().map(|(k, v)| (k.clone(), v.0.clone())).collect(),
-                    pagination: None,
-                }).await?.rows
-            },
-            ExportCommandSource::Inline(s) => {
-                let query = compile_query(&s).map_err(loga::err)?;
-                check_query(&query)?;
-                req::req_simple(&log, ReqQuery {
-                    query: query,
-                    parameters: c.parameters.iter().map(|(k, v)| (k.clone(), v.0.clone())).collect(),
-                    pagination: None,
-                }).await?.rows
-            },
-            ExportCommandSource::ResultFile(s) => s.value,
-        }) else {
-            return Err(loga::err("The list of nodes has structured elements, the input list must be plain nodes."));
-        };
-        let triples = req::req_simple(&log, ReqGetTriplesAround { nodes: nodes }).await?;
+        let triples = export_triples(&log, c.source, &c.parameters).await?;
         write(&triples_path, serde_json::to_string_pretty(&triples).unwrap())
             .await
             .context_with("Error writing received triples", ea!(path = triples_path.dbg_str()))?;
@@ -444,8 +460,24 @@ pub async fn handle_merge_nodes_command(args: MergeNodesCommand) -> Result<(), l
 #[derive(Aargvark)]
 pub struct DuplicateNodesCommand {
     debug: Option<()>,
-    /// Node to duplicate
-    node: AargvarkStrNode,
+    /// Nodes to duplicate. The new ids are written to stdout, one per line in the
+    /// same order.
+    nodes: Vec<AargvarkStrNode>,
+    /// Also duplicate entities linked to duplicated nodes (in either direction) via
+    /// these predicates, recursively. For example, `--follow sunwet/1/track` with an
+    /// album copies the album's tracks too. Files are never duplicated, the copies
+    /// refer to the same files.
+    ///
+    /// All relations of the listed nodes are copied. For followed nodes only their
+    /// outgoing relations are copied, so other entities that point at them aren't
+    /// linked to the copies.
+    follow: Option<HashSet<String>>,
+    /// Only copy relations with the listed predicates. However if this is not
+    /// specified all relations are copied.
+    include: Option<HashSet<String>>,
+    /// Don't copy relations with the listed predicates. Takes precedence over
+    /// `--include`
+    exclude: Option<HashSet<String>>,
 }
 
 pub async fn handle_duplicate_nodes_command(args: DuplicateNodesCommand) -> Result<(), loga::Error> {
@@ -454,31 +486,80 @@ pub async fn handle_duplicate_nodes_command(args: DuplicateNodesCommand) -> Resu
     } else {
         loga::INFO
     });
-    let dest_str = Uuid::new_v4().hyphenated().to_string();
-    let dest = Node::Value(serde_json::Value::String(dest_str.clone()));
+    let follow = args.follow.unwrap_or_default();
+    let new_id = || Uuid::new_v4().hyphenated().to_string();
+
+    // Find the subgraph, assigning new ids to each node in it
+    let mut copies = HashMap::new();
+    let mut frontier = vec![];
+    for n in &args.nodes {
+        if copies.contains_key(&n.0) {
+            continue;
+        }
+        copies.insert(n.0.clone(), new_id());
+        frontier.push(n.0.clone());
+    }
+    let mut triples = HashSet::new();
+    while !frontier.is_empty() {
+        let mut next_frontier = vec![];
+        for t in req_simple(&log, ReqGetTriplesAround { nodes: frontier }).await? {
+            if let Some(include) = args.include.as_ref() {
+                if !include.contains(&t.predicate) {
+                    continue;
+                }
+            }
+            if let Some(exclude) = args.exclude.as_ref() {
+                if exclude.contains(&t.predicate) {
+                    continue;
+                }
+            }
+            if follow.contains(&t.predicate) {
+                for n in [&t.subject, &t.object] {
+                    let Node::Value(serde_json::Value::String(_)) = n else {
+                        continue;
+                    };
+                    if copies.contains_key(n) {
+                        continue;
+                    }
+                    copies.insert(n.clone(), new_id());
+                    next_frontier.push(n.clone());
+                }
+            }
+            triples.insert(t);
+        }
+        frontier = next_frontier;
+    }
+
+    // Rewrite relations onto the copies
+    let rewrite = |n: Node| match copies.get(&n) {
+        Some(id) => Node::Value(serde_json::Value::String(id.clone())),
+        None => n,
+    };
+    let roots = args.nodes.iter().map(|n| n.0.clone()).collect::<HashSet<_>>();
     let mut add = vec![];
-    for t in req_simple(&log, ReqGetTriplesAround { nodes: vec![args.node.0.clone()] }).await? {
-        if t.subject == dest {
-            add.push(Triple {
-                subject: dest.clone(),
-                predicate: t.predicate.clone(),
-                object: t.object.clone(),
-            });
-        } else {
-            add.push(Triple {
-                subject: t.subject.clone(),
-                predicate: t.predicate.clone(),
-                object: dest.clone(),
-            });
+    for t in triples {
+        if !copies.contains_key(&t.subject) && !roots.contains(&t.object) {
+            continue;
         }
+        add.push(Triple {
+            subject: rewrite(t.subject),
+            predicate: t.predicate,
+            object: rewrite(t.object),
+        });
     }
+    add.sort();
     req_simple(&log, ReqCommit::Free(ReqCommitFree {
-        comment: format!("CLI duplicate [{}]", serde_json::to_string(&args.node.0).unwrap()),
+        comment: format!(
+            "CLI duplicate [{}]",
+            args.nodes.iter().map(|n| serde_json::to_string(&n.0).unwrap()).collect::<Vec<_>>().join(", ")
+        ),
         add: add,
         remove: vec![],
         files: vec![],
     })).await?;
-    print!("{}", dest_str);
+    for n in &args.nodes {
+        println!("{}", copies[&n.0]);
+    }
     return Ok(());
 }
 
@@ -592,6 +673,26 @@ pub async fn handle_get_node(c: GetNodeCommand) -> Result<(), loga::Error> {
     return Ok(());
 }
 
+#[derive(Aargvark)]
+pub struct GenerationStatusCommand {
+    debug: Option<()>,
+    /// The file hash, like `sha256:0123...`
+    file: String,
+}
+
+pub async fn handle_generation_status(c: GenerationStatusCommand) -> Result<(), loga::Error> {
+    let log = Log::new_root(if c.debug.is_some() {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    let file =
+        FileHash::from_str(&c.file).map_err(|e| loga::err(e).context_with("Invalid file hash", ea!(hash = c.file)))?;
+    let res = req::req_simple(&log, ReqGenerationStatus { file: file }).await?;
+    println!("{}", serde_json::to_string_pretty(&res).unwrap());
+    return Ok(());
+}
+
 #[derive(Aargvark)]
 pub struct CheckCommand {
     debug: Option<()>,
@@ -0,0 +1,494 @@
+use {
+    aargvark::{
+        Aargvark,
+        traits_impls::AargvarkJson,
+    },
+    loga::{
+        DebugDisplay,
+        Log,
+        ResultContext,
+        ea,
+    },
+    oxrdf::{
+        Literal,
+        NamedNode,
+        Term,
+    },
+    oxrdfio::{
+        JsonLdProfileSet,
+        RdfFormat,
+        RdfParser,
+        RdfSerializer,
+    },
+    shared::interface::{
+        cli::{
+            CliCommit,
+            CliNode,
+            CliRdfMapping,
+            CliTriple,
+        },
+        ont::{
+            OBJ_IS_ALBUM,
+            OBJ_IS_ARTIST,
+            OBJ_IS_DOC,
+            OBJ_IS_NOTE,
+            OBJ_IS_PLAYLIST,
+            OBJ_MEDIA_AUDIO,
+            OBJ_MEDIA_BOOK,
+            OBJ_MEDIA_COMIC,
+            OBJ_MEDIA_IMAGE,
+            OBJ_MEDIA_VIDEO,
+            PREDICATE_ADD_TIMESTAMP,
+            PREDICATE_ARTIST,
+            PREDICATE_COMPOSER,
+            PREDICATE_COVER,
+            PREDICATE_GENRE,
+            PREDICATE_INDEX,
+            PREDICATE_IS,
+            PREDICATE_ISRC,
+            PREDICATE_LABEL,
+            PREDICATE_LANG,
+            PREDICATE_LATITUDE,
+            PREDICATE_LONGITUDE,
+            PREDICATE_NAME,
+            PREDICATE_RELEASE_DATE,
+            PREDICATE_TRACK,
+        },
+        triple::{
+            FileHash,
+            Node,
+        },
+        wire::{
+            ReqGetTriplesAround,
+            Triple,
+        },
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashMap,
+            HashSet,
+        },
+        fs::{
+            File,
+            write,
+        },
+        io::BufReader,
+        path::PathBuf,
+        str::FromStr,
+    },
+    super::{
+        AargvarkStrNode,
+        ExportCommandSource,
+        export_triples,
+        req,
+    },
+    uuid::Uuid,
+};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const SCHEMA: &str = "https://schema.org/";
+
+#[derive(Aargvark, Clone, Copy)]
+pub enum RdfFormatArg {
+    NTriples,
+    Turtle,
+    JsonLd,
+}
+
+impl RdfFormatArg {
+    fn format(self) -> RdfFormat {
+        match self {
+            RdfFormatArg::NTriples => return RdfFormat::NTriples,
+            RdfFormatArg::Turtle => return RdfFormat::Turtle,
+            RdfFormatArg::JsonLd => return RdfFormat::JsonLd { profile: JsonLdProfileSet::empty() },
+        }
+    }
+}
+
+/// `CliRdfMapping` with defaults filled in
+pub(super) struct Mapping {
+    file_prefix: String,
+    node_prefix: String,
+    predicate_prefix: String,
+    predicates: BTreeMap<String, String>,
+    values: BTreeMap<String, String>,
+    prefixes: BTreeMap<String, String>,
+}
+
+impl Mapping {
+    pub fn new(m: Option<CliRdfMapping>) -> Mapping {
+        let m = m.unwrap_or_default();
+        return Mapping {
+            file_prefix: m.file_prefix.unwrap_or_else(|| format!("urn:sunwet:file:")),
+            node_prefix: m.node_prefix.unwrap_or_else(|| format!("urn:sunwet:node:")),
+            predicate_prefix: m.predicate_prefix.unwrap_or_else(|| format!("urn:sunwet:predicate:")),
+            predicates: m.predicates.unwrap_or_else(|| {
+                let mut out = BTreeMap::new();
+                out.insert(PREDICATE_IS.to_string(), format!("{}type", RDF));
+                for (k, v) in [
+                    (PREDICATE_NAME, "name"),
+                    (PREDICATE_ARTIST, "byArtist"),
+                    (PREDICATE_COMPOSER, "composer"),
+                    (PREDICATE_GENRE, "genre"),
+                    (PREDICATE_RELEASE_DATE, "datePublished"),
+                    (PREDICATE_LANG, "inLanguage"),
+                    (PREDICATE_COVER, "image"),
+                    (PREDICATE_TRACK, "hasPart"),
+                    (PREDICATE_INDEX, "position"),
+                    (PREDICATE_ISRC, "isrcCode"),
+                    (PREDICATE_LABEL, "recordLabel"),
+                    (PREDICATE_ADD_TIMESTAMP, "dateCreated"),
+                    (PREDICATE_LATITUDE, "latitude"),
+                    (PREDICATE_LONGITUDE, "longitude"),
+                ] {
+                    out.insert(k.to_string(), format!("{}{}", SCHEMA, v));
+                }
+                out
+            }),
+            values: m.values.unwrap_or_else(|| {
+                let mut out = BTreeMap::new();
+                for (k, v) in [
+                    (OBJ_IS_ALBUM, "Collection"),
+                    (OBJ_IS_PLAYLIST, "MusicPlaylist"),
+                    (OBJ_IS_ARTIST, "Person"),
+                    (OBJ_IS_DOC, "DigitalDocument"),
+                    (OBJ_IS_NOTE, "NoteDigitalDocument"),
+                    (OBJ_MEDIA_AUDIO, "AudioObject"),
+                    (OBJ_MEDIA_VIDEO, "VideoObject"),
+                    (OBJ_MEDIA_COMIC, "ComicStory"),
+                    (OBJ_MEDIA_BOOK, "Book"),
+                    (OBJ_MEDIA_IMAGE, "ImageObject"),
+                ] {
+                    out.insert(k.to_string(), format!("{}{}", SCHEMA, v));
+                }
+                out
+            }),
+            prefixes: m.prefixes.unwrap_or_else(|| {
+                let mut out = BTreeMap::new();
+                out.insert(format!("rdf"), RDF.to_string());
+                out.insert(format!("xsd"), XSD.to_string());
+                out.insert(format!("schema"), SCHEMA.to_string());
+                out
+            }),
+        };
+    }
+}
+
+fn named_node(iri: String) -> Result<NamedNode, loga::Error> {
+    return Ok(NamedNode::new(&iri).context_with("Invalid IRI", ea!(iri = iri))?);
+}
+
+fn xsd(type_: &str) -> NamedNode {
+    return NamedNode::new_unchecked(format!("{}{}", XSD, type_));
+}
+
+fn file_iri(mapping: &Mapping, hash: &FileHash) -> Result<NamedNode, loga::Error> {
+    return named_node(format!("{}{}", mapping.file_prefix, hash.to_string()));
+}
+
+fn entity_iri(mapping: &Mapping, id: &str) -> Result<NamedNode, loga::Error> {
+    return named_node(format!("{}{}", mapping.node_prefix, urlencoding::encode(id)));
+}
+
+fn value_term(mapping: &Mapping, v: &serde_json::Value) -> Result<Term, loga::Error> {
+    match v {
+        serde_json::Value::String(v) => match mapping.values.get(v) {
+            Some(iri) => return Ok(Term::from(named_node(iri.clone())?)),
+            None => return Ok(Term::from(Literal::new_simple_literal(v))),
+        },
+        serde_json::Value::Number(n) => if n.is_f64() {
+            return Ok(Term::from(Literal::new_typed_literal(n.to_string(), xsd("double"))));
+        } else {
+            return Ok(Term::from(Literal::new_typed_literal(n.to_string(), xsd("integer"))));
+        },
+        serde_json::Value::Bool(b) => return Ok(
+            Term::from(Literal::new_typed_literal(b.to_string(), xsd("boolean"))),
+        ),
+        v => return Ok(
+            Term::from(
+                Literal::new_typed_literal(
+                    serde_json::to_string(v).unwrap(),
+                    NamedNode::new_unchecked(format!("{}JSON", RDF)),
+                ),
+            ),
+        ),
+    }
+}
+
+/// Convert a triple to RDF. String values in `entities` are written as node IRIs.
+/// Returns `None` if the subject isn't a string or file, which RDF can't
+/// represent.
+pub(super) fn to_rdf(
+    mapping: &Mapping,
+    entities: &HashSet<String>,
+    triple: &Triple,
+) -> Result<Option<oxrdf::Triple>, loga::Error> {
+    let subject = match &triple.subject {
+        Node::File(h) => file_iri(mapping, h)?,
+        Node::Value(serde_json::Value::String(v)) => entity_iri(mapping, v)?,
+        Node::Value(_) => return Ok(None),
+    };
+    let predicate = named_node(match mapping.predicates.get(&triple.predicate) {
+        Some(p) => p.clone(),
+        None => format!("{}{}", mapping.predicate_prefix, urlencoding::encode(&triple.predicate)),
+    })?;
+    let object = match &triple.object {
+        Node::File(h) => Term::from(file_iri(mapping, h)?),
+        Node::Value(serde_json::Value::String(v)) if entities.contains(v) => Term::from(entity_iri(mapping, v)?),
+        Node::Value(v) => value_term(mapping, v)?,
+    };
+    return Ok(Some(oxrdf::Triple::new(subject, predicate, object)));
+}
+
+/// Converts RDF back to nodes and predicates, the reverse of `to_rdf`.
+pub(super) struct FromRdf<'a> {
+    mapping: &'a Mapping,
+    predicates_rev: HashMap<String, String>,
+    values_rev: HashMap<String, String>,
+    /// Blank node -> new id
+    blank_ids: HashMap<String, String>,
+}
+
+impl<'a> FromRdf<'a> {
+    pub fn new(mapping: &'a Mapping) -> Self {
+        return FromRdf {
+            mapping: mapping,
+            predicates_rev: mapping.predicates.iter().map(|(k, v)| (v.clone(), k.clone())).collect(),
+            values_rev: mapping.values.iter().map(|(k, v)| (v.clone(), k.clone())).collect(),
+            blank_ids: HashMap::new(),
+        };
+    }
+
+    pub fn predicate(&self, iri: &str) -> Result<String, loga::Error> {
+        if let Some(p) = self.predicates_rev.get(iri) {
+            return Ok(p.clone());
+        }
+        match iri.strip_prefix(&self.mapping.predicate_prefix) {
+            Some(p) => return Ok(
+                urlencoding::decode(p).context_with("Invalid predicate IRI", ea!(iri = iri))?.into_owned(),
+            ),
+            None => return Ok(iri.to_string()),
+        }
+    }
+
+    pub fn node(&mut self, t: Term) -> Result<CliNode, loga::Error> {
+        match t {
+            Term::NamedNode(n) => {
+                let iri = n.as_str();
+                if let Some(hash) = iri.strip_prefix(&self.mapping.file_prefix) {
+                    return Ok(
+                        CliNode::File(
+                            FileHash::from_str(hash)
+                                .map_err(loga::err)
+                                .context_with("Invalid file IRI", ea!(iri = iri))?,
+                        ),
+                    );
+                }
+                if let Some(v) = iri.strip_prefix(&self.mapping.node_prefix) {
+                    return Ok(
+                        CliNode::Value(
+                            serde_json::Value::String(
+                                urlencoding::decode(v).context_with("Invalid node IRI", ea!(iri = iri))?.into_owned(),
+                            ),
+                        ),
+                    );
+                }
+                return Ok(
+                    CliNode::Value(
+                        serde_json::Value::String(
+                            self.values_rev.get(iri).cloned().unwrap_or_else(|| iri.to_string()),
+                        ),
+                    ),
+                );
+            },
+            Term::BlankNode(b) => {
+                return Ok(
+                    CliNode::Value(
+                        serde_json::Value::String(
+                            self
+                                .blank_ids
+                                .entry(b.as_str().to_string())
+                                .or_insert_with(|| Uuid::new_v4().to_string())
+                                .clone(),
+                        ),
+                    ),
+                );
+            },
+            Term::Literal(l) => {
+                let datatype = l.datatype().as_str();
+                let v = l.value();
+                let parsed = match datatype.strip_prefix(XSD) {
+                    Some(
+                        "integer" | "int" | "long" | "short" | "byte" | "nonNegativeInteger" | "positiveInteger" |
+                        "unsignedInt" | "unsignedLong",
+                    ) => i64::from_str(v)
+                        .ok()
+                        .map(serde_json::Value::from)
+                        .or_else(|| u64::from_str(v).ok().map(serde_json::Value::from)),
+                    Some("double" | "float" | "decimal") => f64::from_str(v)
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(serde_json::Value::Number),
+                    Some("boolean") => match v {
+                        "true" | "1" => Some(serde_json::Value::Bool(true)),
+                        "false" | "0" => Some(serde_json::Value::Bool(false)),
+                        _ => None,
+                    },
+                    _ => if datatype == format!("{}JSON", RDF) {
+                        serde_json::from_str(v).ok()
+                    } else {
+                        None
+                    },
+                };
+                return Ok(CliNode::Value(parsed.unwrap_or_else(|| serde_json::Value::String(v.to_string()))));
+            },
+        }
+    }
+}
+
+#[derive(Aargvark)]
+pub struct RdfExportCommand {
+    debug: Option<()>,
+    /// The query or file to get the list of nodes to export from, see `export`.
+    source: ExportCommandSource,
+    /// Parameters for the query, if using a query source.
+    #[vark(flag = "--parameters", flag = "--params", flag = "-p")]
+    parameters: HashMap<String, AargvarkStrNode>,
+    format: RdfFormatArg,
+    /// Write the RDF to this file.
+    dest: PathBuf,
+    /// How to convert nodes and predicates to RDF, see the readme for details.
+    mapping: Option<AargvarkJson<CliRdfMapping>>,
+    /// Only include relations with the listed predicates.
+    include: Option<HashSet<String>>,
+    /// Exclude relations with the listed predicates. Takes precedence over `--include`
+    exclude: Option<HashSet<String>>,
+}
+
+pub async fn handle_rdf_export(c: RdfExportCommand) -> Result<(), loga::Error> {
+    let log = Log::new_root(if c.debug.is_some() {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    let mapping = Mapping::new(c.mapping.map(|m| m.value));
+    let triples = export_triples(&log, c.source, &c.parameters).await?;
+
+    // Values used as subjects are entities, written as IRIs wherever they appear.
+    // Objects can also refer to nodes that aren't being exported, so check other
+    // string objects with the server.
+    let mut entities = HashSet::new();
+    for triple in &triples {
+        if let Node::Value(serde_json::Value::String(v)) = &triple.subject {
+            entities.insert(v.clone());
+        }
+    }
+    let mut candidates = HashSet::new();
+    for triple in &triples {
+        if let Node::Value(serde_json::Value::String(v)) = &triple.object {
+            if !entities.contains(v) && !mapping.values.contains_key(v) {
+                candidates.insert(v.clone());
+            }
+        }
+    }
+    let candidates = candidates.into_iter().collect::<Vec<_>>();
+    for chunk in candidates.chunks(100) {
+        let chunk_set = chunk.iter().collect::<HashSet<_>>();
+        for t in req::req_simple(&log, ReqGetTriplesAround {
+            nodes: chunk.iter().map(|v| Node::Value(serde_json::Value::String(v.clone()))).collect(),
+        }).await? {
+            if let Node::Value(serde_json::Value::String(v)) = &t.subject {
+                if chunk_set.contains(v) {
+                    entities.insert(v.clone());
+                }
+            }
+        }
+    }
+    let mut serializer = RdfSerializer::from_format(c.format.format());
+    for (k, v) in &mapping.prefixes {
+        serializer =
+            serializer.with_prefix(k, v).context_with("Invalid prefix in mapping", ea!(prefix = k, iri = v))?;
+    }
+    let mut serializer = serializer.for_writer(vec![]);
+    let mut skipped = 0usize;
+    for triple in &triples {
+        if let Some(include) = c.include.as_ref() {
+            if !include.contains(&triple.predicate) {
+                continue;
+            }
+        }
+        if let Some(exclude) = c.exclude.as_ref() {
+            if exclude.contains(&triple.predicate) {
+                continue;
+            }
+        }
+        let Some(triple) = to_rdf(&mapping, &entities, triple)? else {
+            skipped += 1;
+            continue;
+        };
+        serializer.serialize_triple(&triple).context("Error writing RDF triple")?;
+    }
+    if skipped > 0 {
+        log.log_with(
+            loga::WARN,
+            "Skipped triples with subjects that aren't strings or files, which can't be represented in RDF",
+            ea!(count = skipped),
+        );
+    }
+    let out = serializer.finish().context("Error finishing RDF output")?;
+    write(&c.dest, out).context_with("Error writing RDF", ea!(path = c.dest.dbg_str()))?;
+    return Ok(());
+}
+
+#[derive(Aargvark)]
+pub struct RdfImportCommand {
+    debug: Option<()>,
+    /// The RDF file to import.
+    source: PathBuf,
+    /// Write the CLI commit JSON to this path. Commit it with `commit`.
+    dest: PathBuf,
+    /// The RDF format. If not specified, it's guessed from the source extension
+    /// (`.nt`, `.ttl`, `.jsonld`).
+    format: Option<RdfFormatArg>,
+    /// How to convert RDF to nodes and predicates. This should be the same mapping
+    /// used to export the data if it came from sunwet.
+    mapping: Option<AargvarkJson<CliRdfMapping>>,
+}
+
+pub fn handle_rdf_import(c: RdfImportCommand) -> Result<(), loga::Error> {
+    let mapping = Mapping::new(c.mapping.map(|m| m.value));
+    let format = match c.format {
+        Some(f) => f,
+        None => match c.source.extension().unwrap_or_default().to_string_lossy().as_ref() {
+            "nt" => RdfFormatArg::NTriples,
+            "ttl" => RdfFormatArg::Turtle,
+            "jsonld" | "json" => RdfFormatArg::JsonLd,
+            _ => {
+                return Err(
+                    loga::err_with(
+                        "Unable to determine RDF format from extension, specify it with `--format`",
+                        ea!(path = c.source.dbg_str()),
+                    ),
+                );
+            },
+        },
+    };
+    let mut from_rdf = FromRdf::new(&mapping);
+    let mut add = vec![];
+    let source = File::open(&c.source).context_with("Error opening RDF file", ea!(path = c.source.dbg_str()))?;
+    for quad in RdfParser::from_format(format.format()).for_reader(BufReader::new(source)) {
+        let quad = quad.context_with("Error parsing RDF", ea!(path = c.source.dbg_str()))?;
+        add.push(CliTriple {
+            subject: from_rdf.node(Term::from(quad.subject))?,
+            predicate: from_rdf.predicate(quad.predicate.as_str())?,
+            object: from_rdf.node(quad.object)?,
+        });
+    }
+    write(&c.dest, serde_json::to_string_pretty(&CliCommit {
+        remove: vec![],
+        add: add,
+    }).unwrap()).context_with("Error writing commit", ea!(path = c.dest.dbg_str()))?;
+    return Ok(());
+}
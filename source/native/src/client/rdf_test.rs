@@ -0,0 +1,161 @@
+#![cfg(test)]
+
+use {
+    oxrdf::Term,
+    oxrdfio::{
+        JsonLdProfileSet,
+        RdfFormat,
+        RdfParser,
+        RdfSerializer,
+    },
+    pretty_assertions::assert_eq,
+    shared::interface::{
+        cli::CliNode,
+        ont::{
+            OBJ_IS_ALBUM,
+            PREDICATE_COVER,
+            PREDICATE_INDEX,
+            PREDICATE_IS,
+            PREDICATE_NAME,
+            PREDICATE_TRACK,
+        },
+        triple::{
+            FileHash,
+            Node,
+        },
+        wire::Triple,
+    },
+    std::{
+        collections::HashSet,
+        str::FromStr,
+    },
+    super::rdf::{
+        FromRdf,
+        Mapping,
+        to_rdf,
+    },
+};
+
+fn str_node(v: &str) -> Node {
+    return Node::Value(serde_json::Value::String(v.to_string()));
+}
+
+fn triple(subject: Node, predicate: &str, object: Node) -> Triple {
+    return Triple {
+        subject: subject,
+        predicate: predicate.to_string(),
+        object: object,
+    };
+}
+
+/// Triples as comparable json, sorted.
+fn normalize(triples: Vec<(CliNode, String, CliNode)>) -> Vec<String> {
+    let mut out =
+        triples
+            .into_iter()
+            .map(|t| serde_json::to_string(&(t.0, t.1, t.2)).unwrap())
+            .collect::<Vec<_>>();
+    out.sort();
+    return out;
+}
+
+fn cli_node(n: &Node) -> CliNode {
+    match n {
+        Node::File(h) => return CliNode::File(h.clone()),
+        Node::Value(v) => return CliNode::Value(v.clone()),
+    }
+}
+
+fn round_trip(format: RdfFormat, entities: &HashSet<String>, triples: &[Triple]) -> Vec<(CliNode, String, CliNode)> {
+    let mapping = Mapping::new(None);
+    let mut serializer = RdfSerializer::from_format(format.clone()).for_writer(vec![]);
+    for t in triples {
+        serializer.serialize_triple(&to_rdf(&mapping, entities, t).unwrap().unwrap()).unwrap();
+    }
+    let data = serializer.finish().unwrap();
+    let mut from_rdf = FromRdf::new(&mapping);
+    let mut out = vec![];
+    for quad in RdfParser::from_format(format).for_reader(data.as_slice()) {
+        let quad = quad.unwrap();
+        out.push(
+            (
+                from_rdf.node(Term::from(quad.subject)).unwrap(),
+                from_rdf.predicate(quad.predicate.as_str()).unwrap(),
+                from_rdf.node(quad.object).unwrap(),
+            ),
+        );
+    }
+    return out;
+}
+
+/// Triples covering each kind of node - entities, files, and literals of each json
+/// type - and the entities among them.
+fn round_trip_triples() -> (HashSet<String>, Vec<Triple>) {
+    let file = Node::File(FileHash::from_str("sha256:0123456789abcdef").unwrap());
+    let triples = vec![
+        triple(str_node("n1"), PREDICATE_IS, str_node(OBJ_IS_ALBUM)),
+        triple(str_node("n1"), PREDICATE_NAME, str_node("Some name")),
+        triple(str_node("n1"), PREDICATE_INDEX, Node::Value(serde_json::json!(3))),
+        triple(str_node("n1"), "custom/big number", Node::Value(serde_json::json!(u64::MAX))),
+        triple(str_node("n1"), "custom/negative", Node::Value(serde_json::json!(-5))),
+        triple(str_node("n1"), "custom/float", Node::Value(serde_json::json!(1.5))),
+        triple(str_node("n1"), "custom/bool", Node::Value(serde_json::json!(true))),
+        triple(str_node("n1"), "custom/json", Node::Value(serde_json::json!({
+            "a": [1]
+        }))),
+        triple(str_node("n1"), PREDICATE_TRACK, str_node("n2")),
+        triple(str_node("n1"), PREDICATE_COVER, file.clone()),
+        triple(file.clone(), PREDICATE_NAME, str_node("cover.jpg")),
+    ];
+    let entities = ["n1", "n2"].into_iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+    return (entities, triples);
+}
+
+fn want_triples(triples: &[Triple]) -> Vec<String> {
+    return normalize(
+        triples.iter().map(|t| (cli_node(&t.subject), t.predicate.clone(), cli_node(&t.object))).collect(),
+    );
+}
+
+#[test]
+fn test_rdf_round_trip() {
+    let (entities, triples) = round_trip_triples();
+    let want = want_triples(&triples);
+    assert_eq!(normalize(round_trip(RdfFormat::NTriples, &entities, &triples)), want);
+    assert_eq!(normalize(round_trip(RdfFormat::Turtle, &entities, &triples)), want);
+}
+
+#[test]
+fn test_rdf_round_trip_json_ld() {
+    let (entities, triples) = round_trip_triples();
+    assert_eq!(
+        normalize(round_trip(RdfFormat::JsonLd { profile: JsonLdProfileSet::empty() }, &entities, &triples)),
+        want_triples(&triples)
+    );
+}
+
+#[test]
+fn test_rdf_big_integer() {
+    let mapping = Mapping::new(None);
+    let t = triple(str_node("n1"), PREDICATE_INDEX, Node::Value(serde_json::json!(u64::MAX)));
+    let t = to_rdf(&mapping, &HashSet::new(), &t).unwrap().unwrap();
+    assert_eq!(
+        serde_json::to_value(FromRdf::new(&mapping).node(t.object).unwrap()).unwrap(),
+        serde_json::to_value(CliNode::Value(serde_json::json!(u64::MAX))).unwrap(),
+    );
+}
+
+#[test]
+fn test_rdf_object_entities() {
+    let mapping = Mapping::new(None);
+    let entities = ["n2"].into_iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+
+    // A string referring to a node is written as an IRI even if the node isn't a
+    // subject in the export
+    let t = to_rdf(&mapping, &entities, &triple(str_node("n1"), PREDICATE_TRACK, str_node("n2"))).unwrap().unwrap();
+    assert!(matches!(t.object, Term::NamedNode(_)));
+
+    // Other strings are literals
+    let t = to_rdf(&mapping, &entities, &triple(str_node("n1"), PREDICATE_NAME, str_node("n3"))).unwrap().unwrap();
+    assert!(matches!(t.object, Term::Literal(_)));
+}
@@ -15,12 +15,16 @@ use {
             view::{
                 ClientViewParam,
                 TrackEndMode,
+                ViewFacet,
                 ViewId,
                 WidgetRootDataRows,
             },
         },
         iam::UserIdentityId,
-        query::Query,
+        query::{
+            Query,
+            SortQuery,
+        },
         triple::Node,
     },
     std::{
@@ -101,6 +105,15 @@ pub struct ServerConfigMenuItem {
     pub detail: ServerConfigMenuItemDetail,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, Hash, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ViewSort {
+    /// Text to show in the sort selector.
+    pub name: String,
+    /// Replaces the sort of the root query when selected.
+    pub sort: SortQuery,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, Hash, JsonSchema, TS)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct View {
@@ -119,6 +132,18 @@ pub struct View {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub track_end_mode: TrackEndMode,
+    /// Alternative orders for the rows of the root query the user can choose between.
+    /// The root query's own sort is used when none is chosen. Requires the root data
+    /// to be a query with a record suffix.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub sorts: Vec<ViewSort>,
+    /// Fields of the root query the user can narrow the rows by. Each facet is shown
+    /// with the available values and their row counts. Requires the root data to be a
+    /// query with a record suffix.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub facets: Vec<ViewFacet>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Hash, JsonSchema, TS)]
@@ -147,6 +172,11 @@ pub struct ConfigIamGrantsLimited {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub forms: HashSet<FormId>,
+    /// Let the user make the edits configured in these views (changing editable
+    /// values, bulk deleting). This doesn't give access to the views themselves.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub view_edits: HashSet<ViewId>,
 }
 
 #[derive(Serialize, Deserialize, Clone, JsonSchema, TS, Debug)]
@@ -228,6 +258,138 @@ pub struct FdapConfig {
     pub token: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct BuiltinGeneratorVideoWebm {
+    /// Constant quality factor for the vp9 encode, lower is higher quality (and
+    /// larger). Defaults to 30.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub crf: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum BuiltinGenerator {
+    /// Extract text subtitle tracks from a video as VTT (one per language).
+    VideoSubtitles,
+    /// Transcode a video to webm for web playback.
+    VideoWebm(BuiltinGeneratorVideoWebm),
+    /// Transcode audio to webm (opus) for web playback.
+    AudioWebm,
+    /// Convert an epub to a single self-contained HTML file for the web reader.
+    EpubHtml,
+    /// Extract comic archive pages and generate a page manifest for the web reader.
+    ComicDir,
+    /// Extract text for full text search. For videos this uses extracted subtitles
+    /// and for epubs the generated HTML, so this must come after those generators.
+    Fulltext,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct CommandGenerator {
+    /// An identifier for the generated file, used when requesting it. To provide a
+    /// replacement file (i.e. a transcode) used when the original can't be played,
+    /// use `mime_` followed by the output mimetype with non-alphanumeric characters
+    /// replaced with `_`, like `mime_audio_webm`.
+    pub gentype: String,
+    /// The mimetype of the generated file, sent when serving it.
+    pub mimetype: String,
+    /// The program and arguments to run. In each element, `{source}` is replaced
+    /// with the path of the original file and `{dest}` with the path the output must
+    /// be written to. The command is run in an empty temporary directory.
+    pub command: Vec<String>,
+    /// Create `{dest}` as an empty directory before running the command, for
+    /// generators that produce multiple files.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub dest_dir: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum GeneratorKind {
+    /// One of the generators included with Sunwet.
+    Builtin(BuiltinGenerator),
+    /// Run an external command.
+    Command(CommandGenerator),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct GeneratorConfig {
+    /// Run the generator for files with these mimetypes. Each is either a full
+    /// mimetype (`audio/mpeg`), a type with any subtype (`audio/*`), or `*` to match
+    /// everything.
+    pub mimetypes: Vec<String>,
+    /// Don't run the generator for files with these mimetypes, even if they match
+    /// `mimetypes`. Same format as `mimetypes`.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub exclude_mimetypes: Vec<String>,
+    /// Slow generators are run after all other generators during the startup scan
+    /// so they don't hold up quick results.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub slow: bool,
+    /// The maximum number of files this generator can process at the same time.
+    /// Defaults to 1.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub concurrency: Option<usize>,
+    /// What to run.
+    pub kind: GeneratorKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct GeneratorWorkersConfig {
+    /// How many files to run non-slow generators for at once. Defaults to 4.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub fast: Option<usize>,
+    /// How many files to run slow generators for at once. Defaults to 1.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub slow: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct S3FileStorageConfig {
+    /// Url of the S3-compatible service, like `https://s3.us-west-1.amazonaws.com`.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Store files under this key prefix in the bucket.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Address the bucket as `endpoint/bucket` rather than `bucket.endpoint`. Most
+    /// self-hosted services (Minio, Garage, etc) need this.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub path_style: bool,
+    /// Redirect file downloads to pre-signed urls rather than proxying them through
+    /// Sunwet. The bucket must allow cross-origin requests from the Sunwet domain.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub redirect: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum FileStorageConfig {
+    /// Store files on the local filesystem.
+    #[default]
+    Local,
+    /// Store files in an S3-compatible bucket.
+    S3(S3FileStorageConfig),
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, TS)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 #[ts(export)]
@@ -258,6 +420,31 @@ pub struct Config {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub fdap: Option<FdapConfig>,
+    /// Generators for derived files (transcodes, extracted subtitles, etc). These are
+    /// run in order for each file. If not specified, a default set of builtin
+    /// generators is used; if specified it replaces the defaults, so include any
+    /// builtin generators you want to keep.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub generators: Option<Vec<GeneratorConfig>>,
+    /// How many files are processed in parallel by the generators. Files are
+    /// processed separately for slow and non-slow generators, so a long video
+    /// transcode doesn't hold up quick generation of other files.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub generator_workers: GeneratorWorkersConfig,
+    /// Where to store uploaded files. Defaults to local (`live/files` in the
+    /// persistent dir).
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub storage: FileStorageConfig,
+    /// Where to store generated files. These can all be re-created, so this can be
+    /// less durable than `storage`. Defaults to local (`genfiles` in the cache dir).
+    /// Must not overlap `storage` (same directory, or same bucket with nested
+    /// prefixes).
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub generated_storage: FileStorageConfig,
     /// Everything else.
     pub global: MaybeFdap<GlobalConfig>,
 }
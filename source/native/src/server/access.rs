@@ -81,6 +81,16 @@ pub enum Identity {
     Public,
 }
 
+impl Identity {
+    /// The user id, for per-user data like play state.
+    pub fn user(&self) -> Option<String> {
+        match self {
+            Identity::User(u) => return Some(u.0.clone()),
+            _ => return None,
+        }
+    }
+}
+
 pub async fn identify_requester(state: &State, headers: &HeaderMap) -> Result<Identity, VisErr<loga::Error>> {
     let global_config = get_global_config(state).await.err_internal()?;
     if let Ok(got_token) = htserve::auth::get_auth_token(headers) {
@@ -426,6 +426,25 @@ pub fn meta_upsert_fulltext(
     Ok(())
 }
 
+pub fn meta_has_fulltext(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    node: &crate::interface::triple::DbNode,
+) -> Result<bool, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_opt!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             1 as x
+           from
+             meta
+           where
+             node = ${node = node}
+             and fulltext != ''
+           "#;
+        db
+    ).context("Error executing meta_has_fulltext")?.is_some())
+}
+
 pub fn triple_snapshot_exists(
     db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
     subject: &crate::interface::triple::DbNode,
@@ -490,6 +509,32 @@ pub fn commit_insert(
     Ok(())
 }
 
+pub fn commit_list_all(
+    db: &mut db::Db<impl SqliteConnection>,
+) -> Result<Vec<(DateTime<Utc>, String)>, loga::Error> {
+    let sql = r#"SELECT "idtimestamp", "description" FROM "commit" ORDER BY "idtimestamp""#;
+    Ok(db.0.query(sql, [], |row| {
+        let ts: GoodOrmningSqliteTimestamp = row.get(0)?;
+        let description: String = row.get(1)?;
+        Ok((parse_timestamp(ts)?, description))
+    }).map_err(|e| loga::err(e.to_string()))?)
+}
+
+pub fn commit_any(db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>) -> Result<bool, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_opt!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             idtimestamp
+           from
+             "commit"
+           limit
+             1
+           "#;
+        db
+    ).context("Error executing commit_any")?.is_some())
+}
+
 pub fn commit_get_description(
     db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
     id: &chrono::DateTime<chrono::Utc>,
@@ -600,6 +645,600 @@ pub fn generated_filter_existing_nodes(
     ).context("Error executing generated_filter_existing_nodes")?)
 }
 
+pub fn generate_queue_push(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+    priority: i64,
+    now: DateTime<Utc>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"insert into
+             generate_queue (
+               file,
+               slow,
+               priority,
+               attempts,
+               next_attempt,
+               running,
+               failed,
+               last_error,
+               version
+             )
+           values
+             (
+               ${filehash = file},
+               ${bool = slow},
+               ${i64 = priority},
+               0,
+               ${utctime_ms_chrono = now},
+               false,
+               false,
+               null,
+               0
+             )
+           on conflict (file, slow) do update
+           set
+             priority = excluded.priority,
+             attempts = 0,
+             next_attempt = excluded.next_attempt,
+             failed = false,
+             last_error = null,
+             version = generate_queue.version + 1
+           "#;
+        db
+    ).context("Error executing generate_queue_push")?;
+    Ok(())
+}
+
+pub fn generate_queue_push_if_missing(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+    priority: i64,
+    now: DateTime<Utc>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"insert or ignore into
+             generate_queue (
+               file,
+               slow,
+               priority,
+               attempts,
+               next_attempt,
+               running,
+               failed,
+               last_error,
+               version
+             )
+           values
+             (
+               ${filehash = file},
+               ${bool = slow},
+               ${i64 = priority},
+               0,
+               ${utctime_ms_chrono = now},
+               false,
+               false,
+               null,
+               0
+             )
+           "#;
+        db
+    ).context("Error executing generate_queue_push_if_missing")?;
+    Ok(())
+}
+
+/// Release jobs claimed by workers from a previous run, and give failed jobs
+/// another chance. Only call this before the workers start.
+pub fn generate_queue_reset(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"update generate_queue
+           set
+             running = false
+           where
+             running = true
+           "#;
+        db
+    ).context("Error executing generate_queue_reset (running)")?;
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"update generate_queue
+           set
+             failed = false,
+             attempts = 0
+           where
+             failed = true
+           "#;
+        db
+    ).context("Error executing generate_queue_reset (failed)")?;
+    Ok(())
+}
+
+pub struct GenerateQueueClaim {
+    pub file: crate::interface::triple::DbFileHash,
+    pub attempts: i64,
+    pub version: i64,
+}
+
+/// Get the highest priority job that's ready to run and mark it as running.
+pub fn generate_queue_claim(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    slow: bool,
+    now: DateTime<Utc>,
+) -> Result<Option<GenerateQueueClaim>, loga::Error> {
+    let Some(found) = good_ormning::sqlite::good_query_opt!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             file,
+             attempts,
+             version
+           from
+             generate_queue
+           where
+             slow = ${bool = slow}
+             and failed = false
+             and running = false
+             and next_attempt <= ${utctime_ms_chrono = now}
+           order by
+             priority desc,
+             next_attempt asc
+           limit
+             1
+           "#;
+        db
+    ).context("Error executing generate_queue_claim (select)")? else {
+        return Ok(None);
+    };
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"update generate_queue
+           set
+             running = true
+           where
+             file = ${filehash = &found.file}
+             and slow = ${bool = slow}
+           "#;
+        db
+    ).context("Error executing generate_queue_claim (update)")?;
+    Ok(Some(GenerateQueueClaim {
+        file: found.file,
+        attempts: found.attempts,
+        version: found.version,
+    }))
+}
+
+pub fn generate_queue_earliest(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    slow: bool,
+) -> Result<Option<DateTime<Utc>>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_opt!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             next_attempt
+           from
+             generate_queue
+           where
+             slow = ${bool = slow}
+             and failed = false
+             and running = false
+           order by
+             next_attempt asc
+           limit
+             1
+           "#;
+        db
+    ).context("Error executing generate_queue_earliest")?)
+}
+
+pub fn generate_queue_done(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"delete from generate_queue
+           where
+             file = ${filehash = file}
+             and slow = ${bool = slow}
+           "#;
+        db
+    ).context("Error executing generate_queue_done")?;
+    Ok(())
+}
+
+/// Remove a claimed job once it's done, unless it was replaced while running, in
+/// which case release it to run again.
+pub fn generate_queue_finish(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+    version: i64,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"delete from generate_queue
+           where
+             file = ${filehash = file}
+             and slow = ${bool = slow}
+             and version = ${i64 = version}
+           "#;
+        db
+    ).context("Error executing generate_queue_finish (delete)")?;
+    generate_queue_release(db, file, slow)?;
+    Ok(())
+}
+
+fn generate_queue_release(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"update generate_queue
+           set
+             running = false
+           where
+             file = ${filehash = file}
+             and slow = ${bool = slow}
+           "#;
+        db
+    ).context("Error executing generate_queue_release")?;
+    Ok(())
+}
+
+/// Remove jobs for files not in `files` (all stored files).
+pub fn generate_queue_gc(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    files: Vec<&crate::interface::triple::DbFileHash>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"delete from generate_queue
+           where
+             file not in (
+               select
+                 value
+               from
+                 rarray (${arr filehash = files})
+             )
+           "#;
+        db
+    ).context("Error executing generate_queue_gc")?;
+    Ok(())
+}
+
+/// Schedule a claimed job to run again after failing. If it was replaced while
+/// running it's released as is, since the replacement resets the attempts.
+pub fn generate_queue_retry(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+    slow: bool,
+    version: i64,
+    attempts: i64,
+    next_attempt: DateTime<Utc>,
+    failed: bool,
+    error: &str,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"update generate_queue
+           set
+             running = false,
+             attempts = ${i64 = attempts},
+             next_attempt = ${utctime_ms_chrono = next_attempt},
+             failed = ${bool = failed},
+             last_error = ${string = error}
+           where
+             file = ${filehash = file}
+             and slow = ${bool = slow}
+             and version = ${i64 = version}
+           "#;
+        db
+    ).context("Error executing generate_queue_retry")?;
+    generate_queue_release(db, file, slow)?;
+    Ok(())
+}
+
+pub struct GenerateQueueRow {
+    pub slow: bool,
+    pub priority: i64,
+    pub attempts: i64,
+    pub next_attempt: DateTime<Utc>,
+    pub running: bool,
+    pub failed: bool,
+    pub last_error: Option<String>,
+}
+
+pub fn generate_queue_get(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    file: &crate::interface::triple::DbFileHash,
+) -> Result<Vec<GenerateQueueRow>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_many!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             slow,
+             priority,
+             attempts,
+             next_attempt,
+             running,
+             failed,
+             last_error
+           from
+             generate_queue
+           where
+             file = ${filehash = file}
+           "#;
+        db
+    ).context("Error executing generate_queue_get")?.into_iter().map(|r| GenerateQueueRow {
+        slow: r.slow,
+        priority: r.priority,
+        attempts: r.attempts,
+        next_attempt: r.next_attempt,
+        running: r.running,
+        failed: r.failed,
+        last_error: r.last_error,
+    }).collect())
+}
+
+pub fn generated_list(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    node: &crate::interface::triple::DbNode,
+) -> Result<Vec<(String, String)>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_many!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             gentype,
+             mimetype
+           from
+             generated
+           where
+             node = ${node = node}
+           "#;
+        db
+    ).context("Error executing generated_list")?.into_iter().map(|r| (r.gentype, r.mimetype)).collect())
+}
+
+pub fn user_saved_list(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+) -> Result<Vec<String>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_many!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             "value"
+           from
+             user_saved
+           where
+             "user" = ${string = user}
+           order by
+             stamp asc
+           "#;
+        db
+    ).context("Error executing user_saved_list")?)
+}
+
+pub fn user_saved_keys(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+) -> Result<Vec<String>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_many!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             "key"
+           from
+             user_saved
+           where
+             "user" = ${string = user}
+           "#;
+        db
+    ).context("Error executing user_saved_keys")?)
+}
+
+pub fn user_saved_upsert(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+    key: &str,
+    value: &str,
+    stamp: DateTime<Utc>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"insert into
+             user_saved ("user", "key", "value", stamp)
+           values
+             (
+               ${string = user},
+               ${string = key},
+               ${string = value},
+               ${utctime_ms_chrono = stamp}
+             )
+           on conflict ("user", "key") do update
+           set
+             "value" = excluded."value"
+           "#;
+        db
+    ).context("Error executing user_saved_upsert")?;
+    Ok(())
+}
+
+pub fn user_saved_delete(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+    key: &str,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"delete from user_saved
+           where
+             "user" = ${string = user}
+             and "key" = ${string = key}
+           "#;
+        db
+    ).context("Error executing user_saved_delete")?;
+    Ok(())
+}
+
+/// Record that the user started playing the node, resetting the finished flag and
+/// start time.
+pub fn play_state_start(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+    node: &crate::interface::triple::DbNode,
+    position_ms: i64,
+    duration_ms: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"insert into
+             play_state (
+               "user",
+               node,
+               position_ms,
+               duration_ms,
+               finished,
+               started,
+               updated
+             )
+           values
+             (
+               ${string = user},
+               ${node = node},
+               ${i64 = position_ms},
+               ${opt i64 = duration_ms},
+               false,
+               ${utctime_ms_chrono = now},
+               ${utctime_ms_chrono = now}
+             )
+           on conflict ("user", node) do update
+           set
+             position_ms = excluded.position_ms,
+             duration_ms = excluded.duration_ms,
+             finished = false,
+             started = excluded.started,
+             updated = excluded.updated
+           "#;
+        db
+    ).context("Error executing play_state_start")?;
+    Ok(())
+}
+
+/// Record the user's current position in the node (creating the entry if the
+/// start wasn't recorded).
+pub fn play_state_update(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+    node: &crate::interface::triple::DbNode,
+    position_ms: i64,
+    duration_ms: Option<i64>,
+    finished: bool,
+    now: DateTime<Utc>,
+) -> Result<(), loga::Error> {
+    good_ormning::sqlite::good_query!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"insert into
+             play_state (
+               "user",
+               node,
+               position_ms,
+               duration_ms,
+               finished,
+               started,
+               updated
+             )
+           values
+             (
+               ${string = user},
+               ${node = node},
+               ${i64 = position_ms},
+               ${opt i64 = duration_ms},
+               ${bool = finished},
+               ${utctime_ms_chrono = now},
+               ${utctime_ms_chrono = now}
+             )
+           on conflict ("user", node) do update
+           set
+             position_ms = excluded.position_ms,
+             duration_ms = excluded.duration_ms,
+             finished = excluded.finished,
+             updated = excluded.updated
+           "#;
+        db
+    ).context("Error executing play_state_update")?;
+    Ok(())
+}
+
+pub struct PlayStateRow {
+    pub node: crate::interface::triple::DbNode,
+    pub position_ms: i64,
+    pub duration_ms: Option<i64>,
+    pub finished: bool,
+    pub updated: DateTime<Utc>,
+}
+
+pub fn play_state_get(
+    db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
+    user: &str,
+    nodes: Vec<&crate::interface::triple::DbNode>,
+) -> Result<Vec<PlayStateRow>, loga::Error> {
+    Ok(good_ormning::sqlite::good_query_many!(
+        db,
+        //# genemichaels-external: sql-formatter-sqlite
+        r#"select
+             node,
+             position_ms,
+             duration_ms,
+             finished,
+             updated
+           from
+             play_state
+           where
+             "user" = ${string = user}
+             and node in (
+               select
+                 value
+               from
+                 rarray (${arr node = nodes})
+             )
+           "#;
+        db
+    ).context("Error executing play_state_get")?.into_iter().map(|r| PlayStateRow {
+        node: r.node,
+        position_ms: r.position_ms,
+        duration_ms: r.duration_ms,
+        finished: r.finished,
+        updated: r.updated,
+    }).collect())
+}
+
 pub fn snapshot_filter_nodes_by_end(
     db: &mut db::Db<impl good_ormning::runtime::sqlite::SqliteConnection>,
     col: &str,
@@ -637,6 +1276,29 @@ pub struct HistoryRow {
     pub exists: bool,
 }
 
+fn parse_timestamp(ts: GoodOrmningSqliteTimestamp) -> rusqlite::Result<DateTime<Utc>> {
+    match ts {
+        GoodOrmningSqliteTimestamp::String(s) => {
+            return Ok(
+                chrono::DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+            );
+        },
+        GoodOrmningSqliteTimestamp::I64(ms) => {
+            return Ok(
+                chrono::DateTime::from_timestamp_millis(
+                    ms,
+                ).ok_or_else(
+                    || rusqlite::Error::ToSqlConversionFailure(
+                        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp millis")),
+                    ),
+                )?,
+            );
+        },
+    }
+}
+
 fn parse_history_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryRow> {
     let subject_str: String = row.get(0)?;
     let predicate: String = row.get(1)?;
@@ -659,22 +1321,7 @@ fn parse_history_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryRow> {
                 Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
             ),
         )?;
-    let commit_ = match commit_ts {
-        GoodOrmningSqliteTimestamp::String(s) => {
-            chrono::DateTime::parse_from_rfc3339(&s)
-                .map(|dt| dt.with_timezone(&chrono::Utc))
-                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?
-        },
-        GoodOrmningSqliteTimestamp::I64(ms) => {
-            chrono::DateTime::from_timestamp_millis(
-                ms,
-            ).ok_or_else(
-                || rusqlite::Error::ToSqlConversionFailure(
-                    Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp millis")),
-                ),
-            )?
-        },
-    };
+    let commit_ = parse_timestamp(commit_ts)?;
     Ok(HistoryRow {
         subject,
         predicate,
@@ -727,6 +1374,89 @@ pub fn hist_list_all_after(
     )
 }
 
+// Full history or snapshot in commit order, for dumps
+const DUMP_HIST_SQL: &str = r#"
+    SELECT s."value", p."value", o."value", t."commit_", t."exists", t."subject", t."predicate", t."object"
+    FROM "triple" t
+    JOIN "subjobj" s ON t."subject" = s."id"
+    JOIN "predicate" p ON t."predicate" = p."id"
+    JOIN "subjobj" o ON t."object" = o."id"
+"#;
+const DUMP_SNAPSHOT_SQL: &str = r#"
+    SELECT s."value", p."value", o."value", t."commit_", 1, t."subject", t."predicate", t."object"
+    FROM "triple_snapshot" t
+    JOIN "subjobj" s ON t."subject" = s."id"
+    JOIN "predicate" p ON t."predicate" = p."id"
+    JOIN "subjobj" o ON t."object" = o."id"
+"#;
+const DUMP_ORDER: &str = r#"
+    ORDER BY t."commit_" ASC, t."subject" ASC, t."predicate" ASC, t."object" ASC
+    LIMIT 10000
+"#;
+const DUMP_AFTER: &str = r#"
+    (t."commit_", t."subject", t."predicate", t."object") > (?1, ?2, ?3, ?4)
+"#;
+
+/// Position in a dump listing. These are the raw columns (integer ids rather than
+/// node values) so paging can use the table indexes.
+#[derive(Clone)]
+pub struct DumpKey {
+    commit_: rusqlite::types::Value,
+    subject: i64,
+    predicate: i64,
+    object: i64,
+}
+
+pub struct DumpRow {
+    pub row: HistoryRow,
+    pub key: DumpKey,
+}
+
+fn parse_dump_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<DumpRow> {
+    return Ok(DumpRow {
+        row: parse_history_row(row)?,
+        key: DumpKey {
+            commit_: row.get(3)?,
+            subject: row.get(5)?,
+            predicate: row.get(6)?,
+            object: row.get(7)?,
+        },
+    });
+}
+
+/// A page of triples in commit order. With `snapshot` only the current triples
+/// are listed, otherwise the full history.
+pub fn dump_list(
+    db: &mut db::Db<impl SqliteConnection>,
+    snapshot: bool,
+    after: Option<&DumpKey>,
+) -> Result<Vec<DumpRow>, loga::Error> {
+    let base = if snapshot {
+        DUMP_SNAPSHOT_SQL
+    } else {
+        DUMP_HIST_SQL
+    };
+    match after {
+        None => {
+            let sql = format!("{}{}", base, DUMP_ORDER);
+            Ok(db.0.query(&sql, [], parse_dump_row).map_err(|e| loga::err(e.to_string()))?)
+        },
+        Some(after) => {
+            let sql = format!("{} WHERE {} {}", base, DUMP_AFTER, DUMP_ORDER);
+            Ok(
+                db
+                    .0
+                    .query(
+                        &sql,
+                        rusqlite::params![&after.commit_, after.subject, after.predicate, after.object],
+                        parse_dump_row,
+                    )
+                    .map_err(|e| loga::err(e.to_string()))?,
+            )
+        },
+    }
+}
+
 pub fn hist_list_by_node(
     db: &mut db::Db<impl SqliteConnection>,
     node: &DbNode,
@@ -5,9 +5,13 @@ use {
     },
     crate::{
         interface::triple::DbNode,
-        server::db,
+        server::{
+            db,
+            dbutil,
+        },
     },
     loga::ResultContext,
+    shared::interface::triple::Node,
 };
 
 pub fn write_triple<
@@ -186,3 +190,52 @@ pub fn write_triple<
     }
     return Ok(());
 }
+
+/// Update the fulltext search text for a value node.
+pub fn update_fulltext<
+    C: good_ormning::runtime::sqlite::SqliteConnection,
+>(db: &mut db::Db<C>, node: &Node) -> Result<(), loga::Error> {
+    let mut fulltext = String::new();
+
+    fn gather_value_text(fulltext: &mut String, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Null => {
+                // nop
+            },
+            serde_json::Value::Bool(_) => {
+                // nop
+            },
+            serde_json::Value::Number(_) => {
+                // nop
+            },
+            serde_json::Value::String(v) => {
+                fulltext.push_str(v);
+                fulltext.push_str(" ");
+            },
+            serde_json::Value::Array(v) => {
+                for v in v {
+                    gather_value_text(fulltext, v);
+                }
+            },
+            serde_json::Value::Object(v) => {
+                for (k, v) in v {
+                    fulltext.push_str(k);
+                    fulltext.push_str(" ");
+                    gather_value_text(fulltext, v);
+                }
+            },
+        }
+    }
+
+    match node {
+        Node::File(_) => {
+            // Text is extracted from the file contents in the background, don't clobber
+            // it
+            return Ok(());
+        },
+        Node::Value(v) => gather_value_text(&mut fulltext, v),
+    }
+    let node_db = DbNode(node.clone());
+    dbutil::meta_upsert_fulltext(db, &node_db, &fulltext).context("Error updating fulltext")?;
+    return Ok(());
+}
@@ -0,0 +1,539 @@
+//! Full graph dumps for moving servers and creating trimmed copies.
+//!
+//! A dump is a tar archive with these entries, in order:
+//!
+//! * `sunwet_dump.json` - `DumpHeader`
+//!
+//! * `commits.jsonl` - one `DumpCommit` per line, in commit order
+//!
+//! * `files.jsonl` - one `DumpFile` per line
+//!
+//! * `files/<hash>` - the contents of each file in `files.jsonl`
+//!
+//! The header has the hashes of the two jsonl entries, and files are verified
+//! against their hash when loaded.
+use {
+    aargvark::{
+        Aargvark,
+        traits_impls::AargvarkJson,
+    },
+    chrono::{
+        DateTime,
+        Utc,
+    },
+    crate::{
+        interface::{
+            config::Config,
+            triple::DbNode,
+        },
+        server::{
+            dbutil::{
+                self,
+                tx,
+            },
+            dbwrite,
+            fsutil::create_dirs,
+            open_db,
+            storage::FileStorage,
+        },
+    },
+    loga::{
+        DebugDisplay,
+        Log,
+        ResultContext,
+        ea,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    sha2::{
+        Digest,
+        Sha256,
+    },
+    shared::interface::triple::{
+        FileHash,
+        Node,
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            BTreeSet,
+        },
+        fs::File,
+        io::{
+            self,
+            BufRead,
+            BufReader,
+            BufWriter,
+            Read,
+            Write,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+        str::FromStr,
+    },
+    tempfile::tempdir_in,
+};
+
+const DUMP_VERSION: u32 = 1;
+const ENTRY_HEADER: &str = "sunwet_dump.json";
+const ENTRY_COMMITS: &str = "commits.jsonl";
+const ENTRY_FILES: &str = "files.jsonl";
+const ENTRY_FILES_DIR: &str = "files/";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct DumpHeader {
+    version: u32,
+    created: DateTime<Utc>,
+    commits: usize,
+    triples: usize,
+    files: usize,
+    commits_sha256: String,
+    files_sha256: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct DumpTriple {
+    subject: Node,
+    predicate: String,
+    object: Node,
+    exists: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct DumpCommit {
+    timestamp: DateTime<Utc>,
+    description: String,
+    triples: Vec<DumpTriple>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct DumpFile {
+    hash: FileHash,
+    mimetype: Option<String>,
+}
+
+/// Copies data while hashing it.
+struct HashWriter<W: Write> {
+    inner: W,
+    hash: Sha256,
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hash.update(&buf[..n]);
+        return Ok(n);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return self.inner.flush();
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String, loga::Error> {
+    let mut hash = Sha256::new();
+    io::copy(&mut File::open(path).context_with("Error opening file", ea!(path = path.dbg_str()))?, &mut hash)
+        .context_with("Error hashing file", ea!(path = path.dbg_str()))?;
+    return Ok(hex::encode(hash.finalize()));
+}
+
+fn append_file(builder: &mut tar::Builder<impl Write>, name: &str, path: &Path) -> Result<(), loga::Error> {
+    builder
+        .append_path_with_name(path, name)
+        .context_with("Error adding entry to dump", ea!(entry = name, path = path.dbg_str()))?;
+    return Ok(());
+}
+
+#[derive(Aargvark)]
+pub struct DumpArgs {
+    /// The server config, for the database and file storage locations.
+    config: AargvarkJson<Config>,
+    /// Write the dump archive here.
+    dest: PathBuf,
+    /// Only dump the current triples (grouped by the commits that last added them),
+    /// not deleted triples or history.
+    no_history: Option<()>,
+    /// Don't include file contents. File nodes are kept in the graph.
+    no_files: Option<()>,
+    /// Skip files larger than this many bytes.
+    max_file_size: Option<u64>,
+}
+
+pub struct DumpOptions {
+    pub no_history: bool,
+    pub no_files: bool,
+    pub max_file_size: Option<u64>,
+}
+
+pub async fn handle_dump(args: DumpArgs) -> Result<(), loga::Error> {
+    let config = args.config.value;
+    let log = Log::new_root(if config.debug {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    return dump(&log, &config, &args.dest, DumpOptions {
+        no_history: args.no_history.is_some(),
+        no_files: args.no_files.is_some(),
+        max_file_size: args.max_file_size,
+    }).await;
+}
+
+/// Dump the graph and files of the instance described by `config` to `dest`.
+pub async fn dump(log: &Log, config: &Config, dest: &Path, options: DumpOptions) -> Result<(), loga::Error> {
+    let db = open_db(&log, &config).await?;
+    let files = FileStorage::new(&config.storage, &config.persistent_dir).await?;
+    let temp_root = config.cache_dir.join("dump");
+    create_dirs(&temp_root).await?;
+    let temp = tempdir_in(&temp_root).context("Error creating temp dir for dump")?;
+
+    // Write commits
+    let commits_path = temp.path().join(ENTRY_COMMITS);
+    let snapshot = options.no_history;
+    let (commit_count, triple_count, file_nodes) = tx(&db, {
+        let commits_path = commits_path.clone();
+        move |db| {
+            let descriptions = dbutil::commit_list_all(db)?.into_iter().collect::<BTreeMap<_, _>>();
+            let mut out =
+                BufWriter::new(
+                    File::create(
+                        &commits_path,
+                    ).context_with("Error creating commits file", ea!(path = commits_path.dbg_str()))?,
+                );
+            let mut commit_count = 0usize;
+            let mut triple_count = 0usize;
+            let mut file_nodes = BTreeSet::new();
+            let mut current: Option<DumpCommit> = None;
+            let mut flush = |commit: DumpCommit| -> Result<(), loga::Error> {
+                serde_json::to_writer(&mut out, &commit).context("Error writing commit")?;
+                out.write_all(b"\n").context("Error writing commit")?;
+                return Ok(());
+            };
+            let mut after = None;
+            loop {
+                let page = dbutil::dump_list(db, snapshot, after.as_ref())?;
+                let Some(last) = page.last() else {
+                    break;
+                };
+                let last = last.key.clone();
+                for dbutil::DumpRow { row, .. } in page {
+                    if current.as_ref().map(|c| c.timestamp) != Some(row.commit_) {
+                        if let Some(c) = current.take() {
+                            flush(c)?;
+                        }
+                        commit_count += 1;
+                        current = Some(DumpCommit {
+                            timestamp: row.commit_,
+                            description: descriptions.get(&row.commit_).cloned().unwrap_or_default(),
+                            triples: vec![],
+                        });
+                    }
+                    for n in [&row.subject.0, &row.object.0] {
+                        if let Node::File(h) = n {
+                            file_nodes.insert(h.clone());
+                        }
+                    }
+                    triple_count += 1;
+                    current.as_mut().unwrap().triples.push(DumpTriple {
+                        subject: row.subject.0,
+                        predicate: row.predicate,
+                        object: row.object.0,
+                        exists: row.exists,
+                    });
+                }
+                after = Some(last);
+            }
+            if let Some(c) = current.take() {
+                flush(c)?;
+            }
+            out.flush().context("Error writing commits")?;
+            return Ok((commit_count, triple_count, file_nodes));
+        }
+    }).await?;
+
+    // List files with metadata. Contents are only fetched when adding them to the
+    // archive, one at a time.
+    let mut dump_files = vec![];
+    if !options.no_files {
+        for hash in file_nodes {
+            let size = match files.size(&hash).await {
+                Ok(Some(s)) => s,
+                Ok(None) => {
+                    log.log_with(loga::WARN, "File missing from storage, skipping", ea!(hash = hash.to_string()));
+                    continue;
+                },
+                Err(e) => {
+                    log.log_err(
+                        loga::WARN,
+                        e.context_with("Error getting file, skipping", ea!(hash = hash.to_string())),
+                    );
+                    continue;
+                },
+            };
+            if let Some(max) = options.max_file_size {
+                if size > max {
+                    continue;
+                }
+            }
+            let mimetype = tx(&db, {
+                let hash = hash.clone();
+                move |db| dbutil::meta_get_mimetype(db, &DbNode(Node::File(hash)))
+            }).await?.flatten();
+            dump_files.push(DumpFile {
+                hash: hash,
+                mimetype: mimetype,
+            });
+        }
+    }
+    let files_path = temp.path().join(ENTRY_FILES);
+    {
+        let mut out =
+            BufWriter::new(
+                File::create(&files_path).context_with("Error creating files list", ea!(path = files_path.dbg_str()))?,
+            );
+        for f in &dump_files {
+            serde_json::to_writer(&mut out, f).context("Error writing files list")?;
+            out.write_all(b"\n").context("Error writing files list")?;
+        }
+        out.flush().context("Error writing files list")?;
+    }
+
+    // Assemble archive
+    let header_path = temp.path().join(ENTRY_HEADER);
+    std::fs::write(&header_path, serde_json::to_vec_pretty(&DumpHeader {
+        version: DUMP_VERSION,
+        created: Utc::now(),
+        commits: commit_count,
+        triples: triple_count,
+        files: dump_files.len(),
+        commits_sha256: hash_file(&commits_path)?,
+        files_sha256: hash_file(&files_path)?,
+    }).unwrap()).context("Error writing dump header")?;
+    let mut builder =
+        tar::Builder::new(
+            BufWriter::new(File::create(dest).context_with("Error creating dump", ea!(path = dest.dbg_str()))?),
+        );
+    append_file(&mut builder, ENTRY_HEADER, &header_path)?;
+    append_file(&mut builder, ENTRY_COMMITS, &commits_path)?;
+    append_file(&mut builder, ENTRY_FILES, &files_path)?;
+    for f in &dump_files {
+        // Dropping the local copy after each file keeps at most one downloaded file on
+        // disk
+        let local = files.local_copy(&f.hash, &temp_root).await?;
+        append_file(&mut builder, &format!("{}{}", ENTRY_FILES_DIR, f.hash.to_string()), &local.path)?;
+    }
+    builder.into_inner().context("Error finishing dump")?.flush().context("Error finishing dump")?;
+    log.log_with(
+        loga::INFO,
+        "Dump complete",
+        ea!(commits = commit_count, triples = triple_count, files = dump_files.len()),
+    );
+    return Ok(());
+}
+
+#[derive(Aargvark)]
+pub struct LoadArgs {
+    /// The server config, for the database and file storage locations. The database
+    /// must be empty (no commits).
+    config: AargvarkJson<Config>,
+    /// The dump archive to load.
+    source: PathBuf,
+}
+
+pub async fn handle_load(args: LoadArgs) -> Result<(), loga::Error> {
+    let config = args.config.value;
+    let log = Log::new_root(if config.debug {
+        loga::DEBUG
+    } else {
+        loga::INFO
+    });
+    return load(&log, &config, &args.source).await;
+}
+
+/// Load a dump into the empty instance described by `config`. Everything is
+/// verified before anything is written, and if loading fails partway the database
+/// and file storage are left empty.
+pub async fn load(log: &Log, config: &Config, source: &Path) -> Result<(), loga::Error> {
+    let db = open_db(&log, &config).await?;
+    if tx(&db, |db| dbutil::commit_any(db)).await? {
+        return Err(loga::err("The database already has data, dumps can only be loaded into a new instance"));
+    }
+    let files = FileStorage::new(&config.storage, &config.persistent_dir).await?;
+    let stage_dir = config.persistent_dir.join("stage_files");
+    create_dirs(&stage_dir).await?;
+    let temp = tempdir_in(&stage_dir).context("Error creating temp dir for load")?;
+
+    // Extract the archive to the staging dir, verifying files as they're encountered
+    let mut archive =
+        tar::Archive::new(
+            BufReader::new(File::open(source).context_with("Error opening dump", ea!(path = source.dbg_str()))?),
+        );
+    let mut header = None;
+    let commits_path = temp.path().join(ENTRY_COMMITS);
+    let staged_files_dir = temp.path().join("files");
+    create_dirs(&staged_files_dir).await?;
+    let mut dump_files = BTreeMap::new();
+    let mut staged = vec![];
+    for entry in archive.entries().context("Error reading dump")? {
+        let mut entry = entry.context("Error reading dump entry")?;
+        let name = entry.path().context("Dump entry has invalid path")?.to_string_lossy().to_string();
+        let log = log.fork(ea!(entry = name));
+        if name == ENTRY_HEADER {
+            let mut raw = vec![];
+            entry.read_to_end(&mut raw).context("Error reading dump header")?;
+            let h = serde_json::from_slice::<DumpHeader>(&raw).context("Error parsing dump header")?;
+            if h.version != DUMP_VERSION {
+                return Err(loga::err_with("Unsupported dump version", ea!(version = h.version)));
+            }
+            header = Some(h);
+            continue;
+        }
+        let Some(h) = &header else {
+            return Err(loga::err("Dump doesn't start with a header"));
+        };
+        if name == ENTRY_COMMITS {
+            let mut out = HashWriter {
+                inner: File::create(&commits_path).context("Error creating temp commits file")?,
+                hash: Sha256::new(),
+            };
+            io::copy(&mut entry, &mut out).context("Error extracting commits")?;
+            if hex::encode(out.hash.finalize()) != h.commits_sha256 {
+                return Err(loga::err("Commits in dump don't match hash in header, dump is corrupt"));
+            }
+        } else if name == ENTRY_FILES {
+            let mut raw = vec![];
+            entry.read_to_end(&mut raw).context("Error reading files list")?;
+            if hex::encode(Sha256::digest(&raw)) != h.files_sha256 {
+                return Err(loga::err("Files list in dump doesn't match hash in header, dump is corrupt"));
+            }
+            for line in raw.lines() {
+                let line = line.context("Error reading files list")?;
+                let f = serde_json::from_str::<DumpFile>(&line).context("Error parsing files list entry")?;
+                dump_files.insert(f.hash.clone(), f.mimetype);
+            }
+        } else if let Some(hash) = name.strip_prefix(ENTRY_FILES_DIR) {
+            let hash = FileHash::from_str(hash).map_err(loga::err).context("Invalid file hash in dump")?;
+            if !dump_files.contains_key(&hash) {
+                return Err(log.err("File in dump isn't in files list"));
+            }
+            let stage_path = staged_files_dir.join(staged.len().to_string());
+            let mut out = HashWriter {
+                inner: File::create(&stage_path).context("Error creating staged file")?,
+                hash: Sha256::new(),
+            };
+            io::copy(&mut entry, &mut out).context("Error extracting file")?;
+            out.flush().context("Error extracting file")?;
+            if FileHash::from_sha256(out.hash.finalize()) != hash {
+                return Err(log.err("File contents don't match hash, dump is corrupt"));
+            }
+            staged.push((hash, stage_path));
+        } else {
+            log.log(loga::WARN, "Unknown entry in dump, skipping");
+        }
+    }
+    let Some(header) = header else {
+        return Err(loga::err("Dump is missing header"));
+    };
+    if !commits_path.exists() {
+        return Err(loga::err("Dump is missing commits"));
+    }
+    if staged.len() != header.files || dump_files.len() != header.files {
+        return Err(
+            loga::err_with(
+                "Dump is missing files",
+                ea!(expected = header.files, listed = dump_files.len(), found = staged.len()),
+            ),
+        );
+    }
+
+    // Move files into storage, then replay commits in order in one transaction. If
+    // either fails, the placed files are removed and the database is left empty so
+    // the load can be retried.
+    let mut placed = vec![];
+    let mut place_res = Ok(());
+    for (hash, path) in staged {
+        if let Err(e) = files.place(&hash, &path).await {
+            place_res = Err(e);
+            break;
+        }
+        placed.push(hash);
+    }
+    let replayed = match place_res {
+        Ok(()) => {
+            tx(&db, {
+                let commits_path = commits_path.clone();
+                move |db| {
+                    for (hash, mimetype) in dump_files {
+                        dbutil::meta_upsert_mimetype(db, &DbNode(Node::File(hash)), &mimetype)?;
+                    }
+                    let mut commit_count = 0usize;
+                    let mut triple_count = 0usize;
+                    let commits =
+                        BufReader::new(File::open(&commits_path).context("Error opening extracted commits")?);
+                    for line in commits.lines() {
+                        let line = line.context("Error reading extracted commits")?;
+                        let commit =
+                            serde_json::from_str::<DumpCommit>(&line).context("Error parsing commit in dump")?;
+                        commit_count += 1;
+                        triple_count += commit.triples.len();
+                        for t in commit.triples {
+                            if t.exists {
+                                dbwrite::update_fulltext(db, &t.subject)?;
+                                dbwrite::update_fulltext(db, &t.object)?;
+                            }
+                            dbwrite::write_triple(
+                                db,
+                                &DbNode(t.subject),
+                                &t.predicate,
+                                &DbNode(t.object),
+                                commit.timestamp,
+                                t.exists,
+                            )?;
+                        }
+                        dbutil::commit_insert(db, &commit.timestamp, &commit.description)?;
+                    }
+                    if commit_count != header.commits || triple_count != header.triples {
+                        return Err(
+                            loga::err_with(
+                                "Loaded commits don't match counts in header, dump is corrupt",
+                                ea!(
+                                    expected_commits = header.commits,
+                                    commits = commit_count,
+                                    expected_triples = header.triples,
+                                    triples = triple_count
+                                ),
+                            ),
+                        );
+                    }
+                    return Ok((commit_count, triple_count));
+                }
+            }).await
+        },
+        Err(e) => Err(e),
+    };
+    let (commit_count, triple_count) = match replayed {
+        Ok(r) => r,
+        Err(e) => {
+            for hash in placed {
+                if let Err(e) = files.delete(&hash).await {
+                    log.log_err(loga::WARN, e.context("Error removing placed file after failed load"));
+                }
+            }
+            return Err(e);
+        },
+    };
+    log.log_with(
+        loga::INFO,
+        "Load complete, generated files will be created when the server starts",
+        ea!(commits = commit_count, triples = triple_count, files = header.files),
+    );
+    return Ok(());
+}
@@ -0,0 +1,217 @@
+#![cfg(test)]
+
+use {
+    chrono::{
+        Duration,
+        TimeZone,
+        Utc,
+    },
+    crate::{
+        interface::{
+            config::Config,
+            triple::DbNode,
+        },
+        server::{
+            dbutil::{
+                self,
+                tx,
+            },
+            dbwrite,
+            dump::{
+                dump,
+                load,
+                DumpOptions,
+            },
+            open_db,
+            storage::FileStorage,
+        },
+    },
+    loga::Log,
+    pretty_assertions::assert_eq,
+    serde_json::json,
+    sha2::{
+        Digest,
+        Sha256,
+    },
+    shared::interface::triple::{
+        FileHash,
+        Node,
+    },
+    std::{
+        io::Read,
+        path::Path,
+    },
+    tokio::fs::{
+        read,
+        write,
+    },
+};
+
+fn instance_config(root: &Path) -> Config {
+    return serde_json::from_value(json!({
+        "persistent_dir": root.join("persistent"),
+        "cache_dir": root.join("cache"),
+        "bind_addr": "127.0.0.1:0",
+        "global": {
+            "local": {
+                "api_tokens": {},
+                "menu": [],
+                "views": {},
+                "forms": {},
+            },
+        },
+    })).unwrap();
+}
+
+const FILE_DATA: &[u8] = b"file contents";
+
+/// Two commits: the first adds a file node and a name, the second replaces the
+/// name.
+async fn populate(log: &Log, config: &Config) -> FileHash {
+    let hash = FileHash::from_sha256(Sha256::digest(FILE_DATA));
+    let source = config.cache_dir.join("upload");
+    std::fs::create_dir_all(&config.cache_dir).unwrap();
+    write(&source, FILE_DATA).await.unwrap();
+    FileStorage::new(&config.storage, &config.persistent_dir).await.unwrap().place(&hash, &source).await.unwrap();
+    let db = open_db(log, config).await.unwrap();
+    tx(&db, {
+        let hash = hash.clone();
+        move |db| {
+            let subject = DbNode(Node::Value(json!("album")));
+            let file = DbNode(Node::File(hash));
+            let stamp1 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+            let stamp2 = stamp1 + Duration::hours(1);
+            dbwrite::write_triple(db, &subject, "cover", &file, stamp1, true)?;
+            dbwrite::write_triple(db, &subject, "name", &DbNode(Node::Value(json!("Old"))), stamp1, true)?;
+            dbutil::commit_insert(db, &stamp1, "First")?;
+            dbwrite::write_triple(db, &subject, "name", &DbNode(Node::Value(json!("Old"))), stamp2, false)?;
+            dbwrite::write_triple(db, &subject, "name", &DbNode(Node::Value(json!("New"))), stamp2, true)?;
+            dbutil::commit_insert(db, &stamp2, "Second")?;
+            dbutil::meta_upsert_mimetype(db, &file, &Some("text/plain".to_string()))?;
+            return Ok(());
+        }
+    }).await.unwrap();
+    return hash;
+}
+
+/// All triples, with node values rather than ids so they can be compared across
+/// databases.
+async fn list_triples(log: &Log, config: &Config, snapshot: bool) -> Vec<(Node, String, Node, bool)> {
+    let db = open_db(log, config).await.unwrap();
+    return tx(&db, move |db| {
+        let mut out = vec![];
+        let mut after = None;
+        loop {
+            let page = dbutil::dump_list(db, snapshot, after.as_ref())?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.key.clone());
+            for r in page {
+                out.push((r.row.subject.0, r.row.predicate, r.row.object.0, r.row.exists));
+            }
+        }
+        return Ok(out);
+    }).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_dump_load_round_trip() {
+    let log = Log::new_root(loga::INFO);
+    let root = tempfile::tempdir().unwrap();
+    let config_a = instance_config(&root.path().join("a"));
+    let config_b = instance_config(&root.path().join("b"));
+    let hash = populate(&log, &config_a).await;
+    let dump_path = root.path().join("dump.tar");
+    dump(&log, &config_a, &dump_path, DumpOptions {
+        no_history: false,
+        no_files: false,
+        max_file_size: None,
+    }).await.unwrap();
+    load(&log, &config_b, &dump_path).await.unwrap();
+
+    // Graph
+    assert_eq!(list_triples(&log, &config_b, false).await, list_triples(&log, &config_a, false).await);
+    assert_eq!(list_triples(&log, &config_b, true).await, list_triples(&log, &config_a, true).await);
+    let db_b = open_db(&log, &config_b).await.unwrap();
+    let commits = tx(&db_b, |db| dbutil::commit_list_all(db)).await.unwrap();
+    assert_eq!(commits.into_iter().map(|c| c.1).collect::<Vec<_>>(), vec!["First".to_string(), "Second".to_string()]);
+
+    // Files
+    let files_b = FileStorage::new(&config_b.storage, &config_b.persistent_dir).await.unwrap();
+    assert_eq!(files_b.list(&log).await.unwrap(), vec![hash.clone()]);
+    let local = files_b.local_copy(&hash, &config_b.cache_dir).await.unwrap();
+    assert_eq!(read(&local.path).await.unwrap(), FILE_DATA);
+    let mimetype = tx(&db_b, {
+        let hash = hash.clone();
+        move |db| dbutil::meta_get_mimetype(db, &DbNode(Node::File(hash)))
+    }).await.unwrap();
+    assert_eq!(mimetype, Some(Some("text/plain".to_string())));
+}
+
+#[tokio::test]
+async fn test_dump_no_files() {
+    let log = Log::new_root(loga::INFO);
+    let root = tempfile::tempdir().unwrap();
+    let config_a = instance_config(&root.path().join("a"));
+    let config_b = instance_config(&root.path().join("b"));
+    populate(&log, &config_a).await;
+    let dump_path = root.path().join("dump.tar");
+    dump(&log, &config_a, &dump_path, DumpOptions {
+        no_history: true,
+        no_files: true,
+        max_file_size: None,
+    }).await.unwrap();
+    load(&log, &config_b, &dump_path).await.unwrap();
+    let triples = list_triples(&log, &config_b, false).await;
+    assert_eq!(triples, list_triples(&log, &config_a, true).await);
+    assert!(triples.iter().all(|t| t.3));
+    let files_b = FileStorage::new(&config_b.storage, &config_b.persistent_dir).await.unwrap();
+    assert_eq!(files_b.list(&log).await.unwrap(), vec![]);
+}
+
+/// A dump whose header doesn't match its contents is rejected without leaving
+/// anything in the target instance.
+#[tokio::test]
+async fn test_load_corrupt_cleans_up() {
+    let log = Log::new_root(loga::INFO);
+    let root = tempfile::tempdir().unwrap();
+    let config_a = instance_config(&root.path().join("a"));
+    let config_b = instance_config(&root.path().join("b"));
+    populate(&log, &config_a).await;
+    let dump_path = root.path().join("dump.tar");
+    dump(&log, &config_a, &dump_path, DumpOptions {
+        no_history: false,
+        no_files: false,
+        max_file_size: None,
+    }).await.unwrap();
+
+    // Rewrite the archive with the triple count in the header off by one
+    let corrupt_path = root.path().join("corrupt.tar");
+    {
+        let mut archive = tar::Archive::new(std::fs::File::open(&dump_path).unwrap());
+        let mut builder = tar::Builder::new(std::fs::File::create(&corrupt_path).unwrap());
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let mut data = vec![];
+            entry.read_to_end(&mut data).unwrap();
+            if name == "sunwet_dump.json" {
+                let mut header = serde_json::from_slice::<serde_json::Value>(&data).unwrap();
+                header["triples"] = json!(header["triples"].as_u64().unwrap() + 1);
+                data = serde_json::to_vec(&header).unwrap();
+            }
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, &name, data.as_slice()).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+    assert!(load(&log, &config_b, &corrupt_path).await.is_err());
+    let files_b = FileStorage::new(&config_b.storage, &config_b.persistent_dir).await.unwrap();
+    assert_eq!(files_b.list(&log).await.unwrap(), vec![]);
+    let db_b = open_db(&log, &config_b).await.unwrap();
+    assert!(!tx(&db_b, |db| dbutil::commit_any(db)).await.unwrap());
+    assert_eq!(list_triples(&log, &config_b, false).await, vec![]);
+}
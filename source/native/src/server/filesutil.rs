@@ -9,12 +9,9 @@ use {
         Digest,
         Sha256,
     },
-    shared::interface::{
-        triple::{
-            FileHash,
-            Node,
-        },
-        wire::alphanumeric_only,
+    shared::interface::triple::{
+        FileHash,
+        Node,
     },
     std::{
         io::Write,
@@ -56,24 +53,6 @@ pub fn file_path_(root_path: &Path, hash: &FileHash) -> Result<PathBuf, loga::Er
     }
 }
 
-pub fn file_path(state: &Arc<State>, hash: &FileHash) -> Result<PathBuf, loga::Error> {
-    return file_path_(&state.files_dir, hash);
-}
-
-pub fn genfile_path(
-    state: &Arc<State>,
-    hash: &FileHash,
-    gentype: &str,
-    subpath: &str,
-) -> Result<PathBuf, loga::Error> {
-    let out = file_path_(&state.genfiles_dir, hash)?.with_extension(alphanumeric_only(gentype));
-    if !subpath.is_empty() {
-        return Ok(out.join(subpath));
-    } else {
-        return Ok(out);
-    }
-}
-
 pub fn staged_file_path(state: &Arc<State>, hash: &FileHash) -> Result<PathBuf, loga::Error> {
     match &hash {
         FileHash::Sha256(hash) => {
@@ -85,6 +64,12 @@ pub fn staged_file_path(state: &Arc<State>, hash: &FileHash) -> Result<PathBuf,
     }
 }
 
+/// Records of chunks received for a partial upload, one file per chunk named
+/// `OFFSET_SIZE` containing the chunk checksum.
+pub fn staged_file_chunks_path(state: &Arc<State>, hash: &FileHash) -> Result<PathBuf, loga::Error> {
+    return Ok(staged_file_path(state, hash)?.with_extension("chunks"));
+}
+
 pub async fn hash_file_sha256(log: &Log, source: &Path) -> Result<FileHash, loga::Error> {
     let mut got_file = File::open(&source).await.stack_context(&log, "Failed to open staged uploaded file")?;
 
@@ -5,12 +5,16 @@ pub mod access;
 pub mod dbutil;
 pub mod dbwrite;
 pub mod defaultviews;
+pub mod dump;
+pub mod dump_test;
 pub mod filesutil;
 pub mod fsutil;
 pub mod migrate;
 pub mod query;
 pub mod query_test;
 pub mod state;
+pub mod storage;
+pub mod storage_test;
 pub mod subsystems;
 
 use {
@@ -43,21 +47,24 @@ use {
                 Identity,
                 can_access_file,
             },
-            filesutil::{
-                file_path,
-                hash_file_sha256,
-            },
+            filesutil::hash_file_sha256,
             state::{
                 BackgroundJob,
                 BgCheckResult,
                 IamGrants,
             },
+            storage::{
+                FileStorage,
+                GenFileStorage,
+                check_storage_separate,
+            },
         },
     },
     dbutil::tx,
     deadpool_sqlite::{
         Hook,
         HookError,
+        Pool,
     },
     flowcontrol::{
         shed,
@@ -110,7 +117,10 @@ use {
     shared::interface::{
         config::{
             form::FormId,
-            view::ViewId,
+            view::{
+                QueryOrField,
+                ViewId,
+            },
         },
         query::Query,
         triple::{
@@ -122,24 +132,33 @@ use {
             C2SReq,
             NodeMeta,
             Pagination,
+            PlayEvent,
             ReqCommit,
             ReqHistoryFilterPredicate,
             RespCheck,
+            RespGeneratedFile,
+            RespGenerationJob,
+            RespGenerationStatus,
             RespHistory,
             RespHistoryEvent,
+            RespPlayPosition,
             RespQuery,
             RespQueryRows,
+            RespViewFacet,
             RespWhoAmI,
             TreeNode,
             Triple,
+            UserSavedItem,
         },
     },
     state::{
         FdapGlobalState,
         FdapState,
         FdapUsersState,
+        GeneratorState,
         GlobalState,
         LocalUsersState,
+        ServerView,
         State,
         UsersState,
         build_global_config,
@@ -148,6 +167,7 @@ use {
     },
     std::{
         collections::{
+            BTreeMap,
             HashMap,
             HashSet,
         },
@@ -165,14 +185,20 @@ use {
         time::Duration,
     },
     subsystems::{
-        background::start_background_job,
+        background::{
+            default_generators,
+            start_background_job,
+        },
         files::{
             handle_commit,
             handle_file_get,
             handle_file_head,
             handle_file_post,
             handle_finish_upload,
+            handle_upload_status,
             handle_form_commit,
+            handle_view_commit,
+            handle_view_delete_commit,
         },
         link::{
             handle_link_ws,
@@ -188,6 +214,8 @@ use {
         select,
         spawn,
         sync::{
+            Notify,
+            Semaphore,
             mpsc,
             oneshot,
         },
@@ -272,7 +300,7 @@ async fn autocomplete_values_via_query(
         },
         suffix: None,
     };
-    let results = query::execute_query(&state.db, query, HashMap::new(), Some(Pagination {
+    let results = query::execute_query(&state.db, query, HashMap::new(), None, Default::default(), Some(Pagination {
         count: 20,
         seed: None,
         key: None,
@@ -295,11 +323,13 @@ async fn handle_query_req(
     state: Arc<State>,
     query: Query,
     parameters: HashMap<String, Node>,
+    user: Option<String>,
+    facets: BTreeMap<String, Node>,
     pagination: Option<Pagination>,
     view_access: Option<(ViewId, u64)>,
 ) -> Result<RespQuery, VisErr<loga::Error>> {
     let expect_count = pagination.as_ref().map(|x| x.count);
-    let results = query::execute_query(&state.db, query, parameters, pagination).await?;
+    let results = query::execute_query(&state.db, query, parameters, user, facets, pagination).await?;
     let page_end = expect_count.and_then(|x| match &results {
         query::QueryResults::Scalar(rows) => {
             if rows.len() < x {
@@ -371,6 +401,52 @@ async fn handle_query_req(
     });
 }
 
+/// The most saved items (presets, bookmarks, pins) a user can have.
+const USER_SAVED_MAX_COUNT: usize = 1000;
+
+/// The largest a single saved item can be, serialized.
+const USER_SAVED_MAX_SIZE: usize = 64 * 1024;
+
+/// Facet counts are computed from at most this many of the root query's rows.
+const FACET_ROW_LIMIT: usize = 10000;
+
+/// Count the values of each facet of a view's root query. The counts for a facet
+/// only include rows matching the values chosen for the other facets.
+async fn handle_view_facets_req(
+    state: Arc<State>,
+    view: &ServerView,
+    parameters: HashMap<String, Node>,
+    user: Option<String>,
+    facets: BTreeMap<String, Node>,
+) -> Result<Vec<RespViewFacet>, VisErr<loga::Error>> {
+    if view.item.facets.is_empty() {
+        return Ok(vec![]);
+    }
+    let QueryOrField::Query(query_id) = &view.item.display.data else {
+        return Ok(vec![]);
+    };
+    let Some(query) = view.item.queries.get(query_id) else {
+        return Err(loga::err_with("No known query with id in view", ea!(query = query_id))).err_internal();
+    };
+
+    // Order doesn't affect the counts, and without sorting only the capped rows are
+    // read
+    let mut query = query.clone();
+    if let Some(suffix) = &mut query.suffix {
+        suffix.sort = None;
+    }
+    let query::QueryResults::Record(rows) =
+        query::execute_query(&state.db, query, parameters, user, Default::default(), Some(Pagination {
+            count: FACET_ROW_LIMIT,
+            seed: None,
+            key: None,
+        })).await? else {
+            return Ok(vec![]);
+        };
+    let truncated = rows.len() >= FACET_ROW_LIMIT;
+    return Ok(query::count_facets(&view.item.facets, &rows, &facets, truncated));
+}
+
 async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<BoxBody<Bytes, std::io::Error>> {
     let url = req.uri().clone();
     match {
@@ -471,6 +547,8 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
 
                             impl ReqResp for shared::interface::wire::ReqViewQuery { }
 
+                            impl ReqResp for shared::interface::wire::ReqViewFacets { }
+
                             impl ReqResp for shared::interface::wire::ReqGetNodeMeta { }
 
                             impl ReqResp for shared::interface::wire::ReqHistory { }
@@ -479,6 +557,8 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
 
                             impl ReqResp for shared::interface::wire::ReqUploadFinish { }
 
+                            impl ReqResp for shared::interface::wire::ReqUploadStatus { }
+
                             impl ReqResp for shared::interface::wire::ReqWhoAmI { }
 
                             impl ReqResp for shared::interface::wire::ReqCheckStart { }
@@ -490,6 +570,18 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                             impl ReqResp for shared::interface::wire::ReqAutocompleteFormField { }
 
                             impl ReqResp for shared::interface::wire::ReqAutocompleteViewParam { }
+
+                            impl ReqResp for shared::interface::wire::ReqGenerationStatus { }
+
+                            impl ReqResp for shared::interface::wire::ReqUserSavedList { }
+
+                            impl ReqResp for shared::interface::wire::ReqUserSavedPut { }
+
+                            impl ReqResp for shared::interface::wire::ReqUserSavedDelete { }
+
+                            impl ReqResp for shared::interface::wire::ReqPlayReport { }
+
+                            impl ReqResp for shared::interface::wire::ReqPlayPositions { }
                         }
 
                         use resp::ReqResp;
@@ -510,7 +602,7 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                                 return Ok(response_401());
                                             },
                                         }
-                                        resp = responder(handle_commit(state, req).await.err_internal()?);
+                                        resp = responder(handle_commit(state, req).await?);
                                     },
                                     ReqCommit::Form(req) => {
                                         {
@@ -560,6 +652,100 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                         }
                                         resp = responder(handle_form_commit(state, req).await?);
                                     },
+                                    ReqCommit::View(req) => {
+                                        {
+                                            // Check access
+                                            let grants = get_iam_grants(&state, &identity).await.err_internal()?;
+                                            let res = shed!{
+                                                'ok _;
+                                                if matches!(identity, Identity::Public) {
+                                                    // Never allow anonymous edits, even for public views
+                                                    break 'ok AccessRes::NoIdent;
+                                                }
+                                                match &grants {
+                                                    IamGrants::Admin => {
+                                                        break 'ok AccessRes::Yes;
+                                                    },
+                                                    IamGrants::Limited(grants) => {
+                                                        if grants.views.contains(&req.view_id) &&
+                                                            grants.view_edits.contains(&req.view_id) {
+                                                            break 'ok AccessRes::Yes;
+                                                        }
+                                                    },
+                                                }
+                                                break 'ok AccessRes::NoAccess;
+                                            };
+                                            state
+                                                .log
+                                                .log_with(
+                                                    loga::DEBUG,
+                                                    "View commit access result",
+                                                    ea!(
+                                                        identity = identity.dbg_str(),
+                                                        grants = grants.dbg_str(),
+                                                        view_id = req.view_id,
+                                                        result = res.dbg_str()
+                                                    ),
+                                                );
+                                            match res {
+                                                AccessRes::Yes => { },
+                                                AccessRes::NoIdent => {
+                                                    return Ok(response_401());
+                                                },
+                                                AccessRes::NoAccess => {
+                                                    return Ok(response_403());
+                                                },
+                                            }
+                                        }
+                                        resp = responder(handle_view_commit(state, identity.user(), req).await?);
+                                    },
+                                    ReqCommit::ViewDelete(req) => {
+                                        {
+                                            // Check access
+                                            let grants = get_iam_grants(&state, &identity).await.err_internal()?;
+                                            let res = shed!{
+                                                'ok _;
+                                                if matches!(identity, Identity::Public) {
+                                                    // Never allow anonymous edits, even for public views
+                                                    break 'ok AccessRes::NoIdent;
+                                                }
+                                                match &grants {
+                                                    IamGrants::Admin => {
+                                                        break 'ok AccessRes::Yes;
+                                                    },
+                                                    IamGrants::Limited(grants) => {
+                                                        if grants.views.contains(&req.view_id) &&
+                                                            grants.view_edits.contains(&req.view_id) {
+                                                            break 'ok AccessRes::Yes;
+                                                        }
+                                                    },
+                                                }
+                                                break 'ok AccessRes::NoAccess;
+                                            };
+                                            state
+                                                .log
+                                                .log_with(
+                                                    loga::DEBUG,
+                                                    "View delete commit access result",
+                                                    ea!(
+                                                        identity = identity.dbg_str(),
+                                                        grants = grants.dbg_str(),
+                                                        view_id = req.view_id,
+                                                        result = res.dbg_str()
+                                                    ),
+                                                );
+                                            match res {
+                                                AccessRes::Yes => { },
+                                                AccessRes::NoIdent => {
+                                                    return Ok(response_401());
+                                                },
+                                                AccessRes::NoAccess => {
+                                                    return Ok(response_403());
+                                                },
+                                            }
+                                        }
+                                        resp = responder(handle_view_delete_commit(state, identity.user(), req).await?);
+                                    },
                                 }
                             },
                             C2SReq::UploadFinish(req) => {
@@ -574,6 +760,15 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                 };
                                 resp = responder(res);
                             },
+                            C2SReq::UploadStatus(req) => {
+                                let responder = req.respond();
+                                match can_access_file(&state, &identity, &req.0).await.err_internal()? {
+                                    AccessRes::Yes => (),
+                                    AccessRes::NoIdent => return Ok(response_401()),
+                                    AccessRes::NoAccess => return Ok(response_403()),
+                                }
+                                resp = responder(handle_upload_status(state, req.0).await.err_internal()?);
+                            },
                             C2SReq::Query(req) => {
                                 match check_is_admin(&state, &identity, "Query").await.err_internal()? {
                                     AccessRes::Yes => { },
@@ -591,6 +786,8 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                             state,
                                             req.query,
                                             req.parameters,
+                                            identity.user(),
+                                            Default::default(),
                                             req.pagination,
                                             None,
                                         ).await?,
@@ -657,6 +854,34 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                         ),
                                     ).err_external();
                                 };
+                                let mut query = query.clone();
+                                let mut facets = BTreeMap::new();
+                                if matches!(&view.item.display.data, QueryOrField::Query(q) if *q == req.query) {
+                                    if let Some(sort) = &req.sort {
+                                        let Some(sort) = view.item.sorts.iter().find(|x| &x.name == sort) else {
+                                            return Err(
+                                                loga::err_with(
+                                                    "No known sort with name in view",
+                                                    ea!(view = req.view_id, sort = sort),
+                                                ),
+                                            ).err_external();
+                                        };
+                                        if let Some(suffix) = &mut query.suffix {
+                                            suffix.sort = Some(sort.sort.clone());
+                                        }
+                                    }
+                                    for (field, value) in req.facets {
+                                        if !view.item.facets.iter().any(|x| x.field == field) {
+                                            return Err(
+                                                loga::err_with(
+                                                    "No known facet with field in view",
+                                                    ea!(view = req.view_id, field = field),
+                                                ),
+                                            ).err_external();
+                                        }
+                                        facets.insert(field, value);
+                                    }
+                                }
                                 let mut view_hash = DefaultHasher::new();
                                 view.item.hash(&mut view_hash);
                                 let view_hash = view_hash.finish();
@@ -664,13 +889,77 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                     responder(
                                         handle_query_req(
                                             state,
-                                            query.clone(),
+                                            query,
                                             req.parameters,
+                                            identity.user(),
+                                            facets,
                                             req.pagination,
                                             Some((req.view_id.clone(), view_hash)),
                                         ).await?,
                                     );
                             },
+                            C2SReq::ViewFacets(req) => {
+                                let responder = req.respond();
+                                let global_config = get_global_config(&state).await.err_internal()?;
+                                let Some(view) = global_config.views.get(&req.view_id) else {
+                                    return Err(
+                                        loga::err_with("No known view with id", ea!(view = req.view_id)),
+                                    ).err_external();
+                                };
+
+                                // Check access
+                                {
+                                    let grants = get_iam_grants(&state, &identity).await.err_internal()?;
+                                    let res = shed!{
+                                        'ok _;
+                                        match &grants {
+                                            IamGrants::Admin => {
+                                                break 'ok AccessRes::Yes;
+                                            },
+                                            IamGrants::Limited(grants) => {
+                                                if grants.views.contains(&req.view_id) {
+                                                    break 'ok AccessRes::Yes;
+                                                }
+                                            },
+                                        }
+                                        if matches!(identity, Identity::Public) {
+                                            break 'ok AccessRes::NoIdent;
+                                        }
+                                        else {
+                                            break 'ok AccessRes::NoAccess;
+                                        }
+                                    };
+                                    match res {
+                                        AccessRes::Yes => { },
+                                        AccessRes::NoIdent => {
+                                            return Ok(response_401());
+                                        },
+                                        AccessRes::NoAccess => {
+                                            return Ok(response_403());
+                                        },
+                                    }
+                                }
+                                for field in req.facets.keys() {
+                                    if !view.item.facets.iter().any(|x| &x.field == field) {
+                                        return Err(
+                                            loga::err_with(
+                                                "No known facet with field in view",
+                                                ea!(view = req.view_id, field = field),
+                                            ),
+                                        ).err_external();
+                                    }
+                                }
+                                resp =
+                                    responder(
+                                        handle_view_facets_req(
+                                            state,
+                                            view,
+                                            req.parameters,
+                                            identity.user(),
+                                            req.facets,
+                                        ).await?,
+                                    );
+                            },
                             C2SReq::GetNodeMeta(req) => {
                                 let responder = req.respond();
                                 let meta = tx(&state.db, move |db| -> Result<_, loga::Error> {
@@ -905,11 +1194,16 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                                     }
                                                     match async {
                                                         ta_return!((), loga::Error);
-                                                        let path = file_path(&state, &hash)?;
-                                                        if !path.exists() {
+                                                        if !state.files.exists(&hash).await? {
                                                             return Err(loga::err("File doesn't exist for file node"));
                                                         }
-                                                        let real_hash = hash_file_sha256(&state.log, &path).await?;
+                                                        let local =
+                                                            state
+                                                                .files
+                                                                .local_copy(&hash, &state.genfiles_stage_dir)
+                                                                .await?;
+                                                        let real_hash =
+                                                            hash_file_sha256(&state.log, &local.path).await?;
                                                         if real_hash != *hash {
                                                             return Err(
                                                                 loga::err_with(
@@ -1342,6 +1636,180 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
                                 };
                                 resp = req.respond()(Some(res.err_external()?));
                             },
+                            C2SReq::GenerationStatus(req) => {
+                                let responder = req.respond();
+                                match can_access_file(&state, &identity, &req.file).await.err_internal()? {
+                                    AccessRes::Yes => (),
+                                    AccessRes::NoIdent => return Ok(response_401()),
+                                    AccessRes::NoAccess => return Ok(response_403()),
+                                }
+                                let (generated, jobs) = tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    return Ok(
+                                        (
+                                            dbutil::generated_list(db, &DbNode(Node::File(req.file.clone())))?,
+                                            dbutil::generate_queue_get(db, &DbFileHash(req.file))?,
+                                        ),
+                                    );
+                                }).await.err_internal()?;
+                                resp = responder(RespGenerationStatus {
+                                    generated: generated
+                                        .into_iter()
+                                        .map(|(gentype, mimetype)| RespGeneratedFile {
+                                            gentype: gentype,
+                                            mimetype: mimetype,
+                                        })
+                                        .collect(),
+                                    jobs: jobs.into_iter().map(|j| RespGenerationJob {
+                                        slow: j.slow,
+                                        priority: j.priority,
+                                        running: j.running,
+                                        failed: j.failed,
+                                        attempts: j.attempts,
+                                        next_attempt: j.next_attempt,
+                                        last_error: j.last_error,
+                                    }).collect(),
+                                });
+                            },
+                            C2SReq::UserSavedList(req) => {
+                                let responder = req.respond();
+                                let user = match &identity {
+                                    Identity::User(u) => u.0.clone(),
+                                    Identity::Public => return Ok(response_401()),
+                                    Identity::Token(_) | Identity::Link(_) => return Ok(response_403()),
+                                };
+                                let items = tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    let mut out = vec![];
+                                    for value in dbutil::user_saved_list(db, &user)? {
+                                        out.push(
+                                            serde_json::from_str::<UserSavedItem>(
+                                                &value,
+                                            ).context("Error parsing stored user saved item")?,
+                                        );
+                                    }
+                                    return Ok(out);
+                                }).await.err_internal()?;
+                                resp = responder(items);
+                            },
+                            C2SReq::UserSavedPut(req) => {
+                                let responder = req.respond();
+                                let user = match &identity {
+                                    Identity::User(u) => u.0.clone(),
+                                    Identity::Public => return Ok(response_401()),
+                                    Identity::Token(_) | Identity::Link(_) => return Ok(response_403()),
+                                };
+                                let key = serde_json::to_string(&req.0.key()).unwrap();
+                                let value = serde_json::to_string(&req.0).unwrap();
+                                if value.len() > USER_SAVED_MAX_SIZE {
+                                    return Err(
+                                        loga::err_with(
+                                            "Saved item is too large",
+                                            ea!(size = value.len(), max = USER_SAVED_MAX_SIZE),
+                                        ),
+                                    ).err_external();
+                                }
+                                let stored = tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    // Replacing an existing item is always allowed
+                                    let keys = dbutil::user_saved_keys(db, &user)?;
+                                    if !keys.contains(&key) && keys.len() >= USER_SAVED_MAX_COUNT {
+                                        return Ok(false);
+                                    }
+                                    dbutil::user_saved_upsert(db, &user, &key, &value, Utc::now())?;
+                                    return Ok(true);
+                                }).await.err_internal()?;
+                                if !stored {
+                                    return Err(
+                                        loga::err_with(
+                                            "Too many saved items, delete some first",
+                                            ea!(max = USER_SAVED_MAX_COUNT),
+                                        ),
+                                    ).err_external();
+                                }
+                                resp = responder(());
+                            },
+                            C2SReq::UserSavedDelete(req) => {
+                                let responder = req.respond();
+                                let user = match &identity {
+                                    Identity::User(u) => u.0.clone(),
+                                    Identity::Public => return Ok(response_401()),
+                                    Identity::Token(_) | Identity::Link(_) => return Ok(response_403()),
+                                };
+                                let key = serde_json::to_string(&req.0).unwrap();
+                                tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    dbutil::user_saved_delete(db, &user, &key)?;
+                                    return Ok(());
+                                }).await.err_internal()?;
+                                resp = responder(());
+                            },
+                            C2SReq::PlayReport(req) => {
+                                let responder = req.respond();
+                                let user = match &identity {
+                                    Identity::User(u) => u.0.clone(),
+                                    Identity::Public => return Ok(response_401()),
+                                    Identity::Token(_) | Identity::Link(_) => return Ok(response_403()),
+                                };
+                                match can_access_file(&state, &identity, &req.file).await.err_internal()? {
+                                    AccessRes::Yes => (),
+                                    AccessRes::NoIdent => return Ok(response_401()),
+                                    AccessRes::NoAccess => return Ok(response_403()),
+                                }
+                                tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    let node = DbNode(Node::File(req.file));
+                                    let position_ms = (req.position * 1000.).round() as i64;
+                                    let duration_ms = req.duration.map(|d| (d * 1000.).round() as i64);
+                                    match req.event {
+                                        PlayEvent::Started => {
+                                            dbutil::play_state_start(
+                                                db,
+                                                &user,
+                                                &node,
+                                                position_ms,
+                                                duration_ms,
+                                                Utc::now(),
+                                            )?;
+                                        },
+                                        PlayEvent::Position | PlayEvent::Finished => {
+                                            dbutil::play_state_update(
+                                                db,
+                                                &user,
+                                                &node,
+                                                position_ms,
+                                                duration_ms,
+                                                req.event == PlayEvent::Finished,
+                                                Utc::now(),
+                                            )?;
+                                        },
+                                    }
+                                    return Ok(());
+                                }).await.err_internal()?;
+                                resp = responder(());
+                            },
+                            C2SReq::PlayPositions(req) => {
+                                let responder = req.respond();
+                                let user = match &identity {
+                                    Identity::User(u) => u.0.clone(),
+                                    Identity::Public => return Ok(response_401()),
+                                    Identity::Token(_) | Identity::Link(_) => return Ok(response_403()),
+                                };
+                                let rows = tx(&state.db, move |db| -> Result<_, loga::Error> {
+                                    let nodes =
+                                        req.files.into_iter().map(|f| DbNode(Node::File(f))).collect::<Vec<_>>();
+                                    return Ok(dbutil::play_state_get(db, &user, nodes.iter().collect())?);
+                                }).await.err_internal()?;
+                                let mut out = vec![];
+                                for row in rows {
+                                    let Node::File(file) = row.node.0 else {
+                                        continue;
+                                    };
+                                    out.push(RespPlayPosition {
+                                        file: file,
+                                        position: row.position_ms as f64 / 1000.,
+                                        duration: row.duration_ms.map(|d| d as f64 / 1000.),
+                                        finished: row.finished,
+                                        updated: row.updated,
+                                    });
+                                }
+                                resp = responder(out);
+                            },
                         }
                         return Ok(resp.1);
                     },
@@ -1419,6 +1887,51 @@ async fn handle_req(state: Arc<State>, mut req: Request<Incoming>) -> Response<B
     }
 }
 
+/// Open the database, running migrations if necessary.
+pub async fn open_db(log: &Log, config: &Config) -> Result<Pool, loga::Error> {
+    let db_path = config.persistent_dir.join("live/db.sqlite3");
+    create_dirs(db_path.parent().unwrap()).await?;
+    let db =
+        deadpool_sqlite::Config::new(&db_path)
+            .builder(deadpool_sqlite::Runtime::Tokio1)
+            .context("Error creating sqlite pool builder")?
+            .post_create(Hook::async_fn(|db, _| {
+                Box::pin(async {
+                    db
+                        .interact(|db| {
+                            db.busy_timeout(Duration::from_secs(60 * 10))?;
+                            rusqlite::vtab::array::load_module(db)?;
+                            return Ok(());
+                        })
+                        .await
+                        .map_err(|e| HookError::Message(e.to_string().into()))?
+                        .map_err(|e| HookError::Backend(e))?;
+                    return Ok(());
+                })
+            }))
+            .build()
+            .context("Error creating sqlite pool")?;
+    db.get().await?.interact({
+        let log = log.clone();
+        move |db| {
+            db::migrate(&mut *db, Some(&|v| migrate::migrate(v)))?;
+            if db
+                .prepare("select 1 from sqlite_master where type='table' and name='meta_fts'")?
+                .query([])
+                .context("Error checking for meta_fts")?
+                .next()
+                .context("Error checking for meta_fts")?
+                .is_none() {
+                log.log(loga::DEBUG, "Initializing fts table");
+                db.execute_batch(include_str!("setup_fts.sql")).context("Error setting up meta_fts")?;
+                log.log(loga::DEBUG, "Done initializing fts table");
+            }
+            return Ok(()) as Result<_, loga::Error>;
+        }
+    }).await?.context_with("Migration failed", ea!(action = "db_init", path = db_path.to_string_lossy()))?;
+    return Ok(db);
+}
+
 #[derive(Aargvark)]
 pub struct Args {
     config: AargvarkJson<Config>,
@@ -1439,53 +1952,14 @@ pub async fn main(args: Args) -> Result<(), loga::Error> {
         );
     let tm = taskmanager::TaskManager::new();
     {
-        let genfiles_dir = config.cache_dir.join("genfiles");
-        create_dirs(&genfiles_dir).await?;
+        check_storage_separate(&config.storage, &config.persistent_dir, &config.generated_storage, &config.cache_dir)?;
+        let genfiles = GenFileStorage::new(&config.generated_storage, &config.cache_dir).await?;
         let genfiles_stage_dir = config.cache_dir.join("genfiles_temp");
         create_dirs(&genfiles_stage_dir).await?;
         let stage_dir = config.persistent_dir.join("stage_files");
         create_dirs(&stage_dir).await?;
-        let files_dir = config.persistent_dir.join("live/files");
-        create_dirs(&files_dir).await?;
-        let db_path = config.persistent_dir.join("live/db.sqlite3");
-        let db =
-            deadpool_sqlite::Config::new(&db_path)
-                .builder(deadpool_sqlite::Runtime::Tokio1)
-                .context("Error creating sqlite pool builder")?
-                .post_create(Hook::async_fn(|db, _| {
-                    Box::pin(async {
-                        db
-                            .interact(|db| {
-                                db.busy_timeout(Duration::from_secs(60 * 10))?;
-                                rusqlite::vtab::array::load_module(db)?;
-                                return Ok(());
-                            })
-                            .await
-                            .map_err(|e| HookError::Message(e.to_string().into()))?
-                            .map_err(|e| HookError::Backend(e))?;
-                        return Ok(());
-                    })
-                }))
-                .build()
-                .context("Error creating sqlite pool")?;
-        db.get().await?.interact({
-            let log = log.clone();
-            move |db| {
-                db::migrate(&mut *db, Some(&|v| migrate::migrate(v)))?;
-                if db
-                    .prepare("select 1 from sqlite_master where type='table' and name='meta_fts'")?
-                    .query([])
-                    .context("Error checking for meta_fts")?
-                    .next()
-                    .context("Error checking for meta_fts")?
-                    .is_none() {
-                    log.log(loga::DEBUG, "Initializing fts table");
-                    db.execute_batch(include_str!("setup_fts.sql")).context("Error setting up meta_fts")?;
-                    log.log(loga::DEBUG, "Done initializing fts table");
-                }
-                return Ok(()) as Result<_, loga::Error>;
-            }
-        }).await?.context_with("Migration failed", ea!(action = "db_init", path = db_path.to_string_lossy()))?;
+        let files = FileStorage::new(&config.storage, &config.persistent_dir).await?;
+        let db = open_db(&log, &config).await?;
 
         // Setup state
         let oidc_state = match &config.oidc {
@@ -1539,6 +2013,17 @@ pub async fn main(args: Args) -> Result<(), loga::Error> {
             ),
             None => UsersState::Local(LocalUsersState { users: Default::default() }),
         };
+        let generators =
+            config
+                .generators
+                .clone()
+                .unwrap_or_else(|| default_generators())
+                .into_iter()
+                .map(|g| GeneratorState {
+                    limit: Semaphore::new(g.concurrency.unwrap_or(1).max(1)),
+                    config: g,
+                })
+                .collect::<Vec<_>>();
         let (background_tx, background_rx) = mpsc::unbounded_channel();
         let state = Arc::new(State {
             oidc_state: oidc_state,
@@ -1548,12 +2033,15 @@ pub async fn main(args: Args) -> Result<(), loga::Error> {
             tm: tm.clone(),
             db: db.clone(),
             log: log.clone(),
-            files_dir: files_dir.clone(),
+            files: files,
             files_stage_dir: stage_dir,
-            genfiles_dir: genfiles_dir.clone(),
+            genfiles: genfiles,
             genfiles_stage_dir: genfiles_stage_dir.clone(),
             finishing_uploads: Mutex::new(HashSet::new()),
             background: background_tx,
+            generators: generators,
+            generate_wake_fast: Notify::new(),
+            generate_wake_slow: Notify::new(),
             bg_check: Default::default(),
             http_resp_headers: HeaderMap::from_iter([
                 //. .
@@ -1564,9 +2052,13 @@ pub async fn main(args: Args) -> Result<(), loga::Error> {
             link_sessions: Cache::builder().time_to_idle(Duration::from_secs(24 * 60 * 60)).build(),
         });
 
-        // Background tasks
+        // Background tasks. Jobs left running by a previous run are released before the
+        // workers start, so they aren't released while a worker holds them.
+        tx(&state.db, |db| dbutil::generate_queue_reset(db))
+            .await
+            .context("Error releasing generation jobs from previous run")?;
         state.background.send(BackgroundJob::All).log(&log, loga::WARN, "Error triggering initial generate files scan");
-        start_background_job(&state, &tm, background_rx);
+        start_background_job(&state, &tm, background_rx, &config.generator_workers);
 
         // Client<->server
         tm.critical_stream(
@@ -32,6 +32,12 @@ use {
     },
     sea_query_rusqlite::RusqliteBinder,
     shared::interface::{
+        config::view::ViewFacet,
+        ont::{
+            PREDICATE_PLAY_FINISHED,
+            PREDICATE_PLAY_POSITION,
+            PREDICATE_PLAYED_AT,
+        },
         query::{
             ChainHead,
             ChainRoot,
@@ -42,6 +48,7 @@ use {
             FilterSuffixSimpleOperator,
             JunctionType,
             MoveDirection,
+            PlayedFilter,
             Query,
             SortDir,
             SortQuery,
@@ -53,6 +60,7 @@ use {
         triple::Node,
         wire::{
             Pagination,
+            RespViewFacet,
             TreeNode,
         },
     },
@@ -76,6 +84,8 @@ fn sql_fn(name: &str, args: Vec<SimpleExpr>) -> SimpleExpr {
 
 struct QueryBuildState {
     parameters: HashMap<String, Node>,
+    /// For play state lookups; no user means no play state.
+    user: Option<String>,
     // # Immutable
     ident_rowid: sea_query::DynIden,
     ident_table_primary: sea_query::DynIden,
@@ -242,6 +252,105 @@ fn build_filter(
     }
 }
 
+#[derive(Clone, Copy)]
+enum PlayStateValue {
+    PlayedAt,
+    Position,
+    Finished,
+}
+
+fn play_state_predicate(predicate: &StrValue) -> Option<PlayStateValue> {
+    let StrValue::Literal(predicate) = predicate else {
+        return None;
+    };
+    match predicate.as_str() {
+        PREDICATE_PLAYED_AT => return Some(PlayStateValue::PlayedAt),
+        PREDICATE_PLAY_POSITION => return Some(PlayStateValue::Position),
+        PREDICATE_PLAY_FINISHED => return Some(PlayStateValue::Finished),
+        _ => return None,
+    }
+}
+
+/// Move over a virtual play state predicate, reading the requesting user's play
+/// history instead of the graph. Outputs the same shape as a regular move.
+fn build_step_move_play_state(
+    query_state: &mut QueryBuildState,
+    seg_name: &str,
+    previous: Option<&BuildStepRes>,
+    dir: MoveDirection,
+    value: PlayStateValue,
+) -> Result<BuildStepRes, VisErr<loga::Error>> {
+    if dir != MoveDirection::Forward {
+        return Err(loga::err("Play state predicates can only be followed forward (->)")).err_external();
+    }
+    let ident_cte = SeaRc::new(Alias::new(seg_name));
+    let mut sql_cte = sea_query::CommonTableExpression::new();
+    sql_cte.table_name(ident_cte.clone());
+    let mut sql_sel = sea_query::Query::select();
+    let local_ident_table_primary = query_state.ident_table_primary.clone();
+    sql_sel.from_as(SeaRc::new(Alias::new("play_state")), local_ident_table_primary.clone());
+    let local_col = |name: &str| colref(local_ident_table_primary.clone(), SeaRc::new(Alias::new(name)));
+    sql_sel.and_where(sea_query::Expr::col(local_col("user")).eq(query_state.user.clone()));
+
+    // Output start col - subset of previous results
+    let local_col_primary_start = local_col("node");
+    let out_col_start;
+    if let Some(previous) = previous {
+        let local_ident_table_prev = query_state.ident_table_prev.clone();
+        sql_sel.join_as(
+            sea_query::JoinType::InnerJoin,
+            previous.ident_table.clone(),
+            local_ident_table_prev.clone(),
+            sea_query::Expr::col(
+                colref(local_ident_table_prev.clone(), previous.col_end.clone()),
+            ).eq(local_col_primary_start.clone()),
+        );
+        out_col_start = colref(local_ident_table_prev.clone(), previous.col_start.clone());
+    } else {
+        out_col_start = local_col_primary_start.clone();
+    }
+    sql_cte.column(query_state.ident_col_start.clone());
+    sql_sel.column(out_col_start);
+
+    // Output rowid
+    sql_cte.column(query_state.ident_rowid.clone());
+    sql_sel.expr_window(sql_fn("row_number", vec![]), WindowStatement::new());
+
+    // Output end col, as a json node (matching stored node format)
+    let end_value = match value {
+        PlayStateValue::PlayedAt => sql_fn(
+            "strftime",
+            vec![
+                Expr::value("%Y-%m-%dT%H:%M:%fZ"),
+                SimpleExpr::from(local_col("updated")).div(1000.),
+                Expr::value("unixepoch"),
+            ],
+        ),
+        PlayStateValue::Position => SimpleExpr::from(local_col("position_ms")).div(1000.),
+        PlayStateValue::Finished => sql_fn(
+            "json",
+            vec![
+                sql_fn(
+                    "iif",
+                    vec![SimpleExpr::from(local_col("finished")), Expr::value("true"), Expr::value("false")],
+                ),
+            ],
+        ),
+    };
+    sql_cte.column(query_state.ident_col_end.clone());
+    sql_sel.expr(sql_fn("json_object", vec![Expr::value("t"), Expr::value("v"), Expr::value("v"), end_value]));
+
+    // Assemble
+    sql_cte.query(sql_sel);
+    query_state.ctes.push(sql_cte);
+    return Ok(BuildStepRes {
+        ident_table: ident_cte,
+        col_start: query_state.ident_col_start.clone(),
+        col_end: query_state.ident_col_end.clone(),
+        plural: true,
+    });
+}
+
 fn build_step(
     query_state: &mut QueryBuildState,
     previous: Option<BuildStepRes>,
@@ -256,7 +365,16 @@ fn build_step(
         StepSpecific::Move(step) => {
             seg_name = format!("seg{}_move", query_state.global_unique);
             query_state.global_unique += 1;
-            {
+            if let Some(play_state_value) = play_state_predicate(&step.predicate) {
+                out =
+                    build_step_move_play_state(
+                        query_state,
+                        &seg_name,
+                        previous.as_ref(),
+                        step.dir,
+                        play_state_value,
+                    )?;
+            } else {
                 let ident_cte = SeaRc::new(Alias::new(&seg_name));
                 let mut sql_cte = sea_query::CommonTableExpression::new();
                 sql_cte.table_name(ident_cte.clone());
@@ -780,6 +898,38 @@ fn build_chain_head(
                 };
                 new_root_seg = root_res;
             },
+            ChainRoot::Played(filter) => {
+                let ident_table_root = SeaRc::new(Alias::new(format!("root{}", query_state.global_unique)));
+                query_state.global_unique += 1;
+                let mut sql_cte = sea_query::CommonTableExpression::new();
+                sql_cte.table_name(ident_table_root.clone());
+                sql_cte.query({
+                    let ident_play_state = SeaRc::new(Alias::new("play_state"));
+                    let play_state_col = |name: &str| colref(ident_play_state.clone(), SeaRc::new(Alias::new(name)));
+                    let mut sql_sel = sea_query::Query::select();
+                    sql_sel.from(tableref(ident_play_state.clone()));
+                    let node_expr = Expr::col(play_state_col("node"));
+                    sql_sel.expr(node_expr.clone());
+                    sql_sel.expr(node_expr.clone());
+                    sql_sel.and_where(Expr::col(play_state_col("user")).eq(query_state.user.clone()));
+                    match filter {
+                        PlayedFilter::All => { },
+                        PlayedFilter::Unfinished => {
+                            sql_sel.and_where(Expr::col(play_state_col("finished")).eq(false));
+                        },
+                    }
+                    sql_sel
+                });
+                sql_cte.column(query_state.ident_col_start.clone());
+                sql_cte.column(query_state.ident_col_end.clone());
+                query_state.ctes.push(sql_cte);
+                new_root_seg = BuildStepRes {
+                    ident_table: ident_table_root,
+                    col_start: query_state.ident_col_start.clone(),
+                    col_end: query_state.ident_col_end.clone(),
+                    plural: false,
+                };
+            },
         }
         prev_subchain_seg = Some(new_root_seg);
     }
@@ -952,10 +1102,12 @@ const COL_PAGE_KEY: &str = "page_key";
 pub fn build_root_chain(
     query: &Query,
     parameters: HashMap<String, Node>,
+    user: Option<String>,
 ) -> Result<(String, sea_query_rusqlite::RusqliteValues), VisErr<loga::Error>> {
     // Prep
     let mut query_state = QueryBuildState {
         parameters: parameters,
+        user: user,
         ident_rowid: SeaRc::new(Alias::new("rowid")),
         ident_table_primary: SeaRc::new(Alias::new("primary")),
         ident_table_prev: SeaRc::new(Alias::new("prev")),
@@ -1042,18 +1194,81 @@ pub enum QueryResults {
     Record(Vec<RecordRow>),
 }
 
+/// Count the values of each facet field in the rows, most common first. The counts
+/// for a facet only include rows matching the values chosen for the other facets.
+/// `truncated` is whether the rows are only part of the query's results.
+pub fn count_facets(
+    facets: &[ViewFacet],
+    rows: &[RecordRow],
+    chosen: &BTreeMap<String, Node>,
+    truncated: bool,
+) -> Vec<RespViewFacet> {
+    let mut out = vec![];
+    for facet in facets {
+        let mut counts = HashMap::<Node, usize>::new();
+        for row in rows {
+            if !chosen
+                .iter()
+                .all(|(field, value)| *field == facet.field || record_field_has_value(&row.tail_data, field, value)) {
+                continue;
+            }
+            let mut values = match row.tail_data.get(&facet.field) {
+                Some(TreeNode::Scalar(v)) => vec![v.clone()],
+                Some(TreeNode::Array(v)) => v.iter().filter_map(|v| match v {
+                    TreeNode::Scalar(v) => Some(v.clone()),
+                    _ => None,
+                }).collect(),
+                _ => vec![],
+            };
+            values.sort();
+            values.dedup();
+            for v in values {
+                *counts.entry(v).or_default() += 1;
+            }
+        }
+        let mut values = counts.into_iter().collect::<Vec<_>>();
+        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        out.push(RespViewFacet {
+            field: facet.field.clone(),
+            values: values,
+            truncated: truncated,
+        });
+    }
+    return out;
+}
+
+/// Whether a record field is the value, or contains it if the field is an array.
+pub fn record_field_has_value(record: &BTreeMap<String, TreeNode>, field: &str, value: &Node) -> bool {
+    match record.get(field) {
+        Some(TreeNode::Scalar(v)) => return v == value,
+        Some(TreeNode::Array(v)) => return v.iter().any(|v| matches!(v, TreeNode::Scalar(v) if v == value)),
+        _ => return false,
+    }
+}
+
 pub fn execute_sql_query(
     db: &mut crate::server::db::Db<&mut rusqlite::Transaction<'_>>,
     sql_query: String,
     sql_parameters: sea_query_rusqlite::RusqliteValues,
     query: &Query,
+    facets: &BTreeMap<String, Node>,
     paginate: Option<Pagination>,
 ) -> Result<Vec<RecordRow>, loga::Error> {
     let mut s = db.0.prepare(&sql_query)?;
     let column_names = s.column_names().into_iter().map(|k| k.to_string()).collect::<Vec<_>>();
     let mut sql_rows = s.query(&*sql_parameters.as_params()).context("Error executing query")?;
+
+    // Without sorting or filtering the first page is the first rows returned, so
+    // stop reading there
+    let read_limit = match (&paginate, query.suffix.as_ref().and_then(|s| s.sort.as_ref())) {
+        (Some(p), None) if p.key.is_none() && facets.is_empty() => Some(p.count),
+        _ => None,
+    };
     let mut out = vec![];
     loop {
+        if read_limit.is_some_and(|l| out.len() >= l) {
+            break;
+        }
         let Some(got_row) = sql_rows.next()? else {
             break;
         };
@@ -1083,6 +1298,11 @@ pub fn execute_sql_query(
             head_data: pagination_key.unwrap(),
         });
     }
+    if !facets.is_empty() {
+        out.retain(
+            |r| facets.iter().all(|(field, value)| record_field_has_value(&r.tail_data, field, value)),
+        );
+    }
     match &query.suffix {
         Some(suffix) => {
             if let Some(sort) = &suffix.sort {
@@ -1154,6 +1374,8 @@ pub async fn execute_query(
     db: &Pool,
     query: Query,
     parameters: HashMap<String, Node>,
+    user: Option<String>,
+    facets: BTreeMap<String, Node>,
     paginate: Option<Pagination>,
 ) -> Result<QueryResults, VisErr<loga::Error>> {
     let results_are_record = query.suffix.is_some();
@@ -1161,9 +1383,9 @@ pub async fn execute_query(
     // Sorting currently happens in rust because sql does string sorting on json
     // fields, not value-based sorting (ex: numbers). Therefore pagination also has to
     // happen in rust.
-    let (sql_query, sql_parameters) = build_root_chain(&query, parameters)?;
+    let (sql_query, sql_parameters) = build_root_chain(&query, parameters, user)?;
     let results = tx(&db, move |db| {
-        return Ok(execute_sql_query(db, sql_query, sql_parameters, &query, paginate)?);
+        return Ok(execute_sql_query(db, sql_query, sql_parameters, &query, &facets, paginate)?);
     }).await.err_internal()?;
     if results_are_record {
         return Ok(QueryResults::Record(results));
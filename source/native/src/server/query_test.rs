@@ -16,7 +16,9 @@ use {
             defaultviews::node_media_audio,
             migrate,
             query::{
+                RecordRow,
                 build_root_chain,
+                count_facets,
                 execute_sql_query,
             },
         },
@@ -25,12 +27,16 @@ use {
     rusqlite::OptionalExtension,
     shared::{
         interface::{
+            config::view::ViewFacet,
             ont::{
                 PREDICATE_ADD_TIMESTAMP,
                 PREDICATE_ARTIST,
                 PREDICATE_IS,
                 PREDICATE_MEDIA,
                 PREDICATE_NAME,
+                PREDICATE_PLAYED_AT,
+                PREDICATE_PLAY_FINISHED,
+                PREDICATE_PLAY_POSITION,
                 PREDICATE_TRACK,
             },
             query::{
@@ -55,7 +61,10 @@ use {
                 Value,
             },
             triple::Node,
-            wire::TreeNode,
+            wire::{
+                Pagination,
+                TreeNode,
+            },
         },
         query_parser::compile_query,
     },
@@ -84,7 +93,7 @@ fn n() -> Node {
 }
 
 fn execute(triples: &[(&Node, &str, &Node)], want: &[&[(&str, TreeNode)]], query: Query) {
-    let (query_string, query_values) = build_root_chain(&query, HashMap::new()).map_err(|e| {
+    let (query_string, query_values) = build_root_chain(&query, HashMap::new(), None).map_err(|e| {
         panic!("{}", match e {
             VisErr::Internal(e) => e.to_string(),
             VisErr::External(e) => e,
@@ -136,7 +145,14 @@ fn execute(triples: &[(&Node, &str, &Node)], want: &[&[(&str, TreeNode)]], query
     //.        }
     //.    }
     let got =
-        execute_sql_query(&mut db::Db(&mut db.0.transaction().unwrap()), query_string, query_values, &query, None)
+        execute_sql_query(
+            &mut db::Db(&mut db.0.transaction().unwrap()),
+            query_string,
+            query_values,
+            &query,
+            &Default::default(),
+            None,
+        )
             .unwrap()
             .into_iter()
             .map(|x| x.tail_data)
@@ -186,7 +202,7 @@ fn test_base() {
 #[test]
 fn test_versions() {
     let query = compile_query("\"x\" -> \"y\" { => y }").unwrap();
-    let (query_sql, query_values) = build_root_chain(&query, HashMap::new()).map_err(|e| {
+    let (query_sql, query_values) = build_root_chain(&query, HashMap::new(), None).map_err(|e| {
         panic!("{}", match e {
             VisErr::Internal(e) => e.to_string(),
             VisErr::External(e) => e,
@@ -222,7 +238,14 @@ fn test_versions() {
         }
     }
     let got =
-        execute_sql_query(&mut db::Db(&mut db.0.transaction().unwrap()), query_sql, query_values, &query, None)
+        execute_sql_query(
+            &mut db::Db(&mut db.0.transaction().unwrap()),
+            query_sql,
+            query_values,
+            &query,
+            &Default::default(),
+            None,
+        )
             .unwrap()
             .into_iter()
             .map(|x| x.tail_data)
@@ -233,7 +256,7 @@ fn test_versions() {
 #[test]
 fn test_delete() {
     let query = compile_query("\"x\" -> \"y\" { => y }").unwrap();
-    let (query_sql, query_values) = build_root_chain(&query, HashMap::new()).map_err(|e| {
+    let (query_sql, query_values) = build_root_chain(&query, HashMap::new(), None).map_err(|e| {
         panic!("{}", match e {
             VisErr::Internal(e) => e.to_string(),
             VisErr::External(e) => e,
@@ -269,7 +292,14 @@ fn test_delete() {
         }
     }
     let got =
-        execute_sql_query(&mut db::Db(&mut db.0.transaction().unwrap()), query_sql, query_values, &query, None)
+        execute_sql_query(
+            &mut db::Db(&mut db.0.transaction().unwrap()),
+            query_sql,
+            query_values,
+            &query,
+            &Default::default(),
+            None,
+        )
             .unwrap()
             .into_iter()
             .map(|x| x.tail_data)
@@ -280,7 +310,7 @@ fn test_delete() {
 #[test]
 fn test_undelete() {
     let query = compile_query("\"x\" -> \"y\" { => y }").unwrap();
-    let (query_sql, query_values) = build_root_chain(&query, HashMap::new()).map_err(|e| {
+    let (query_sql, query_values) = build_root_chain(&query, HashMap::new(), None).map_err(|e| {
         panic!("{}", match e {
             VisErr::Internal(e) => e.to_string(),
             VisErr::External(e) => e,
@@ -324,7 +354,14 @@ fn test_undelete() {
         }
     }
     let got =
-        execute_sql_query(&mut db::Db(&mut db.0.transaction().unwrap()), query_sql, query_values, &query, None)
+        execute_sql_query(
+            &mut db::Db(&mut db.0.transaction().unwrap()),
+            query_sql,
+            query_values,
+            &query,
+            &Default::default(),
+            None,
+        )
             .unwrap()
             .into_iter()
             .map(|x| x.tail_data)
@@ -820,7 +857,7 @@ fn test_migration_v1_to_latest() {
 
     // 5. Dynamic query works after migration
     let query = compile_query("\"album-uuid-1\" -> \"sunwet/1/name\" { => name }").unwrap();
-    let (query_sql, query_values) = build_root_chain(&query, HashMap::new()).map_err(|e| match e {
+    let (query_sql, query_values) = build_root_chain(&query, HashMap::new(), None).map_err(|e| match e {
         VisErr::Internal(e) => panic!("{}", e),
         VisErr::External(e) => panic!("{}", e),
     }).unwrap();
@@ -830,6 +867,7 @@ fn test_migration_v1_to_latest() {
             query_sql,
             query_values,
             &query,
+            &Default::default(),
             None,
         ).unwrap();
     assert_eq!(got.len(), 1, "Query should find album name");
@@ -950,3 +988,215 @@ fn test_migration_example_db() {
     let predicate_count: i64 = db.0.query_row("SELECT count(*) FROM predicate", [], |r| r.get(0)).unwrap();
     assert_eq!(distinct_predicates_before, predicate_count, "All predicates should be in predicate table");
 }
+
+fn open_db(triples: &[(&Node, &str, &Node)]) -> db::Db<rusqlite::Connection> {
+    let db = rusqlite::Connection::open_in_memory().unwrap();
+    let mut db = db::migrate(db, None).unwrap();
+    for (s, p, o) in triples {
+        dbwrite::write_triple(&mut db, &DbNode((*s).clone()), p, &DbNode((*o).clone()), Utc::now().into(), true)
+            .unwrap();
+    }
+    return db;
+}
+
+/// Run a query, returning the full rows.
+fn execute_rows(
+    db: &mut db::Db<rusqlite::Connection>,
+    query: &Query,
+    user: Option<String>,
+    facets: &BTreeMap<String, Node>,
+    paginate: Option<Pagination>,
+) -> Vec<RecordRow> {
+    let (query_string, query_values) = build_root_chain(query, HashMap::new(), user).map_err(|e| {
+        panic!("{}", match e {
+            VisErr::Internal(e) => e.to_string(),
+            VisErr::External(e) => e,
+        })
+    }).unwrap();
+    return execute_sql_query(
+        &mut db::Db(&mut db.0.transaction().unwrap()),
+        query_string,
+        query_values,
+        query,
+        facets,
+        paginate,
+    ).unwrap();
+}
+
+fn facet_triples() -> Vec<(Node, &'static str, Node)> {
+    return vec![
+        (s("a"), PREDICATE_IS, node_is_album()),
+        (s("a"), "genre", s("rock")),
+        (s("a"), "tag", s("live")),
+        (s("a"), "tag", s("old")),
+        (s("b"), PREDICATE_IS, node_is_album()),
+        (s("b"), "genre", s("rock")),
+        (s("b"), "tag", s("old")),
+        (s("c"), PREDICATE_IS, node_is_album()),
+        (s("c"), "genre", s("jazz")),
+        (s("c"), "tag", s("live")),
+    ];
+}
+
+const FACET_QUERY: &str =
+    "\"sunwet/1/album\" -< \"sunwet/1/is\" { => id ( -> \"genre\" first { => genre } ) ( -> \"tag\" { => tag } ) }";
+
+#[test]
+fn test_facet_filter() {
+    let triples = facet_triples();
+    let triples = triples.iter().map(|(subject, predicate, object)| (subject, *predicate, object)).collect::<Vec<_>>();
+    let query = compile_query(FACET_QUERY).unwrap();
+    let mut db = open_db(&triples);
+    let mut ids = |facets: &[(&str, Node)]| {
+        let facets = facets.iter().map(|(k, v)| (k.to_string(), v.clone())).collect::<BTreeMap<_, _>>();
+        let mut out =
+            execute_rows(&mut db, &query, None, &facets, None)
+                .into_iter()
+                .map(|r| match r.tail_data.get("id") {
+                    Some(TreeNode::Scalar(v)) => v.clone(),
+                    _ => panic!("Missing id"),
+                })
+                .collect::<Vec<_>>();
+        out.sort();
+        return out;
+    };
+
+    // Scalar field
+    assert_eq!(ids(&[("genre", s("rock"))]), vec![s("a"), s("b")]);
+
+    // Array field, matching any element
+    assert_eq!(ids(&[("tag", s("live"))]), vec![s("a"), s("c")]);
+
+    // Combined
+    assert_eq!(ids(&[("genre", s("rock")), ("tag", s("live"))]), vec![s("a")]);
+    assert_eq!(ids(&[("genre", s("pop"))]), vec![]);
+}
+
+#[test]
+fn test_facet_counts() {
+    let triples = facet_triples();
+    let triples = triples.iter().map(|(subject, predicate, object)| (subject, *predicate, object)).collect::<Vec<_>>();
+    let query = compile_query(FACET_QUERY).unwrap();
+    let rows = execute_rows(&mut open_db(&triples), &query, None, &BTreeMap::new(), None);
+    let facets = vec![ViewFacet {
+        name: "Genre".to_string(),
+        field: "genre".to_string(),
+    }, ViewFacet {
+        name: "Tag".to_string(),
+        field: "tag".to_string(),
+    }];
+    let counts = |chosen: &[(&str, Node)]| {
+        let chosen = chosen.iter().map(|(k, v)| (k.to_string(), v.clone())).collect::<BTreeMap<_, _>>();
+        return count_facets(&facets, &rows, &chosen, false)
+            .into_iter()
+            .map(|f| (f.field, f.values))
+            .collect::<Vec<_>>();
+    };
+
+    // Most common first, ties by value
+    assert_eq!(
+        counts(&[]),
+        vec![
+            ("genre".to_string(), vec![(s("rock"), 2), (s("jazz"), 1)]),
+            ("tag".to_string(), vec![(s("live"), 2), (s("old"), 2)])
+        ]
+    );
+
+    // A chosen value narrows the other facets but not its own
+    assert_eq!(
+        counts(&[("genre", s("rock"))]),
+        vec![
+            ("genre".to_string(), vec![(s("rock"), 2), (s("jazz"), 1)]),
+            ("tag".to_string(), vec![(s("old"), 2), (s("live"), 1)])
+        ]
+    );
+}
+
+#[test]
+fn test_unsorted_page_read_limit() {
+    let triples = facet_triples();
+    let triples = triples.iter().map(|(subject, predicate, object)| (subject, *predicate, object)).collect::<Vec<_>>();
+    let query = compile_query(FACET_QUERY).unwrap();
+    let rows = execute_rows(&mut open_db(&triples), &query, None, &BTreeMap::new(), Some(Pagination {
+        count: 2,
+        seed: None,
+        key: None,
+    }));
+    assert_eq!(rows.len(), 2);
+}
+
+/// Play history for two users: `u1` finished `a` and is partway through `b`, `u2`
+/// is partway through `c`.
+fn open_play_db() -> db::Db<rusqlite::Connection> {
+    let mut db = open_db(&[(&s("a"), PREDICATE_NAME, &s("a_name")), (&s("b"), PREDICATE_NAME, &s("b_name"))]);
+    let stamp = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    dbutil::play_state_update(&mut db, "u1", &DbNode(s("a")), 60000, Some(60000), true, stamp).unwrap();
+    dbutil::play_state_update(&mut db, "u1", &DbNode(s("b")), 1500, Some(60000), false, stamp).unwrap();
+    dbutil::play_state_update(&mut db, "u2", &DbNode(s("c")), 1500, Some(60000), false, stamp).unwrap();
+    return db;
+}
+
+fn played_ids(db: &mut db::Db<rusqlite::Connection>, query: &str, user: &str) -> Vec<Node> {
+    let mut out =
+        execute_rows(db, &compile_query(query).unwrap(), Some(user.to_string()), &BTreeMap::new(), None)
+            .into_iter()
+            .map(|r| match r.tail_data.get("id") {
+                Some(TreeNode::Scalar(v)) => v.clone(),
+                _ => panic!("Missing id"),
+            })
+            .collect::<Vec<_>>();
+    out.sort();
+    return out;
+}
+
+#[test]
+fn test_played_root() {
+    let mut db = open_play_db();
+    assert_eq!(played_ids(&mut db, "played all { => id }", "u1"), vec![s("a"), s("b")]);
+    assert_eq!(played_ids(&mut db, "played unfinished { => id }", "u1"), vec![s("b")]);
+    assert_eq!(played_ids(&mut db, "played all { => id }", "u2"), vec![s("c")]);
+    assert_eq!(played_ids(&mut db, "played all { => id }", "u3"), vec![]);
+}
+
+#[test]
+fn test_play_state_predicates() {
+    let mut db = open_play_db();
+    let query = compile_query(&format!(
+        "played all {{ => id ( -> \"{}\" first {{ => name }} ) ( -> \"{}\" first {{ => at }} ) ( -> \"{}\" first {{ => \
+         position }} ) ( -> \"{}\" first {{ => finished }} ) }}",
+        PREDICATE_NAME,
+        PREDICATE_PLAYED_AT,
+        PREDICATE_PLAY_POSITION,
+        PREDICATE_PLAY_FINISHED
+    )).unwrap();
+    let mut got =
+        execute_rows(&mut db, &query, Some("u1".to_string()), &BTreeMap::new(), None)
+            .into_iter()
+            .map(|r| r.tail_data)
+            .collect::<Vec<_>>();
+    got.sort_by_key(|r| format!("{:?}", r.get("id")));
+    let row = |id: &str, position: f64, finished: bool| {
+        return [
+            ("id".to_string(), TreeNode::Scalar(s(id))),
+            ("name".to_string(), TreeNode::Scalar(s(format!("{}_name", id)))),
+            ("at".to_string(), TreeNode::Scalar(s("2024-01-01T00:00:00.000Z"))),
+            ("position".to_string(), TreeNode::Scalar(Node::Value(serde_json::json!(position)))),
+            ("finished".to_string(), TreeNode::Scalar(Node::Value(serde_json::Value::Bool(finished)))),
+        ].into_iter().collect::<BTreeMap<_, _>>();
+    };
+    assert_eq!(got, vec![row("a", 60., true), row("b", 1.5, false)]);
+
+    // Another user's history isn't visible
+    let got = execute_rows(&mut db, &query, Some("u2".to_string()), &BTreeMap::new(), None);
+    assert_eq!(got.len(), 1);
+    assert_eq!(got[0].tail_data.get("position"), Some(&TreeNode::Scalar(Node::Value(serde_json::json!(1.5)))));
+
+    // Virtual predicates can't be followed backward
+    assert!(
+        build_root_chain(
+            &compile_query(&format!("\"a\" -< \"{}\" {{ => id }}", PREDICATE_PLAY_FINISHED)).unwrap(),
+            HashMap::new(),
+            Some("u1".to_string()),
+        ).is_err()
+    );
+}
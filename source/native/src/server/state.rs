@@ -7,6 +7,7 @@ use {
             config::{
                 ConfigIamGrants,
                 ConfigIamGrantsLimited,
+                GeneratorConfig,
                 MenuItemPage,
                 ServerConfigMenuItem,
                 ServerConfigMenuItemDetail,
@@ -14,7 +15,13 @@ use {
             },
             self,
         },
-        server::access::AccessSourceId,
+        server::{
+            access::AccessSourceId,
+            storage::{
+                FileStorage,
+                GenFileStorage,
+            },
+        },
     },
     deadpool_sqlite::Pool,
     flowcontrol::shed,
@@ -68,6 +75,8 @@ use {
     },
     taskmanager::TaskManager,
     tokio::sync::{
+        Notify,
+        Semaphore,
         mpsc::{
             UnboundedSender,
             self,
@@ -94,6 +103,8 @@ pub struct ServerView {
     pub item: interface::config::View,
     pub query_parameters: BTreeMap<String, Vec<String>>,
     pub shuffle: bool,
+    /// Predicates of relations that can be changed with view commits.
+    pub edit_predicates: HashSet<String>,
 }
 
 pub struct GlobalConfig {
@@ -115,48 +126,69 @@ pub fn build_global_config(
     }
     let mut views = HashMap::new();
     for (k, v) in &config0.views {
-        fn recurse_build_query_parameters(
+        fn build_data_query_parameters(
+            queries: &BTreeMap<String, query::Query>,
+            data: &view::QueryOrField,
+            query_parameters: &mut BTreeMap<String, Vec<String>>,
+        ) -> Result<(), loga::Error> {
+            match data {
+                view::QueryOrField::Field(_) => { },
+                view::QueryOrField::Query(q) => {
+                    let query = queries.get(q).context(format!("Missing query [{}]", q))?;
+                    query_parameters.entry(q.clone()).or_insert_with(|| {
+                        let analysis = analyze_query(query);
+                        let Some(r#struct) = analysis.r#struct else {
+                            return vec![];
+                        };
+                        return r#struct.inputs.into_iter().collect::<Vec<_>>();
+                    });
+                },
+            }
+            return Ok(());
+        }
+
+        fn recurse_build_view_info(
             queries: &BTreeMap<String, query::Query>,
             w: &view::Widget,
             query_parameters: &mut BTreeMap<String, Vec<String>>,
+            edit_predicates: &mut HashSet<String>,
         ) -> Result<(), loga::Error> {
             match w {
                 view::Widget::Layout(w) => {
                     for e in &w.elements {
-                        recurse_build_query_parameters(queries, e, query_parameters)?;
+                        recurse_build_view_info(queries, e, query_parameters, edit_predicates)?;
                     }
                 },
                 view::Widget::DataRows(w) => {
-                    match &w.data {
-                        view::QueryOrField::Field(_) => { },
-                        view::QueryOrField::Query(q) => {
-                            let query = queries.get(q).context(format!("Missing query [{}]", q))?;
-                            query_parameters.entry(q.clone()).or_insert_with(|| {
-                                let analysis = analyze_query(query);
-                                let Some(r#struct) = analysis.r#struct else {
-                                    return vec![];
-                                };
-                                return r#struct.inputs.into_iter().collect::<Vec<_>>();
-                            });
-                        },
-                    }
+                    build_data_query_parameters(queries, &w.data, query_parameters)?;
                     match &w.row_widget {
                         view::DataRowsLayout::Unaligned(w) => {
-                            recurse_build_query_parameters(queries, &w.widget, query_parameters)?;
+                            recurse_build_view_info(queries, &w.widget, query_parameters, edit_predicates)?;
                         },
                         view::DataRowsLayout::Table(w) => {
                             for e in &w.elements {
-                                recurse_build_query_parameters(queries, e, query_parameters)?;
+                                recurse_build_view_info(queries, e, query_parameters, edit_predicates)?;
                             }
                         },
+                        view::DataRowsLayout::Calendar(w) => {
+                            recurse_build_view_info(queries, &w.widget, query_parameters, edit_predicates)?;
+                        },
+                        view::DataRowsLayout::Timeline(w) => {
+                            recurse_build_view_info(queries, &w.widget, query_parameters, edit_predicates)?;
+                        },
                     }
                 },
                 view::Widget::Table(w) => {
                     for e in &w.elements {
-                        recurse_build_query_parameters(queries, e, query_parameters)?;
+                        recurse_build_view_info(queries, e, query_parameters, edit_predicates)?;
+                    }
+                },
+                view::Widget::Text(w) => {
+                    if let Some(edit) = &w.edit {
+                        edit_predicates.insert(edit.predicate.clone());
                     }
                 },
-                view::Widget::Text(_) => { },
+                view::Widget::Markdown(_) => { },
                 view::Widget::Date(_) => { },
                 view::Widget::Time(_) => { },
                 view::Widget::Datetime(_) => { },
@@ -166,6 +198,23 @@ pub fn build_global_config(
                 view::Widget::PlayButton(_) => { },
                 view::Widget::Space => { },
                 view::Widget::Node(_) => { },
+                view::Widget::If(w) => {
+                    recurse_build_view_info(queries, &w.then, query_parameters, edit_predicates)?;
+                    if let Some(w) = &w.otherwise {
+                        recurse_build_view_info(queries, w, query_parameters, edit_predicates)?;
+                    }
+                },
+                view::Widget::Switch(w) => {
+                    for c in &w.cases {
+                        recurse_build_view_info(queries, &c.widget, query_parameters, edit_predicates)?;
+                    }
+                    if let Some(w) = &w.default {
+                        recurse_build_view_info(queries, w, query_parameters, edit_predicates)?;
+                    }
+                },
+                view::Widget::Chart(w) => {
+                    build_data_query_parameters(queries, &w.data, query_parameters)?;
+                },
             }
             return Ok(());
         }
@@ -197,22 +246,42 @@ pub fn build_global_config(
                 }
             },
         }
-        recurse_build_query_parameters(
+        if !v.sorts.is_empty() || !v.facets.is_empty() {
+            let has_record_root = match &v.display.data {
+                view::QueryOrField::Field(_) => false,
+                view::QueryOrField::Query(q) => v.queries.get(q).map(|q| q.suffix.is_some()).unwrap_or(false),
+            };
+            if !has_record_root {
+                return Err(
+                    loga::err(
+                        format!(
+                            "View [{}] has sorts or facets but its root data isn't a query with a record suffix",
+                            k
+                        ),
+                    ),
+                );
+            }
+        }
+        let mut edit_predicates = HashSet::new();
+        recurse_build_view_info(
             &v.queries,
             &v.display.element_body,
             &mut query_parameters,
+            &mut edit_predicates,
         ).context(format!("Error extracting query parameters in view [{}]", k))?;
         if let Some(b) = &v.display.element_expansion {
-            recurse_build_query_parameters(
+            recurse_build_view_info(
                 &v.queries,
                 &b,
                 &mut query_parameters,
+                &mut edit_predicates,
             ).context(format!("Error extracting query parameters in view [{}]", k))?;
         }
         views.insert(k.clone(), ServerView {
             item: v.clone(),
             query_parameters: query_parameters,
             shuffle: shuffle,
+            edit_predicates: edit_predicates,
         });
     }
 
@@ -322,6 +391,12 @@ pub enum BackgroundJob {
     All,
 }
 
+pub struct GeneratorState {
+    pub config: GeneratorConfig,
+    /// Limits concurrent runs to the configured concurrency
+    pub limit: Semaphore,
+}
+
 pub enum BgCheckResult {
     Fut(oneshot::Receiver<Result<RespCheck, loga::Error>>),
     Value(Result<RespCheck, loga::Error>),
@@ -335,12 +410,16 @@ pub struct State {
     pub tm: TaskManager,
     pub log: Log,
     pub db: Pool,
-    pub files_dir: PathBuf,
+    pub files: FileStorage,
     pub files_stage_dir: PathBuf,
     pub finishing_uploads: Mutex<HashSet<FileHash>>,
-    pub genfiles_dir: PathBuf,
+    pub genfiles: GenFileStorage,
     pub genfiles_stage_dir: PathBuf,
     pub background: UnboundedSender<BackgroundJob>,
+    pub generators: Vec<GeneratorState>,
+    /// Notified when generation jobs are queued
+    pub generate_wake_fast: Notify,
+    pub generate_wake_slow: Notify,
     pub http_resp_headers: HeaderMap,
     pub bg_check: Mutex<Option<BgCheckResult>>,
     // Websockets
@@ -430,6 +509,7 @@ pub struct IamGrantsLimited {
     pub menu_items: HashSet<MenuItemId>,
     pub views: HashSet<ViewId>,
     pub forms: HashSet<FormId>,
+    pub view_edits: HashSet<ViewId>,
 }
 
 #[derive(Debug)]
@@ -469,6 +549,7 @@ fn build_iam_grants_limited(
         menu_items: access.menu_items.clone(),
         views: views,
         forms: forms,
+        view_edits: access.view_edits.clone(),
     });
 }
 
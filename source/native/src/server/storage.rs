@@ -0,0 +1,805 @@
+//! Storage for uploaded (original) files and generated files. Both are stored the
+//! same way, either in a local directory or an S3-compatible bucket, addressed by
+//! relative keys like `sha256/01/23/0123...`. Generated files add the gentype as
+//! an extension, and some generated types are directories of files rather than a
+//! single file.
+use {
+    crate::{
+        interface::config::{
+            FileStorageConfig,
+            S3FileStorageConfig,
+        },
+        server::{
+            filesutil::{
+                file_path_,
+                get_hash_from_file_path,
+            },
+            fsutil::{
+                create_dirs,
+                delete_tree,
+            },
+        },
+    },
+    async_walkdir::WalkDir,
+    futures::StreamExt,
+    http::{
+        HeaderMap,
+        Response,
+        header::{
+            ACCEPT_RANGES,
+            CONTENT_LENGTH,
+            CONTENT_RANGE,
+            CONTENT_TYPE,
+            LOCATION,
+            RANGE,
+        },
+    },
+    http_body_util::{
+        BodyExt,
+        StreamBody,
+        combinators::BoxBody,
+    },
+    htwrap::htserve::{
+        self,
+        responses::{
+            body_empty,
+            body_full,
+        },
+    },
+    hyper::body::{
+        Bytes,
+        Frame,
+    },
+    loga::{
+        DebugDisplay,
+        Log,
+        ResultContext,
+        ea,
+    },
+    s3::{
+        Bucket,
+        Region,
+        creds::Credentials,
+    },
+    shared::interface::{
+        triple::FileHash,
+        wire::alphanumeric_only,
+    },
+    std::{
+        collections::BTreeSet,
+        path::{
+            Component,
+            Path,
+            PathBuf,
+        },
+    },
+    tempfile::{
+        TempDir,
+        tempdir_in,
+    },
+    tokio::{
+        fs::{
+            File,
+            metadata,
+            remove_dir_all,
+            remove_file,
+            rename,
+        },
+        sync::OnceCell,
+    },
+};
+
+/// Limit the size of proxied range responses, clients will request the rest as
+/// needed.
+const S3_MAX_RANGE: u64 = 16 * 1024 * 1024;
+
+/// How long pre-signed urls handed out in redirects are valid.
+const S3_PRESIGN_SECS: u32 = 60 * 60;
+
+/// Number of key segments identifying a stored file (or generated file tree):
+/// hash type, two directory levels, and the hash.
+const ROOT_KEY_SEGMENTS: usize = 4;
+
+pub struct LocalStorage {
+    pub dir: PathBuf,
+}
+
+pub struct S3Storage {
+    pub bucket: Box<Bucket>,
+    pub prefix: String,
+    pub redirect: bool,
+}
+
+/// Files (or trees of files) stored by relative key.
+pub enum Storage {
+    Local(LocalStorage),
+    S3(S3Storage),
+}
+
+/// Uploaded files, by hash.
+pub struct FileStorage(pub Storage);
+
+/// Files generated from uploaded files (transcodes, extracted subtitles, etc), by
+/// the source file hash and gentype. The subpath selects a file within generated
+/// directories, and is empty otherwise.
+pub struct GenFileStorage(pub Storage);
+
+/// A path to a stored file on the local filesystem, for processing with external
+/// tools. If the file had to be downloaded, the copy is deleted when this is
+/// dropped.
+pub struct LocalFile {
+    pub path: PathBuf,
+    _temp: Option<TempDir>,
+}
+
+/// Only gets a local copy of the file when it's first needed.
+pub struct LazyLocalFile<'a> {
+    storage: &'a Storage,
+    key: PathBuf,
+    temp_root: PathBuf,
+    file: OnceCell<LocalFile>,
+}
+
+impl<'a> LazyLocalFile<'a> {
+    pub async fn path(&self) -> Result<&Path, loga::Error> {
+        return Ok(&self.file.get_or_try_init(|| self.storage.local_copy(&self.key, &self.temp_root)).await?.path);
+    }
+}
+
+/// A single range from a `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=START-END`, end inclusive
+    FromTo(u64, u64),
+    /// `bytes=START-`, to the end of the file
+    From(u64),
+    /// `bytes=-LEN`, the last `LEN` bytes of the file
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// The inclusive start and end of the range within a file of `size` bytes, or
+    /// `None` if the range can't be satisfied.
+    pub fn resolve(&self, size: u64) -> Option<(u64, u64)> {
+        match *self {
+            ByteRange::FromTo(start, end) => {
+                if start >= size {
+                    return None;
+                }
+                return Some((start, end.min(size - 1)));
+            },
+            ByteRange::From(start) => {
+                if start >= size {
+                    return None;
+                }
+                return Some((start, size - 1));
+            },
+            ByteRange::Suffix(len) => {
+                if len == 0 || size == 0 {
+                    return None;
+                }
+                return Some((size - len.min(size), size - 1));
+            },
+        }
+    }
+}
+
+/// Parse a `Range` header value with a single byte range. Multiple ranges aren't
+/// supported (the whole file is sent instead).
+pub fn parse_range(header: &str) -> Option<ByteRange> {
+    let range = header.trim().strip_prefix("bytes=")?;
+    if range.contains(",") {
+        return None;
+    }
+    let (start, end) = range.split_once("-")?;
+    let start = start.trim();
+    let end = end.trim();
+    if start.is_empty() {
+        return Some(ByteRange::Suffix(end.parse::<u64>().ok()?));
+    }
+    let start = start.parse::<u64>().ok()?;
+    if end.is_empty() {
+        return Some(ByteRange::From(start));
+    }
+    let end = end.parse::<u64>().ok()?;
+    if end < start {
+        return None;
+    }
+    return Some(ByteRange::FromTo(start, end));
+}
+
+fn file_key(hash: &FileHash) -> Result<PathBuf, loga::Error> {
+    return file_path_(Path::new(""), hash);
+}
+
+fn genfile_key(hash: &FileHash, gentype: &str, subpath: &str) -> Result<PathBuf, loga::Error> {
+    let out = file_key(hash)?.with_extension(alphanumeric_only(gentype));
+    if !subpath.is_empty() {
+        return Ok(out.join(subpath));
+    } else {
+        return Ok(out);
+    }
+}
+
+/// Keys always use `/` as the separator, regardless of platform.
+fn key_str(key: &Path) -> String {
+    return key.components().filter_map(|c| match c {
+        Component::Normal(c) => Some(c.to_string_lossy()),
+        _ => None,
+    }).collect::<Vec<_>>().join("/");
+}
+
+/// The key of the file or tree containing the key.
+fn root_key(key: &Path) -> Option<PathBuf> {
+    let components = key.components().filter_map(|c| match c {
+        Component::Normal(c) => Some(c),
+        _ => None,
+    }).take(ROOT_KEY_SEGMENTS).collect::<Vec<_>>();
+    if components.len() < ROOT_KEY_SEGMENTS {
+        return None;
+    }
+    return Some(components.into_iter().collect());
+}
+
+impl S3Storage {
+    fn new(config: &S3FileStorageConfig) -> Result<Self, loga::Error> {
+        let mut bucket =
+            Bucket::new(
+                &config.bucket,
+                Region::Custom {
+                    region: config.region.clone(),
+                    endpoint: config.endpoint.clone(),
+                },
+                Credentials::new(Some(&config.access_key), Some(&config.secret_key), None, None, None)
+                    .context("Error setting up object storage credentials")?,
+            ).context_with("Error setting up object storage bucket", ea!(bucket = config.bucket))?;
+        if config.path_style {
+            bucket = bucket.with_path_style();
+        }
+        return Ok(S3Storage {
+            bucket: bucket,
+            prefix: s3_prefix(config),
+            redirect: config.redirect,
+        });
+    }
+
+    fn object_key(&self, key: &Path) -> String {
+        return format!("{}{}", self.prefix, key_str(key));
+    }
+
+    /// Object keys of the file at `key` or all files in the tree at `key`.
+    async fn list_under(&self, key: &Path) -> Result<Vec<String>, loga::Error> {
+        let object_key = self.object_key(key);
+        let tree_prefix = format!("{}/", object_key);
+        let mut out = vec![];
+        for page in self
+            .bucket
+            .list(object_key.clone(), None)
+            .await
+            .context_with("Error listing objects", ea!(prefix = object_key))? {
+            for object in page.contents {
+                if object.key == object_key || object.key.starts_with(&tree_prefix) {
+                    out.push(object.key);
+                }
+            }
+        }
+        return Ok(out);
+    }
+
+    async fn put_file(&self, object_key: &str, source: &Path) -> Result<(), loga::Error> {
+        let mut f = File::open(source).await.context_with("Error opening file to store", ea!(path = source.dbg_str()))?;
+        self
+            .bucket
+            .put_object_stream(&mut f, object_key)
+            .await
+            .context_with("Error uploading file to object storage", ea!(key = object_key))?;
+        return Ok(());
+    }
+
+    async fn get_file(&self, object_key: &str, dest: &Path) -> Result<(), loga::Error> {
+        let mut f =
+            File::create(dest)
+                .await
+                .context_with("Error creating local copy of file", ea!(path = dest.dbg_str()))?;
+        self
+            .bucket
+            .get_object_to_writer(object_key, &mut f)
+            .await
+            .context_with("Error downloading object", ea!(key = object_key))?;
+        return Ok(());
+    }
+}
+
+impl Storage {
+    /// `local_dir` is used if the config is for local storage.
+    pub async fn new(config: &FileStorageConfig, local_dir: &Path) -> Result<Self, loga::Error> {
+        match config {
+            FileStorageConfig::Local => {
+                create_dirs(local_dir).await?;
+                return Ok(Storage::Local(LocalStorage { dir: local_dir.to_path_buf() }));
+            },
+            FileStorageConfig::S3(config) => {
+                return Ok(Storage::S3(S3Storage::new(config)?));
+            },
+        }
+    }
+
+    /// Whether there's a file or tree at the key.
+    pub async fn exists(&self, key: &Path) -> Result<bool, loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                return Ok(s.dir.join(key).exists());
+            },
+            Storage::S3(s) => {
+                return Ok(!s.list_under(key).await?.is_empty());
+            },
+        }
+    }
+
+    /// The size of the file at the key, or `None` if there's no file.
+    pub async fn size(&self, key: &Path) -> Result<Option<u64>, loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                let path = s.dir.join(key);
+                if !path.exists() {
+                    return Ok(None);
+                }
+                return Ok(
+                    Some(
+                        metadata(&path)
+                            .await
+                            .context_with("Error reading file metadata", ea!(path = path.dbg_str()))?
+                            .len(),
+                    ),
+                );
+            },
+            Storage::S3(s) => {
+                if s.list_under(key).await?.is_empty() {
+                    return Ok(None);
+                }
+                let object_key = s.object_key(key);
+                let (head, _) =
+                    s
+                        .bucket
+                        .head_object(&object_key)
+                        .await
+                        .context_with("Error getting object metadata", ea!(key = object_key))?;
+                return Ok(Some(head.content_length.unwrap_or_default().max(0) as u64));
+            },
+        }
+    }
+
+    /// Move a file or directory into storage, replacing anything already at the key.
+    pub async fn place(&self, key: &Path, source: &Path) -> Result<(), loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                let dest = s.dir.join(key);
+                delete_tree(&dest).await?;
+                if let Some(p) = dest.parent() {
+                    create_dirs(&p).await.context("Failed to create parent directories for stored file")?;
+                }
+                rename(&source, &dest)
+                    .await
+                    .context_with(
+                        "Failed to move file into storage",
+                        ea!(source = source.dbg_str(), dest = dest.dbg_str()),
+                    )?;
+            },
+            Storage::S3(s) => {
+                self.delete(key).await?;
+                if metadata(source)
+                    .await
+                    .context_with("Error reading metadata of file to store", ea!(path = source.dbg_str()))?
+                    .is_dir() {
+                    let mut walk = WalkDir::new(source);
+                    while let Some(entry) = walk.next().await {
+                        let entry =
+                            entry.context_with("Error reading entry in dir to store", ea!(dir = source.dbg_str()))?;
+                        if !entry.file_type().await.context("Error reading file type")?.is_file() {
+                            continue;
+                        }
+                        let path = entry.path();
+                        s.put_file(&s.object_key(&key.join(path.strip_prefix(source).unwrap())), &path).await?;
+                    }
+                    delete_tree(source).await?;
+                } else {
+                    s.put_file(&s.object_key(key), source).await?;
+                    remove_file(source)
+                        .await
+                        .context_with("Error removing file after storing", ea!(path = source.dbg_str()))?;
+                }
+            },
+        }
+        return Ok(());
+    }
+
+    /// Delete the file or tree at the key.
+    pub async fn delete(&self, key: &Path) -> Result<(), loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                let path = s.dir.join(key);
+                if metadata(&path)
+                    .await
+                    .context_with("Error reading file metadata", ea!(path = path.dbg_str()))?
+                    .is_dir() {
+                    remove_dir_all(&path).await.context_with("Error deleting file tree", ea!(path = path.dbg_str()))?;
+                } else {
+                    remove_file(&path).await.context_with("Error deleting file", ea!(path = path.dbg_str()))?;
+                }
+            },
+            Storage::S3(s) => {
+                for object_key in s.list_under(key).await? {
+                    s
+                        .bucket
+                        .delete_object(&object_key)
+                        .await
+                        .context_with("Error deleting object", ea!(key = object_key))?;
+                }
+            },
+        }
+        return Ok(());
+    }
+
+    /// Keys of all stored files and trees.
+    pub async fn list(&self, log: &Log) -> Result<Vec<PathBuf>, loga::Error> {
+        let mut out = BTreeSet::new();
+        match self {
+            Storage::Local(s) => {
+                let mut walk = WalkDir::new(&s.dir);
+                while let Some(entry) = walk.next().await {
+                    let entry = entry.context_with("Error reading entry in files dir", ea!(dir = s.dir.dbg_str()))?;
+                    if !entry.file_type().await.context("Error reading file type")?.is_file() {
+                        continue;
+                    }
+                    let path = entry.path();
+                    let Some(key) = root_key(path.strip_prefix(&s.dir).unwrap()) else {
+                        log.log_with(loga::WARN, "File in files dir isn't a stored file", ea!(path = path.dbg_str()));
+                        continue;
+                    };
+                    out.insert(key);
+                }
+            },
+            Storage::S3(s) => {
+                for page in s.bucket.list(s.prefix.clone(), None).await.context("Error listing objects")? {
+                    for object in page.contents {
+                        let Some(key) =
+                            object.key.strip_prefix(&s.prefix).and_then(|k| root_key(Path::new(k))) else {
+                                log.log_with(
+                                    loga::WARN,
+                                    "Object in bucket isn't a stored file",
+                                    ea!(key = object.key),
+                                );
+                                continue;
+                            };
+                        out.insert(key);
+                    }
+                }
+            },
+        }
+        return Ok(out.into_iter().collect());
+    }
+
+    /// Get a local path to the file or tree, downloading it to a temp dir in
+    /// `temp_root` if necessary.
+    pub async fn local_copy(&self, key: &Path, temp_root: &Path) -> Result<LocalFile, loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                return Ok(LocalFile {
+                    path: s.dir.join(key),
+                    _temp: None,
+                });
+            },
+            Storage::S3(s) => {
+                let object_key = s.object_key(key);
+                let object_keys = s.list_under(key).await?;
+                if object_keys.is_empty() {
+                    return Err(loga::err_with("No stored file with key", ea!(key = object_key)));
+                }
+                let temp = tempdir_in(temp_root)?;
+                let path = temp.path().join("file");
+                if object_keys.len() == 1 && object_keys[0] == object_key {
+                    s.get_file(&object_key, &path).await?;
+                } else {
+                    let tree_prefix = format!("{}/", object_key);
+                    for k in object_keys {
+                        let Some(rel) = k.strip_prefix(&tree_prefix) else {
+                            continue;
+                        };
+                        let dest = path.join(rel);
+                        if let Some(p) = dest.parent() {
+                            create_dirs(p).await?;
+                        }
+                        s.get_file(&k, &dest).await?;
+                    }
+                }
+                return Ok(LocalFile {
+                    path: path,
+                    _temp: Some(temp),
+                });
+            },
+        }
+    }
+
+    pub fn lazy_local_copy<'a>(&'a self, key: PathBuf, temp_root: &Path) -> LazyLocalFile<'a> {
+        return LazyLocalFile {
+            storage: self,
+            key: key,
+            temp_root: temp_root.to_path_buf(),
+            file: OnceCell::new(),
+        };
+    }
+
+    /// Respond to a request for the file, supporting range requests.
+    pub async fn respond(
+        &self,
+        req_headers: &HeaderMap,
+        mimetype: &str,
+        key: &Path,
+        resp_headers: &HeaderMap,
+    ) -> Result<Response<BoxBody<Bytes, std::io::Error>>, loga::Error> {
+        match self {
+            Storage::Local(s) => {
+                return Ok(
+                    htserve::responses::response_file(
+                        req_headers,
+                        mimetype,
+                        &s.dir.join(key),
+                        true,
+                        resp_headers,
+                    ).await?,
+                );
+            },
+            Storage::S3(s) => {
+                let object_key = s.object_key(key);
+                if s.redirect {
+                    let url =
+                        s
+                            .bucket
+                            .presign_get(&object_key, S3_PRESIGN_SECS, None)
+                            .await
+                            .context_with("Error pre-signing object url", ea!(key = object_key))?;
+                    return Ok(
+                        Response::builder()
+                            .status(http::StatusCode::TEMPORARY_REDIRECT)
+                            .header(LOCATION, url)
+                            .body(body_empty())
+                            .unwrap(),
+                    );
+                }
+                let mut resp = Response::builder();
+                for (k, v) in resp_headers {
+                    resp = resp.header(k, v);
+                }
+                resp = resp.header(CONTENT_TYPE, mimetype).header(ACCEPT_RANGES, "bytes");
+                let (head, _) =
+                    s
+                        .bucket
+                        .head_object(&object_key)
+                        .await
+                        .context_with("Error getting object metadata", ea!(key = object_key))?;
+                let size = head.content_length.unwrap_or_default().max(0) as u64;
+                match req_headers.get(RANGE).and_then(|r| r.to_str().ok()).and_then(parse_range) {
+                    Some(range) => {
+                        let Some((start, end)) = range.resolve(size) else {
+                            return Ok(
+                                resp
+                                    .status(http::StatusCode::RANGE_NOT_SATISFIABLE)
+                                    .header(CONTENT_RANGE, format!("bytes */{}", size))
+                                    .body(body_empty())
+                                    .unwrap(),
+                            );
+                        };
+                        let end = end.min(start + S3_MAX_RANGE - 1);
+                        let data =
+                            s
+                                .bucket
+                                .get_object_range(&object_key, start, Some(end))
+                                .await
+                                .context_with(
+                                    "Error reading object range",
+                                    ea!(key = object_key, start = start, end = end),
+                                )?;
+                        return Ok(
+                            resp
+                                .status(http::StatusCode::PARTIAL_CONTENT)
+                                .header(CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size))
+                                .header(CONTENT_LENGTH, end + 1 - start)
+                                .body(body_full(data.bytes().to_vec()))
+                                .unwrap(),
+                        );
+                    },
+                    None => {
+                        let stream =
+                            s
+                                .bucket
+                                .get_object_stream(&object_key)
+                                .await
+                                .context_with("Error reading object", ea!(key = object_key))?;
+                        return Ok(
+                            resp
+                                .status(http::StatusCode::OK)
+                                .header(CONTENT_LENGTH, size)
+                                .body(
+                                    BodyExt::boxed(
+                                        StreamBody::new(
+                                            stream
+                                                .bytes
+                                                .map(|b| b.map(Frame::data).map_err(|e| std::io::Error::other(e))),
+                                        ),
+                                    ),
+                                )
+                                .unwrap(),
+                        );
+                    },
+                }
+            },
+        }
+    }
+}
+
+fn s3_prefix(config: &S3FileStorageConfig) -> String {
+    let mut prefix = config.prefix.clone();
+    if !prefix.is_empty() && !prefix.ends_with("/") {
+        prefix.push('/');
+    }
+    return prefix;
+}
+
+fn files_local_dir(persistent_dir: &Path) -> PathBuf {
+    return persistent_dir.join("live/files");
+}
+
+fn genfiles_local_dir(cache_dir: &Path) -> PathBuf {
+    return cache_dir.join("genfiles");
+}
+
+/// Generated file garbage collection deletes everything in the generated storage
+/// that isn't a known generated file, so it must not overlap the uploaded file
+/// storage.
+pub fn check_storage_separate(
+    storage: &FileStorageConfig,
+    persistent_dir: &Path,
+    generated_storage: &FileStorageConfig,
+    cache_dir: &Path,
+) -> Result<(), loga::Error> {
+    match (storage, generated_storage) {
+        (FileStorageConfig::Local, FileStorageConfig::Local) => {
+            let files_dir = files_local_dir(persistent_dir);
+            let genfiles_dir = genfiles_local_dir(cache_dir);
+            if files_dir.starts_with(&genfiles_dir) || genfiles_dir.starts_with(&files_dir) {
+                return Err(
+                    loga::err_with(
+                        "Uploaded and generated file directories overlap, use separate persistent and cache dirs",
+                        ea!(files = files_dir.dbg_str(), generated = genfiles_dir.dbg_str()),
+                    ),
+                );
+            }
+        },
+        (FileStorageConfig::S3(files), FileStorageConfig::S3(genfiles)) => {
+            if files.endpoint.trim_end_matches('/') != genfiles.endpoint.trim_end_matches('/') ||
+                files.bucket != genfiles.bucket {
+                return Ok(());
+            }
+            let files_prefix = s3_prefix(files);
+            let genfiles_prefix = s3_prefix(genfiles);
+            if files_prefix.starts_with(&genfiles_prefix) || genfiles_prefix.starts_with(&files_prefix) {
+                return Err(
+                    loga::err_with(
+                        "Uploaded and generated file storage use overlapping prefixes in the same bucket",
+                        ea!(bucket = files.bucket, files = files_prefix, generated = genfiles_prefix),
+                    ),
+                );
+            }
+        },
+        _ => { },
+    }
+    return Ok(());
+}
+
+impl FileStorage {
+    pub async fn new(config: &FileStorageConfig, persistent_dir: &Path) -> Result<Self, loga::Error> {
+        return Ok(FileStorage(Storage::new(config, &files_local_dir(persistent_dir)).await?));
+    }
+
+    pub async fn exists(&self, hash: &FileHash) -> Result<bool, loga::Error> {
+        return self.0.exists(&file_key(hash)?).await;
+    }
+
+    pub async fn size(&self, hash: &FileHash) -> Result<Option<u64>, loga::Error> {
+        return self.0.size(&file_key(hash)?).await;
+    }
+
+    /// Move a fully uploaded file into storage.
+    pub async fn place(&self, hash: &FileHash, source: &Path) -> Result<(), loga::Error> {
+        return self.0.place(&file_key(hash)?, source).await;
+    }
+
+    pub async fn delete(&self, hash: &FileHash) -> Result<(), loga::Error> {
+        return self.0.delete(&file_key(hash)?).await;
+    }
+
+    /// Hashes of all stored files.
+    pub async fn list(&self, log: &Log) -> Result<Vec<FileHash>, loga::Error> {
+        return Ok(
+            self
+                .0
+                .list(log)
+                .await?
+                .into_iter()
+                .filter_map(|key| get_hash_from_file_path(log, Path::new(""), &key))
+                .collect(),
+        );
+    }
+
+    pub async fn local_copy(&self, hash: &FileHash, temp_root: &Path) -> Result<LocalFile, loga::Error> {
+        return self.0.local_copy(&file_key(hash)?, temp_root).await;
+    }
+
+    pub fn lazy_local_copy<'a>(&'a self, hash: &FileHash, temp_root: &Path) -> Result<LazyLocalFile<'a>, loga::Error> {
+        return Ok(self.0.lazy_local_copy(file_key(hash)?, temp_root));
+    }
+
+    pub async fn respond(
+        &self,
+        req_headers: &HeaderMap,
+        mimetype: &str,
+        hash: &FileHash,
+        resp_headers: &HeaderMap,
+    ) -> Result<Response<BoxBody<Bytes, std::io::Error>>, loga::Error> {
+        return self.0.respond(req_headers, mimetype, &file_key(hash)?, resp_headers).await;
+    }
+}
+
+impl GenFileStorage {
+    pub async fn new(config: &FileStorageConfig, cache_dir: &Path) -> Result<Self, loga::Error> {
+        return Ok(GenFileStorage(Storage::new(config, &genfiles_local_dir(cache_dir)).await?));
+    }
+
+    pub async fn exists(&self, hash: &FileHash, gentype: &str, subpath: &str) -> Result<bool, loga::Error> {
+        return self.0.exists(&genfile_key(hash, gentype, subpath)?).await;
+    }
+
+    /// Move a generated file or directory into storage.
+    pub async fn place(&self, hash: &FileHash, gentype: &str, subpath: &str, source: &Path) -> Result<(), loga::Error> {
+        return self.0.place(&genfile_key(hash, gentype, subpath)?, source).await;
+    }
+
+    /// The source file hash and key of all generated files and directories.
+    pub async fn list(&self, log: &Log) -> Result<Vec<(FileHash, PathBuf)>, loga::Error> {
+        let mut out = vec![];
+        for key in self.0.list(log).await? {
+            let Some(hash) = get_hash_from_file_path(log, Path::new(""), &key) else {
+                continue;
+            };
+            out.push((hash, key));
+        }
+        return Ok(out);
+    }
+
+    /// Delete a generated file or directory by key (from `list`).
+    pub async fn delete_key(&self, key: &Path) -> Result<(), loga::Error> {
+        return self.0.delete(key).await;
+    }
+
+    pub async fn local_copy(
+        &self,
+        hash: &FileHash,
+        gentype: &str,
+        subpath: &str,
+        temp_root: &Path,
+    ) -> Result<LocalFile, loga::Error> {
+        return self.0.local_copy(&genfile_key(hash, gentype, subpath)?, temp_root).await;
+    }
+
+    pub async fn respond(
+        &self,
+        req_headers: &HeaderMap,
+        mimetype: &str,
+        hash: &FileHash,
+        gentype: &str,
+        subpath: &str,
+        resp_headers: &HeaderMap,
+    ) -> Result<Response<BoxBody<Bytes, std::io::Error>>, loga::Error> {
+        return self.0.respond(req_headers, mimetype, &genfile_key(hash, gentype, subpath)?, resp_headers).await;
+    }
+}
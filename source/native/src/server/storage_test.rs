@@ -0,0 +1,171 @@
+#![cfg(test)]
+
+use {
+    crate::{
+        interface::config::{
+            FileStorageConfig,
+            S3FileStorageConfig,
+        },
+        server::storage::{
+            check_storage_separate,
+            parse_range,
+            ByteRange,
+            FileStorage,
+            GenFileStorage,
+        },
+    },
+    loga::Log,
+    pretty_assertions::assert_eq,
+    shared::interface::triple::FileHash,
+    std::path::Path,
+    tokio::fs::{
+        create_dir_all,
+        read,
+        write,
+    },
+};
+
+#[test]
+fn test_parse_range_from_to() {
+    assert_eq!(parse_range("bytes=0-99"), Some(ByteRange::FromTo(0, 99)));
+    assert_eq!(parse_range(" bytes=10 - 20 "), Some(ByteRange::FromTo(10, 20)));
+}
+
+#[test]
+fn test_parse_range_from() {
+    assert_eq!(parse_range("bytes=100-"), Some(ByteRange::From(100)));
+}
+
+#[test]
+fn test_parse_range_suffix() {
+    assert_eq!(parse_range("bytes=-500"), Some(ByteRange::Suffix(500)));
+}
+
+#[test]
+fn test_parse_range_invalid() {
+    assert_eq!(parse_range("bytes=20-10"), None);
+    assert_eq!(parse_range("bytes=-"), None);
+    assert_eq!(parse_range("bytes=a-b"), None);
+    assert_eq!(parse_range("items=0-10"), None);
+    assert_eq!(parse_range("bytes=0-10,20-30"), None);
+}
+
+#[test]
+fn test_resolve_range() {
+    assert_eq!(ByteRange::FromTo(0, 99).resolve(50), Some((0, 49)));
+    assert_eq!(ByteRange::FromTo(10, 20).resolve(50), Some((10, 20)));
+    assert_eq!(ByteRange::FromTo(50, 60).resolve(50), None);
+    assert_eq!(ByteRange::From(10).resolve(50), Some((10, 49)));
+    assert_eq!(ByteRange::From(50).resolve(50), None);
+    assert_eq!(ByteRange::Suffix(10).resolve(50), Some((40, 49)));
+    assert_eq!(ByteRange::Suffix(100).resolve(50), Some((0, 49)));
+    assert_eq!(ByteRange::Suffix(0).resolve(50), None);
+    assert_eq!(ByteRange::Suffix(10).resolve(0), None);
+}
+
+/// Store, list, copy out, and delete an uploaded file and a generated directory.
+fn s3_config(endpoint: &str, bucket: &str, prefix: &str) -> FileStorageConfig {
+    return FileStorageConfig::S3(S3FileStorageConfig {
+        endpoint: endpoint.to_string(),
+        region: "x".to_string(),
+        bucket: bucket.to_string(),
+        prefix: prefix.to_string(),
+        access_key: "x".to_string(),
+        secret_key: "x".to_string(),
+        path_style: false,
+        redirect: false,
+    });
+}
+
+#[test]
+fn test_storage_separate() {
+    let local = FileStorageConfig::Local;
+    let separate = |a: &FileStorageConfig, b: &FileStorageConfig| {
+        check_storage_separate(a, Path::new("/data"), b, Path::new("/cache")).is_ok()
+    };
+    assert!(separate(&local, &local));
+    assert!(!check_storage_separate(&local, Path::new("/data"), &local, Path::new("/data/live/files")).is_ok());
+    assert!(!check_storage_separate(&local, Path::new("/cache/genfiles/x"), &local, Path::new("/cache")).is_ok());
+    assert!(separate(&local, &s3_config("https://s3", "b", "")));
+    assert!(separate(&s3_config("https://s3", "b", "files"), &s3_config("https://s3", "b", "gen")));
+    assert!(separate(&s3_config("https://s3", "b", "files"), &s3_config("https://s3", "b", "files2")));
+    assert!(separate(&s3_config("https://s3", "a", ""), &s3_config("https://s3", "b", "")));
+    assert!(!separate(&s3_config("https://s3", "b", "files"), &s3_config("https://s3/", "b", "files/")));
+    assert!(!separate(&s3_config("https://s3", "b", ""), &s3_config("https://s3", "b", "gen")));
+    assert!(!separate(&s3_config("https://s3", "b", "x/files"), &s3_config("https://s3", "b", "x")));
+}
+
+async fn check_storage(config: &FileStorageConfig, root: &Path) {
+    let log = Log::new_root(loga::INFO);
+    let temp_root = root.join("temp");
+    create_dir_all(&temp_root).await.unwrap();
+    let hash = FileHash::Sha256("0123456789abcdef".to_string());
+
+    // Uploaded file
+    let files = FileStorage::new(config, &root.join("persistent")).await.unwrap();
+    assert!(!files.exists(&hash).await.unwrap());
+    let source = temp_root.join("upload");
+    write(&source, b"hello").await.unwrap();
+    files.place(&hash, &source).await.unwrap();
+    assert!(files.exists(&hash).await.unwrap());
+    assert_eq!(files.list(&log).await.unwrap(), vec![hash.clone()]);
+    let local = files.local_copy(&hash, &temp_root).await.unwrap();
+    assert_eq!(read(&local.path).await.unwrap(), b"hello");
+    drop(local);
+    files.delete(&hash).await.unwrap();
+    assert!(!files.exists(&hash).await.unwrap());
+    assert_eq!(files.list(&log).await.unwrap(), vec![]);
+
+    // Generated directory
+    let genfiles = GenFileStorage::new(config, &root.join("cache")).await.unwrap();
+    let source = temp_root.join("gen");
+    create_dir_all(source.join("sub")).await.unwrap();
+    write(source.join("a.txt"), b"a").await.unwrap();
+    write(source.join("sub/b.txt"), b"b").await.unwrap();
+    genfiles.place(&hash, "testgen", "", &source).await.unwrap();
+    assert!(genfiles.exists(&hash, "testgen", "").await.unwrap());
+    assert!(genfiles.exists(&hash, "testgen", "sub/b.txt").await.unwrap());
+    assert!(!genfiles.exists(&hash, "othergen", "").await.unwrap());
+    let listed = genfiles.list(&log).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].0, hash);
+    let local = genfiles.local_copy(&hash, "testgen", "", &temp_root).await.unwrap();
+    assert_eq!(read(local.path.join("a.txt")).await.unwrap(), b"a");
+    assert_eq!(read(local.path.join("sub/b.txt")).await.unwrap(), b"b");
+    drop(local);
+    genfiles.delete_key(&listed[0].1).await.unwrap();
+    assert!(!genfiles.exists(&hash, "testgen", "").await.unwrap());
+    assert_eq!(genfiles.list(&log).await.unwrap(), vec![]);
+}
+
+#[tokio::test]
+async fn test_local_storage() {
+    let root = tempfile::tempdir().unwrap();
+    check_storage(&FileStorageConfig::Local, root.path()).await;
+}
+
+/// Run against an S3-compatible service like MinIO. Enable with the `test_minio`
+/// feature and set `SUNWET_TEST_S3_ENDPOINT`, `SUNWET_TEST_S3_BUCKET`,
+/// `SUNWET_TEST_S3_ACCESS_KEY`, and `SUNWET_TEST_S3_SECRET_KEY`. The bucket must
+/// already exist; files are stored under a random prefix.
+#[cfg(feature = "test_minio")]
+#[tokio::test]
+async fn test_s3_storage() {
+    use {
+        crate::interface::config::S3FileStorageConfig,
+        std::env,
+    };
+
+    let env = |k: &str| env::var(k).unwrap_or_else(|_| panic!("Missing env var {}", k));
+    let root = tempfile::tempdir().unwrap();
+    check_storage(&FileStorageConfig::S3(S3FileStorageConfig {
+        endpoint: env("SUNWET_TEST_S3_ENDPOINT"),
+        region: env::var("SUNWET_TEST_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+        bucket: env("SUNWET_TEST_S3_BUCKET"),
+        prefix: format!("test-{}", uuid::Uuid::new_v4()),
+        access_key: env("SUNWET_TEST_S3_ACCESS_KEY"),
+        secret_key: env("SUNWET_TEST_S3_SECRET_KEY"),
+        path_style: true,
+        redirect: false,
+    }), root.path()).await;
+}
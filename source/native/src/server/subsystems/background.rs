@@ -15,17 +15,25 @@ use {
     async_walkdir::WalkDir,
     chrono::Utc,
     crate::{
-        interface::triple::DbNode,
+        interface::{
+            config::{
+                BuiltinGenerator,
+                BuiltinGeneratorVideoWebm,
+                CommandGenerator,
+                GeneratorConfig,
+                GeneratorKind,
+                GeneratorWorkersConfig,
+            },
+            triple::{
+                DbFileHash,
+                DbNode,
+            },
+        },
         server::{
             db,
             dbutil,
             dbutil::tx,
-            filesutil::{
-                file_path,
-                genfile_path,
-                get_hash_from_file_path,
-                get_meta,
-            },
+            filesutil::get_meta,
             fsutil::{
                 create_dirs,
                 delete_tree,
@@ -35,10 +43,9 @@ use {
                 BackgroundJob,
                 State,
             },
+            storage::LazyLocalFile,
         },
     },
-    deadpool_sqlite::Pool,
-    enclose::enclose,
     flowcontrol::{
         exenum,
         ta_return,
@@ -89,7 +96,7 @@ use {
         process::Stdio,
         sync::{
             Arc,
-            Mutex,
+            LazyLock,
         },
         time::SystemTime,
     },
@@ -98,13 +105,13 @@ use {
     tokio::{
         fs::{
             read_to_string,
-            remove_dir_all,
             remove_file,
             write,
         },
         process::Command,
         select,
         sync::mpsc::UnboundedReceiver,
+        time::sleep,
     },
     tokio_stream::{
         StreamExt,
@@ -112,7 +119,12 @@ use {
     },
 };
 
-async fn generated_exists(state: &Arc<State>, file: &FileHash, gentype: &str) -> Result<bool, loga::Error> {
+async fn generated_exists(
+    state: &Arc<State>,
+    file: &FileHash,
+    gentype: &str,
+    subpath: &str,
+) -> Result<bool, loga::Error> {
     let found = tx(&state.db, {
         let gentype = gentype.to_string();
         let file = file.clone();
@@ -123,7 +135,7 @@ async fn generated_exists(state: &Arc<State>, file: &FileHash, gentype: &str) ->
     }).await?;
     match found {
         Some(_) => {
-            return Ok(genfile_path(state, file, gentype, "")?.exists());
+            return Ok(state.genfiles.exists(file, gentype, subpath).await?);
         },
         None => {
             return Ok(false);
@@ -135,20 +147,11 @@ async fn commit_generated(
     state: &Arc<State>,
     file: FileHash,
     gentype: &str,
+    subpath: &str,
     mimetype: &str,
     temp_path: &Path,
-    dest_path: &Path,
 ) -> Result<(), loga::Error> {
-    delete_tree(dest_path).await?;
-    if let Some(p) = dest_path.parent() {
-        create_dirs(&p).await.context("Failed to create parent directories for generated file")?;
-    }
-    tokio::fs::rename(&temp_path, &dest_path)
-        .await
-        .context_with(
-            "Error moving generated file to final destination",
-            ea!(source = temp_path.dbg_str(), dest = dest_path.dbg_str()),
-        )?;
+    state.genfiles.place(&file, gentype, subpath, temp_path).await?;
     let gentype = gentype.to_string();
     let mimetype = mimetype.to_string();
     tx(&state.db, move |db| -> Result<_, loga::Error> {
@@ -203,7 +206,12 @@ fn is_text_sub(codec_name: &str) -> bool {
     }
 }
 
-async fn generate_subs(state: &Arc<State>, file: &FileHash, source: &Path) -> Result<(), loga::Error> {
+async fn generate_subs(
+    state: &Arc<State>,
+    file: &FileHash,
+    source: &LazyLocalFile<'_>,
+) -> Result<(), loga::Error> {
+    let source = source.path().await?;
     for stream in ffprobe(source).await?.streams {
         let (Some(codec_type), Some(codec_name)) = (stream.codec_type, stream.codec_name) else {
             continue;
@@ -216,7 +224,8 @@ async fn generate_subs(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
         }
         let lang = stream.tags.get("language").cloned().unwrap_or_else(|| format!("eng"));
         let gentype = GENTYPE_VTT;
-        if generated_exists(state, file, gentype).await? {
+        let subpath = gentype_vtt_subpath(&lang);
+        if generated_exists(state, file, gentype, &subpath).await? {
             continue;
         }
         let tmp = tempdir_in(&state.genfiles_stage_dir)?;
@@ -238,24 +247,23 @@ async fn generate_subs(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
                 ),
             );
         }
-        commit_generated(
-            state,
-            file.clone(),
-            gentype,
-            "text/vtt",
-            &tempdest_path,
-            &genfile_path(&state, file, &gentype, &gentype_vtt_subpath(&lang))?,
-        ).await?;
+        commit_generated(state, file.clone(), gentype, &subpath, "text/vtt", &tempdest_path).await?;
     }
     return Ok(());
 }
 
-async fn generate_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Result<(), loga::Error> {
+async fn generate_webm(
+    state: &Arc<State>,
+    file: &FileHash,
+    source: &LazyLocalFile<'_>,
+    config: &BuiltinGeneratorVideoWebm,
+) -> Result<(), loga::Error> {
     let mimetype = TRANSCODE_MIME_WEBM;
     let gentype = gentype_transcode(mimetype);
-    if generated_exists(state, file, &gentype).await? {
+    if generated_exists(state, file, &gentype, "").await? {
         return Ok(());
     }
+    let source = source.path().await?;
     let mut include_streams = vec![];
     let mut first_video_stream = None;
     {
@@ -289,6 +297,7 @@ async fn generate_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
     // Ffmpeg pass abstraction is leaky, need to ensure video stream index matches for
     // both passes
     include_streams.insert(0, first_video_stream);
+    let crf = config.crf.unwrap_or(30).to_string();
     let tmp = tempdir_in(&state.genfiles_stage_dir)?;
     let passlog_path = tmp.path().join("passlog");
     let tempdest_path = tmp.path().join("out");
@@ -301,7 +310,7 @@ async fn generate_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
         // Video
         cmd.arg("-map").arg(&format!("0:{}", first_video_stream));
         cmd.args(&["-b:v", "0"]);
-        cmd.args(&["-crf", "30"]);
+        cmd.arg("-crf").arg(&crf);
 
         // Output
         cmd.args(&["-pass", "1"]);
@@ -330,7 +339,7 @@ async fn generate_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
 
         // Video
         cmd.args(&["-b:v", "0"]);
-        cmd.args(&["-crf", "30"]);
+        cmd.arg("-crf").arg(&crf);
         cmd.args(&["-pass", "2"]);
         cmd.arg("-passlogfile").arg(&passlog_path);
 
@@ -353,23 +362,21 @@ async fn generate_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Re
             );
         }
     }
-    commit_generated(
-        state,
-        file.clone(),
-        &gentype,
-        mimetype,
-        &tempdest_path,
-        &genfile_path(&state, file, &gentype, "")?,
-    ).await?;
+    commit_generated(state, file.clone(), &gentype, "", mimetype, &tempdest_path).await?;
     return Ok(());
 }
 
-async fn generate_audio_webm(state: &Arc<State>, file: &FileHash, source: &Path) -> Result<(), loga::Error> {
+async fn generate_audio_webm(
+    state: &Arc<State>,
+    file: &FileHash,
+    source: &LazyLocalFile<'_>,
+) -> Result<(), loga::Error> {
     let mimetype = TRANSCODE_MIME_AUDIO_WEBM;
     let gentype = gentype_transcode(mimetype);
-    if generated_exists(state, file, &gentype).await? {
+    if generated_exists(state, file, &gentype, "").await? {
         return Ok(());
     }
+    let source = source.path().await?;
     let tmp = tempdir_in(&state.genfiles_stage_dir)?;
     let tempdest_path = tmp.path().join("out");
     let mut cmd = Command::new("ffmpeg");
@@ -393,27 +400,21 @@ async fn generate_audio_webm(state: &Arc<State>, file: &FileHash, source: &Path)
             loga::err_with("Error converting audio to webm", ea!(res = res.dbg_str(), command = cmd.dbg_str())),
         );
     }
-    commit_generated(
-        state,
-        file.clone(),
-        &gentype,
-        mimetype,
-        &tempdest_path,
-        &genfile_path(&state, file, &gentype, "")?,
-    ).await?;
+    commit_generated(state, file.clone(), &gentype, "", mimetype, &tempdest_path).await?;
     return Ok(());
 }
 
 async fn generate_book_html_dir(
     state: &Arc<State>,
     file: &FileHash,
-    source: &Path,
+    source: &LazyLocalFile<'_>,
     mime: &str,
 ) -> Result<(), loga::Error> {
     let gentype = GENTYPE_EPUBHTML;
-    if generated_exists(state, file, gentype).await? {
+    if generated_exists(state, file, gentype, "").await? {
         return Ok(());
     }
+    let source = source.path().await?;
     let tmp_dest = tempdir_in(&state.genfiles_stage_dir)?;
     let out = tmp_dest.path().join("index.html");
     let mut cmd = Command::new("pandoc");
@@ -441,22 +442,20 @@ async fn generate_book_html_dir(
             loga::err_with("Error converting ebook to html", ea!(res = res.dbg_str(), command = cmd.dbg_str())),
         );
     }
-    commit_generated(
-        state,
-        file.clone(),
-        gentype,
-        "text/html",
-        &out,
-        &genfile_path(&state, file, gentype, "")?,
-    ).await?;
+    commit_generated(state, file.clone(), gentype, "", "text/html", &out).await?;
     return Ok(());
 }
 
-async fn generate_comic_dir(state: &Arc<State>, file: &FileHash, source: &Path) -> Result<(), loga::Error> {
+async fn generate_comic_dir(
+    state: &Arc<State>,
+    file: &FileHash,
+    source: &LazyLocalFile<'_>,
+) -> Result<(), loga::Error> {
     let gentype = GENTYPE_CBZDIR;
-    if generated_exists(state, file, gentype).await? {
+    if generated_exists(state, file, gentype, "").await? {
         return Ok(());
     }
+    let source = source.path().await?;
     let tmp_dest = tempdir_in(&state.genfiles_stage_dir)?;
     let mut cmd = Command::new("7zz");
     cmd.kill_on_drop(true);
@@ -517,62 +516,474 @@ async fn generate_comic_dir(state: &Arc<State>, file: &FileHash, source: &Path)
         rtl: rtl,
         pages: manifest.into_values().collect::<Vec<_>>(),
     }).unwrap()).await.context_with("Error creating sunwet manifest", ea!(path = manifest_path.dbg_str()))?;
-    commit_generated(
-        state,
-        file.clone(),
-        gentype,
-        "",
-        &tmp_dest.path(),
-        &genfile_path(&state, file, gentype, "")?,
-    ).await?;
+    commit_generated(state, file.clone(), gentype, "", "", &tmp_dest.path()).await?;
     return Ok(());
 }
 
-async fn generate_files(
+/// Limit the amount of extracted text stored per file, to keep the search index
+/// reasonably sized.
+const FULLTEXT_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// Strip cue identifiers, timings, and markup from vtt subtitles, leaving just the
+/// spoken text.
+fn vtt_text(out: &mut String, vtt: &str) {
+    static TAG_MATCHER: LazyLock<Regex> = LazyLock::new(|| Regex::new("<[^>]*>").unwrap());
+    let mut in_note = false;
+    for line in vtt.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            in_note = false;
+            continue;
+        }
+        if in_note {
+            continue;
+        }
+        if line.starts_with("WEBVTT") || line.starts_with("STYLE") || line.starts_with("REGION") {
+            continue;
+        }
+        if line.starts_with("NOTE") {
+            in_note = true;
+            continue;
+        }
+        if line.contains("-->") {
+            continue;
+        }
+        if line.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        out.push_str(&TAG_MATCHER.replace_all(line, ""));
+        out.push_str("\n");
+    }
+}
+
+async fn generate_fulltext(
     state: &Arc<State>,
-    log: &Log,
     file: &FileHash,
-    include_slow: bool,
+    source: &LazyLocalFile<'_>,
+    mime: &str,
 ) -> Result<(), loga::Error> {
-    let Some(meta) = get_meta(&state, &file).await? else {
+    // Stored only in the search index (not as a generated file)
+    let done = tx(&state.db, {
+        let file = file.clone();
+        move |db| -> Result<_, loga::Error> {
+            return Ok(dbutil::meta_has_fulltext(db, &DbNode(Node::File(file)))?);
+        }
+    }).await?;
+    if done {
         return Ok(());
-    };
-    let source = file_path(&state, &file)?;
-    let mime = meta.mimetype.as_ref().map(|x| x.as_str()).unwrap_or("");
+    }
     let mime_slice = mime.split_once("/").unwrap_or((mime, ""));
+    let mut text = String::new();
     match (mime_slice.0, mime_slice.1) {
-        ("video", _) if include_slow => {
-            generate_subs(&state, &file, &source).await.log(&log, loga::WARN, "Error doing sub file generation");
-            if mime_slice.1 != "webm" {
-                generate_webm(&state, &file, &source)
+        ("video", _) => {
+            // Use subtitles extracted previously
+            if !state.genfiles.exists(file, GENTYPE_VTT, "").await? {
+                return Ok(());
+            }
+            let subs_dir = state.genfiles.local_copy(file, GENTYPE_VTT, "", &state.genfiles_stage_dir).await?;
+            let subs_dir = &subs_dir.path;
+            let mut subs_walk = WalkDir::new(subs_dir);
+            while let Some(entry) = subs_walk.next().await {
+                let entry =
+                    entry.context_with("Error reading entry in generated dir", ea!(dir = subs_dir.dbg_str()))?;
+                let path = entry.path();
+                if !tokio::fs::metadata(&path).await.context("Error reading fs metadata")?.is_file() {
+                    continue;
+                }
+                let vtt =
+                    read_to_string(&path)
+                        .await
+                        .context_with("Error reading extracted subtitles", ea!(path = path.dbg_str()))?;
+                vtt_text(&mut text, &vtt);
+            }
+        },
+        ("application", "epub+zip") => {
+            // Use html generated previously
+            if !state.genfiles.exists(file, GENTYPE_EPUBHTML, "").await? {
+                return Ok(());
+            }
+            let html_path = state.genfiles.local_copy(file, GENTYPE_EPUBHTML, "", &state.genfiles_stage_dir).await?;
+            let html_path = &html_path.path;
+            let mut cmd = Command::new("pandoc");
+            cmd.kill_on_drop(true);
+            cmd.stdin(Stdio::null());
+            cmd.args(&["--from", "html"]);
+            cmd.args(&["--to", "plain"]);
+            cmd.arg("--wrap=none");
+            cmd.arg(&html_path);
+            let res =
+                cmd
+                    .output()
                     .await
-                    .log(&log, loga::WARN, "Error doing webm transcode file generation");
+                    .context_with("Error extracting text from ebook html", ea!(command = cmd.dbg_str()))?;
+            if !res.status.success() {
+                return Err(
+                    loga::err_with(
+                        "Error extracting text from ebook html",
+                        ea!(res = res.dbg_str(), command = cmd.dbg_str()),
+                    ),
+                );
             }
+            text = String::from_utf8_lossy(&res.stdout).to_string();
         },
-        ("audio", _) => match mime_slice.1 {
-            "webm" => { },
-            _ => {
-                generate_audio_webm(&state, &file, &source)
+        ("application", "pdf") => {
+            let mut cmd = Command::new("pdftotext");
+            cmd.kill_on_drop(true);
+            cmd.stdin(Stdio::null());
+            cmd.args(&["-enc", "UTF-8"]);
+            cmd.arg(source.path().await?);
+            cmd.arg("-");
+            let res =
+                cmd
+                    .output()
                     .await
-                    .log(&log, loga::WARN, "Error doing audio webm transcode file generation");
-            },
+                    .context_with("Error extracting text from pdf", ea!(command = cmd.dbg_str()))?;
+            if !res.status.success() {
+                return Err(
+                    loga::err_with(
+                        "Error extracting text from pdf",
+                        ea!(res = res.dbg_str(), command = cmd.dbg_str()),
+                    ),
+                );
+            }
+            text = String::from_utf8_lossy(&res.stdout).to_string();
         },
-        ("application", "epub+zip") => {
-            generate_book_html_dir(&state, &file, &source, mime)
-                .await
-                .log(&log, loga::WARN, "Error doing epub html generation");
+        ("text", _) => {
+            let source = source.path().await?;
+            let raw =
+                tokio::fs::read(source)
+                    .await
+                    .context_with("Error reading text file", ea!(path = source.dbg_str()))?;
+            text = String::from_utf8_lossy(&raw).to_string();
+            if mime_slice.1 == "vtt" {
+                let vtt = steal(&mut text);
+                vtt_text(&mut text, &vtt);
+            }
         },
-        ("application", "x-cbr") | ("application", "x-cbz") | ("application", "x-cb7") => {
-            generate_comic_dir(&state, &file, &source)
-                .await
-                .log(&log, loga::WARN, "Error doing comic extraction/meta generation");
+        _ => {
+            return Ok(());
         },
-        _ => { },
     }
+    if text.len() > FULLTEXT_MAX_BYTES {
+        let mut end = FULLTEXT_MAX_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    if text.is_empty() {
+        // Non-empty marks extraction as done, so files without text aren't reprocessed
+        text.push_str("\n");
+    }
+    tx(&state.db, {
+        let file = file.clone();
+        move |db| -> Result<_, loga::Error> {
+            dbutil::meta_upsert_fulltext(db, &DbNode(Node::File(file)), &text)?;
+            return Ok(());
+        }
+    }).await?;
+    return Ok(());
+}
+
+async fn generate_command(
+    state: &Arc<State>,
+    file: &FileHash,
+    source: &LazyLocalFile<'_>,
+    config: &CommandGenerator,
+) -> Result<(), loga::Error> {
+    let gentype = config.gentype.as_str();
+    if generated_exists(state, file, gentype, "").await? {
+        return Ok(());
+    }
+    let source = source.path().await?;
+    let Some((program, args)) = config.command.split_first() else {
+        return Err(loga::err_with("Generator command is empty", ea!(gentype = gentype)));
+    };
+    let tmp = tempdir_in(&state.genfiles_stage_dir)?;
+    let work_path = tmp.path().join("work");
+    create_dirs(&work_path).await?;
+    let tempdest_path = tmp.path().join("out");
+    if config.dest_dir {
+        create_dirs(&tempdest_path).await?;
+    }
+    let source_str = source.to_string_lossy();
+    let dest_str = tempdest_path.to_string_lossy();
+    let substitute = |arg: &str| arg.replace("{source}", &source_str).replace("{dest}", &dest_str);
+    let mut cmd = Command::new(substitute(program));
+    cmd.kill_on_drop(true);
+    cmd.stdin(Stdio::null());
+    cmd.current_dir(&work_path);
+    for arg in args {
+        cmd.arg(substitute(arg));
+    }
+    let res =
+        cmd
+            .output()
+            .await
+            .context_with("Error starting generator command", ea!(command = cmd.dbg_str()))?;
+    if !res.status.success() {
+        return Err(
+            loga::err_with("Generator command failed", ea!(res = res.pretty_dbg_str(), command = cmd.dbg_str())),
+        );
+    }
+    if !tempdest_path.exists() {
+        return Err(
+            loga::err_with(
+                "Generator command succeeded but didn't produce output",
+                ea!(command = cmd.dbg_str(), dest = tempdest_path.dbg_str()),
+            ),
+        );
+    }
+    commit_generated(state, file.clone(), gentype, "", &config.mimetype, &tempdest_path).await?;
+    return Ok(());
+}
+
+/// The generators used if none are configured.
+pub fn default_generators() -> Vec<GeneratorConfig> {
+    fn builtin(mimetypes: &[&str], exclude_mimetypes: &[&str], slow: bool, b: BuiltinGenerator) -> GeneratorConfig {
+        return GeneratorConfig {
+            mimetypes: mimetypes.iter().map(|x| x.to_string()).collect(),
+            exclude_mimetypes: exclude_mimetypes.iter().map(|x| x.to_string()).collect(),
+            slow: slow,
+            concurrency: None,
+            kind: GeneratorKind::Builtin(b),
+        };
+    }
+
+    return vec![
+        builtin(&["video/*"], &[], true, BuiltinGenerator::VideoSubtitles),
+        builtin(&["video/*"], &["video/webm"], true, BuiltinGenerator::VideoWebm(BuiltinGeneratorVideoWebm {
+            crf: None,
+        })),
+        builtin(&["video/*"], &[], true, BuiltinGenerator::Fulltext),
+        builtin(&["audio/*"], &["audio/webm"], false, BuiltinGenerator::AudioWebm),
+        builtin(&["application/epub+zip"], &[], false, BuiltinGenerator::EpubHtml),
+        builtin(
+            &["application/x-cbr", "application/x-cbz", "application/x-cb7"],
+            &[],
+            false,
+            BuiltinGenerator::ComicDir,
+        ),
+        builtin(&["application/epub+zip", "application/pdf", "text/*"], &[], false, BuiltinGenerator::Fulltext),
+    ];
+}
+
+/// Patterns are `type/subtype`, `type/*`, or `*`
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    if let Some(type_) = pattern.strip_suffix("/*") {
+        return mime.split_once("/").map(|x| x.0) == Some(type_);
+    }
+    return pattern == mime;
+}
+
+fn generator_applies(config: &GeneratorConfig, mime: &str) -> bool {
+    return config.mimetypes.iter().any(|p| mime_matches(p, mime)) &&
+        !config.exclude_mimetypes.iter().any(|p| mime_matches(p, mime));
+}
+
+/// Run all fast or slow generators for the file. Generators that fail don't stop
+/// the rest from running, but the errors are all returned at the end.
+async fn generate_files(state: &Arc<State>, file: &FileHash, slow: bool) -> Result<(), loga::Error> {
+    let Some(meta) = get_meta(&state, &file).await? else {
+        return Ok(());
+    };
+    let source = state.files.lazy_local_copy(&file, &state.genfiles_stage_dir)?;
+    let mime = meta.mimetype.as_ref().map(|x| x.as_str()).unwrap_or("");
+    let mut errors = vec![];
+    for generator in &state.generators {
+        let config = &generator.config;
+        if config.slow != slow || !generator_applies(config, mime) {
+            continue;
+        }
+        let _permit = generator.limit.acquire().await.context("Error waiting for generator availability")?;
+        let res = match &config.kind {
+            GeneratorKind::Builtin(b) => match b {
+                BuiltinGenerator::VideoSubtitles => generate_subs(&state, &file, &source).await,
+                BuiltinGenerator::VideoWebm(c) => generate_webm(&state, &file, &source, c).await,
+                BuiltinGenerator::AudioWebm => generate_audio_webm(&state, &file, &source).await,
+                BuiltinGenerator::EpubHtml => generate_book_html_dir(&state, &file, &source, mime).await,
+                BuiltinGenerator::ComicDir => generate_comic_dir(&state, &file, &source).await,
+                BuiltinGenerator::Fulltext => generate_fulltext(&state, &file, &source, mime).await,
+            },
+            GeneratorKind::Command(c) => generate_command(&state, &file, &source, c).await,
+        };
+        if let Err(e) = res {
+            errors.push(e.context_with("Error generating derived file", ea!(generator = config.kind.dbg_str())));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(loga::agg_err("Error(s) running generators", errors));
+    }
+    return Ok(());
+}
+
+/// Jobs queued due to uploads run before those from the startup scan
+const GENERATE_PRIORITY_UPLOAD: i64 = 10;
+const GENERATE_PRIORITY_SCAN: i64 = 0;
+const GENERATE_MAX_ATTEMPTS: i64 = 6;
+
+fn generate_retry_delay(attempts: i64) -> chrono::Duration {
+    return chrono::Duration::minutes(1 << attempts.clamp(0, 10)).min(chrono::Duration::days(1));
+}
+
+/// Queue fast and slow generation for files, for whichever classes have generators
+/// matching the file's mimetype.
+async fn generate_enqueue(
+    state: &Arc<State>,
+    files: Vec<FileHash>,
+    priority: i64,
+    replace: bool,
+) -> Result<(), loga::Error> {
+    tx(&state.db, {
+        let state = state.clone();
+        move |db| -> Result<_, loga::Error> {
+            let now = Utc::now();
+            for file in files {
+                let Some(mime) = dbutil::meta_get_mimetype(db, &DbNode(Node::File(file.clone())))? else {
+                    continue;
+                };
+                let mime = mime.unwrap_or_default();
+                let file = DbFileHash(file);
+                for slow in [false, true] {
+                    if !state.generators.iter().any(|g| g.config.slow == slow && generator_applies(&g.config, &mime)) {
+                        // Drop any job left over from a previous generator config
+                        dbutil::generate_queue_done(db, &file, slow)?;
+                        continue;
+                    }
+                    if replace {
+                        dbutil::generate_queue_push(db, &file, slow, priority, now)?;
+                    } else {
+                        dbutil::generate_queue_push_if_missing(db, &file, slow, priority, now)?;
+                    }
+                }
+            }
+            return Ok(());
+        }
+    }).await?;
+    state.generate_wake_fast.notify_waiters();
+    state.generate_wake_slow.notify_waiters();
     return Ok(());
 }
 
-pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedReceiver<BackgroundJob>) {
+fn start_generate_worker(state: &Arc<State>, tm: &TaskManager, slow: bool, index: usize) {
+    let class = if slow {
+        "slow"
+    } else {
+        "fast"
+    };
+    tm.task(format!("Generate worker ({}, {})", class, index), {
+        let state = state.clone();
+        let tm = tm.clone();
+        let log = state.log.fork(ea!(subsys = "filegen", class = class, worker = index));
+        async move {
+            let wake = if slow {
+                &state.generate_wake_slow
+            } else {
+                &state.generate_wake_fast
+            };
+            loop {
+                // Register before checking the queue so jobs queued in the meantime aren't
+                // missed
+                let woken = wake.notified();
+                tokio::pin!(woken);
+                woken.as_mut().enable();
+                let claimed = match tx(&state.db, move |db| -> Result<_, loga::Error> {
+                    return Ok(dbutil::generate_queue_claim(db, slow, Utc::now())?);
+                }).await {
+                    Ok(c) => c,
+                    Err(e) => {
+                        log.log_err(loga::WARN, e.context("Error claiming generation job"));
+                        None
+                    },
+                };
+                let Some(claim) = claimed else {
+                    // Nothing ready, wait for new jobs or the next retry
+                    let mut delay = std::time::Duration::from_secs(60);
+                    match tx(&state.db, move |db| -> Result<_, loga::Error> {
+                        return Ok(dbutil::generate_queue_earliest(db, slow)?);
+                    }).await {
+                        Ok(Some(earliest)) => {
+                            delay = delay.min((earliest - Utc::now()).to_std().unwrap_or_default());
+                        },
+                        Ok(None) => { },
+                        Err(e) => {
+                            log.log_err(loga::WARN, e.context("Error getting next generation retry time"));
+                        },
+                    }
+                    select!{
+                        _ = woken => { },
+                        _ = sleep(delay) => { },
+                        _ = tm.until_terminate() => {
+                            return;
+                        }
+                    }
+                    continue;
+                };
+                let log = log.fork(ea!(file = claim.file.0.to_string()));
+                log.log(loga::DEBUG, "Generating files");
+                let res = select!{
+                    r = generate_files(&state, &claim.file.0, slow) => r,
+                    _ = tm.until_terminate() => {
+                        return;
+                    }
+                };
+                let res = tx(&state.db, move |db| -> Result<_, loga::Error> {
+                    match res {
+                        Ok(_) => {
+                            dbutil::generate_queue_finish(db, &claim.file, slow, claim.version)?;
+                            return Ok(None);
+                        },
+                        Err(e) => {
+                            let attempts = claim.attempts + 1;
+                            dbutil::generate_queue_retry(
+                                db,
+                                &claim.file,
+                                slow,
+                                claim.version,
+                                attempts,
+                                Utc::now() + generate_retry_delay(attempts),
+                                attempts >= GENERATE_MAX_ATTEMPTS,
+                                &e.to_string(),
+                            )?;
+                            return Ok(Some((e, attempts)));
+                        },
+                    }
+                }).await;
+                match res {
+                    Ok(None) => { },
+                    Ok(Some((e, attempts))) => {
+                        log.log_err(
+                            loga::WARN,
+                            e.context_with(
+                                "Error generating derived files",
+                                ea!(attempts = attempts, will_retry = attempts < GENERATE_MAX_ATTEMPTS),
+                            ),
+                        );
+                    },
+                    Err(e) => {
+                        log.log_err(loga::WARN, e.context("Error updating generation job state"));
+                    },
+                }
+            }
+        }
+    });
+}
+
+pub fn start_background_job(
+    state: &Arc<State>,
+    tm: &TaskManager,
+    rx: UnboundedReceiver<BackgroundJob>,
+    workers: &GeneratorWorkersConfig,
+) {
+    for i in 0 .. workers.fast.unwrap_or(4).max(1) {
+        start_generate_worker(state, tm, false, i);
+    }
+    for i in 0 .. workers.slow.unwrap_or(1).max(1) {
+        start_generate_worker(state, tm, true, i);
+    }
     tm.stream("Background", UnboundedReceiverStream::new(rx), {
         let state = state.clone();
         let log = state.log.fork(ea!(subsys = "filegen"));
@@ -587,91 +998,67 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                 match file {
                     BackgroundJob::GenerateOne(file) => {
                         let log = log.fork(ea!(file = file.to_string()));
-                        log.log_with(loga::DEBUG, "Generating one file", ea!(file = file));
-                        generate_files(&state, &log, &file, true)
+                        log.log_with(loga::DEBUG, "Queueing generation for one file", ea!(file = file));
+                        generate_enqueue(&state, vec![file], GENERATE_PRIORITY_UPLOAD, true)
                             .await
-                            .log(&log, loga::WARN, "Error generating derived files");
+                            .log(&log, loga::WARN, "Error queueing derived file generation");
                     },
                     BackgroundJob::All => {
                         match async {
                             ta_return!((), loga::Error);
 
-                            // # Generate/derive files
-                            log.log(loga::DEBUG, "Doing file generation");
-                            for slow in [false, true] {
-                                async fn generate_batch(
-                                    state: &Arc<State>,
-                                    dbc: &Pool,
-                                    slow: bool,
-                                    batch: Vec<DbNode>,
-                                ) -> Result<(), loga::Error> {
-                                    let (found_sub, found_obj) = tx(&dbc, {
-                                        let batch = batch.clone();
-                                        move |db| -> Result<(Vec<DbNode>, Vec<DbNode>), loga::Error> {
-                                            let refs: Vec<&DbNode> = batch.iter().collect();
-                                            Ok(
-                                                (
-                                                    dbutil::snapshot_filter_nodes_by_end(db, "subject", refs.clone())?,
-                                                    dbutil::snapshot_filter_nodes_by_end(db, "object", refs)?,
-                                                ),
-                                            )
-                                        }
-                                    }).await?;
-                                    let found_keys: HashSet<_> =
-                                        found_sub.into_iter().chain(found_obj.into_iter()).map(|n| n.0).collect();
-                                    for key in batch {
-                                        if !found_keys.contains(&key.0) {
-                                            continue;
-                                        }
-                                        let file = exenum!(key.0, Node:: File(x) => x).unwrap();
-                                        let log = state.log.fork(ea!(subsys = "filegen", file = file.to_string()));
-                                        generate_files(&state, &log, &file, slow).await?;
+                            // # Queue generating/deriving files
+                            log.log(loga::DEBUG, "Queueing file generation");
+
+                            async fn enqueue_batch(state: &Arc<State>, batch: Vec<DbNode>) -> Result<(), loga::Error> {
+                                let (found_sub, found_obj) = tx(&state.db, {
+                                    let batch = batch.clone();
+                                    move |db| -> Result<(Vec<DbNode>, Vec<DbNode>), loga::Error> {
+                                        let refs: Vec<&DbNode> = batch.iter().collect();
+                                        Ok(
+                                            (
+                                                dbutil::snapshot_filter_nodes_by_end(db, "subject", refs.clone())?,
+                                                dbutil::snapshot_filter_nodes_by_end(db, "object", refs)?,
+                                            ),
+                                        )
                                     }
-                                    return Ok(());
+                                }).await?;
+                                let found_keys: HashSet<_> =
+                                    found_sub.into_iter().chain(found_obj.into_iter()).map(|n| n.0).collect();
+                                let mut files = vec![];
+                                for key in batch {
+                                    if !found_keys.contains(&key.0) {
+                                        continue;
+                                    }
+                                    files.push(exenum!(key.0, Node:: File(x) => x).unwrap());
                                 }
+                                generate_enqueue(state, files, GENERATE_PRIORITY_SCAN, false).await?;
+                                return Ok(());
+                            }
 
-                                let batch = Arc::new(Mutex::new(vec![]));
-                                soft_read_dir(&log, &state.files_dir, async |hash_type| {
-                                    soft_read_dir(&log, &hash_type.path(), async |hash_part1| {
-                                        soft_read_dir(&log, &hash_part1.path(), async |hash_part2| {
-                                            soft_read_dir(&log, &hash_part2.path(), async |entry| {
-                                                if !entry
-                                                    .metadata()
-                                                    .await
-                                                    .stack_context(&log, "Error reading metadata")?
-                                                    .is_file() {
-                                                    return Ok(());
-                                                }
-                                                let path = entry.path();
-                                                let log = log.fork(ea!(path = path.to_string_lossy()));
-                                                let Some(file) =
-                                                    get_hash_from_file_path(&log, &state.files_dir, &path) else {
-                                                        return Ok(());
-                                                    };
-                                                let consume_batch = {
-                                                    let mut batch = batch.lock().unwrap();
-                                                    batch.push(DbNode(Node::File(file)));
-                                                    if batch.len() >= 1000 {
-                                                        Some(steal(&mut *batch))
-                                                    } else {
-                                                        None
-                                                    }
-                                                };
-                                                if let Some(batch) = consume_batch {
-                                                    generate_batch(&state, &state.db, slow, batch).await?;
-                                                }
-                                                return Ok(());
-                                            }).await;
-                                            return Ok(());
-                                        }).await;
+                            let stored = match state.files.list(&log).await {
+                                Ok(s) => Some(s),
+                                Err(e) => {
+                                    log.log_err(loga::WARN, e.context("Error listing stored files"));
+                                    None
+                                },
+                            };
+                            if let Some(stored) = &stored {
+                                // Drop jobs for files that are no longer stored
+                                tx(&state.db, {
+                                    let stored = stored.iter().map(|f| DbFileHash(f.clone())).collect::<Vec<_>>();
+                                    move |db| {
+                                        dbutil::generate_queue_gc(db, stored.iter().collect())?;
                                         return Ok(());
-                                    }).await;
-                                    return Ok(());
-                                }).await;
-                                let batch = steal(&mut *batch.lock().unwrap());
-                                if !batch.is_empty() {
-                                    generate_batch(&state, &state.db, slow, batch).await?;
-                                }
+                                    }
+                                }).await?;
+                            }
+                            let stored = stored.unwrap_or_default();
+                            for batch in stored.chunks(1000) {
+                                enqueue_batch(
+                                    &state,
+                                    batch.iter().map(|f| DbNode(Node::File(f.clone()))).collect(),
+                                ).await?;
                             }
 
                             // # Garbage collect
@@ -695,12 +1082,12 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
 
                                 async fn clean_batch(
                                     log: &Log,
-                                    dbc: &Pool,
-                                    mut batch: HashMap<FileHash, PathBuf>,
+                                    state: &Arc<State>,
+                                    batch: &[FileHash],
                                 ) -> Result<(), loga::Error> {
                                     let unfiltered_keys =
-                                        batch.keys().map(|k| DbNode(Node::File(k.clone()))).collect::<Vec<_>>();
-                                    let found_keys = tx(&dbc, move |db| -> Result<HashSet<Node>, loga::Error> {
+                                        batch.iter().map(|k| DbNode(Node::File(k.clone()))).collect::<Vec<_>>();
+                                    let found_keys = tx(&state.db, move |db| -> Result<HashSet<Node>, loga::Error> {
                                         return Ok(good_ormning::sqlite::good_query_many!(
                                             db,
                                             //# genemichaels-external: sql-formatter-sqlite
@@ -720,76 +1107,40 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                                             unfiltered_keys: arr node = unfiltered_keys.iter().collect::< Vec < _ >>()
                                         )?.into_iter().map(|x| x.0).collect::<HashSet<_>>());
                                     }).await?;
-                                    for key in found_keys {
-                                        batch.remove(&exenum!(key, Node:: File(x) => x).unwrap());
-                                    }
-                                    for path in batch.values() {
+                                    for hash in batch {
+                                        if found_keys.contains(&Node::File(hash.clone())) {
+                                            continue;
+                                        }
                                         log.log_with(
                                             loga::DEBUG,
                                             "Garbage collecting file",
-                                            ea!(file = path.dbg_str()),
+                                            ea!(file = hash.to_string()),
                                         );
-                                        remove_file(path)
+                                        state
+                                            .files
+                                            .delete(hash)
                                             .await
                                             .log_with(
                                                 &log,
                                                 loga::WARN,
                                                 "Failed to delete unreferenced file",
-                                                ea!(path = path.display().to_string()),
+                                                ea!(file = hash.to_string()),
                                             );
+                                        tx(&state.db, {
+                                            let file = DbFileHash(hash.clone());
+                                            move |db| {
+                                                for slow in [false, true] {
+                                                    dbutil::generate_queue_done(db, &file, slow)?;
+                                                }
+                                                return Ok(());
+                                            }
+                                        }).await?;
                                     }
                                     return Ok(());
                                 }
 
-                                let batch = Arc::new(Mutex::new(HashMap::new()));
-                                soft_read_dir(&log, &state.files_dir, enclose!((batch) | entry_hashtype | async move {
-                                    soft_read_dir(
-                                        &log,
-                                        &entry_hashtype.path(),
-                                        enclose!((batch) | entry_hash1 | async move {
-                                            soft_read_dir(
-                                                &log,
-                                                &entry_hash1.path(),
-                                                enclose!((batch) | entry_hash2 | async move {
-                                                    soft_read_dir(
-                                                        &log,
-                                                        &entry_hash2.path(),
-                                                        enclose!((batch) | entry | async move {
-                                                            let path = entry.path();
-                                                            let Some(hash) =
-                                                                get_hash_from_file_path(
-                                                                    &log,
-                                                                    &state.files_dir,
-                                                                    &path
-                                                                ) else {
-                                                                    return Ok(());
-                                                                };
-                                                            let consume_batch = {
-                                                                let mut batch = batch.lock().unwrap();
-                                                                batch.insert(hash.clone(), path);
-                                                                if batch.len() >= 1000 {
-                                                                    Some(steal(&mut *batch))
-                                                                } else {
-                                                                    None
-                                                                }
-                                                            };
-                                                            if let Some(batch) = consume_batch {
-                                                                clean_batch(&log, &state.db, batch).await?;
-                                                            }
-                                                            return Ok(());
-                                                        })
-                                                    ).await;
-                                                    return Ok(());
-                                                })
-                                            ).await;
-                                            return Ok(());
-                                        })
-                                    ).await;
-                                    return Ok(());
-                                })).await;
-                                let batch = steal(&mut *batch.lock().unwrap());
-                                if !batch.is_empty() {
-                                    clean_batch(&log, &state.db, batch).await?;
+                                for batch in stored.chunks(1000) {
+                                    clean_batch(&log, &state, batch).await?;
                                 }
                             }
 
@@ -799,8 +1150,8 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
 
                                 async fn clean_batch(
                                     log: &Log,
-                                    dbc: &Pool,
-                                    batch: Vec<(FileHash, PathBuf)>,
+                                    state: &Arc<State>,
+                                    batch: &[(FileHash, PathBuf)],
                                 ) -> Result<(), loga::Error> {
                                     let unfiltered_keys =
                                         batch
@@ -808,7 +1159,7 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                                             .map(|(k, _)| DbNode(Node::File(k.clone())))
                                             .collect::<Vec<_>>();
                                     let found_keys =
-                                        tx(&dbc, move |db| -> Result<HashSet<Node>, loga::Error> {
+                                        tx(&state.db, move |db| -> Result<HashSet<Node>, loga::Error> {
                                             return Ok(good_ormning::sqlite::good_query_many!(
                                                 db,
                                                 //# genemichaels-external: sql-formatter-sqlite
@@ -833,95 +1184,38 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                                             .into_iter()
                                             .map(|x| exenum!(x, Node:: File(x) => x).unwrap())
                                             .collect::<HashSet<_>>();
-                                    for (hash, path) in batch {
-                                        if found_keys.contains(&hash) {
+                                    for (hash, key) in batch {
+                                        if found_keys.contains(hash) {
                                             continue;
                                         }
                                         log.log_with(
                                             loga::DEBUG,
                                             "Garbage collecting generated file",
-                                            ea!(path = path.dbg_str()),
+                                            ea!(key = key.dbg_str()),
                                         );
-                                        match tokio::fs::metadata(&path).await {
-                                            Ok(meta) => {
-                                                if meta.is_dir() {
-                                                    remove_dir_all(&path).await
-                                                } else {
-                                                    remove_file(&path).await
-                                                }.log_with(
-                                                    &log,
-                                                    loga::WARN,
-                                                    "Failed to delete unreferenced file",
-                                                    ea!(path = path.display().to_string()),
-                                                )
-                                            },
-                                            Err(e) => {
-                                                log.log_err(
-                                                    loga::WARN,
-                                                    e.context_with(
-                                                        "Unable to get file to clean metadata",
-                                                        ea!(path = path.dbg_str()),
-                                                    ),
-                                                );
-                                            },
-                                        }
+                                        state
+                                            .genfiles
+                                            .delete_key(key)
+                                            .await
+                                            .log_with(
+                                                &log,
+                                                loga::WARN,
+                                                "Failed to delete unreferenced generated file",
+                                                ea!(key = key.dbg_str()),
+                                            );
                                     }
                                     return Ok(());
                                 }
 
-                                let batch = Arc::new(Mutex::new(vec![]));
-                                soft_read_dir(
-                                    &log,
-                                    &state.genfiles_dir,
-                                    enclose!((batch) | entry_hashtype | async move {
-                                        soft_read_dir(
-                                            &log,
-                                            &entry_hashtype.path(),
-                                            enclose!((batch) | entry_hash1 | async move {
-                                                soft_read_dir(
-                                                    &log,
-                                                    &entry_hash1.path(),
-                                                    enclose!((batch) | entry_hash2 | async move {
-                                                        soft_read_dir(
-                                                            &log,
-                                                            &entry_hash2.path(),
-                                                            enclose!((batch) | entry | async move {
-                                                                let path = entry.path();
-                                                                let Some(hash) =
-                                                                    get_hash_from_file_path(
-                                                                        &log,
-                                                                        &state.genfiles_dir,
-                                                                        &path
-                                                                    ) else {
-                                                                        return Ok(());
-                                                                    };
-                                                                let consume_batch = {
-                                                                    let mut batch = batch.lock().unwrap();
-                                                                    batch.push((hash.clone(), path));
-                                                                    if batch.len() >= 1000 {
-                                                                        Some(steal(&mut *batch))
-                                                                    } else {
-                                                                        None
-                                                                    }
-                                                                };
-                                                                if let Some(batch) = consume_batch {
-                                                                    clean_batch(&log, &state.db, batch).await?;
-                                                                }
-                                                                return Ok(());
-                                                            })
-                                                        ).await;
-                                                        return Ok(());
-                                                    })
-                                                ).await;
-                                                return Ok(());
-                                            })
-                                        ).await;
-                                        return Ok(());
-                                    }),
-                                ).await;
-                                let batch = steal(&mut *batch.lock().unwrap());
-                                if !batch.is_empty() {
-                                    clean_batch(&log, &state.db, batch).await?;
+                                match state.genfiles.list(&log).await {
+                                    Ok(generated) => {
+                                        for batch in generated.chunks(1000) {
+                                            clean_batch(&log, &state, batch).await?;
+                                        }
+                                    },
+                                    Err(e) => {
+                                        log.log_err(loga::WARN, e.context("Error listing generated files"));
+                                    },
                                 }
                             }
 
@@ -936,7 +1230,7 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                                         .metadata()
                                         .await
                                         .context_with("Error reading metadata", ea!(path = path.dbg_str()))?;
-                                if !meta.is_file() {
+                                if !meta.is_file() && !meta.is_dir() {
                                     return Ok(());
                                 }
                                 let modified_time = match meta.modified() {
@@ -958,14 +1252,18 @@ pub fn start_background_job(state: &Arc<State>, tm: &TaskManager, rx: UnboundedR
                                         "Garbage collecting stale partial upload",
                                         ea!(file = path.dbg_str()),
                                     );
-                                    remove_file(&path)
-                                        .await
-                                        .log_with(
-                                            &log,
-                                            loga::WARN,
-                                            "Failed to delete stale partial upload file",
-                                            ea!(path = path.display().to_string()),
-                                        );
+                                    // Dirs are the chunk records for partial uploads
+                                    let res = if meta.is_dir() {
+                                        delete_tree(&path).await
+                                    } else {
+                                        remove_file(&path).await.map_err(loga::Error::from)
+                                    };
+                                    res.log_with(
+                                        &log,
+                                        loga::WARN,
+                                        "Failed to delete stale partial upload file",
+                                        ea!(path = path.display().to_string()),
+                                    );
                                 }
                                 return Ok(());
                             }).await;
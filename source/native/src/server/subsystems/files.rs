@@ -10,16 +10,24 @@ use {
                 AccessSourceId,
                 DbAccessSourceId,
             },
+            db,
             dbutil,
             dbutil::tx,
             dbwrite,
             filesutil::{
-                file_path,
-                genfile_path,
                 get_meta,
                 hash_file_sha256,
+                staged_file_chunks_path,
                 staged_file_path,
             },
+            fsutil::{
+                create_dirs,
+                delete_tree,
+            },
+            query::{
+                build_root_chain,
+                execute_sql_query,
+            },
             state::{
                 BackgroundJob,
                 State,
@@ -39,7 +47,6 @@ use {
             response_200_json,
             response_404,
         },
-        self,
         viserr::{
             ResultVisErr,
             VisErr,
@@ -50,6 +57,7 @@ use {
         Incoming,
     },
     loga::{
+        DebugDisplay,
         ResultContext,
         conversion::ResultIgnore,
         ea,
@@ -60,6 +68,8 @@ use {
             InputOrInline,
             InputOrInlineText,
         },
+        ont::VIRTUAL_PREDICATES,
+        query::Query,
         triple::{
             FileHash,
             Node,
@@ -68,13 +78,23 @@ use {
             HEADER_OFFSET,
             ReqCommitForm,
             ReqCommitFree,
+            ReqCommitView,
+            ReqCommitViewDelete,
             RespCommit,
+            RespUploadChunk,
             RespUploadFinish,
+            RespUploadStatus,
             TreeNode,
             Triple,
+            upload_chunk_checksum,
         },
     },
     std::{
+        collections::{
+            BTreeMap,
+            HashMap,
+            HashSet,
+        },
         hash::{
             DefaultHasher,
             Hash,
@@ -87,7 +107,10 @@ use {
         fs::{
             File,
             create_dir_all,
-            rename,
+            metadata,
+            read_dir,
+            read_to_string,
+            write,
         },
         io::{
             AsyncSeekExt,
@@ -100,20 +123,45 @@ async fn commit(
     state: Arc<State>,
     c: ReqCommitFree,
     update_access_reqs: Option<(FormId, u64)>,
-) -> Result<RespCommit, loga::Error> {
+) -> Result<RespCommit, VisErr<loga::Error>> {
+    for t in &c.add {
+        if VIRTUAL_PREDICATES.contains(&t.predicate.as_str()) {
+            return Err(
+                loga::err_with("Triples can't use virtual predicates", ea!(predicate = t.predicate)),
+            ).err_external();
+        }
+    }
+
     // Preallocate files for upload, confirm already present files
     let mut incomplete = vec![];
     for info in &c.files {
-        if file_path(&state, &info.hash)?.exists() {
+        if state.files.exists(&info.hash).await.err_internal()? {
             continue;
         }
         incomplete.push(info.hash.clone());
-        let path = staged_file_path(&state, &info.hash)?;
+        let path = staged_file_path(&state, &info.hash).err_internal()?;
+        if metadata(&path).await.ok().filter(|m| m.len() == info.size).is_some() {
+            // Partially uploaded already, keep received chunks so the upload can be resumed
+            continue;
+        }
         create_dir_all(&path.parent().unwrap())
             .await
-            .stack_context(&state.log, "Failed to create upload staging dirs")?;
-        let f = File::create(&path).await.stack_context(&state.log, "Failed to create upload staged file")?;
-        f.set_len(info.size).await.stack_context(&state.log, "Error preallocating disk space for upload")?;
+            .stack_context(&state.log, "Failed to create upload staging dirs")
+            .err_internal()?;
+        delete_tree(&staged_file_chunks_path(&state, &info.hash).err_internal()?)
+            .await
+            .stack_context(&state.log, "Failed to clear stale upload chunk records")
+            .err_internal()?;
+        let f =
+            File::create(&path)
+                .await
+                .stack_context(&state.log, "Failed to create upload staged file")
+                .err_internal()?;
+        f
+            .set_len(info.size)
+            .await
+            .stack_context(&state.log, "Error preallocating disk space for upload")
+            .err_internal()?;
     }
 
     // Write new triples, commit (no-op if all triples already committed)
@@ -146,52 +194,6 @@ async fn commit(
         let mut modified = false;
         let stamp = Utc::now();
 
-        fn update_fulltext<
-            C: good_ormning::runtime::sqlite::SqliteConnection,
-        >(db: &mut crate::server::db::Db<C>, node: &Node) -> Result<(), loga::Error> {
-            let mut fulltext = String::new();
-
-            fn gather_value_text(fulltext: &mut String, value: &serde_json::Value) {
-                match value {
-                    serde_json::Value::Null => {
-                        // nop
-                    },
-                    serde_json::Value::Bool(_) => {
-                        // nop
-                    },
-                    serde_json::Value::Number(_) => {
-                        // nop
-                    },
-                    serde_json::Value::String(v) => {
-                        fulltext.push_str(v);
-                        fulltext.push_str(" ");
-                    },
-                    serde_json::Value::Array(v) => {
-                        for v in v {
-                            gather_value_text(fulltext, v);
-                        }
-                    },
-                    serde_json::Value::Object(v) => {
-                        for (k, v) in v {
-                            fulltext.push_str(k);
-                            fulltext.push_str(" ");
-                            gather_value_text(fulltext, v);
-                        }
-                    },
-                }
-            }
-
-            match node {
-                Node::File(_) => {
-                    // nop
-                },
-                Node::Value(v) => gather_value_text(&mut fulltext, v),
-            }
-            let node_db = DbNode(node.clone());
-            dbutil::meta_upsert_fulltext(db, &node_db, &fulltext).context("Error updating fulltext")?;
-            return Ok(());
-        }
-
         for t in c.remove {
             let subject = DbNode(t.subject.clone());
             let object = DbNode(t.object.clone());
@@ -207,8 +209,8 @@ async fn commit(
             if dbutil::triple_snapshot_exists(db, &subject, &t.predicate, &object)? {
                 continue;
             }
-            update_fulltext(db, &t.subject)?;
-            update_fulltext(db, &t.object)?;
+            dbwrite::update_fulltext(db, &t.subject)?;
+            dbwrite::update_fulltext(db, &t.object)?;
             dbwrite::write_triple(db, &DbNode(t.subject), &t.predicate, &DbNode(t.object), stamp, true)?;
             modified = true;
         }
@@ -218,11 +220,11 @@ async fn commit(
             dbutil::commit_insert(db, &stamp, &c.comment).context("Error inserting commit")?;
         }
         return Ok(());
-    }).await?;
+    }).await.err_internal()?;
     return Ok(RespCommit { incomplete: incomplete });
 }
 
-pub async fn handle_commit(state: Arc<State>, c: ReqCommitFree) -> Result<RespCommit, loga::Error> {
+pub async fn handle_commit(state: Arc<State>, c: ReqCommitFree) -> Result<RespCommit, VisErr<loga::Error>> {
     return Ok(commit(state, c, None).await?);
 }
 
@@ -310,7 +312,169 @@ pub async fn handle_form_commit(state: Arc<State>, c: ReqCommitForm) -> Result<R
         add: add,
         remove: vec![],
         files: c.files,
-    }, Some((c.form_id.clone(), form_hash.finish()))).await.err_internal()?);
+    }, Some((c.form_id.clone(), form_hash.finish()))).await?);
+}
+
+fn gather_tree_nodes(out: &mut HashSet<Node>, tree: &TreeNode) {
+    match tree {
+        TreeNode::Scalar(v) => {
+            out.insert(v.clone());
+        },
+        TreeNode::Array(v) => {
+            for v in v {
+                gather_tree_nodes(out, v);
+            }
+        },
+        TreeNode::Record(v) => {
+            for v in v.values() {
+                gather_tree_nodes(out, v);
+            }
+        },
+    }
+}
+
+/// All nodes returned by a view's queries, run with the parameters the client
+/// used. Queries that can't be built with the parameters (ex: missing a parameter)
+/// are skipped.
+pub fn view_returned_nodes(
+    db: &mut db::Db<&mut rusqlite::Transaction<'_>>,
+    queries: &BTreeMap<String, Query>,
+    parameters: &HashMap<String, Node>,
+    user: Option<String>,
+) -> Result<HashSet<Node>, loga::Error> {
+    let mut out = HashSet::new();
+    for query in queries.values() {
+        let Ok((sql_query, sql_parameters)) = build_root_chain(query, parameters.clone(), user.clone()) else {
+            continue;
+        };
+        for row in execute_sql_query(db, sql_query, sql_parameters, query, &Default::default(), None)? {
+            out.insert(row.head_data);
+            for v in row.tail_data.values() {
+                gather_tree_nodes(&mut out, v);
+            }
+        }
+    }
+    return Ok(out);
+}
+
+pub async fn handle_view_commit(
+    state: Arc<State>,
+    user: Option<String>,
+    c: ReqCommitView,
+) -> Result<RespCommit, VisErr<loga::Error>> {
+    let global_config = get_global_config(&state).await.err_internal()?;
+    let Some(view) = global_config.views.get(&c.view_id) else {
+        return Err(loga::err_with("No known view with id", ea!(id = c.view_id))).err_external();
+    };
+
+    // Views only grant changing a single value of a node the view shows
+    if c.add.len() > 1 || c.remove.len() > 1 {
+        return Err(
+            loga::err_with("View edits can only change one value at a time", ea!(view = c.view_id)),
+        ).err_external();
+    }
+    let mut edited = None;
+    for triple in c.add.iter().chain(c.remove.iter()) {
+        if !view.edit_predicates.contains(&triple.predicate) {
+            return Err(
+                loga::err_with(
+                    "Predicate isn't editable in view",
+                    ea!(view = c.view_id, predicate = triple.predicate),
+                ),
+            ).err_external();
+        }
+        match edited {
+            None => {
+                edited = Some((&triple.subject, &triple.predicate));
+            },
+            Some((subject, predicate)) => {
+                if *subject != triple.subject || *predicate != triple.predicate {
+                    return Err(
+                        loga::err_with(
+                            "View edits must remove and add values of the same subject and predicate",
+                            ea!(view = c.view_id),
+                        ),
+                    ).err_external();
+                }
+            },
+        }
+    }
+    if let Some((subject, _)) = edited {
+        let returned = tx(&state.db, {
+            let queries = view.item.queries.clone();
+            let parameters = c.parameters.clone();
+            let subject = subject.clone();
+            move |db| Ok(view_returned_nodes(db, &queries, &parameters, user)?.contains(&subject))
+        }).await.err_internal()?;
+        if !returned {
+            return Err(
+                loga::err_with(
+                    "Edited subject isn't returned by the view's queries",
+                    ea!(view = c.view_id, subject = subject.dbg_str()),
+                ),
+            ).err_external();
+        }
+    }
+    return Ok(commit(state, ReqCommitFree {
+        comment: format!("View [{}]", c.view_id),
+        add: c.add,
+        remove: c.remove,
+        files: vec![],
+    }, None).await?);
+}
+
+pub async fn handle_view_delete_commit(
+    state: Arc<State>,
+    user: Option<String>,
+    c: ReqCommitViewDelete,
+) -> Result<RespCommit, VisErr<loga::Error>> {
+    let global_config = get_global_config(&state).await.err_internal()?;
+    let Some(view) = global_config.views.get(&c.view_id) else {
+        return Err(loga::err_with("No known view with id", ea!(id = c.view_id))).err_external();
+    };
+    let edit_predicates = view.edit_predicates.clone();
+    let (outside, remove) = tx(&state.db, {
+        let queries = view.item.queries.clone();
+        let parameters = c.parameters.clone();
+        let nodes = c.nodes.clone();
+        move |db| {
+            let returned = view_returned_nodes(db, &queries, &parameters, user)?;
+            for node in &nodes {
+                if !returned.contains(node) {
+                    return Ok((Some(node.clone()), vec![]));
+                }
+            }
+
+            // Only relations the view can edit are removed
+            let nodes = nodes.into_iter().map(DbNode).collect::<Vec<_>>();
+            let mut remove = vec![];
+            for t in dbutil::snapshot_triples_around(db, nodes.iter().collect())? {
+                if !edit_predicates.contains(&t.predicate) {
+                    continue;
+                }
+                remove.push(Triple {
+                    subject: t.subject.0,
+                    predicate: t.predicate,
+                    object: t.object.0,
+                });
+            }
+            return Ok((None, remove));
+        }
+    }).await.err_internal()?;
+    if let Some(node) = outside {
+        return Err(
+            loga::err_with(
+                "Deleted node isn't returned by the view's queries",
+                ea!(view = c.view_id, node = node.dbg_str()),
+            ),
+        ).err_external();
+    }
+    return Ok(commit(state, ReqCommitFree {
+        comment: format!("Delete via view [{}]", c.view_id),
+        add: vec![],
+        remove: remove,
+        files: vec![],
+    }, None).await?);
 }
 
 pub async fn handle_finish_upload(
@@ -318,7 +482,7 @@ pub async fn handle_finish_upload(
     file: FileHash,
 ) -> Result<Option<RespUploadFinish>, loga::Error> {
     let done;
-    if file_path(&state, &file)?.exists() {
+    if state.files.exists(&file).await? {
         done = true;
     } else {
         done = false;
@@ -341,16 +505,10 @@ pub async fn handle_finish_upload(
                         }
 
                         // Place file
-                        let dest = file_path(&state, &file)?;
-                        if let Some(p) = dest.parent() {
-                            create_dir_all(&p)
-                                .await
-                                .context_with(
-                                    "Failed to create parent directories for uploaded file",
-                                    ea!(path = dest.display()),
-                                )?;
-                        }
-                        rename(&source, &dest).await.context("Failed to place uploaded file")?;
+                        state.files.place(&file, &source).await?;
+                        delete_tree(&staged_file_chunks_path(&state, &file)?)
+                            .await
+                            .log(&state.log, loga::WARN, "Failed to clean up upload chunk records");
 
                         // Trigger generation
                         state.background.send(BackgroundJob::GenerateOne(file.clone())).ignore();
@@ -405,7 +563,6 @@ pub async fn handle_file_get(
         return Ok(response_404());
     };
     let mimetype;
-    let local_path;
     superif!({
         if gentype.is_empty() {
             break 'nogen;
@@ -419,21 +576,27 @@ pub async fn handle_file_get(
         }).await.err_internal()? else {
             break 'nogen;
         };
-        let gen_path = genfile_path(&state, &file, &gentype, &subpath).err_internal()?;
-        if !gen_path.exists() {
+        if !state.genfiles.exists(&file, &gentype, &subpath).await.err_internal()? {
             break 'nogen;
         }
         mimetype = gen_mimetype;
-        local_path = gen_path;
     } 'nogen {
         if !subpath.is_empty() {
             return Ok(response_404());
         }
         mimetype = meta.mimetype.unwrap_or_else(|| format!("application/octet-stream"));
-        local_path = file_path(&state, &file).err_internal()?;
+        return Ok(
+            state
+                .files
+                .respond(&head.headers, &mimetype, &file, &state.http_resp_headers)
+                .await
+                .err_internal()?,
+        );
     });
     return Ok(
-        htserve::responses::response_file(&head.headers, &mimetype, &local_path, true, &state.http_resp_headers)
+        state
+            .genfiles
+            .respond(&head.headers, &mimetype, &file, &gentype, &subpath, &state.http_resp_headers)
             .await
             .err_internal()?,
     );
@@ -442,7 +605,7 @@ pub async fn handle_file_get(
 pub async fn handle_file_post(
     state: Arc<State>,
     head: http::request::Parts,
-    file: FileHash,
+    hash: FileHash,
     body: Incoming,
 ) -> Result<Response<BoxBody<Bytes, std::io::Error>>, loga::Error> {
     let offset = async {
@@ -458,7 +621,7 @@ pub async fn handle_file_post(
         ) as
             Result<u64, loga::Error>
     }.await.stack_context_with(&state.log, "Error reading header", ea!(header = HEADER_OFFSET))?;
-    let file_path = staged_file_path(&state, &file)?;
+    let file_path = staged_file_path(&state, &hash)?;
     let mut file =
         File::options()
             .write(true)
@@ -476,5 +639,49 @@ pub async fn handle_file_post(
     let chunk = body.collect().await.stack_context(&state.log, "Error reading chunk")?.to_bytes();
     file.write_all(&chunk).await.stack_context(&state.log, "Error writing chunk")?;
     file.flush().await?;
+
+    // Record chunk for resuming
+    let chunks_path = staged_file_chunks_path(&state, &hash)?;
+    create_dirs(&chunks_path).await?;
+    write(chunks_path.join(format!("{}_{}", offset, chunk.len())), upload_chunk_checksum(&chunk))
+        .await
+        .stack_context(&state.log, "Error recording received chunk")?;
     return Ok(response_200_json(()));
 }
+
+pub async fn handle_upload_status(state: Arc<State>, file: FileHash) -> Result<RespUploadStatus, loga::Error> {
+    let mut chunks = vec![];
+    let chunks_path = staged_file_chunks_path(&state, &file)?;
+    if state.files.exists(&file).await? || !chunks_path.exists() {
+        return Ok(RespUploadStatus { chunks: chunks });
+    }
+    let mut entries =
+        read_dir(&chunks_path)
+            .await
+            .context_with("Error listing upload chunk records", ea!(path = chunks_path.dbg_str()))?;
+    while let Some(entry) =
+        entries
+            .next_entry()
+            .await
+            .context_with("Error reading upload chunk records", ea!(path = chunks_path.dbg_str()))? {
+        let name = entry.file_name();
+        let Some((offset, size)) =
+            name
+                .to_str()
+                .and_then(|n| n.split_once("_"))
+                .and_then(|(o, s)| Some((o.parse::<u64>().ok()?, s.parse::<u64>().ok()?))) else {
+                continue;
+            };
+        let checksum =
+            read_to_string(entry.path())
+                .await
+                .context_with("Error reading upload chunk record", ea!(path = entry.path().dbg_str()))?;
+        chunks.push(RespUploadChunk {
+            offset: offset,
+            size: size,
+            checksum: checksum.trim().to_string(),
+        });
+    }
+    chunks.sort_by_key(|c| c.offset);
+    return Ok(RespUploadStatus { chunks: chunks });
+}
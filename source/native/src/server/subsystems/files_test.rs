@@ -0,0 +1,52 @@
+#![cfg(test)]
+
+use {
+    chrono::Utc,
+    crate::{
+        interface::triple::DbNode,
+        server::{
+            db,
+            dbwrite,
+            subsystems::files::view_returned_nodes,
+        },
+    },
+    shared::{
+        interface::triple::Node,
+        query_parser::compile_query,
+    },
+    std::collections::{
+        BTreeMap,
+        HashMap,
+    },
+};
+
+fn s(value: impl AsRef<str>) -> Node {
+    return Node::Value(serde_json::Value::String(value.as_ref().to_string()));
+}
+
+#[test]
+fn test_view_edit_subject_scope() {
+    let mut db = db::migrate(rusqlite::Connection::open_in_memory().unwrap(), None).unwrap();
+    for (subject, object) in [("album1", "track1"), ("album1", "track2"), ("album2", "track3")] {
+        dbwrite::write_triple(&mut db, &DbNode(s(subject)), "track", &DbNode(s(object)), Utc::now(), true).unwrap();
+    }
+    let queries =
+        [("tracks".to_string(), compile_query("$album -> \"track\" { => track }").unwrap())]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+    let mut tx = db.0.transaction().unwrap();
+    let mut db = db::Db(&mut tx);
+    let album1 = [("album".to_string(), s("album1"))].into_iter().collect::<HashMap<_, _>>();
+    let returned = view_returned_nodes(&mut db, &queries, &album1, None).unwrap();
+
+    // Nodes the view shows with the parameters can be edited
+    assert!(returned.contains(&s("track1")));
+    assert!(returned.contains(&s("track2")));
+
+    // Nodes outside the view can't, even if another parameter would show them
+    assert!(!returned.contains(&s("track3")));
+    assert!(!returned.contains(&s("album2")));
+
+    // Queries that can't be run with the parameters don't match anything
+    assert!(view_returned_nodes(&mut db, &queries, &HashMap::new(), None).unwrap().is_empty());
+}
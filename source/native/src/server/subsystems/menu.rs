@@ -124,10 +124,17 @@ pub async fn handle_get_filtered_client_config(
             query_parameter_keys: view.query_parameters.clone(),
             shuffle: view.shuffle,
             track_end_mode: view.item.track_end_mode,
+            sorts: view.item.sorts.iter().map(|x| x.name.clone()).collect(),
+            facets: view.item.facets.clone(),
+            editable: !matches!(identity, Identity::Public) && match &iam_grants {
+                IamGrants::Admin => true,
+                IamGrants::Limited(g) => g.views.contains(k) && g.view_edits.contains(k),
+            },
         });
     }
     return Ok(ClientConfig {
         can_login: state.oidc_state.is_some(),
+        admin: matches!(iam_grants, IamGrants::Admin),
         menu: menu,
         forms: forms,
         views: views,
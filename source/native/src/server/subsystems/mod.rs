@@ -1,5 +1,6 @@
 pub mod background;
 pub mod files;
+pub mod files_test;
 pub mod link;
 pub mod menu;
 pub mod oidc;
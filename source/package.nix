@@ -82,8 +82,18 @@ let
         pkgs.ffmpeg-headless
         pkgs.pandoc
         pkgs._7zz
+        pkgs.poppler_utils
       ]
-      ++ (if cli-import then [ pkgs.mkvtoolnix-cli ] else [ ]);
+      ++ (
+        if cli-import then [
+          pkgs.mkvtoolnix-cli
+          pkgs.gnutar
+          pkgs.gzip
+          pkgs.bzip2
+          pkgs.xz
+          pkgs.zstd
+        ] else [ ]
+      );
     };
   };
 in
@@ -33,9 +33,13 @@ use {
     shared::interface::wire::{
         ReqCommit,
         ReqUploadFinish,
+        ReqUploadStatus,
+        UPLOAD_CHUNK_SIZE,
+        upload_chunk_checksum,
     },
     std::{
         cell::RefCell,
+        collections::HashMap,
         rc::{
             Rc,
             Weak,
@@ -136,20 +140,27 @@ async fn run_onlining(state: Weak<OnliningState>, eg: EventGraph, log: Rc<dyn Lo
             let need_files = req_post_json(&log, &base_url, req).await?;
             for file in need_files.incomplete {
                 let data = task_dir.get_file(vec![file.to_string()]).await?.read_binary().await?;
-                const CHUNK_SIZE: u64 = 1024 * 1024 * 8;
+
+                // Skip chunks received before an interruption
+                let received =
+                    req_post_json(&log, &base_url, ReqUploadStatus(file.clone()))
+                        .await?
+                        .chunks
+                        .into_iter()
+                        .map(|c| ((c.offset, c.size), c.checksum))
+                        .collect::<HashMap<_, _>>();
                 let file_size = data.len() as u64;
-                let chunks = file_size.div_ceil(CHUNK_SIZE);
+                let chunks = file_size.div_ceil(UPLOAD_CHUNK_SIZE);
                 for i in 0 .. chunks {
-                    let chunk_start = i * CHUNK_SIZE;
-                    let chunk_end = (chunk_start + CHUNK_SIZE).min(file_size);
+                    let chunk_start = i * UPLOAD_CHUNK_SIZE;
+                    let chunk_end = (chunk_start + UPLOAD_CHUNK_SIZE).min(file_size);
                     let chunk_size = chunk_end - chunk_start;
-                    file_post_json(
-                        &log,
-                        &base_url,
-                        &file,
-                        chunk_start,
-                        &data[chunk_start as usize .. (chunk_start + chunk_size) as usize],
-                    ).await?;
+                    let chunk = &data[chunk_start as usize .. (chunk_start + chunk_size) as usize];
+                    if received.get(&(chunk_start, chunk_size)).map(|c| c.as_str()) ==
+                        Some(upload_chunk_checksum(chunk).as_str()) {
+                        continue;
+                    }
+                    file_post_json(&log, &base_url, &file, chunk_start, chunk).await?;
                 }
                 loop {
                     let resp = req_post_json(&log, &base_url, ReqUploadFinish(file.clone())).await?;
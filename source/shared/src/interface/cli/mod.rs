@@ -4,7 +4,10 @@ use {
         Deserialize,
         Serialize,
     },
-    std::path::PathBuf,
+    std::{
+        collections::BTreeMap,
+        path::PathBuf,
+    },
     super::triple::FileHash,
 };
 
@@ -32,3 +35,67 @@ pub struct CliCommit {
     #[serde(default)]
     pub add: Vec<CliTriple>,
 }
+
+/// Import settings for a directory and its subdirectories, read from
+/// `sunwet_import.json` when preparing a media import commit.
+#[derive(Serialize, Deserialize, JsonSchema, Default, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct CliImportDir {
+    /// Patterns to get missing metadata from file paths, like
+    /// `{artist}/{album}/{index} - {name}`. These are tried before patterns
+    /// specified on the command line. See the readme for details.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Use this album name instead of the one in the files' tags.
+    #[serde(default)]
+    pub album: Option<String>,
+    /// Use these album artists instead of the ones in the files' tags.
+    #[serde(default)]
+    pub album_artist: Option<Vec<String>>,
+    /// Use these track artists instead of the ones in the files' tags.
+    #[serde(default)]
+    pub artist: Option<Vec<String>>,
+    /// Use this disc number instead of the one in the files' tags.
+    #[serde(default)]
+    pub disc: Option<f64>,
+    /// Use this language instead of the one in the files' tags.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Use this release date (`YYYY`, `YYYY-MM`, or `YYYY-MM-DD`) instead of the
+    /// one in the files' tags.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Use these genres instead of the ones in the files' tags.
+    #[serde(default)]
+    pub genre: Option<Vec<String>>,
+}
+
+/// How sunwet nodes and predicates are converted to and from RDF.
+#[derive(Serialize, Deserialize, JsonSchema, Default, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct CliRdfMapping {
+    /// IRI prefix for file nodes, followed by the file hash. Defaults to
+    /// `urn:sunwet:file:`.
+    #[serde(default)]
+    pub file_prefix: Option<String>,
+    /// IRI prefix for entity nodes (values used as a subject somewhere), followed
+    /// by the url-encoded value. Defaults to `urn:sunwet:node:`.
+    #[serde(default)]
+    pub node_prefix: Option<String>,
+    /// IRI prefix for predicates not in `predicates`, followed by the url-encoded
+    /// predicate. Defaults to `urn:sunwet:predicate:`.
+    #[serde(default)]
+    pub predicate_prefix: Option<String>,
+    /// Sunwet predicate to IRI. If not specified, standard ontology predicates are
+    /// mapped to RDF and schema.org where there's a close equivalent.
+    #[serde(default)]
+    pub predicates: Option<BTreeMap<String, String>>,
+    /// String values to convert to IRIs when used as objects, like the values of
+    /// `sunwet/1/is`. If not specified, standard ontology types are mapped to
+    /// schema.org classes.
+    #[serde(default)]
+    pub values: Option<BTreeMap<String, String>>,
+    /// Prefixes to use when writing Turtle, prefix name to IRI.
+    #[serde(default)]
+    pub prefixes: Option<BTreeMap<String, String>>,
+}
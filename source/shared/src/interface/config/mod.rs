@@ -89,6 +89,9 @@ pub struct ClientMenuItem {
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct ClientConfig {
     pub can_login: bool,
+    /// The requester has full access. Actions that can change any node (ex: editing
+    /// nodes selected in a view) are only shown to admins.
+    pub admin: bool,
     pub menu: Vec<ClientMenuItem>,
     /// View ids to view definitions
     pub views: HashMap<ViewId, ClientView>,
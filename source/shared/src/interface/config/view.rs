@@ -176,6 +176,35 @@ pub struct Link {
     pub dest: LinkDest,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, TS, Hash, Default)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum WidgetEditInput {
+    #[default]
+    Text,
+    Number,
+    /// A date like `2024-05-30`.
+    Date,
+}
+
+/// Allow changing the value in the view. The relation `subject predicate old value`
+/// is replaced with `subject predicate new value` (if there was no old value, the
+/// relation is just added).
+///
+/// Edits are accepted from logged in users with edit access to the view (admins,
+/// or `view_edits` in the user's grants), for predicates marked editable somewhere
+/// in the view.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetEdit {
+    /// The field with the node that has the relation being edited.
+    pub subject_field: String,
+    /// The predicate of the relation being edited.
+    pub predicate: String,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub input: WidgetEditInput,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct WidgetText {
@@ -205,6 +234,38 @@ pub struct WidgetText {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub link: Option<Link>,
+    /// Make the text editable. `data` must be a field with the current value.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub edit: Option<WidgetEdit>,
+}
+
+/// Render a string as CommonMark. Raw html is shown as text.
+///
+/// Links and images can refer to sunwet data:
+///
+/// * `node:ID` links to the node view of the string node `ID`
+///
+/// * `sha256:HASH` links to the file, or as an image (`![alt](sha256:HASH)`) shows
+///   the file inline (audio and video are shown with controls if the file's mime
+///   type is known from the query)
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetMarkdown {
+    pub data: FieldOrLiteralString,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub font_size: Option<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub color: Option<String>,
+    /// Max width of the rendered text. Longer lines are wrapped.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub con_size_max: Option<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub trans_align: TransAlign,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
@@ -447,11 +508,87 @@ pub struct DataRowsLayoutTable {
     pub elements: Vec<Widget>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, TS, Hash, Default)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum CalendarPeriod {
+    #[default]
+    Month,
+    Week,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DataRowsLayoutCalendar {
+    /// The field with each row's date or datetime (ISO 8601, like `2024-05-30` or
+    /// `2024-05-30T10:00:00Z`). Datetimes are placed on the date as written,
+    /// regardless of time zone. Rows without a date are not shown.
+    pub date_field: String,
+    /// Show a grid per month, or a row of days per week. Only periods with rows are
+    /// shown.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub period: CalendarPeriod,
+    /// The widget for each row, placed in the row's day.
+    pub widget: Box<Widget>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub con_size_max: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, TS, Hash, Default)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TimelineGroup {
+    #[default]
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DataRowsLayoutTimeline {
+    /// The field with each row's date or datetime, as in `calendar`. Rows without a
+    /// date are not shown.
+    pub date_field: String,
+    /// Rows are shown under a heading for each day (week, etc.) that has rows.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub group: TimelineGroup,
+    /// Show the latest rows first.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub descending: bool,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub gap: Option<String>,
+    /// The converse direction is the direction of time. If unspecified, keep the
+    /// parent widget's orientation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub orientation: Option<Orientation>,
+    pub widget: Box<Widget>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub con_scroll: bool,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub con_size_max: Option<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub trans_size_max: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub enum DataRowsLayout {
     Unaligned(DataRowsLayoutUnaligned),
     Table(DataRowsLayoutTable),
+    /// Place rows in a month or week grid by date.
+    Calendar(DataRowsLayoutCalendar),
+    /// List rows in date order, grouped by day, week, month, or year.
+    Timeline(DataRowsLayoutTimeline),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
@@ -466,6 +603,43 @@ pub struct WidgetDataRows {
     pub trans_align: TransAlign,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DataRowsBulkActionForm {
+    pub form_id: FormId,
+    /// The button text, also used as the form page title.
+    pub name: String,
+    /// The form parameter that receives the selected nodes. Outputs using this
+    /// parameter are created once for each selected node.
+    pub parameter: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum DataRowsBulkAction {
+    /// Open the selected nodes together in the node editor. Only shown to admins.
+    Edit,
+    /// Add the selected nodes to the end of the current list. Only shown to admins.
+    AddToList,
+    /// Open a form with the selected nodes as a parameter.
+    Form(DataRowsBulkActionForm),
+    /// Save the view for offline use, with only the selected rows. The view `data`
+    /// must be a query.
+    Offline,
+    /// Delete the selected nodes' relations that have a predicate editable in the
+    /// view. Only shown to users with the view edit grant.
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DataRowsSelection {
+    /// The field with the node each row represents.
+    pub node_field: String,
+    /// Actions offered for the selected rows.
+    pub actions: Vec<DataRowsBulkAction>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct WidgetRootDataRows {
@@ -490,6 +664,10 @@ pub struct WidgetRootDataRows {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub element_expansion: Option<Widget>,
+    /// Allow selecting rows and applying actions to all selected rows at once.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub selection: Option<DataRowsSelection>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
@@ -534,6 +712,112 @@ pub struct WidgetNode {
     pub trans_align: TransAlign,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ChartType {
+    Bar,
+    Line,
+    Pie,
+    /// Count rows by buckets of the numeric `x_field`. `y_field` is ignored.
+    Histogram,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetChart {
+    /// Where to get the rows for the chart.
+    pub data: QueryOrField,
+    pub chart_type: ChartType,
+    /// The field with each row's category (bar, line, pie) or number (histogram).
+    /// Categories are shown in the order they first appear in the rows.
+    pub x_field: String,
+    /// The field with each row's number. Numbers for rows with the same category are
+    /// summed. If not specified, rows are counted.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub y_field: Option<String>,
+    /// Number of buckets for histograms. Defaults to 10, at most 1000.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub buckets: Option<usize>,
+    /// Defaults to 100%.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub width: Option<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub height: Option<String>,
+    /// The color of bars and lines. Pie charts use a fixed palette.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub color: Option<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub trans_align: TransAlign,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetConditionEquals {
+    pub a: FieldOrLiteral,
+    pub b: FieldOrLiteral,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetConditionMime {
+    /// A file node.
+    pub data: FieldOrLiteral,
+    /// Ex: `image/` or `video/webm`.
+    pub prefix: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum WidgetCondition {
+    /// The value is present and isn't null, an empty string, or an empty list.
+    Exists(FieldOrLiteral),
+    /// Both values are present and equal.
+    Equals(WidgetConditionEquals),
+    /// The value is a file with a known mime type starting with the prefix.
+    Mime(WidgetConditionMime),
+    Not(Box<WidgetCondition>),
+    /// True if all of the conditions are true (or there are no conditions).
+    All(Vec<WidgetCondition>),
+    /// True if any of the conditions are true.
+    Any(Vec<WidgetCondition>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetIf {
+    pub condition: WidgetCondition,
+    /// Shown if the condition is true.
+    pub then: Box<Widget>,
+    /// Shown if the condition is false. If not specified, nothing is shown.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub otherwise: Option<Box<Widget>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetSwitchCase {
+    pub condition: WidgetCondition,
+    pub widget: Widget,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct WidgetSwitch {
+    /// The widget of the first case whose condition is true is shown.
+    pub cases: Vec<WidgetSwitchCase>,
+    /// Shown if no case matches. If not specified, nothing is shown.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub default: Option<Box<Widget>>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct WidgetTable {
@@ -575,6 +859,7 @@ pub enum Widget {
     DataRows(WidgetDataRows),
     Table(WidgetTable),
     Text(WidgetText),
+    Markdown(WidgetMarkdown),
     Date(WidgetDate),
     Time(WidgetTime),
     Datetime(WidgetDatetime),
@@ -584,6 +869,9 @@ pub enum Widget {
     PlayButton(WidgetPlayButton),
     Space,
     Node(WidgetNode),
+    If(WidgetIf),
+    Switch(WidgetSwitch),
+    Chart(WidgetChart),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
@@ -605,6 +893,16 @@ pub enum TrackEndMode {
     Loop,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS, Hash)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ViewFacet {
+    /// The label for the facet's control.
+    pub name: String,
+    /// A field of the root query's records. Choosing a value narrows the rows to
+    /// those where the field has that value (or, for arrays, contains it).
+    pub field: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct ClientView {
@@ -615,4 +913,14 @@ pub struct ClientView {
     #[serde(default)]
     #[ts(optional, as = "Option<_>")]
     pub track_end_mode: TrackEndMode,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub sorts: Vec<String>,
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub facets: Vec<ViewFacet>,
+    /// The requester can make the edits configured in the view.
+    #[serde(default)]
+    #[ts(optional, as = "Option<_>")]
+    pub editable: bool,
 }
@@ -172,6 +172,136 @@ pub const PREDICATE_LANG: &str = "sunwet/1/language";
 /// Example triple: `(track_entity, "sunwet/1/orig_lang", "ja")`
 pub const PREDICATE_ORIGINAL_LANGUAGE: &str = "sunwet/1/orig_lang";
 
+/// * Subject: an album or a media entity
+///
+/// * Object: a string
+///
+/// * Plurality: many
+///
+/// A genre, as written in the source tags (ex: `"Jazz"`). Albums have all the
+/// genres of their tracks.
+pub const PREDICATE_GENRE: &str = "sunwet/1/genre";
+
+/// * Subject: an album
+///
+/// * Object: a string, `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`
+///
+/// * Plurality: zero or one
+///
+/// When the album was released, with as much precision as is known.
+pub const PREDICATE_RELEASE_DATE: &str = "sunwet/1/release_date";
+
+/// * Subject: an album
+///
+/// * Object: a number
+///
+/// * Plurality: zero or one
+///
+/// The year part of `sunwet/1/release_date`, for filtering and sorting.
+pub const PREDICATE_YEAR: &str = "sunwet/1/year";
+
+/// * Subject: a media entity
+///
+/// * Object: an artist entity
+///
+/// * Plurality: many
+///
+/// Who wrote the music, if different from the performer (`sunwet/1/artist`).
+pub const PREDICATE_COMPOSER: &str = "sunwet/1/composer";
+
+/// * Subject: an album
+///
+/// * Object: a string
+///
+/// * Plurality: zero or one
+///
+/// The record label or publisher that released the album.
+pub const PREDICATE_LABEL: &str = "sunwet/1/label";
+
+/// * Subject: an album
+///
+/// * Object: a number
+///
+/// * Plurality: zero or one
+///
+/// The number of disks (values of `sunwet/1/superindex`) in the album.
+pub const PREDICATE_SUPERINDEX_TOTAL: &str = "sunwet/1/superindex_total";
+
+/// * Subject: a media entity
+///
+/// * Object: a string
+///
+/// * Plurality: zero or one
+///
+/// The International Standard Recording Code of the recording.
+pub const PREDICATE_ISRC: &str = "sunwet/1/isrc";
+
+/// * Subject: an album, media entity, or artist
+///
+/// * Object: a string (MusicBrainz UUID)
+///
+/// * Plurality: zero or one
+///
+/// The MusicBrainz id of the release (for albums), recording (for media
+/// entities), or artist. Useful for matching against the MusicBrainz database.
+pub const PREDICATE_MUSICBRAINZ_ID: &str = "sunwet/1/musicbrainz_id";
+
+/// * Subject: an image media entity
+///
+/// * Object: a string, `YYYY-MM-DDTHH:MM:SS` with a utc offset (`+09:00`) if
+///   known, otherwise in the (unknown) local time of the camera
+///
+/// * Plurality: zero or one
+///
+/// When the photo was taken.
+pub const PREDICATE_CAPTURE_TIMESTAMP: &str = "sunwet/1/capture_timestamp";
+
+/// * Subject: an image media entity
+///
+/// * Object: a string
+///
+/// * Plurality: zero or one
+///
+/// The make and model of the camera that took the photo.
+pub const PREDICATE_CAMERA: &str = "sunwet/1/camera";
+
+/// * Subject: an image media entity
+///
+/// * Object: a number, 1-8
+///
+/// * Plurality: zero or one
+///
+/// The EXIF orientation of the image. 1 is upright, see the EXIF specification
+/// for the meaning of other values.
+pub const PREDICATE_ORIENTATION: &str = "sunwet/1/orientation";
+
+/// * Subject: any entity
+///
+/// * Object: a number, decimal degrees (negative is south)
+///
+/// * Plurality: zero or one
+///
+/// Where the thing was, ex: where a photo was taken.
+pub const PREDICATE_LATITUDE: &str = "sunwet/1/latitude";
+
+/// * Subject: any entity
+///
+/// * Object: a number, decimal degrees (negative is west)
+///
+/// * Plurality: zero or one
+///
+/// See `sunwet/1/latitude`.
+pub const PREDICATE_LONGITUDE: &str = "sunwet/1/longitude";
+
+/// * Subject: any entity
+///
+/// * Object: a number, meters above sea level
+///
+/// * Plurality: zero or one
+///
+/// See `sunwet/1/latitude`.
+pub const PREDICATE_ALTITUDE: &str = "sunwet/1/altitude";
+
 /// * Subject: any entity
 ///
 /// * Object: JSON `null`
@@ -182,6 +312,39 @@ pub const PREDICATE_ORIGINAL_LANGUAGE: &str = "sunwet/1/orig_lang";
 /// exclude entities with this predicate.
 pub const PREDICATE_DELETE: &str = "sunwet/1/delete";
 
+/// * Subject: a media file the requesting user has played
+///
+/// * Object: a string, RFC 3339 UTC timestamp of the last playback update
+///
+/// * Plurality: zero or one
+///
+/// This is virtual - it isn't stored in the graph but generated from the
+/// requesting user's play history, and can only be followed forward (`->`). See
+/// the `played` query root.
+pub const PREDICATE_PLAYED_AT: &str = "sunwet/1/played_at";
+
+/// * Subject: a media file the requesting user has played
+///
+/// * Object: a number, seconds for audio/video or page for books/comics
+///
+/// * Plurality: zero or one
+///
+/// Virtual, see `sunwet/1/played_at`.
+pub const PREDICATE_PLAY_POSITION: &str = "sunwet/1/play_position";
+
+/// * Subject: a media file the requesting user has played
+///
+/// * Object: a bool, whether playback reached the end since it was last started
+///
+/// * Plurality: zero or one
+///
+/// Virtual, see `sunwet/1/played_at`.
+pub const PREDICATE_PLAY_FINISHED: &str = "sunwet/1/play_finished";
+
+/// Predicates generated from play history at query time. Commits can't write
+/// triples with these.
+pub const VIRTUAL_PREDICATES: &[&str] = &[PREDICATE_PLAYED_AT, PREDICATE_PLAY_POSITION, PREDICATE_PLAY_FINISHED];
+
 /// Indicates an entity that is an official collection of things.
 pub const OBJ_IS_ALBUM: &str = "sunwet/1/album";
 
@@ -166,11 +166,21 @@ pub enum StepSpecific {
     Junction(StepJunction),
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, JsonSchema, TS)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum PlayedFilter {
+    All,
+    /// Started but not played to the end
+    Unfinished,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone, Debug, JsonSchema, TS)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub enum ChainRoot {
     Value(Value),
     Search(StrValue),
+    /// Files the requesting user has played
+    Played(PlayedFilter),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash, Clone, Debug, JsonSchema, TS)]
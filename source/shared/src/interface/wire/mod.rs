@@ -19,12 +19,19 @@ use {
         Serialize,
         de::DeserializeOwned,
     },
+    sha2::{
+        Digest,
+        Sha256,
+    },
     std::collections::{
         BTreeMap,
         HashMap,
     },
     super::{
-        config::ClientConfig,
+        config::{
+            ClientConfig,
+            MenuItemId,
+        },
         query::Query,
     },
 };
@@ -33,6 +40,14 @@ pub mod link;
 
 pub const HEADER_OFFSET: &'static str = "x-file-offset";
 
+/// Files are uploaded in chunks of this size (the last may be smaller)
+pub const UPLOAD_CHUNK_SIZE: u64 = 1024 * 1024 * 8;
+
+/// The checksum of uploaded chunk data reported in upload status
+pub fn upload_chunk_checksum(data: &[u8]) -> String {
+    return hex::encode(Sha256::digest(data));
+}
+
 pub trait C2SReqTrait: Serialize + DeserializeOwned + Into<C2SReq> {
     type Resp: Serialize + DeserializeOwned;
 }
@@ -73,11 +88,39 @@ pub struct ReqCommitForm {
     pub files: Vec<CommitFile>,
 }
 
+/// Changes made by editing a value in a view. Only predicates marked editable in
+/// the view may be changed, and only one value (a removal and/or addition with the
+/// same subject and predicate) may be changed at a time. The subject must be a node
+/// the view's queries return.
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqCommitView {
+    pub view_id: ViewId,
+    /// The parameters of the queries that returned the edited node.
+    pub parameters: HashMap<String, Node>,
+    pub add: Vec<Triple>,
+    pub remove: Vec<Triple>,
+}
+
+/// Delete nodes shown in a view. Only the nodes' relations with predicates marked
+/// editable in the view are removed, and the nodes must be ones the view's queries
+/// return.
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqCommitViewDelete {
+    pub view_id: ViewId,
+    /// The parameters of the queries that returned the nodes.
+    pub parameters: HashMap<String, Node>,
+    pub nodes: Vec<Node>,
+}
+
 #[derive(Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub enum ReqCommit {
     Free(ReqCommitFree),
     Form(ReqCommitForm),
+    View(ReqCommitView),
+    ViewDelete(ReqCommitViewDelete),
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -117,6 +160,38 @@ impl C2SReqTrait for ReqUploadFinish {
     type Resp = RespUploadFinish;
 }
 
+// # Upload status
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqUploadStatus(pub FileHash);
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespUploadChunk {
+    pub offset: u64,
+    pub size: u64,
+    /// See `upload_chunk_checksum`
+    pub checksum: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespUploadStatus {
+    /// Chunks received so far for a partial upload. Empty if the upload hasn't
+    /// started or has already completed.
+    pub chunks: Vec<RespUploadChunk>,
+}
+
+impl Into<C2SReq> for ReqUploadStatus {
+    fn into(self) -> C2SReq {
+        return C2SReq::UploadStatus(self);
+    }
+}
+
+impl C2SReqTrait for ReqUploadStatus {
+    type Resp = RespUploadStatus;
+}
+
 // # Query
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
@@ -184,6 +259,13 @@ pub struct ReqViewQuery {
     pub query: String,
     pub parameters: HashMap<String, Node>,
     pub pagination: Option<Pagination>,
+    /// The name of one of the view's sorts to use instead of the query's sort. Only
+    /// applies to the root query.
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// Facet field values to narrow the rows by. Only applies to the root query.
+    #[serde(default)]
+    pub facets: BTreeMap<String, Node>,
 }
 
 impl Into<C2SReq> for ReqViewQuery {
@@ -196,6 +278,38 @@ impl C2SReqTrait for ReqViewQuery {
     type Resp = RespQuery;
 }
 
+// # View facets
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqViewFacets {
+    pub view_id: ViewId,
+    pub parameters: HashMap<String, Node>,
+    /// The currently chosen facet values. The counts for each facet are narrowed by
+    /// the values chosen for the other facets.
+    pub facets: BTreeMap<String, Node>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespViewFacet {
+    pub field: String,
+    /// Values and the number of rows with each value, most common first.
+    pub values: Vec<(Node, usize)>,
+    /// The view had more rows than are counted, so the counts are lower bounds and
+    /// some values may be missing.
+    pub truncated: bool,
+}
+
+impl Into<C2SReq> for ReqViewFacets {
+    fn into(self) -> C2SReq {
+        return C2SReq::ViewFacets(self);
+    }
+}
+
+impl C2SReqTrait for ReqViewFacets {
+    type Resp = Vec<RespViewFacet>;
+}
+
 // # Get triples from
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
@@ -362,6 +476,53 @@ impl C2SReqTrait for ReqCheckGet {
     type Resp = Option<RespCheck>;
 }
 
+// # Generation status
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqGenerationStatus {
+    pub file: FileHash,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespGeneratedFile {
+    pub gentype: String,
+    pub mimetype: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespGenerationJob {
+    /// Job for the slow generators (ex: video transcoding) rather than the others
+    pub slow: bool,
+    pub priority: i64,
+    pub running: bool,
+    /// Gave up after repeated failures (retried at next server start)
+    pub failed: bool,
+    pub attempts: i64,
+    pub next_attempt: DateTime<Utc>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespGenerationStatus {
+    /// Derived files that have been generated so far
+    pub generated: Vec<RespGeneratedFile>,
+    /// Outstanding generation jobs, empty when generation is complete
+    pub jobs: Vec<RespGenerationJob>,
+}
+
+impl Into<C2SReq> for ReqGenerationStatus {
+    fn into(self) -> C2SReq {
+        return C2SReq::GenerationStatus(self);
+    }
+}
+
+impl C2SReqTrait for ReqGenerationStatus {
+    type Resp = RespGenerationStatus;
+}
+
 // # Autocomplete
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
@@ -426,6 +587,159 @@ impl C2SReqTrait for ReqAutocompleteViewParam {
     type Resp = Vec<String>;
 }
 
+// # User saved
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct UserSavedPreset {
+    pub name: String,
+    pub view_id: ViewId,
+    #[serde(default)]
+    pub params: HashMap<String, Node>,
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub facets: BTreeMap<String, Node>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct UserSavedBookmark {
+    pub name: String,
+    pub node: Node,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum UserSavedItem {
+    /// A view with parameters, sort, and facets filled in
+    Preset(UserSavedPreset),
+    Bookmark(UserSavedBookmark),
+    /// Shown at the top of the menu
+    PinnedMenuItem(MenuItemId),
+}
+
+impl UserSavedItem {
+    pub fn key(&self) -> UserSavedKey {
+        match self {
+            UserSavedItem::Preset(p) => return UserSavedKey::Preset(p.name.clone()),
+            UserSavedItem::Bookmark(b) => return UserSavedKey::Bookmark(b.node.clone()),
+            UserSavedItem::PinnedMenuItem(i) => return UserSavedKey::PinnedMenuItem(i.clone()),
+        }
+    }
+}
+
+/// Identifies a saved item; saving an item with the same key replaces the existing
+/// one.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum UserSavedKey {
+    Preset(String),
+    Bookmark(Node),
+    PinnedMenuItem(MenuItemId),
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqUserSavedList;
+
+impl Into<C2SReq> for ReqUserSavedList {
+    fn into(self) -> C2SReq {
+        return C2SReq::UserSavedList(self);
+    }
+}
+
+impl C2SReqTrait for ReqUserSavedList {
+    type Resp = Vec<UserSavedItem>;
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqUserSavedPut(pub UserSavedItem);
+
+impl Into<C2SReq> for ReqUserSavedPut {
+    fn into(self) -> C2SReq {
+        return C2SReq::UserSavedPut(self);
+    }
+}
+
+impl C2SReqTrait for ReqUserSavedPut {
+    type Resp = ();
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqUserSavedDelete(pub UserSavedKey);
+
+impl Into<C2SReq> for ReqUserSavedDelete {
+    fn into(self) -> C2SReq {
+        return C2SReq::UserSavedDelete(self);
+    }
+}
+
+impl C2SReqTrait for ReqUserSavedDelete {
+    type Resp = ();
+}
+
+// # Play state
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum PlayEvent {
+    /// Playback of the file began (from the start or resumed)
+    Started,
+    /// Periodic/pause position update
+    Position,
+    /// Playback reached the end
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqPlayReport {
+    pub file: FileHash,
+    pub event: PlayEvent,
+    /// Seconds for audio/video, page for books/comics.
+    pub position: f64,
+    #[serde(default)]
+    pub duration: Option<f64>,
+}
+
+impl Into<C2SReq> for ReqPlayReport {
+    fn into(self) -> C2SReq {
+        return C2SReq::PlayReport(self);
+    }
+}
+
+impl C2SReqTrait for ReqPlayReport {
+    type Resp = ();
+}
+
+/// Only files with recorded play state are included in the response.
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct ReqPlayPositions {
+    pub files: Vec<FileHash>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct RespPlayPosition {
+    pub file: FileHash,
+    pub position: f64,
+    pub duration: Option<f64>,
+    pub finished: bool,
+    pub updated: DateTime<Utc>,
+}
+
+impl Into<C2SReq> for ReqPlayPositions {
+    fn into(self) -> C2SReq {
+        return C2SReq::PlayPositions(self);
+    }
+}
+
+impl C2SReqTrait for ReqPlayPositions {
+    type Resp = Vec<RespPlayPosition>;
+}
+
 // # Assemble
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
@@ -434,10 +748,14 @@ pub enum C2SReq {
     Commit(ReqCommit),
     /// Tell the server to verify and commit a file after uploading all chunks
     UploadFinish(ReqUploadFinish),
+    /// Get the chunks received so far for a partial upload, to resume it
+    UploadStatus(ReqUploadStatus),
     /// Read from the graph
     Query(ReqQuery),
     /// Read from the graph via a view (uses view permissions)
     ViewQuery(ReqViewQuery),
+    /// Get the facet value counts for a view's root query (uses view permissions)
+    ViewFacets(ReqViewFacets),
     /// Get all triples where the subject/object is a given node
     GetTriplesAround(ReqGetTriplesAround),
     /// Get metadata associated with nodes (ex: mime type for files)
@@ -457,6 +775,18 @@ pub enum C2SReq {
     AutocompleteFormField(ReqAutocompleteFormField),
     /// Autocomplete for a view parameter, narrowed by query context
     AutocompleteViewParam(ReqAutocompleteViewParam),
+    /// Get the derived file generation status for a file
+    GenerationStatus(ReqGenerationStatus),
+    /// List the logged in user's saved presets, bookmarks, and pinned menu items
+    UserSavedList(ReqUserSavedList),
+    /// Add or replace a saved item for the logged in user
+    UserSavedPut(ReqUserSavedPut),
+    /// Remove a saved item for the logged in user
+    UserSavedDelete(ReqUserSavedDelete),
+    /// Record a playback event for the current user
+    PlayReport(ReqPlayReport),
+    /// Get the current user's saved playback positions for files
+    PlayPositions(ReqPlayPositions),
 }
 
 pub fn alphanumeric_only(s: &str) -> String {
@@ -483,3 +813,4 @@ pub fn gentype_vtt_subpath(lang: &str) -> String {
 pub const GENTYPE_EPUBHTML: &str = "epubhtml";
 pub const GENTYPE_CBZDIR: &str = "cbzdir";
 pub const GEN_FILENAME_COMICMANIFEST: &str = "sunwet.json";
+
@@ -5,6 +5,7 @@ pub mod query_parser;
 pub mod query_parser_test;
 pub mod query_analysis;
 pub mod stringpattern;
+pub mod stringpattern_test;
 
 pub fn steal<T: Default>(x: &mut T) -> T {
     let mut x1 = T::default();
@@ -97,6 +97,7 @@ pub fn analyze_query(q: &query::Query) -> QueryAnalysis {
                     },
                 },
                 query::ChainRoot::Search(r) => recurse_query_str_value(r, state),
+                query::ChainRoot::Played(_) => { },
             }
         }
         let mut plural = true;
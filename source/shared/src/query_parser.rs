@@ -23,6 +23,7 @@ use {
             FilterSuffixSimpleOperator,
             JunctionType,
             MoveDirection,
+            PlayedFilter,
             Query,
             SortDir,
             SortQuery,
@@ -188,6 +189,12 @@ fn compile_chain_head(
             query_parser_actions::ROOT::ROOT_SEARCH(s) => {
                 root = Some(ChainRoot::Search(compile_str_value(s)));
             },
+            query_parser_actions::ROOT::ROOT_PLAYED(f) => {
+                root = Some(ChainRoot::Played(match f {
+                    query_parser_actions::PLAYED_FILTER::kw_played_all => PlayedFilter::All,
+                    query_parser_actions::PLAYED_FILTER::kw_played_unfinished => PlayedFilter::Unfinished,
+                }));
+            },
         },
         None => {
             root = None;
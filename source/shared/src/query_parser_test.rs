@@ -8,6 +8,7 @@ use {
                 ChainRoot,
                 ChainTail,
                 MoveDirection,
+                PlayedFilter,
                 Query,
                 QuerySuffix,
                 Step,
@@ -64,6 +65,27 @@ fn test_rt_move() {
     });
 }
 
+#[test]
+fn test_rt_played() {
+    for (text, filter) in [("all", PlayedFilter::All), ("unfinished", PlayedFilter::Unfinished)] {
+        assert_eq!(compile_query(&format!("played {} {{ => a }}", text)).unwrap(), Query {
+            chain_head: ChainHead {
+                root: Some(ChainRoot::Played(filter)),
+                filter: None,
+                steps: vec![],
+            },
+            suffix: Some(QuerySuffix {
+                chain_tail: ChainTail {
+                    bind: Some("a".to_string()),
+                    subchains: vec![],
+                },
+                sort: None,
+            }),
+        });
+    }
+    assert!(compile_query("played { => a }").is_err());
+}
+
 fn src_query_dir() -> PathBuf {
     return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/server");
 }
@@ -45,6 +45,44 @@ impl Pattern {
             },
         }).collect::<Vec<_>>().join("");
     }
+
+    /// The reverse of `interpolate`: find field values that would produce `text`.
+    /// Fields match as few characters as possible (at least one) and never match
+    /// `/`, so patterns can be used on paths.
+    pub fn extract(&self, text: &str) -> Option<BTreeMap<String, String>> {
+        fn step(parts: &[PatternPart], text: &str, out: &mut BTreeMap<String, String>) -> bool {
+            let Some((first, rest)) = parts.split_first() else {
+                return text.is_empty();
+            };
+            match first {
+                PatternPart::Lit(l) => {
+                    let Some(text) = text.strip_prefix(l.as_str()) else {
+                        return false;
+                    };
+                    return step(rest, text, out);
+                },
+                PatternPart::Field(f) => {
+                    for (i, c) in text.char_indices() {
+                        if c == '/' {
+                            return false;
+                        }
+                        let end = i + c.len_utf8();
+                        if step(rest, &text[end..], out) {
+                            out.insert(f.clone(), text[..end].to_string());
+                            return true;
+                        }
+                    }
+                    return false;
+                },
+            }
+        }
+
+        let mut out = BTreeMap::new();
+        if !step(&self.parts, text, &mut out) {
+            return None;
+        }
+        return Some(out);
+    }
 }
 
 impl<'a> From<&'a str> for Pattern {
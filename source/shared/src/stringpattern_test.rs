@@ -0,0 +1,51 @@
+#![cfg(test)]
+
+use {
+    crate::stringpattern::Pattern,
+    std::collections::BTreeMap,
+};
+
+fn extract(pattern: &str, text: &str) -> Option<BTreeMap<String, String>> {
+    return Pattern::from(pattern).extract(text);
+}
+
+fn fields(pairs: &[(&str, &str)]) -> Option<BTreeMap<String, String>> {
+    return Some(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect());
+}
+
+#[test]
+fn test_extract_literal() {
+    assert_eq!(extract("abc", "abc"), fields(&[]));
+    assert_eq!(extract("abc", "abd"), None);
+    assert_eq!(extract("abc", "abcd"), None);
+}
+
+#[test]
+fn test_extract_fields() {
+    assert_eq!(
+        extract("{artist}/{album}/{index} - {name}", "Artist/Some Album/01 - Track - Name"),
+        fields(&[("artist", "Artist"), ("album", "Some Album"), ("index", "01"), ("name", "Track - Name")]),
+    );
+}
+
+#[test]
+fn test_extract_field_shortest() {
+    assert_eq!(extract("{a}-{b}", "x-y-z"), fields(&[("a", "x"), ("b", "y-z")]));
+}
+
+#[test]
+fn test_extract_escaped() {
+    assert_eq!(extract("\\{{name}\\}", "{x}"), fields(&[("name", "x")]));
+}
+
+#[test]
+fn test_extract_no_match() {
+    // Fields don't cross path segments
+    assert_eq!(extract("{album}/{name}", "a/b/c"), None);
+
+    // Fields match at least one character
+    assert_eq!(extract("{index} - {name}", " - x"), None);
+
+    // Missing literal
+    assert_eq!(extract("{index} - {name}", "01 x"), None);
+}
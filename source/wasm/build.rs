@@ -82,6 +82,12 @@ fn main() {
         rust_type: quote!(usize),
         ts_type: "number".to_string(),
     };
+    let float = Type {
+        mod_: TypeMod::None,
+        rust_type: quote!(f64),
+        ts_type: "number".to_string(),
+    };
+    let arrfloat = type_arr(&float);
     let string_ = Type {
         mod_: TypeMod::None,
         rust_type: quote!(String),
@@ -127,6 +133,26 @@ fn main() {
         rust_type: quote!(TransAlign),
         ts_type: "TransAlign".to_string(),
     };
+    let calendar_period = Type {
+        mod_: TypeMod::None,
+        rust_type: quote!(CalendarPeriod),
+        ts_type: "CalendarPeriod".to_string(),
+    };
+    let timeline_group = Type {
+        mod_: TypeMod::None,
+        rust_type: quote!(TimelineGroup),
+        ts_type: "TimelineGroup".to_string(),
+    };
+    let edit_input = Type {
+        mod_: TypeMod::None,
+        rust_type: quote!(WidgetEditInput),
+        ts_type: "WidgetEditInput".to_string(),
+    };
+    let chart_type = Type {
+        mod_: TypeMod::None,
+        rust_type: quote!(ChartType),
+        ts_type: "ChartType".to_string(),
+    };
 
     //. .
     let mut ts = vec![];
@@ -493,7 +519,7 @@ fn main() {
         // xx Components, styles: page, view
         Func {
             name: "contPageView",
-            args: vec![("transport", &optel_), ("params", &arrel_), ("elements", &el_)],
+            args: vec![("transport", &optel_), ("params", &arrel_), ("bulk", &optel_), ("elements", &el_)],
             returns: vec![("root", &el_)],
         },
         Func {
@@ -506,6 +532,11 @@ fn main() {
             args: vec![],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "leafMenuPageButtonSavePreset",
+            args: vec![],
+            returns: vec![("root", &el_)],
+        },
         Func {
             name: "contBarViewTransport",
             args: vec![],
@@ -528,7 +559,22 @@ fn main() {
         },
         Func {
             name: "contViewElement",
-            args: vec![("body", &el_), ("height", &optstring_), ("expand", &optel_)],
+            args: vec![("body", &el_), ("height", &optstring_), ("expand", &optel_), ("select", &optel_)],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "leafViewSelectToggle",
+            args: vec![],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "contViewBulkBar",
+            args: vec![("actions", &arrel_)],
+            returns: vec![("root", &el_), ("count", &el_), ("buttonClear", &el_)],
+        },
+        Func {
+            name: "leafViewBulkButton",
+            args: vec![("text", &string_)],
             returns: vec![("root", &el_)],
         },
         Func {
@@ -549,6 +595,11 @@ fn main() {
             args: vec![],
             returns: vec![("root", &el_), ("buttonClose", &el_), ("buttonOk", &el_)],
         },
+        Func {
+            name: "contViewModalConfirmBulkDelete",
+            args: vec![("count", &int)],
+            returns: vec![("root", &el_), ("buttonClose", &el_), ("buttonOk", &el_)],
+        },
         Func {
             name: "contViewModalShare",
             args: vec![("qr", &el_), ("link", &string_)],
@@ -571,6 +622,34 @@ fn main() {
             ],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "contViewCalendar",
+            args: vec![
+                ("parentOrientation", &orientation),
+                ("parentOrientationType", &orientation_type),
+                ("transAlign", &transalign),
+                ("period", &calendar_period),
+                ("titles", &arrstring_),
+                ("periods", &arrarrel_),
+                ("conSizeMax", &optstring_),
+            ],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "contViewCalendarDay",
+            args: vec![("date", &string_), ("outside", &bool_), ("children", &arrel_)],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "contViewTimelineGroup",
+            args: vec![
+                ("orientation", &orientation),
+                ("group", &timeline_group),
+                ("date", &string_),
+                ("body", &el_),
+            ],
+            returns: vec![("root", &el_)],
+        },
         Func {
             name: "contViewTable",
             args: vec![
@@ -663,6 +742,39 @@ fn main() {
             ],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "leafViewMarkdown",
+            args: vec![
+                ("parentOrientation", &orientation),
+                ("parentOrientationType", &orientation_type),
+                ("transAlign", &transalign),
+                ("body", &el_),
+                ("fontSize", &optstring_),
+                ("conSizeMax", &optstring_),
+                ("color", &optstring_),
+            ],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "leafViewChart",
+            args: vec![
+                ("parentOrientation", &orientation),
+                ("parentOrientationType", &orientation_type),
+                ("transAlign", &transalign),
+                ("chartType", &chart_type),
+                ("labels", &arrstring_),
+                ("values", &arrfloat),
+                ("width", &optstring_),
+                ("height", &optstring_),
+                ("color", &optstring_),
+            ],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "leafViewEditable",
+            args: vec![("body", &el_), ("inputType", &edit_input), ("value", &string_)],
+            returns: vec![("root", &el_), ("display", &el_), ("input", &el_)],
+        },
         Func {
             name: "leafViewPlayButton",
             args: vec![
@@ -768,11 +880,26 @@ fn main() {
             args: vec![("defaultName", &string_)],
             returns: vec![("root", &el_), ("buttonClose", &el_), ("buttonOk", &el_), ("input", &el_)],
         },
+        Func {
+            name: "contViewModalPresetName",
+            args: vec![("defaultName", &string_)],
+            returns: vec![("root", &el_), ("buttonClose", &el_), ("buttonOk", &el_), ("input", &el_)],
+        },
         Func {
             name: "leafFormComment",
             args: vec![("text", &string_)],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "contFormSaved",
+            args: vec![("title", &string_)],
+            returns: vec![("root", &el_), ("body", &el_)],
+        },
+        Func {
+            name: "leafFormSavedEntry",
+            args: vec![("title", &string_), ("href", &optstring_)],
+            returns: vec![("root", &el_), ("buttonRemove", &el_)],
+        },
         // /////////////////////////////////////////////////////////////////////////////
         // xx Components, styles: page, node view/edit/history
         Func {
@@ -827,6 +954,11 @@ fn main() {
             args: vec![("value", &string_), ("link", &optstring_)],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "leafNodeViewGenerationStatus",
+            args: vec![("value", &string_)],
+            returns: vec![("root", &el_)],
+        },
         // /////////////////////////////////////////////////////////////////////////////
         // xx Components, styles: page, node view/edit
         Func {
@@ -856,12 +988,39 @@ fn main() {
             args: vec![("link", &string_)],
             returns: vec![("root", &el_)],
         },
+        Func {
+            name: "leafNodeViewToolbarGraphLinkButton",
+            args: vec![("link", &string_)],
+            returns: vec![("root", &el_)],
+        },
         Func {
             name: "leafNodeViewToolbarNodeButton",
             args: vec![],
             returns: vec![("root", &el_)],
         },
         // /////////////////////////////////////////////////////////////////////////////
+        // xx Components, styles: page, node graph
+        Func {
+            name: "contPageNodeGraph",
+            args: vec![("barChildren", &arrel_), ("children", &arrel_)],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "leafNodeGraphPredicateToggle",
+            args: vec![("text", &string_)],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "contNodeGraph",
+            args: vec![("edges", &arrfloat), ("edgeLabels", &arrstring_), ("nodes", &arrel_)],
+            returns: vec![("root", &el_)],
+        },
+        Func {
+            name: "leafNodeGraphNode",
+            args: vec![("text", &string_), ("x", &float), ("y", &float), ("pivot", &bool_), ("expanded", &bool_)],
+            returns: vec![("root", &el_)],
+        },
+        // /////////////////////////////////////////////////////////////////////////////
         // xx Components, styles: page, node edit
         Func {
             name: "contPageNodeEdit",
@@ -1053,7 +1212,8 @@ fn main() {
                 ("buttonClose", &el_),
                 ("buttonSetList", &el_),
                 ("buttonAddToList", &el_),
-                ("buttonCopy", &el_)
+                ("buttonCopy", &el_),
+                ("buttonBookmark", &el_)
             ],
         },
         // /////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,293 @@
+use {
+    crate::libnonlink::{
+        ministate::{
+            Ministate,
+            MinistateNodeView,
+            ministate_octothorpe,
+        },
+        offline::{
+            offline_audio_url,
+            offline_file_url,
+            offline_video_url,
+        },
+        state::state,
+        viewutil::{
+            DataStackLevel,
+            maybe_get_meta,
+        },
+    },
+    pulldown_cmark::{
+        Event,
+        Options,
+        Parser,
+        Tag,
+        TagEnd,
+    },
+    rooting::{
+        El,
+        el,
+    },
+    shared::interface::triple::{
+        FileHash,
+        Node,
+    },
+    std::{
+        rc::Rc,
+        str::FromStr,
+    },
+    wasm::{
+        js::{
+            env_preferred_audio_url,
+            env_preferred_video_url,
+        },
+        world::file_url,
+    },
+};
+
+const SCHEME_NODE: &str = "node:";
+
+fn parser(text: &str) -> Parser<'_> {
+    return Parser::new_ext(text, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS);
+}
+
+/// Only allow link schemes that can't run code.
+fn safe_url(url: &str) -> Option<String> {
+    let Some((scheme, _)) = url.split_once(":") else {
+        // Relative
+        return Some(url.to_string());
+    };
+    if scheme.contains(['/', '?', '#']) {
+        // Relative, colon in path
+        return Some(url.to_string());
+    }
+    match scheme.to_ascii_lowercase().as_str() {
+        "http" | "https" | "mailto" | "tel" => return Some(url.to_string()),
+        _ => return None,
+    }
+}
+
+fn link_url(url: &str, title: &str) -> Option<String> {
+    if let Some(id) = url.strip_prefix(SCHEME_NODE) {
+        return Some(ministate_octothorpe(&Ministate::NodeView(MinistateNodeView {
+            title: if title.is_empty() {
+                id.to_string()
+            } else {
+                title.to_string()
+            },
+            node: Node::Value(serde_json::Value::String(id.to_string())),
+        })));
+    }
+    if let Ok(hash) = FileHash::from_str(url) {
+        return Some(file_url(&state().env, &hash));
+    }
+    return safe_url(url);
+}
+
+async fn build_embed(
+    url: &str,
+    alt: String,
+    data_stack: &Vec<Rc<DataStackLevel>>,
+    offline: bool,
+) -> El {
+    let Ok(hash) = FileHash::from_str(url) else {
+        let out = el("img").attr("loading", "lazy").attr("alt", &alt).attr("title", &alt);
+        if let Some(url) = safe_url(url) {
+            out.ref_attr("src", &url);
+        }
+        return out;
+    };
+    let mime =
+        maybe_get_meta(data_stack, &Node::File(hash.clone()))
+            .and_then(|m| m.mime.clone())
+            .unwrap_or_else(|| format!("image/"));
+    match mime.split("/").next().unwrap() {
+        "video" => {
+            return el("video").attr("controls", "").attr("title", &alt).attr("src", &if offline {
+                offline_video_url(&hash).await
+            } else {
+                env_preferred_video_url(&state().env, &hash)
+            });
+        },
+        "audio" => {
+            return el("audio").attr("controls", "").attr("title", &alt).attr("src", &if offline {
+                offline_audio_url(&hash).await
+            } else {
+                env_preferred_audio_url(&state().env, &hash)
+            });
+        },
+        _ => {
+            return el("img").attr("loading", "lazy").attr("alt", &alt).attr("title", &alt).attr("src", &if offline {
+                match offline_file_url(&hash).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        state().log.log(&format!("Error getting offline markdown image url: {}", e));
+                        format!("")
+                    },
+                }
+            } else {
+                file_url(&state().env, &hash)
+            });
+        },
+    }
+}
+
+/// Files embedded as images, for offlining.
+pub fn markdown_embedded_files(text: &str) -> Vec<FileHash> {
+    let mut out = vec![];
+    for event in parser(text) {
+        let Event::Start(Tag::Image { dest_url, .. }) = event else {
+            continue;
+        };
+        if let Ok(hash) = FileHash::from_str(&dest_url) {
+            out.push(hash);
+        }
+    }
+    return out;
+}
+
+/// Build an element for markdown. Html in the source is shown as text.
+pub async fn build_markdown(text: &str, data_stack: &Vec<Rc<DataStackLevel>>, offline: bool) -> El {
+    enum Level {
+        El(El),
+        // Alt text is accumulated until the end tag. The count is of images nested in
+        // the alt text, which are ignored.
+        Image(String, String, usize),
+    }
+
+    let mut stack = vec![Level::El(el("div"))];
+    let push = |stack: &mut Vec<Level>, child: El| {
+        match stack.last_mut() {
+            Some(Level::El(e)) => {
+                e.ref_push(child);
+            },
+            _ => { },
+        }
+    };
+
+    // Pop an element and add it to its parent. The root is never popped.
+    let pop = |stack: &mut Vec<Level>| {
+        if stack.len() <= 1 {
+            return;
+        }
+        if let Some(Level::El(child)) = stack.pop() {
+            push(stack, child);
+        }
+    };
+    let mut in_table_head = false;
+    for event in parser(text) {
+        if let Some(Level::Image(_, alt, depth)) = stack.last_mut() {
+            match &event {
+                Event::Text(t) | Event::Code(t) => {
+                    alt.push_str(t);
+                    continue;
+                },
+                Event::Start(Tag::Image { .. }) => {
+                    *depth += 1;
+                    continue;
+                },
+                Event::End(TagEnd::Image) if *depth > 0 => {
+                    *depth -= 1;
+                    continue;
+                },
+                Event::End(TagEnd::Image) => { },
+                _ => {
+                    continue;
+                },
+            }
+        }
+        match event {
+            Event::Start(tag) => {
+                let new = match tag {
+                    Tag::Paragraph => el("p"),
+                    Tag::Heading { level, .. } => el(&level.to_string()),
+                    Tag::BlockQuote(_) => el("blockquote"),
+                    Tag::CodeBlock(_) => el("pre"),
+                    Tag::List(Some(start)) => el("ol").attr("start", &start.to_string()),
+                    Tag::List(None) => el("ul"),
+                    Tag::Item => el("li"),
+                    Tag::Table(_) => el("table"),
+                    Tag::TableHead => {
+                        // Header cells aren't in a row, unlike body cells
+                        in_table_head = true;
+                        stack.push(Level::El(el("thead")));
+                        el("tr")
+                    },
+                    Tag::TableRow => el("tr"),
+                    Tag::TableCell => el(if in_table_head {
+                        "th"
+                    } else {
+                        "td"
+                    }),
+                    Tag::Emphasis => el("em"),
+                    Tag::Strong => el("strong"),
+                    Tag::Strikethrough => el("s"),
+                    Tag::Link { dest_url, title, .. } => {
+                        let out = el("a");
+                        if let Some(url) = link_url(&dest_url, &title) {
+                            out.ref_attr("href", &url);
+                        }
+                        out
+                    },
+                    Tag::Image { dest_url, .. } => {
+                        stack.push(Level::Image(dest_url.to_string(), String::new(), 0));
+                        continue;
+                    },
+                    _ => el("div"),
+                };
+                stack.push(Level::El(new));
+            },
+            Event::End(TagEnd::Image) => {
+                let Some(Level::Image(..)) = stack.last() else {
+                    continue;
+                };
+                let Some(Level::Image(url, alt, _)) = stack.pop() else {
+                    continue;
+                };
+                let child = build_embed(&url, alt, data_stack, offline).await;
+                push(&mut stack, child);
+            },
+            Event::End(TagEnd::TableHead) => {
+                in_table_head = false;
+                pop(&mut stack);
+                pop(&mut stack);
+            },
+            Event::End(_) => {
+                pop(&mut stack);
+            },
+            Event::Text(t) | Event::Html(t) | Event::InlineHtml(t) | Event::InlineMath(t) | Event::DisplayMath(t) => {
+                push(&mut stack, el("span").text(&t));
+            },
+            Event::Code(t) => {
+                push(&mut stack, el("code").text(&t));
+            },
+            Event::FootnoteReference(t) => {
+                push(&mut stack, el("sup").text(&t));
+            },
+            Event::SoftBreak => {
+                push(&mut stack, el("span").text(" "));
+            },
+            Event::HardBreak => {
+                push(&mut stack, el("br"));
+            },
+            Event::Rule => {
+                push(&mut stack, el("hr"));
+            },
+            Event::TaskListMarker(checked) => {
+                let out = el("input").attr("type", "checkbox").attr("disabled", "");
+                if checked {
+                    out.ref_attr("checked", "");
+                }
+                push(&mut stack, out);
+            },
+        }
+    }
+
+    // Close anything left open
+    while stack.len() > 1 {
+        pop(&mut stack);
+    }
+    let Some(Level::El(root)) = stack.pop() else {
+        return el("div");
+    };
+    return root;
+}
@@ -23,7 +23,10 @@ use {
         LogJsErr,
     },
     std::{
-        collections::HashMap,
+        collections::{
+            BTreeMap,
+            HashMap,
+        },
         rc::Rc,
     },
     super::playlist::PlaylistIndex,
@@ -52,6 +55,10 @@ pub struct MinistateView {
     pub pos: Option<PlaylistRestorePos>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub params: HashMap<String, Node>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: BTreeMap<String, Node>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -73,6 +80,9 @@ pub struct MinistateForm {
     pub title: String,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub params: HashMap<String, Node>,
+    /// Parameters submitted as arrays, like the nodes selected in a view.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub list_params: HashMap<String, Vec<Node>>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -96,6 +106,13 @@ pub struct MinistateNodeView {
     pub node: Node,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct MinistateNodeGraph {
+    pub title: String,
+    pub node: Node,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub enum MinistateHistoryPredicate {
@@ -131,6 +148,7 @@ pub enum Ministate {
     Form(MinistateForm),
     NodeEdit(MinistateNodeEdit),
     NodeView(MinistateNodeView),
+    NodeGraph(MinistateNodeGraph),
     ListEdit(MinistateListEdit),
     History(MinistateHistory),
     Query(MinistateQuery),
@@ -151,6 +169,7 @@ pub fn ministate_title(s: &Ministate) -> String {
         Ministate::Form(s) => return s.title.clone(),
         Ministate::NodeEdit(s) => return s.title.clone(),
         Ministate::NodeView(s) => return s.title.clone(),
+        Ministate::NodeGraph(s) => return s.title.clone(),
         Ministate::ListEdit(s) => return s.title.clone(),
         Ministate::History(_) => return format!("History"),
         Ministate::Query(_) => return format!("Query"),
@@ -5,6 +5,7 @@ pub mod page_view;
 pub mod page_form;
 pub mod page_node_edit;
 pub mod page_node_view;
+pub mod page_node_graph;
 pub mod page_list_edit;
 pub mod page_history;
 pub mod infinite;
@@ -19,3 +20,5 @@ pub mod online;
 pub mod viewutil;
 pub mod api;
 pub mod autocomplete;
+pub mod markdown;
+pub mod user_saved;
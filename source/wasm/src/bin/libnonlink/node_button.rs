@@ -10,12 +10,10 @@ use {
             CurrentList,
             state,
         },
+        user_saved::user_saved_put,
         viewutil::tree_node_to_text,
     },
-    flowcontrol::{
-        exenum,
-        ta_return,
-    },
+    flowcontrol::exenum,
     gloo::storage::{
         LocalStorage,
         SessionStorage,
@@ -25,7 +23,10 @@ use {
         EventGraph,
         link,
     },
-    rooting::El,
+    rooting::{
+        El,
+        spawn_rooted,
+    },
     shared::{
         interface::{
             ont::{
@@ -54,8 +55,11 @@ use {
                 ReqCommitFree,
                 ReqQuery,
                 RespQueryRows,
+                RespWhoAmI,
                 TreeNode,
                 Triple,
+                UserSavedBookmark,
+                UserSavedItem,
             },
         },
         stringpattern::node_to_text,
@@ -201,6 +205,47 @@ pub async fn req_list(node: &Node) -> Result<Vec<ReqListResEntry>, String> {
     return Ok(out);
 }
 
+pub async fn add_to_current_list(nodes: Vec<Node>, comment: &str) -> Result<(), String> {
+    let Some(current_list) = state().current_list.borrow().clone() else {
+        return Ok(());
+    };
+    let rows = req_list(&current_list.node).await?;
+    let mut next_index = if rows.is_empty() || rows.iter().any(|x| x.index.is_some()) {
+        Some(rows.iter().flat_map(|x| x.index).max_by(f64::total_cmp).map(|x| x as i64).unwrap_or(0) + 1)
+    } else {
+        None
+    };
+    let mut add = vec![];
+    for node in nodes {
+        let middle = Node::Value(serde_json::Value::String(uuid::Uuid::new_v4().to_string()));
+        add.push(Triple {
+            subject: current_list.node.clone(),
+            predicate: PREDICATE_TRACK.to_string(),
+            object: middle.clone(),
+        });
+        add.push(Triple {
+            subject: middle.clone(),
+            predicate: PREDICATE_VALUE.to_string(),
+            object: node,
+        });
+        if let Some(index) = &mut next_index {
+            add.push(Triple {
+                subject: middle.clone(),
+                predicate: PREDICATE_INDEX.to_string(),
+                object: Node::Value(serde_json::Value::Number(serde_json::Number::from(*index))),
+            });
+            *index += 1;
+        }
+    }
+    req_post_json(ReqCommit::Free(ReqCommitFree {
+        add: add,
+        comment: comment.to_string(),
+        remove: vec![],
+        files: vec![],
+    })).await?;
+    return Ok(());
+}
+
 pub fn setup_node_button(eg: &EventGraph, out: &El, name: String, node: Node) {
     out.ref_on("click", {
         let eg = eg.clone();
@@ -255,7 +300,18 @@ pub fn setup_node_button(eg: &EventGraph, out: &El, name: String, node: Node) {
                 }
             });
 
-            // Add to list
+            // Add to list. This edits the list directly, which only admins can do.
+            modal_res.button_add_to_list.ref_classes(&[&style_export::class_state_hide().value]);
+            modal_res.button_add_to_list.ref_own(|self0| spawn_rooted({
+                let self0 = self0.weak();
+                async move {
+                    let admin = state().client_config.get().await.borrow().admin;
+                    let Some(self0) = self0.upgrade() else {
+                        return;
+                    };
+                    self0.ref_modify_classes(&[(&style_export::class_state_hide().value, !admin)]);
+                }
+            }));
             modal_res.button_add_to_list.ref_own({
                 |self0| (
                     //. .
@@ -272,51 +328,56 @@ pub fn setup_node_button(eg: &EventGraph, out: &El, name: String, node: Node) {
                 let modal_el = modal_res.root.weak();
                 let modal_errs = modal_res.errors.weak();
                 async move || {
-                    let res = async {
-                        ta_return!((), String);
-                        let rows = req_list(&node).await?;
-                        let Some(current_list) = state().current_list.borrow().clone() else {
-                            return Ok(());
-                        };
-                        let middle = Node::Value(serde_json::Value::String(uuid::Uuid::new_v4().to_string()));
-                        let mut add = vec![
-                            //. .
-                            Triple {
-                                subject: current_list.node.clone(),
-                                predicate: PREDICATE_TRACK.to_string(),
-                                object: middle.clone(),
-                            },
-                            Triple {
-                                subject: middle.clone(),
-                                predicate: PREDICATE_VALUE.to_string(),
-                                object: node.clone(),
-                            },
-                        ];
-                        if rows.is_empty() || rows.iter().any(|x| x.index.is_some()) {
-                            let last_index =
-                                if let Some(last) = rows.iter().flat_map(|x| x.index).max_by(f64::total_cmp) {
-                                    serde_json::Number::from_f64(last).unwrap_or(serde_json::Number::from(0))
-                                } else {
-                                    serde_json::Number::from(0)
-                                };
-                            add.push(Triple {
-                                subject: middle.clone(),
-                                predicate: PREDICATE_INDEX.to_string(),
-                                object: Node::Value(
-                                    serde_json::Value::Number(
-                                        serde_json::Number::from(last_index.as_i64().unwrap_or(0) + 1),
-                                    ),
+                    let res = add_to_current_list(vec![node.clone()], "Add node to list via UI").await;
+                    let Some(modal_errs) = modal_errs.upgrade() else {
+                        return;
+                    };
+                    modal_errs.ref_clear();
+                    match res {
+                        Ok(_) => {
+                            let Some(modal_el) = modal_el.upgrade() else {
+                                return;
+                            };
+                            modal_el.ref_replace(vec![]);
+                        },
+                        Err(e) => {
+                            modal_errs.ref_push(style_export::leaf_err_block(style_export::LeafErrBlockArgs {
+                                data: e,
+                                in_root: false,
+                            }).root);
+                        },
+                    }
+                }
+            });
+
+            // Bookmark
+            modal_res.button_bookmark.ref_own({
+                |self0| (
+                    //. .
+                    link!((_pc = pc), (whoami = state().whoami.clone()), (), (self0 = self0.weak()) {
+                        let self0 = self0.upgrade()?;
+                        self0.ref_modify_classes(
+                            &[
+                                (
+                                    &style_export::class_state_disabled().value,
+                                    !matches!(&*whoami.borrow(), Some(RespWhoAmI::User(_))),
                                 ),
-                            });
-                        }
-                        req_post_json(ReqCommit::Free(ReqCommitFree {
-                            add: add,
-                            comment: "Add node to list via UI".to_string(),
-                            remove: vec![],
-                            files: vec![],
-                        })).await?;
-                        return Ok(());
-                    }.await;
+                            ],
+                        );
+                    }),
+                )
+            });
+            on_thinking(&modal_res.button_bookmark, {
+                let eg = pc.eg();
+                let name = name.clone();
+                let node = node.clone();
+                let modal_el = modal_res.root.weak();
+                let modal_errs = modal_res.errors.weak();
+                async move || {
+                    let res = user_saved_put(eg.clone(), UserSavedItem::Bookmark(UserSavedBookmark {
+                        name: name.clone(),
+                        node: node.clone(),
+                    })).await;
                     let Some(modal_errs) = modal_errs.upgrade() else {
                         return;
                     };
@@ -5,13 +5,17 @@ use {
             req_file,
             req_post_json,
         },
+        markdown::markdown_embedded_files,
         ministate::MinistateView,
         state::state,
         viewutil::{
             DataStackLevel,
+            if_branch,
             maybe_get_field,
             maybe_get_field_or_literal,
+            maybe_get_field_or_literal_string,
             maybe_get_meta,
+            switch_branch,
             unwrap_value_media_hash,
         },
     },
@@ -170,6 +174,58 @@ pub async fn ensure_offline(eg: EventGraph, view: MinistateView) -> Result<(), S
     return Ok(());
 }
 
+/// Like `ensure_offline` but only rows of the root query whose `node_field` is one
+/// of `selected` are kept. The filtered root query result is stored up front so
+/// offlining and the offline view use it instead of requesting the full results.
+pub async fn ensure_offline_selected(
+    eg: EventGraph,
+    view: MinistateView,
+    view_def: &ClientView,
+    node_field: &str,
+    selected: &HashSet<Node>,
+) -> Result<(), String> {
+    let QueryOrField::Query(query_id) = &view_def.root.data else {
+        return Err(format!("Offlining selected rows requires the view data to be a query"));
+    };
+    let params = data_to_query_params(view_def, query_id, &vec![Rc::new(DataStackLevel {
+        data: TreeNode::Record(view.params.iter().map(|(k, v)| (k.clone(), TreeNode::Scalar(v.clone()))).collect()),
+        node_meta: Default::default(),
+    })]);
+    let mut res = req_post_json(ReqViewQuery {
+        view_id: view.id.clone(),
+        query: query_id.clone(),
+        parameters: params.clone(),
+        pagination: None,
+        sort: view.sort.clone(),
+        facets: view.facets.clone(),
+    }).await?;
+    match &mut res.rows {
+        RespQueryRows::Scalar(rows) => {
+            rows.retain(|x| selected.contains(x));
+        },
+        RespQueryRows::Record(rows) => {
+            rows.retain(|x| match x.get(node_field) {
+                Some(TreeNode::Scalar(n)) => selected.contains(n),
+                _ => false,
+            });
+        },
+    }
+    request_persistent(&state().log).await;
+    let key = Utc::now().to_rfc3339();
+    let view_root = &opfs_root().await.ensure_dir(vec![OPFS_OFFLINE_VIEWS_ROOT.to_string(), key.clone()]).await?;
+    view_root
+        .ensure_file(vec![opfs_offline_views_query_filename(query_id, &params)])
+        .await?
+        .write_json(&res)
+        .await?;
+    view_root.ensure_file(vec![OPFS_OFFLINE_VIEWS_VIEW_FILENAME.to_string()]).await?.write_json(&view).await?;
+    eg.event(|pc| {
+        state().offline_list.splice(pc, 0, 0, vec![(key.clone(), view.clone())]);
+    }).unwrap();
+    trigger_offlining(eg);
+    return Ok(());
+}
+
 pub async fn remove_offline(eg: EventGraph, key: &str) -> Result<(), String> {
     let views_dir = opfs_root().await.get_dir(vec![OPFS_OFFLINE_VIEWS_ROOT.to_string()]).await?;
     views_dir.delete(&state().log, key).await;
@@ -533,6 +589,8 @@ pub fn trigger_offlining(eg: EventGraph) {
                                             query: query_id.clone(),
                                             parameters: params.clone(),
                                             pagination: None,
+                                            sort: view.sort.clone(),
+                                            facets: view.facets.clone(),
                                         }).await?;
                                         view_dir.ensure_file(vec![query_filename]).await?.write_json(&res).await?;
                                         res
@@ -594,10 +652,34 @@ pub fn trigger_offlining(eg: EventGraph) {
                                                             );
                                                         }
                                                     },
+                                                    DataRowsLayout::Calendar(w) => {
+                                                        stack.push(
+                                                            (RootOrWidget::Widget(&w.widget), row_params.clone()),
+                                                        );
+                                                    },
+                                                    DataRowsLayout::Timeline(w) => {
+                                                        stack.push(
+                                                            (RootOrWidget::Widget(&w.widget), row_params.clone()),
+                                                        );
+                                                    },
                                                 }
                                             }
                                         },
                                         Widget::Text(_) => { },
+                                        Widget::Markdown(config_at) => {
+                                            let Some(
+                                                TreeNode::Scalar(Node::Value(serde_json::Value::String(text))),
+                                            ) = maybe_get_field_or_literal_string(&config_at.data, &data_at) else {
+                                                continue;
+                                            };
+                                            for hash in markdown_embedded_files(&text) {
+                                                fetch_media_file(
+                                                    &FieldOrLiteral::Literal(Node::File(hash)),
+                                                    &data_at,
+                                                    &mut live_files,
+                                                ).await?;
+                                            }
+                                        },
                                         Widget::Date(_) => { },
                                         Widget::Time(_) => { },
                                         Widget::Datetime(_) => { },
@@ -615,6 +697,19 @@ pub fn trigger_offlining(eg: EventGraph) {
                                         },
                                         Widget::Space => { },
                                         Widget::Node(_) => { },
+                                        Widget::If(w) => {
+                                            if let Some(w) = if_branch(w, &data_at) {
+                                                stack.push((RootOrWidget::Widget(w), data_at.clone()));
+                                            }
+                                        },
+                                        Widget::Switch(w) => {
+                                            if let Some(w) = switch_branch(w, &data_at) {
+                                                stack.push((RootOrWidget::Widget(w), data_at.clone()));
+                                            }
+                                        },
+                                        Widget::Chart(w) => {
+                                            fetch_query_or_field(&w.data, &data_at).await?;
+                                        },
                                     },
                                 }
                             }
@@ -154,6 +154,7 @@ pub fn build_page_form(
     title: String,
     form: ClientForm,
     initial_params: HashMap<String, Node>,
+    list_params: HashMap<String, Vec<Node>>,
 ) -> Result<El, String> {
     let draft_id = format!("form-draft-{}", id);
     let error_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
@@ -638,6 +639,7 @@ pub fn build_page_form(
         let form = form.clone();
         let id = id.clone();
         let initial_params = initial_params.clone();
+        let list_params = list_params.clone();
         move || {
             let eg = eg.clone();
             let error_slot = error_slot.clone();
@@ -646,6 +648,7 @@ pub fn build_page_form(
             let form = form.clone();
             let id = id.clone();
             let initial_params = initial_params.clone();
+            let list_params = list_params.clone();
             async move {
                 {
                     let Some(error_slot) = error_slot.upgrade() else {
@@ -706,6 +709,12 @@ pub fn build_page_form(
                         }
                         params_to_post.insert(k.clone(), TreeNode::Scalar(n));
                     }
+                    for (k, v) in &list_params {
+                        params_to_post.insert(
+                            k.clone(),
+                            TreeNode::Array(v.iter().map(|n| TreeNode::Scalar(n.clone())).collect()),
+                        );
+                    }
                     online::ensure_commit(eg.clone(), ReqCommit::Form(ReqCommitForm {
                         form_id: id.clone(),
                         parameters: params_to_post,
@@ -719,7 +728,14 @@ pub fn build_page_form(
                             set_page(
                                 pc,
                                 &title,
-                                build_page_form(pc.eg(), id, title.clone(), form, initial_params).unwrap(),
+                                build_page_form(
+                                    pc.eg(),
+                                    id,
+                                    title.clone(),
+                                    form,
+                                    initial_params,
+                                    list_params,
+                                ).unwrap(),
                             );
                         }).unwrap();
                         return;
@@ -0,0 +1,419 @@
+use {
+    crate::libnonlink::{
+        api::req_post_json,
+        ministate::{
+            Ministate,
+            MinistateNodeView,
+            ministate_octothorpe,
+        },
+        state::{
+            set_page,
+            state,
+        },
+    },
+    lunk::ProcessingContext,
+    rooting::{
+        ScopeValue,
+        WeakEl,
+        spawn_rooted,
+    },
+    shared::{
+        interface::{
+            triple::Node,
+            wire::{
+                ReqGetTriplesAround,
+                Triple,
+            },
+        },
+        stringpattern::node_to_text,
+    },
+    std::{
+        cell::{
+            Cell,
+            RefCell,
+        },
+        collections::{
+            BTreeSet,
+            HashMap,
+            HashSet,
+        },
+        rc::Rc,
+    },
+    wasm::js::style_export,
+    wasm_bindgen::JsCast,
+    web_sys::HtmlInputElement,
+};
+
+/// Stop adding nodes past this, the layout gets slow and the graph unreadable.
+const MAX_NODES: usize = 200;
+const LAYOUT_ITERATIONS: usize = 300;
+const DEFAULT_HOPS: usize = 1;
+
+struct GraphNode {
+    node: Node,
+    x: f64,
+    y: f64,
+    expanded: bool,
+}
+
+#[derive(Default)]
+struct Graph {
+    /// The pivot is always index 0
+    nodes: Vec<GraphNode>,
+    lookup: HashMap<Node, usize>,
+    edges: Vec<(usize, String, usize)>,
+    edge_lookup: HashSet<(usize, String, usize)>,
+    hidden_predicates: HashSet<String>,
+    selected: Option<usize>,
+    truncated: bool,
+}
+
+impl Graph {
+    fn new(pivot: Node) -> Self {
+        let mut out = Graph::default();
+        out.add_node(pivot, None);
+        return out;
+    }
+
+    fn add_node(&mut self, node: Node, near: Option<usize>) -> Option<usize> {
+        if let Some(i) = self.lookup.get(&node) {
+            return Some(*i);
+        }
+        if self.nodes.len() >= MAX_NODES {
+            self.truncated = true;
+            return None;
+        }
+        let i = self.nodes.len();
+
+        // Start new nodes next to the node they were found from (spread by the golden
+        // angle) so the layout only needs to adjust locally
+        let (x, y) = match near {
+            Some(near) => (self.nodes[near].x, self.nodes[near].y),
+            None => (0., 0.),
+        };
+        let angle = i as f64 * 2.399963;
+        self.nodes.push(GraphNode {
+            node: node.clone(),
+            x: x + angle.cos(),
+            y: y + angle.sin(),
+            expanded: false,
+        });
+        self.lookup.insert(node, i);
+        return Some(i);
+    }
+
+    fn add_triples(&mut self, triples: Vec<Triple>) {
+        for t in triples {
+            let near = self.lookup.get(&t.subject).or(self.lookup.get(&t.object)).copied();
+            let Some(subject) = self.add_node(t.subject, near) else {
+                continue;
+            };
+            let Some(object) = self.add_node(t.object, Some(subject)) else {
+                continue;
+            };
+            let edge = (subject, t.predicate, object);
+            if self.edge_lookup.insert(edge.clone()) {
+                self.edges.push(edge);
+            }
+        }
+    }
+
+    /// Nodes connected to the pivot without going through hidden predicates, and the
+    /// edges between them.
+    fn visible(&self) -> (Vec<bool>, Vec<usize>) {
+        let mut visible_nodes = vec![false; self.nodes.len()];
+        let visible_edges =
+            (0 .. self.edges.len())
+                .filter(|i| !self.hidden_predicates.contains(&self.edges[*i].1))
+                .collect::<Vec<_>>();
+        let mut adjacent = HashMap::<usize, Vec<usize>>::new();
+        for i in &visible_edges {
+            let (a, _, b) = &self.edges[*i];
+            adjacent.entry(*a).or_default().push(*b);
+            adjacent.entry(*b).or_default().push(*a);
+        }
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            if visible_nodes[i] {
+                continue;
+            }
+            visible_nodes[i] = true;
+            stack.extend(adjacent.get(&i).into_iter().flatten().copied());
+        }
+        return (visible_nodes, visible_edges);
+    }
+
+    /// Force directed layout (Fruchterman-Reingold, ideal edge length 1) of the
+    /// visible nodes, continuing from the current positions. The pivot stays at the
+    /// origin.
+    fn layout(&mut self) {
+        let (visible_nodes, visible_edges) = self.visible();
+        let ids = (0 .. self.nodes.len()).filter(|i| visible_nodes[*i]).collect::<Vec<_>>();
+        for iteration in 0 .. LAYOUT_ITERATIONS {
+            let temperature = 0.5 * (1. - iteration as f64 / LAYOUT_ITERATIONS as f64) + 0.01;
+            let mut disp = vec![(0f64, 0f64); self.nodes.len()];
+            for (offset, a) in ids.iter().enumerate() {
+                for b in &ids[offset + 1..] {
+                    let dx = self.nodes[*a].x - self.nodes[*b].x;
+                    let dy = self.nodes[*a].y - self.nodes[*b].y;
+                    let dist2 = (dx * dx + dy * dy).max(0.0001);
+                    disp[*a].0 += dx / dist2;
+                    disp[*a].1 += dy / dist2;
+                    disp[*b].0 -= dx / dist2;
+                    disp[*b].1 -= dy / dist2;
+                }
+            }
+            for i in &visible_edges {
+                let (a, _, b) = &self.edges[*i];
+                if a == b {
+                    continue;
+                }
+                let dx = self.nodes[*a].x - self.nodes[*b].x;
+                let dy = self.nodes[*a].y - self.nodes[*b].y;
+                let dist = (dx * dx + dy * dy).sqrt();
+                disp[*a].0 -= dx * dist;
+                disp[*a].1 -= dy * dist;
+                disp[*b].0 += dx * dist;
+                disp[*b].1 += dy * dist;
+            }
+            for i in &ids {
+                if *i == 0 {
+                    continue;
+                }
+                let node = &mut self.nodes[*i];
+
+                // Weak pull to the center keeps disconnected parts from drifting away
+                let dx = disp[*i].0 - node.x * 0.05;
+                let dy = disp[*i].1 - node.y * 0.05;
+                let len = (dx * dx + dy * dy).sqrt();
+                let scale = if len > temperature {
+                    temperature / len
+                } else {
+                    1.
+                };
+                node.x += dx * scale;
+                node.y += dy * scale;
+            }
+        }
+    }
+}
+
+struct GraphPage {
+    graph: RefCell<Graph>,
+    /// Bumped when the graph is replaced, so node indices from elements of an older
+    /// graph are ignored
+    generation: Cell<usize>,
+    predicates_slot: WeakEl,
+    graph_slot: WeakEl,
+    details_slot: WeakEl,
+    bg: RefCell<Option<ScopeValue>>,
+}
+
+async fn expand(page: &Rc<GraphPage>, start: Vec<usize>, hops: usize) -> Result<(), String> {
+    let generation = page.generation.get();
+    let mut frontier = start;
+    for _ in 0 .. hops {
+        frontier.retain(|i| page.graph.borrow().nodes.get(*i).is_some_and(|n| !n.expanded));
+        if frontier.is_empty() {
+            break;
+        }
+        let nodes = frontier.iter().map(|i| page.graph.borrow().nodes[*i].node.clone()).collect::<Vec<_>>();
+        let triples = req_post_json(ReqGetTriplesAround { nodes: nodes }).await?;
+        if page.generation.get() != generation {
+            return Ok(());
+        }
+        let mut graph = page.graph.borrow_mut();
+        for i in &frontier {
+            graph.nodes[*i].expanded = true;
+        }
+        let first_new = graph.nodes.len();
+        graph.add_triples(triples);
+        frontier = (first_new .. graph.nodes.len()).collect();
+    }
+    page.graph.borrow_mut().layout();
+    return Ok(());
+}
+
+fn spawn_expand(page: &Rc<GraphPage>, start: Vec<usize>, hops: usize) {
+    if let Some(graph_slot) = page.graph_slot.upgrade() {
+        graph_slot.ref_classes(&[&style_export::class_state_thinking().value]);
+    }
+    *page.bg.borrow_mut() = Some(spawn_rooted({
+        let page = page.clone();
+        async move {
+            if let Err(e) = expand(&page, start, hops).await {
+                state().log.log(&format!("Error expanding node graph: {}", e));
+            }
+            if let Some(graph_slot) = page.graph_slot.upgrade() {
+                graph_slot.ref_remove_classes(&[&style_export::class_state_thinking().value]);
+            }
+            render(&page);
+        }
+    }));
+}
+
+fn render(page: &Rc<GraphPage>) {
+    let graph = page.graph.borrow();
+    let (visible_nodes, visible_edges) = graph.visible();
+
+    // Predicate filter
+    if let Some(predicates_slot) = page.predicates_slot.upgrade() {
+        predicates_slot.ref_clear();
+        let predicates = graph.edges.iter().map(|e| e.1.clone()).collect::<BTreeSet<_>>();
+        for predicate in predicates {
+            let toggle =
+                style_export::leaf_node_graph_predicate_toggle(
+                    style_export::LeafNodeGraphPredicateToggleArgs { text: predicate.clone() },
+                ).root;
+            toggle.ref_modify_classes(
+                &[(&style_export::class_state_pressed().value, !graph.hidden_predicates.contains(&predicate))],
+            );
+            toggle.ref_on("click", {
+                let page = page.clone();
+                move |_| {
+                    {
+                        let mut graph = page.graph.borrow_mut();
+                        if !graph.hidden_predicates.remove(&predicate) {
+                            graph.hidden_predicates.insert(predicate.clone());
+                        }
+                        graph.layout();
+                    }
+                    render(&page);
+                }
+            });
+            predicates_slot.ref_push(toggle);
+        }
+    }
+
+    // Graph, scaled to fit
+    if let Some(graph_slot) = page.graph_slot.upgrade() {
+        let mut min_x = f64::MAX;
+        let mut max_x = f64::MIN;
+        let mut min_y = f64::MAX;
+        let mut max_y = f64::MIN;
+        for (i, n) in graph.nodes.iter().enumerate() {
+            if !visible_nodes[i] {
+                continue;
+            }
+            min_x = min_x.min(n.x);
+            max_x = max_x.max(n.x);
+            min_y = min_y.min(n.y);
+            max_y = max_y.max(n.y);
+        }
+        let span_x = (max_x - min_x).max(0.001);
+        let span_y = (max_y - min_y).max(0.001);
+        let fit = |n: &GraphNode| -> (f64, f64) {
+            return (0.05 + 0.9 * (n.x - min_x) / span_x, 0.05 + 0.9 * (n.y - min_y) / span_y);
+        };
+        let mut node_els = vec![];
+        for (i, n) in graph.nodes.iter().enumerate() {
+            if !visible_nodes[i] {
+                continue;
+            }
+            let (x, y) = fit(n);
+            let node_el = style_export::leaf_node_graph_node(style_export::LeafNodeGraphNodeArgs {
+                text: node_to_text(&n.node),
+                x: x,
+                y: y,
+                pivot: i == 0,
+                expanded: n.expanded,
+            }).root;
+            node_el.ref_modify_classes(&[(&style_export::class_state_selected().value, graph.selected == Some(i))]);
+            node_el.ref_on("click", {
+                let page = page.clone();
+                let generation = page.generation.get();
+                move |_| {
+                    if page.generation.get() != generation {
+                        return;
+                    }
+                    page.graph.borrow_mut().selected = Some(i);
+                    spawn_expand(&page, vec![i], 1);
+                }
+            });
+            node_els.push(node_el);
+        }
+        let mut edges = vec![];
+        let mut edge_labels = vec![];
+        for i in &visible_edges {
+            let (a, predicate, b) = &graph.edges[*i];
+            if a == b {
+                continue;
+            }
+            let (x1, y1) = fit(&graph.nodes[*a]);
+            let (x2, y2) = fit(&graph.nodes[*b]);
+            edges.extend([x1, y1, x2, y2]);
+            edge_labels.push(predicate.clone());
+        }
+        graph_slot.ref_clear();
+        graph_slot.ref_push(style_export::cont_node_graph(style_export::ContNodeGraphArgs {
+            edges: edges,
+            edge_labels: edge_labels,
+            nodes: node_els,
+        }).root);
+    }
+
+    // Selected node
+    if let Some(details_slot) = page.details_slot.upgrade() {
+        details_slot.ref_clear();
+        if graph.truncated {
+            details_slot.ref_push(style_export::leaf_err_block(style_export::LeafErrBlockArgs {
+                in_root: false,
+                data: format!("Graph limited to {} nodes, some relations aren't shown", MAX_NODES),
+            }).root);
+        }
+        if let Some(selected) = graph.selected.and_then(|i| graph.nodes.get(i)) {
+            let node = &selected.node;
+            let text = node_to_text(node);
+            details_slot.ref_push(style_export::leaf_node_view_node_text(style_export::LeafNodeViewNodeTextArgs {
+                value: text.clone(),
+                link: Some(ministate_octothorpe(&Ministate::NodeView(MinistateNodeView {
+                    title: text,
+                    node: node.clone(),
+                }))),
+            }).root);
+        }
+    }
+}
+
+pub fn build_page_node_graph(pc: &mut ProcessingContext, title: &str, node: &Node) {
+    let predicates_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
+    let graph_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
+    let details_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
+    let hops = style_export::leaf_input_pair_number(style_export::LeafInputPairNumberArgs {
+        id: format!("node_graph_hops"),
+        title: format!("Hops"),
+        value: DEFAULT_HOPS.to_string(),
+    });
+    let page = Rc::new(GraphPage {
+        graph: RefCell::new(Graph::new(node.clone())),
+        generation: Cell::new(0),
+        predicates_slot: predicates_slot.weak(),
+        graph_slot: graph_slot.weak(),
+        details_slot: details_slot.weak(),
+        bg: RefCell::new(None),
+    });
+    hops.input.ref_on("change", {
+        let page = page.clone();
+        let node = node.clone();
+        let input = hops.input.weak();
+        move |_| {
+            let Some(input) = input.upgrade() else {
+                return;
+            };
+            let hops = parse_hops(&input.raw().dyn_ref::<HtmlInputElement>().map(|x| x.value()).unwrap_or_default());
+            *page.graph.borrow_mut() = Graph::new(node.clone());
+            page.generation.set(page.generation.get() + 1);
+            render(&page);
+            spawn_expand(&page, vec![0], hops);
+        }
+    });
+    spawn_expand(&page, vec![0], DEFAULT_HOPS);
+    set_page(pc, &format!("Graph: {}", title), style_export::cont_page_node_graph(style_export::ContPageNodeGraphArgs {
+        bar_children: vec![hops.root],
+        children: vec![predicates_slot, graph_slot, details_slot],
+    }).root);
+}
+
+fn parse_hops(text: &str) -> usize {
+    return text.trim().parse::<usize>().unwrap_or(DEFAULT_HOPS).clamp(1, 5);
+}
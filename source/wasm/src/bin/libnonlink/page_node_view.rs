@@ -6,6 +6,7 @@ use {
             MinistateHistoryFilter,
             MinistateListEdit,
             MinistateNodeEdit,
+            MinistateNodeGraph,
             ministate_octothorpe,
             record_replace_ministate,
         },
@@ -30,6 +31,7 @@ use {
                 Node,
             },
             wire::{
+                ReqGenerationStatus,
                 ReqGetNodeMeta,
                 ReqGetTriplesAround,
             },
@@ -103,6 +105,47 @@ pub fn build_node_media_el(node: &Node) -> Option<El> {
     }));
 }
 
+/// Show outstanding or failed derived file generation (transcodes, etc) for a
+/// file.
+fn build_node_generation_status_el(file: &FileHash) -> El {
+    let file = file.clone();
+    return el_async(async move {
+        ta_return!(Vec < El >, String);
+        let status = req_post_json(ReqGenerationStatus { file: file }).await?;
+        let mut lines = vec![];
+        for job in status.jobs {
+            let class = if job.slow {
+                "Slow generation"
+            } else {
+                "Generation"
+            };
+            let mut line = if job.running {
+                format!("{}: running", class)
+            } else if job.failed {
+                format!("{}: failed after {} attempts", class, job.attempts)
+            } else if job.attempts > 0 {
+                format!("{}: retrying at {} ({} attempts)", class, job.next_attempt.to_rfc3339(), job.attempts)
+            } else {
+                format!("{}: queued", class)
+            };
+            if let Some(e) = job.last_error {
+                line.push_str(&format!("\n{}", e));
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            return Ok(vec![]);
+        }
+        return Ok(
+            vec![
+                style_export::leaf_node_view_generation_status(
+                    style_export::LeafNodeViewGenerationStatusArgs { value: lines.join("\n") },
+                ).root
+            ],
+        );
+    });
+}
+
 pub fn build_node_el(node: &Node) -> El {
     let text = node_to_text(node);
     return style_export::leaf_node_view_node_text(style_export::LeafNodeViewNodeTextArgs {
@@ -226,6 +269,14 @@ pub fn build_page_node_view(pc: &mut ProcessingContext, title: &str, node: &Node
                                     })),
                                 },
                             ).root,
+                            style_export::leaf_node_view_toolbar_graph_link_button(
+                                style_export::LeafNodeViewToolbarGraphLinkButtonArgs {
+                                    link: ministate_octothorpe(&Ministate::NodeGraph(MinistateNodeGraph {
+                                        title: title.clone(),
+                                        node: node.clone(),
+                                    })),
+                                },
+                            ).root,
                             node_button,
                         ],
                     }).root
@@ -233,6 +284,9 @@ pub fn build_page_node_view(pc: &mut ProcessingContext, title: &str, node: &Node
                 if let Some(ele) = build_node_media_el(&node) {
                     children.push(ele);
                 };
+                if let Node::File(file) = &node {
+                    children.push(build_node_generation_status_el(file));
+                }
                 out.push(
                     style_export::cont_node_section_center(
                         style_export::ContNodeSectionCenterArgs { children: children },
@@ -8,7 +8,15 @@ use {
             set_page,
             state,
         },
+        user_saved::{
+            bookmark_ministate,
+            menu_item_paths,
+            preset_ministate,
+            user_saved_delete,
+            user_saved_put,
+        },
     },
+    flowcontrol::ta_return,
     gloo::{
         storage::{
             LocalStorage,
@@ -17,16 +25,26 @@ use {
         utils::window,
     },
     lunk::{
+        EventGraph,
         Prim,
         ProcessingContext,
         link,
     },
     rooting::El,
+    shared::interface::{
+        config::MenuItemId,
+        wire::{
+            RespWhoAmI,
+            UserSavedItem,
+            UserSavedKey,
+        },
+    },
     shared_wasm::world::Lang,
     shared_wasm::log::LogJsErr,
     std::collections::HashMap,
     wasm::{
         js::{
+            el_async,
             on_thinking,
             style_export,
         },
@@ -52,6 +70,150 @@ pub use wasm::media::{
     LOCALSTORAGE_SHOW_SUBS_IF_MATCHING_AUDIO,
 };
 
+fn menu_item_title(carry_titles: &Vec<String>, name: &String) -> String {
+    let mut titles = carry_titles.clone();
+    titles.push(name.clone());
+    return titles.join(", ");
+}
+
+fn build_saved_entry(eg: EventGraph, title: String, href: Option<String>, key: UserSavedKey) -> El {
+    let entry = style_export::leaf_form_saved_entry(style_export::LeafFormSavedEntryArgs {
+        title: title,
+        href: href,
+    });
+    on_thinking(&entry.button_remove, async move || {
+        if let Err(e) = user_saved_delete(eg.clone(), key.clone()).await {
+            state().log.log(&format!("Error removing saved item: {}", e));
+        }
+    });
+    return entry.root;
+}
+
+/// Presets, bookmarks, and pins are stored on the server so they're shared between
+/// devices, and are changed immediately (not via the save button).
+fn build_user_saved(pc: &mut ProcessingContext) -> Vec<El> {
+    let presets = style_export::cont_form_saved(style_export::ContFormSavedArgs { title: format!("Saved presets") });
+    presets.body.ref_own(
+        |body| link!((pc = pc), (user_saved = state().user_saved.clone()), (), (body = body.weak()) {
+            let body = body.upgrade()?;
+            body.ref_clear();
+            for item in user_saved.borrow().iter() {
+                let UserSavedItem::Preset(preset) = item else {
+                    continue;
+                };
+                body.ref_push(
+                    build_saved_entry(
+                        pc.eg(),
+                        preset.name.clone(),
+                        Some(ministate_octothorpe(&preset_ministate(preset))),
+                        item.key(),
+                    ),
+                );
+            }
+        }),
+    );
+    let bookmarks = style_export::cont_form_saved(style_export::ContFormSavedArgs { title: format!("Bookmarks") });
+    bookmarks.body.ref_own(
+        |body| link!((pc = pc), (user_saved = state().user_saved.clone()), (), (body = body.weak()) {
+            let body = body.upgrade()?;
+            body.ref_clear();
+            for item in user_saved.borrow().iter() {
+                let UserSavedItem::Bookmark(bookmark) = item else {
+                    continue;
+                };
+                body.ref_push(
+                    build_saved_entry(
+                        pc.eg(),
+                        bookmark.name.clone(),
+                        Some(ministate_octothorpe(&bookmark_ministate(bookmark))),
+                        item.key(),
+                    ),
+                );
+            }
+        }),
+    );
+    let pins = style_export::cont_form_saved(style_export::ContFormSavedArgs { title: format!("Pinned menu items") });
+    pins.body.ref_push(el_async({
+        let eg = pc.eg();
+        async move {
+            ta_return!(Vec < El >, String);
+            let client_config = state().client_config.get().await;
+            return Ok(eg.event(|pc| {
+                let paths = menu_item_paths(&client_config.borrow().menu);
+                let list = style_export::cont_vbox(style_export::ContVboxArgs { children: vec![] }).root;
+                list.ref_own(|list| link!(
+                    (pc = pc),
+                    (user_saved = state().user_saved.clone()),
+                    (),
+                    (list = list.weak(), paths = paths.clone()) {
+                    let list = list.upgrade()?;
+                    list.ref_clear();
+                    for item in user_saved.borrow().iter() {
+                        let UserSavedItem::PinnedMenuItem(id) = item else {
+                            continue;
+                        };
+                        let title = match paths.iter().find(|(_, menu_item)| &menu_item.id == id) {
+                            Some((carry_titles, menu_item)) => menu_item_title(carry_titles, &menu_item.name),
+                            None => format!("{} (missing)", id),
+                        };
+                        list.ref_push(build_saved_entry(pc.eg(), title, None, item.key()));
+                    }
+                }));
+                let mut options = HashMap::new();
+                options.insert(String::new(), format!("Pin a menu item..."));
+                for (carry_titles, menu_item) in &paths {
+                    options.insert(menu_item.id.0.clone(), menu_item_title(carry_titles, &menu_item.name));
+                }
+                let pin_pair = style_export::leaf_input_pair_enum(style_export::LeafInputPairEnumArgs {
+                    id: "pin_menu_item".to_string(),
+                    title: "Pin".to_string(),
+                    value: String::new(),
+                    options: options,
+                });
+                pin_pair.input.ref_on("change", {
+                    let eg = pc.eg();
+                    let input = pin_pair.input.weak();
+                    move |_| {
+                        let Some(input) = input.upgrade() else {
+                            return;
+                        };
+                        let select = input.raw().dyn_into::<HtmlSelectElement>().unwrap();
+                        let id = select.value();
+                        if id.is_empty() {
+                            return;
+                        }
+                        select.set_value("");
+                        let eg = eg.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            if let Err(e) = user_saved_put(eg, UserSavedItem::PinnedMenuItem(MenuItemId(id))).await {
+                                state().log.log(&format!("Error pinning menu item: {}", e));
+                            }
+                        });
+                    }
+                });
+                return vec![list, pin_pair.root];
+            }).unwrap());
+        }
+    }));
+    let out = vec![presets.root, bookmarks.root, pins.root];
+    for section in &out {
+        section.ref_own(
+            |section| link!((_pc = pc), (whoami = state().whoami.clone()), (), (section = section.weak()) {
+                let section = section.upgrade()?;
+                section.ref_modify_classes(
+                    &[
+                        (
+                            &style_export::class_state_hide().value,
+                            !matches!(&*whoami.borrow(), Some(RespWhoAmI::User(_))),
+                        ),
+                    ],
+                );
+            }),
+        );
+    }
+    return out;
+}
+
 pub fn build_page_settings(pc: &mut ProcessingContext) {
     let offline_enabled = LocalStorage::get::<bool>(LOCALSTORAGE_OFFLINE_ENABLED).unwrap_or(false);
     let offline_pair = style_export::leaf_input_pair_bool(style_export::LeafInputPairBoolArgs {
@@ -226,17 +388,19 @@ pub fn build_page_settings(pc: &mut ProcessingContext) {
         href: ministate_octothorpe(&Ministate::Logs),
     }).root;
 
+    let mut entries = vec![
+        offline_pair.root,
+        font_size_pair.root,
+        audio_lang_pair.root,
+        sub_lang_pair.root,
+        show_subs_pair.root,
+        opfs_link,
+        logs_link,
+    ];
+    entries.extend(build_user_saved(pc));
     let page = style_export::cont_page_form(style_export::ContPageFormArgs {
         bar_children: vec![save_button],
-        entries: vec![
-            offline_pair.root,
-            font_size_pair.root,
-            audio_lang_pair.root,
-            sub_lang_pair.root,
-            show_subs_pair.root,
-            opfs_link,
-            logs_link,
-        ],
+        entries: entries,
     });
     set_page(pc, "Settings", page.root);
 }
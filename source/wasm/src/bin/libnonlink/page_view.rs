@@ -2,16 +2,22 @@ use {
     crate::libnonlink::{
         api::req_post_json,
         infinite::InfPageRes,
+        markdown::build_markdown,
         ministate::{
             Ministate,
             MinistateForm,
+            MinistateNodeEdit,
             MinistateView,
             ministate_octothorpe,
         },
-        node_button::setup_node_button,
+        node_button::{
+            add_to_current_list,
+            setup_node_button,
+        },
         page_settings::LOCALSTORAGE_OFFLINE_ENABLED,
         offline::{
             ensure_offline,
+            ensure_offline_selected,
             offline_audio_url,
             offline_file_url,
             offline_video_url,
@@ -32,16 +38,27 @@ use {
         },
         seekbar::setup_seekbar,
         state::goto_replace_ministate,
+        user_saved::user_saved_put,
         viewutil::{
             DataStackLevel,
+            if_branch,
+            maybe_get_date,
             maybe_get_field,
             maybe_get_field_or_literal,
             maybe_get_field_or_literal_string,
             maybe_get_meta,
+            switch_branch,
             tree_node_to_text,
             unwrap_value_media_hash,
         },
     },
+    chrono::{
+        Datelike,
+        Days,
+        Months,
+        NaiveDate,
+        Weekday,
+    },
     flowcontrol::{
         shed,
         ta_return,
@@ -74,26 +91,38 @@ use {
         WeakEl,
         el,
         el_from_raw,
+        spawn_rooted,
     },
     shared::interface::{
         config::view::{
             Axis,
+            CalendarPeriod,
+            ChartType,
             ClientView,
             ClientViewParam,
+            DataRowsBulkAction,
             Direction,
+            FieldOrLiteralString,
             Link,
             LinkDest,
             Orientation,
             QueryOrField,
+            TimelineGroup,
             TrackEndMode,
+            TransAlign,
+            ViewFacet,
             ViewId,
             Widget,
+            WidgetChart,
             WidgetColor,
             WidgetDataRows,
             WidgetDate,
             WidgetDatetime,
+            WidgetEdit,
+            WidgetEditInput,
             WidgetIcon,
             WidgetLayout,
+            WidgetMarkdown,
             WidgetMedia,
             WidgetNode,
             WidgetPlayButton,
@@ -106,9 +135,17 @@ use {
         wire::{
             NodeMeta,
             Pagination,
+            ReqCommit,
+            ReqCommitView,
+            ReqCommitViewDelete,
+            ReqViewFacets,
             ReqViewQuery,
             RespQueryRows,
+            RespWhoAmI,
             TreeNode,
+            Triple,
+            UserSavedItem,
+            UserSavedPreset,
         },
     },
     shared_wasm::{
@@ -123,6 +160,7 @@ use {
         collections::{
             BTreeMap,
             HashMap,
+            HashSet,
         },
         rc::Rc,
         u64,
@@ -160,6 +198,7 @@ use {
         DomParser,
         HtmlElement,
         HtmlInputElement,
+        HtmlSelectElement,
         ScrollIntoViewOptions,
         ScrollLogicalPosition,
     },
@@ -167,6 +206,324 @@ use {
 
 pub const LOCALSTORAGE_SHARE_SESSION_ID: &str = "share_session_id";
 
+/// Get the rows for a widget data source.
+async fn fetch_data_rows(
+    view_id: &ViewId,
+    offline: &Option<String>,
+    config_at: &QueryOrField,
+    config_query_params: &BTreeMap<String, Vec<String>>,
+    data_at: &Vec<Rc<DataStackLevel>>,
+) -> Result<(Vec<TreeNode>, Rc<HashMap<Node, NodeMeta>>), String> {
+    match config_at {
+        QueryOrField::Field(config_at) => {
+            let Some(TreeNode::Array(res)) = maybe_get_field(config_at, data_at) else {
+                return Err(
+                    format!("Data rows field [{}] must be an array, but it is missing or some other type", config_at),
+                );
+            };
+            return Ok((res, Default::default()));
+        },
+        QueryOrField::Query(query_id) => {
+            let mut params = HashMap::new();
+            if let Some(query_params) = config_query_params.get(query_id) {
+                for k in query_params {
+                    let Some(TreeNode::Scalar(v)) = maybe_get_field(k, data_at) else {
+                        return Err(
+                            format!(
+                                "Parameters must be scalars, but query paramter [{}] is missing or not a scalar",
+                                k
+                            ),
+                        );
+                    };
+                    params.insert(k.clone(), v);
+                }
+            }
+            let res = if let Some(key) = offline {
+                retrieve_offline_query(key, query_id, &params).await?
+            } else {
+                req_post_json(ReqViewQuery {
+                    view_id: view_id.clone(),
+                    query: query_id.clone(),
+                    parameters: params.clone(),
+                    pagination: None,
+                    sort: None,
+                    facets: Default::default(),
+                }).await?
+            };
+            let mut out = vec![];
+            match res.rows {
+                RespQueryRows::Scalar(rows) => {
+                    for v in rows {
+                        out.push(TreeNode::Scalar(v));
+                    }
+                },
+                RespQueryRows::Record(rows) => {
+                    for v in rows {
+                        out.push(TreeNode::Record(v));
+                    }
+                },
+            }
+            return Ok((out, Rc::new(res.meta.into_iter().collect::<HashMap<_, _>>())));
+        },
+    }
+}
+
+/// Interpret a field value as a number for charting, accepting numeric strings.
+fn tree_node_to_f64(v: &TreeNode) -> Option<f64> {
+    match v {
+        TreeNode::Scalar(Node::Value(serde_json::Value::Number(x))) => return x.as_f64(),
+        TreeNode::Scalar(Node::Value(serde_json::Value::String(x))) => return x.trim().parse::<f64>().ok(),
+        _ => return None,
+    }
+}
+
+/// Reduce rows to chart labels and values.
+fn aggregate_chart_rows(
+    config_at: &WidgetChart,
+    rows: Vec<TreeNode>,
+    node_meta: Rc<HashMap<Node, NodeMeta>>,
+    data_at: &Vec<Rc<DataStackLevel>>,
+) -> (Vec<String>, Vec<f64>) {
+    let row_stacks = rows.into_iter().map(|row| {
+        let mut row_data_at = data_at.clone();
+        row_data_at.push(Rc::new(DataStackLevel {
+            data: row,
+            node_meta: node_meta.clone(),
+        }));
+        row_data_at
+    }).collect::<Vec<_>>();
+    match config_at.chart_type {
+        ChartType::Histogram => {
+            let xs =
+                row_stacks
+                    .iter()
+                    .filter_map(|row| maybe_get_field(&config_at.x_field, row).and_then(|x| tree_node_to_f64(&x)))
+                    .filter(|x| x.is_finite())
+                    .collect::<Vec<_>>();
+            if xs.is_empty() {
+                return (vec![], vec![]);
+            }
+            let min = xs.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let buckets = config_at.buckets.unwrap_or(10).clamp(1, 1000);
+            let width = (max - min) / buckets as f64;
+            let mut values = vec![0.; buckets];
+            for x in xs {
+                let i = if width > 0. {
+                    (((x - min) / width) as usize).min(buckets - 1)
+                } else {
+                    0
+                };
+                values[i] += 1.;
+            }
+            let round = |x: f64| (x * 100.).round() / 100.;
+            let labels =
+                (0 .. buckets)
+                    .map(|i| format!("{}–{}", round(min + width * i as f64), round(min + width * (i + 1) as f64)))
+                    .collect();
+            return (labels, values);
+        },
+        ChartType::Bar | ChartType::Line | ChartType::Pie => {
+            let mut labels = vec![];
+            let mut values = vec![];
+            let mut lookup = HashMap::new();
+            for row in &row_stacks {
+                let Some(x) = maybe_get_field(&config_at.x_field, row) else {
+                    continue;
+                };
+                let y = match &config_at.y_field {
+                    Some(y_field) => {
+                        let Some(y) = maybe_get_field(y_field, row).and_then(|y| tree_node_to_f64(&y)) else {
+                            continue;
+                        };
+                        y
+                    },
+                    None => 1.,
+                };
+                let x = tree_node_to_text(&x);
+                let i = *lookup.entry(x.clone()).or_insert_with(|| {
+                    labels.push(x);
+                    values.push(0.);
+                    values.len() - 1
+                });
+                values[i] += y;
+            }
+            return (labels, values);
+        },
+    }
+}
+
+/// Order rows for time based layouts, dropping rows without a date. Rows are
+/// renumbered in the new order so the playlist (ordered by row id) plays them in
+/// the order they're shown.
+fn sort_rows_by_date(
+    rows: Vec<(Vec<usize>, Vec<Rc<DataStackLevel>>)>,
+    date_field: &String,
+    descending: bool,
+) -> Vec<(Vec<usize>, Vec<Rc<DataStackLevel>>)> {
+    let mut rows =
+        rows
+            .into_iter()
+            .filter_map(|row| Some((maybe_get_date(date_field, &row.1)?, row)))
+            .collect::<Vec<_>>();
+    rows.sort_by(|a, b| if descending {
+        b.0.cmp(&a.0)
+    } else {
+        a.0.cmp(&b.0)
+    });
+    return rows.into_iter().enumerate().map(|(i, (_, (mut row_data_id, row_data_at)))| {
+        if let Some(last) = row_data_id.last_mut() {
+            *last = i;
+        }
+        (row_data_id, row_data_at)
+    }).collect();
+}
+
+fn timeline_group_start(group: TimelineGroup, date: NaiveDate) -> NaiveDate {
+    match group {
+        TimelineGroup::Day => return date,
+        TimelineGroup::Week => return date.week(Weekday::Mon).first_day(),
+        TimelineGroup::Month => return date.with_day(1).unwrap(),
+        TimelineGroup::Year => return date.with_ordinal(1).unwrap(),
+    }
+}
+
+/// Wrap a widget showing a value with controls to change the value.
+fn build_editable(
+    view_id: ViewId,
+    parameters: HashMap<String, Node>,
+    config_at: &WidgetEdit,
+    data: &FieldOrLiteralString,
+    data_stack: &Vec<Rc<DataStackLevel>>,
+    build_display: impl 'static + Fn(&TreeNode) -> El,
+) -> Result<El, String> {
+    let FieldOrLiteralString::Field(field) = data else {
+        return Err(format!("Editable widget data must be a field, not a literal"));
+    };
+    let Some(TreeNode::Scalar(subject)) = maybe_get_field(&config_at.subject_field, data_stack) else {
+        return Err(
+            format!("Edit subject field [{}] is missing or not a scalar", config_at.subject_field),
+        );
+    };
+    let value_tree = |value: &Option<Node>| match value {
+        Some(value) => TreeNode::Scalar(value.clone()),
+        None => TreeNode::Scalar(Node::Value(serde_json::Value::String(String::new()))),
+    };
+    let old = match maybe_get_field(field, data_stack) {
+        Some(TreeNode::Scalar(value)) => Some(value),
+        _ => None,
+    };
+    let res = style_export::leaf_view_editable(style_export::LeafViewEditableArgs {
+        body: build_display(&value_tree(&old)),
+        input_type: config_at.input,
+        value: match &old {
+            Some(_) => tree_node_to_text(&value_tree(&old)),
+            None => String::new(),
+        },
+    });
+    let old = Rc::new(RefCell::new(old));
+    let bg = Rc::new(RefCell::new(None));
+    res.input.ref_on("change", {
+        let root = res.root.weak();
+        let display = res.display.weak();
+        let input = res.input.weak();
+        let predicate = config_at.predicate.clone();
+        let input_type = config_at.input;
+        let build_display = Rc::new(build_display);
+        move |_| {
+            if bg.borrow().is_some() {
+                return;
+            }
+            let Some(input) = input.upgrade() else {
+                return;
+            };
+            let value = input.raw().dyn_into::<HtmlInputElement>().unwrap().value();
+            let new = if value.is_empty() {
+                None
+            } else {
+                match input_type {
+                    WidgetEditInput::Text | WidgetEditInput::Date => Some(
+                        Node::Value(serde_json::Value::String(value.clone())),
+                    ),
+                    WidgetEditInput::Number => match serde_json::from_str::<serde_json::Number>(&value) {
+                        Ok(v) => Some(Node::Value(serde_json::Value::Number(v))),
+                        Err(_) => {
+                            input.ref_classes(&[&style_export::class_state_invalid().value]);
+                            return;
+                        },
+                    },
+                }
+            };
+            if new == *old.borrow() {
+                input.ref_remove_classes(&[&style_export::class_state_invalid().value]);
+                return;
+            }
+            let mut add = vec![];
+            let mut remove = vec![];
+            if let Some(old) = &*old.borrow() {
+                remove.push(Triple {
+                    subject: subject.clone(),
+                    predicate: predicate.clone(),
+                    object: old.clone(),
+                });
+            }
+            if let Some(new) = &new {
+                add.push(Triple {
+                    subject: subject.clone(),
+                    predicate: predicate.clone(),
+                    object: new.clone(),
+                });
+            }
+            if let Some(root) = root.upgrade() {
+                root.ref_classes(&[&style_export::class_state_thinking().value]);
+            }
+            *bg.borrow_mut() = Some(spawn_rooted({
+                let view_id = view_id.clone();
+                let parameters = parameters.clone();
+                let root = root.clone();
+                let display = display.clone();
+                let input = input.weak();
+                let old = old.clone();
+                let bg = bg.clone();
+                let build_display = build_display.clone();
+                async move {
+                    let res = req_post_json(ReqCommit::View(ReqCommitView {
+                        view_id: view_id,
+                        parameters: parameters,
+                        add: add,
+                        remove: remove,
+                    })).await;
+                    *bg.borrow_mut() = None;
+                    if let Some(root) = root.upgrade() {
+                        root.ref_remove_classes(&[&style_export::class_state_thinking().value]);
+                    }
+                    let Some(input) = input.upgrade() else {
+                        return;
+                    };
+                    match res {
+                        Ok(_) => {
+                            input.ref_remove_classes(&[&style_export::class_state_invalid().value]);
+
+                            // Cancelling edits resets to the default value
+                            input.ref_attr("value", &value);
+                            if let Some(display) = display.upgrade() {
+                                display.ref_clear();
+                                display.ref_push(build_display(&value_tree(&new)));
+                            }
+                            *old.borrow_mut() = new;
+                        },
+                        Err(e) => {
+                            state().log.log(&format!("Error saving edit: {}", e));
+                            input.ref_classes(&[&style_export::class_state_invalid().value]);
+                        },
+                    }
+                }
+            }));
+        }
+    });
+    return Ok(res.root);
+}
+
 fn unwrap_value_move_url(data_stack: &Vec<Rc<DataStackLevel>>, link: &Link) -> Result<Option<String>, String> {
     let title = match maybe_get_field_or_literal(&link.title, data_stack) {
         Some(x) => tree_node_to_text(&x),
@@ -202,6 +559,8 @@ fn unwrap_value_move_url(data_stack: &Vec<Rc<DataStackLevel>>, link: &Link) -> R
                 title: title,
                 pos: None,
                 params: params,
+                sort: None,
+                facets: Default::default(),
             }))));
         },
         LinkDest::Form(d) => {
@@ -216,6 +575,7 @@ fn unwrap_value_move_url(data_stack: &Vec<Rc<DataStackLevel>>, link: &Link) -> R
                 id: d.id.clone(),
                 title: title,
                 params: params,
+                list_params: Default::default(),
             }))));
         },
         LinkDest::Node(d) => {
@@ -292,6 +652,8 @@ struct Build {
     vs: MinistateViewState,
     seed: u64,
     offline: Option<String>,
+    /// Show the edit controls configured in the view.
+    editable: bool,
 }
 
 fn calc_child_bctx(
@@ -457,64 +819,10 @@ impl Build {
             let data_at = data_at.clone();
             let seed = self.seed;
             let offline = self.offline.clone();
+            let editable = self.editable;
             async move {
-                let node_meta;
-                let new_data_at_tops;
-                match config_at.data {
-                    QueryOrField::Field(config_at) => {
-                        let Some(TreeNode::Array(res)) = maybe_get_field(&config_at, &data_at) else {
-                            return Err(
-                                format!(
-                                    "Data rows field [{}] must be an array, but it is missing or some other type",
-                                    config_at
-                                ),
-                            );
-                        };
-                        new_data_at_tops = res;
-                        node_meta = Default::default();
-                    },
-                    QueryOrField::Query(query_id) => {
-                        let mut params = HashMap::new();
-                        if let Some(query_params) = config_query_params.get(&query_id) {
-                            for k in query_params {
-                                let Some(TreeNode::Scalar(v)) = maybe_get_field(k, &data_at) else {
-                                    return Err(
-                                        format!(
-                                            "Parameters must be scalars, but query paramter [{}] is missing or not a scalar",
-                                            k
-                                        ),
-                                    );
-                                };
-                                params.insert(k.clone(), v);
-                            }
-                        }
-                        let res = if let Some(key) = &offline {
-                            retrieve_offline_query(key, &query_id, &params).await?
-                        } else {
-                            req_post_json(ReqViewQuery {
-                                view_id: view_id.clone(),
-                                query: query_id.clone(),
-                                parameters: params.clone(),
-                                pagination: None,
-                            }).await?
-                        };
-                        let mut out = vec![];
-                        match res.rows {
-                            RespQueryRows::Scalar(rows) => {
-                                for v in rows {
-                                    out.push(TreeNode::Scalar(v));
-                                }
-                            },
-                            RespQueryRows::Record(rows) => {
-                                for v in rows {
-                                    out.push(TreeNode::Record(v));
-                                }
-                            },
-                        }
-                        new_data_at_tops = out;
-                        node_meta = Rc::new(res.meta.into_iter().collect::<HashMap<_, _>>());
-                    },
-                };
+                let (new_data_at_tops, node_meta) =
+                    fetch_data_rows(&view_id, &offline, &config_at.data, &config_query_params, &data_at).await?;
 
                 // Build data stack for each row
                 let row_stacks: Vec<_> = new_data_at_tops.into_iter().enumerate().map(|(i, new_data_at_top)| {
@@ -527,6 +835,15 @@ impl Build {
                     row_data_id.push(i);
                     (row_data_id, row_data_at)
                 }).collect();
+                let row_stacks = match &config_at.row_widget {
+                    shared::interface::config::view::DataRowsLayout::Calendar(config_calendar) => {
+                        sort_rows_by_date(row_stacks, &config_calendar.date_field, false)
+                    },
+                    shared::interface::config::view::DataRowsLayout::Timeline(config_timeline) => {
+                        sort_rows_by_date(row_stacks, &config_timeline.date_field, config_timeline.descending)
+                    },
+                    _ => row_stacks,
+                };
 
                 // Extract playlist entries (first walk)
                 let mut playlist_add = vec![];
@@ -545,6 +862,22 @@ impl Build {
                                 extract_playlist_entries_from_page(el, row_data_id, row_data_at, &mut playlist_add);
                             }
                         },
+                        shared::interface::config::view::DataRowsLayout::Calendar(config_calendar) => {
+                            extract_playlist_entries_from_page(
+                                &config_calendar.widget,
+                                row_data_id,
+                                row_data_at,
+                                &mut playlist_add,
+                            );
+                        },
+                        shared::interface::config::view::DataRowsLayout::Timeline(config_timeline) => {
+                            extract_playlist_entries_from_page(
+                                &config_timeline.widget,
+                                row_data_id,
+                                row_data_at,
+                                &mut playlist_add,
+                            );
+                        },
                     }
                 }
                 let want_media = !playlist_add.is_empty();
@@ -560,6 +893,7 @@ impl Build {
                     transport_slot: transport_slot,
                     seed: seed,
                     offline: offline.clone(),
+                    editable: editable,
                 };
                 let out;
                 match &config_at.row_widget {
@@ -654,6 +988,161 @@ impl Build {
                             column_gap: config_table.column_gap.clone(),
                         }).root;
                     },
+                    shared::interface::config::view::DataRowsLayout::Calendar(config_calendar) => {
+                        let child_bctx =
+                            calc_child_bctx(
+                                bctx,
+                                Orientation::DownRight,
+                                false,
+                                false,
+                                false,
+                                false,
+                            ).with_parent_orientation(Orientation::DownRight, OrientationType::Flex);
+
+                        // Rows are sorted, so group consecutive rows by day
+                        let mut days: Vec<(NaiveDate, Vec<El>)> = vec![];
+                        for (row_data_id, row_data_at) in &row_stacks {
+                            let Some(date) = maybe_get_date(&config_calendar.date_field, row_data_at) else {
+                                continue;
+                            };
+                            let date = date.date();
+                            let child =
+                                build
+                                    .build_widget(
+                                        &eg,
+                                        child_bctx,
+                                        &config_calendar.widget,
+                                        &config_query_params,
+                                        row_data_id,
+                                        row_data_at,
+                                    )
+                                    .await;
+                            match days.last_mut() {
+                                Some((last_date, children)) if *last_date == date => {
+                                    children.push(child);
+                                },
+                                _ => {
+                                    days.push((date, vec![child]));
+                                },
+                            }
+                        }
+
+                        // Lay out full weeks for each period with rows
+                        let mut titles = vec![];
+                        let mut periods = vec![];
+                        let mut days = days.into_iter().peekable();
+                        while let Some((first_date, _)) = days.peek() {
+                            let period_start = match config_calendar.period {
+                                CalendarPeriod::Month => first_date.with_day(1).unwrap(),
+                                CalendarPeriod::Week => first_date.week(Weekday::Mon).first_day(),
+                            };
+                            let period_end = match config_calendar.period {
+                                CalendarPeriod::Month => period_start + Months::new(1),
+                                CalendarPeriod::Week => period_start + Days::new(7),
+                            };
+                            titles.push(period_start.format("%Y-%m-%d").to_string());
+                            let mut cells = vec![];
+                            let mut day = period_start.week(Weekday::Mon).first_day();
+                            let grid_end = (period_end - Days::new(1)).week(Weekday::Mon).last_day();
+                            while day <= grid_end {
+                                let outside = day < period_start || day >= period_end;
+                                let mut children = vec![];
+                                if !outside && days.peek().map(|(d, _)| *d == day).unwrap_or(false) {
+                                    children = days.next().unwrap().1;
+                                }
+                                cells.push(style_export::cont_view_calendar_day(style_export::ContViewCalendarDayArgs {
+                                    date: day.format("%Y-%m-%d").to_string(),
+                                    outside: outside,
+                                    children: children,
+                                }).root);
+                                day = day + Days::new(1);
+                            }
+                            periods.push(cells);
+                        }
+                        out = style_export::cont_view_calendar(style_export::ContViewCalendarArgs {
+                            parent_orientation: bctx.parent_orientation,
+                            parent_orientation_type: bctx.parent_orientation_type,
+                            trans_align: config_at.trans_align,
+                            period: config_calendar.period,
+                            titles: titles,
+                            periods: periods,
+                            con_size_max: config_calendar.con_size_max.clone(),
+                        }).root;
+                    },
+                    shared::interface::config::view::DataRowsLayout::Timeline(config_timeline) => {
+                        let orientation = config_timeline.orientation.unwrap_or(bctx.parent_orientation);
+                        let child_bctx =
+                            calc_child_bctx(
+                                bctx,
+                                orientation,
+                                config_timeline.con_scroll,
+                                false,
+                                config_timeline.con_size_max.is_some(),
+                                config_timeline.trans_size_max.is_some(),
+                            ).with_parent_orientation(orientation, OrientationType::Flex);
+
+                        // Rows are sorted, so group consecutive rows
+                        let mut groups: Vec<(NaiveDate, Vec<El>)> = vec![];
+                        for (row_data_id, row_data_at) in &row_stacks {
+                            let Some(date) = maybe_get_date(&config_timeline.date_field, row_data_at) else {
+                                continue;
+                            };
+                            let group_start = timeline_group_start(config_timeline.group, date.date());
+                            let child =
+                                build
+                                    .build_widget(
+                                        &eg,
+                                        child_bctx,
+                                        &config_timeline.widget,
+                                        &config_query_params,
+                                        row_data_id,
+                                        row_data_at,
+                                    )
+                                    .await;
+                            match groups.last_mut() {
+                                Some((last_start, children)) if *last_start == group_start => {
+                                    children.push(child);
+                                },
+                                _ => {
+                                    groups.push((group_start, vec![child]));
+                                },
+                            }
+                        }
+                        let mut children = vec![];
+                        for (group_start, group_children) in groups {
+                            children.push(style_export::cont_view_timeline_group(style_export::ContViewTimelineGroupArgs {
+                                orientation: orientation,
+                                group: config_timeline.group,
+                                date: group_start.format("%Y-%m-%d").to_string(),
+                                body: style_export::cont_view_list(style_export::ContViewListArgs {
+                                    parent_con_restricted: child_bctx.is_con_restricted(),
+                                    parent_orientation: orientation,
+                                    parent_orientation_type: OrientationType::Flex,
+                                    orientation: orientation,
+                                    trans_align: TransAlign::Start,
+                                    con_scroll: false,
+                                    con_size_max: None,
+                                    trans_size_max: None,
+                                    con_wrap: false,
+                                    children: group_children,
+                                    gap: config_timeline.gap.clone(),
+                                }).root,
+                            }).root);
+                        }
+                        out = style_export::cont_view_list(style_export::ContViewListArgs {
+                            parent_con_restricted: bctx.is_con_restricted(),
+                            parent_orientation: bctx.parent_orientation,
+                            parent_orientation_type: bctx.parent_orientation_type,
+                            orientation: orientation,
+                            trans_align: config_at.trans_align,
+                            con_scroll: config_timeline.con_scroll,
+                            con_size_max: config_timeline.con_size_max.clone(),
+                            trans_size_max: config_timeline.trans_size_max.clone(),
+                            con_wrap: false,
+                            children: children,
+                            gap: config_timeline.gap.clone(),
+                        }).root;
+                    },
                 }
                 playlist_extend(
                     &eg,
@@ -679,35 +1168,56 @@ impl Build {
         &mut self,
         bctx: BuildContext,
         config_at: &WidgetText,
+        config_query_params: &BTreeMap<String, Vec<String>>,
         data_stack: &Vec<Rc<DataStackLevel>>,
     ) -> El {
         match (|| {
             ta_return!(El, String);
-            return Ok(style_export::leaf_view_text(style_export::LeafViewTextArgs {
-                parent_orientation: bctx.parent_orientation,
-                parent_orientation_type: bctx.parent_orientation_type,
-                trans_align: config_at.trans_align,
-                orientation: config_at.orientation,
-                text: format!(
-                    "{}{}{}",
-                    config_at.prefix,
-                    tree_node_to_text(&match maybe_get_field_or_literal_string(&config_at.data, data_stack) {
-                        Some(x) => x,
-                        None => return Ok(el("div")),
-                    }),
-                    config_at.suffix
-                ),
-                font_size: config_at.font_size.clone(),
-                color: config_at.color.clone(),
-                con_size_max: config_at.con_size_max.clone(),
-                con_size_mode: Some(config_at.con_size_mode.clone()),
-                link: shed!{
-                    let Some(link) = config_at.link.as_ref() else {
-                        break None;
-                    };
-                    break unwrap_value_move_url(data_stack, &link)?;
-                },
-            }).root);
+            let link = shed!{
+                let Some(link) = config_at.link.as_ref() else {
+                    break None;
+                };
+                break unwrap_value_move_url(data_stack, &link)?;
+            };
+            let build_text = {
+                let config_at = config_at.clone();
+                move |value: &TreeNode| style_export::leaf_view_text(style_export::LeafViewTextArgs {
+                    parent_orientation: bctx.parent_orientation,
+                    parent_orientation_type: bctx.parent_orientation_type,
+                    trans_align: config_at.trans_align,
+                    orientation: config_at.orientation,
+                    text: format!("{}{}{}", config_at.prefix, tree_node_to_text(value), config_at.suffix),
+                    font_size: config_at.font_size.clone(),
+                    color: config_at.color.clone(),
+                    con_size_max: config_at.con_size_max.clone(),
+                    con_size_mode: Some(config_at.con_size_mode.clone()),
+                    link: link.clone(),
+                }).root
+            };
+            if let Some(edit) = &config_at.edit {
+                if self.offline.is_none() && self.editable {
+                    // The server checks that the edited node is returned by one of the view's
+                    // queries, so send the parameters the queries were run with
+                    let mut parameters = self.param_data.clone();
+                    for k in config_query_params.values().flatten() {
+                        if let Some(TreeNode::Scalar(v)) = maybe_get_field(k, data_stack) {
+                            parameters.insert(k.clone(), v);
+                        }
+                    }
+                    return build_editable(
+                        self.view_id.clone(),
+                        parameters,
+                        edit,
+                        &config_at.data,
+                        data_stack,
+                        build_text,
+                    );
+                }
+            }
+            return Ok(build_text(&match maybe_get_field_or_literal_string(&config_at.data, data_stack) {
+                Some(x) => x,
+                None => return Ok(el("div")),
+            }));
         })() {
             Ok(e) => return e,
             Err(e) => return style_export::leaf_err_block(style_export::LeafErrBlockArgs {
@@ -717,6 +1227,60 @@ impl Build {
         }
     }
 
+    async fn build_widget_markdown(
+        &mut self,
+        bctx: BuildContext,
+        config_at: &WidgetMarkdown,
+        data_stack: &Vec<Rc<DataStackLevel>>,
+    ) -> El {
+        let text = match maybe_get_field_or_literal_string(&config_at.data, data_stack) {
+            Some(TreeNode::Scalar(Node::Value(serde_json::Value::String(x)))) => x,
+            Some(x) => tree_node_to_text(&x),
+            None => return el("div"),
+        };
+        return style_export::leaf_view_markdown(style_export::LeafViewMarkdownArgs {
+            parent_orientation: bctx.parent_orientation,
+            parent_orientation_type: bctx.parent_orientation_type,
+            trans_align: config_at.trans_align,
+            body: build_markdown(&text, data_stack, self.offline.is_some()).await,
+            font_size: config_at.font_size.clone(),
+            con_size_max: config_at.con_size_max.clone(),
+            color: config_at.color.clone(),
+        }).root;
+    }
+
+    async fn build_widget_chart(
+        &mut self,
+        bctx: BuildContext,
+        config_at: &WidgetChart,
+        config_query_params: &BTreeMap<String, Vec<String>>,
+        data_at: &Vec<Rc<DataStackLevel>>,
+    ) -> El {
+        return el_async({
+            let view_id = self.view_id.clone();
+            let offline = self.offline.clone();
+            let config_at = config_at.clone();
+            let config_query_params = config_query_params.clone();
+            let data_at = data_at.clone();
+            async move {
+                let (rows, node_meta) =
+                    fetch_data_rows(&view_id, &offline, &config_at.data, &config_query_params, &data_at).await?;
+                let (labels, values) = aggregate_chart_rows(&config_at, rows, node_meta, &data_at);
+                return Ok(vec![style_export::leaf_view_chart(style_export::LeafViewChartArgs {
+                    parent_orientation: bctx.parent_orientation,
+                    parent_orientation_type: bctx.parent_orientation_type,
+                    trans_align: config_at.trans_align,
+                    chart_type: config_at.chart_type,
+                    labels: labels,
+                    values: values,
+                    width: config_at.width.clone(),
+                    height: config_at.height.clone(),
+                    color: config_at.color.clone(),
+                }).root]);
+            }
+        });
+    }
+
     async fn build_widget_media(
         &mut self,
         bctx: BuildContext,
@@ -1148,7 +1712,11 @@ impl Build {
                 .boxed_local()
                 .await,
             Widget::Text(config_at) => return self
-                .build_widget_text(bctx, config_at, data_stack)
+                .build_widget_text(bctx, config_at, config_query_params, data_stack)
+                .boxed_local()
+                .await,
+            Widget::Markdown(config_at) => return self
+                .build_widget_markdown(bctx, config_at, data_stack)
                 .boxed_local()
                 .await,
             Widget::Media(config_at) => return self
@@ -1184,6 +1752,28 @@ impl Build {
                 .build_widget_node(eg, bctx, config_at, data_stack)
                 .boxed_local()
                 .await,
+            Widget::If(config_at) => {
+                let Some(config_at) = if_branch(config_at, data_stack) else {
+                    return el("div");
+                };
+                return self
+                    .build_widget(eg, bctx, config_at, config_query_params, data_id, data_stack)
+                    .boxed_local()
+                    .await;
+            },
+            Widget::Switch(config_at) => {
+                let Some(config_at) = switch_branch(config_at, data_stack) else {
+                    return el("div");
+                };
+                return self
+                    .build_widget(eg, bctx, config_at, config_query_params, data_id, data_stack)
+                    .boxed_local()
+                    .await;
+            },
+            Widget::Chart(config_at) => return self
+                .build_widget_chart(bctx, config_at, config_query_params, data_stack)
+                .boxed_local()
+                .await,
         }
     }
 }
@@ -1276,6 +1866,16 @@ fn extract_playlist_entries_from_page(
                 extract_playlist_entries_from_page(child, data_id, data_stack, out);
             }
         },
+        Widget::If(config_at) => {
+            if let Some(child) = if_branch(config_at, data_stack) {
+                extract_playlist_entries_from_page(child, data_id, data_stack, out);
+            }
+        },
+        Widget::Switch(config_at) => {
+            if let Some(child) = switch_branch(config_at, data_stack) {
+                extract_playlist_entries_from_page(child, data_id, data_stack, out);
+            }
+        },
         _ => { },
     }
 }
@@ -1290,6 +1890,8 @@ fn build_widget_root_data_rows(
     transport_slot: El,
     seed: u64,
     offline: Option<String>,
+    editable: bool,
+    selection: Option<ViewSelection>,
     config_at: &WidgetRootDataRows,
     config_query_params: &BTreeMap<String, Vec<String>>,
     data_at: &Vec<Rc<DataStackLevel>>,
@@ -1306,6 +1908,7 @@ fn build_widget_root_data_rows(
         let config_query_params = config_query_params.clone();
         let data_at = data_at.clone();
         let offline = offline.clone();
+        let selection = selection.clone();
         move |chunk: Vec<(usize, TreeNode)>, node_meta: Rc<HashMap<Node, NodeMeta>>| -> LocalBoxFuture<Vec<El>> {
             async move {
                 // Build data stack for each row
@@ -1344,9 +1947,19 @@ fn build_widget_root_data_rows(
                     vs: vs.clone(),
                     seed: seed,
                     offline: offline.clone(),
+                    editable: editable,
                 };
                 let mut children = vec![];
                 for (i, data_at) in &row_stacks {
+                    let select = shed!{
+                        let Some(selection) = &selection else {
+                            break None;
+                        };
+                        let Some(TreeNode::Scalar(node)) = maybe_get_field(&selection.node_field, data_at) else {
+                            break None;
+                        };
+                        Some(build_select_toggle(&eg, selection, node))
+                    };
                     children.push(style_export::cont_view_element(style_export::ContViewElementArgs {
                         body: build.build_widget(&eg, BuildContext {
                             restrict_x: true,
@@ -1364,6 +1977,7 @@ fn build_widget_root_data_rows(
                                 parent_orientation_type: OrientationType::Grid,
                             }, &exp, &config_query_params, &vec![*i], data_at).await),
                         },
+                        select: select,
                     }).root);
                 }
                 playlist_extend(
@@ -1386,6 +2000,10 @@ fn build_widget_root_data_rows(
         }
     };
     let restore = restore_playlist_pos.as_ref().and_then(|x| x.index.first().copied());
+    let (sort, facets) = {
+        let vs = vs.0.borrow();
+        (vs.sort.clone(), vs.facets.clone())
+    };
     return el_async({
         let config_at = config_at.clone();
         let view_id = view_id.clone();
@@ -1490,6 +2108,8 @@ fn build_widget_root_data_rows(
                             let view_id = view_id.clone();
                             let query_id = query_id.clone();
                             let params = params.clone();
+                            let sort = sort.clone();
+                            let facets = facets.clone();
                             let config_at = config_at.clone();
                             let data_at = data_at.clone();
                             let source_count = Rc::new(Cell::new(0usize));
@@ -1499,6 +2119,8 @@ fn build_widget_root_data_rows(
                                 let view_id = view_id.clone();
                                 let query_id = query_id.clone();
                                 let params = params.clone();
+                                let sort = sort.clone();
+                                let facets = facets.clone();
                                 let config_at = config_at.clone();
                                 let data_at = data_at.clone();
                                 let source_count = source_count.clone();
@@ -1522,6 +2144,8 @@ fn build_widget_root_data_rows(
                                             seed: Some(seed),
                                             key: source_page_key.borrow().clone(),
                                         }),
+                                        sort: sort.clone(),
+                                        facets: facets.clone(),
                                     }).await {
                                         Ok(r) => r,
                                         Err(_) => return None,
@@ -1577,6 +2201,8 @@ fn build_widget_root_data_rows(
                                 let view_id = view_id.clone();
                                 let query_id = query_id.clone();
                                 let params = params.clone();
+                                let sort = sort.clone();
+                                let facets = facets.clone();
                                 let build_infinite_page = build_infinite_page.clone();
                                 let count = count.clone();
                                 async move {
@@ -1589,6 +2215,8 @@ fn build_widget_root_data_rows(
                                             seed: Some(seed),
                                             key: key.clone(),
                                         }),
+                                        sort: sort.clone(),
+                                        facets: facets.clone(),
                                     }).await?;
                                     let mut chunk = vec![];
                                     match res.rows {
@@ -1889,6 +2517,292 @@ fn build_transport(pc: &mut ProcessingContext, offline: bool) -> El {
 }
 
 #[derive(Clone)]
+#[derive(Clone)]
+struct ViewSelection {
+    node_field: String,
+    selected: Prim<Vec<Node>>,
+}
+
+fn build_select_toggle(eg: &EventGraph, selection: &ViewSelection, node: Node) -> El {
+    let out = style_export::leaf_view_select_toggle().root;
+    out.ref_on("click", {
+        let eg = eg.clone();
+        let selected = selection.selected.clone();
+        let node = node.clone();
+        move |ev| {
+            // Don't toggle the element expansion
+            ev.prevent_default();
+            ev.stop_propagation();
+            eg.event(|pc| {
+                let mut new_selected = selected.borrow().clone();
+                if let Some(i) = new_selected.iter().position(|x| *x == node) {
+                    new_selected.remove(i);
+                } else {
+                    new_selected.push(node.clone());
+                }
+                selected.set(pc, new_selected);
+            }).unwrap();
+        }
+    });
+    eg.event(|pc| {
+        out.ref_own(
+            |out| link!(
+                (_pc = pc),
+                (selected = selection.selected.clone()),
+                (),
+                (out = out.weak(), node = node.clone()) {
+                    let out = out.upgrade()?;
+                    out.ref_modify_classes(
+                        &[(&style_export::class_state_selected().value, selected.borrow().contains(&node))],
+                    );
+                }
+            ),
+        );
+    }).unwrap();
+    return out;
+}
+
+fn build_bulk_bar(
+    pc: &mut ProcessingContext,
+    common: &Rc<BuildViewBodyCommon>,
+    title: &str,
+    param_data: &Rc<RefCell<HashMap<String, Node>>>,
+    selection: &ViewSelection,
+    actions: &Vec<DataRowsBulkAction>,
+) -> El {
+    let selection_title = {
+        let title = title.to_string();
+        let selected = selection.selected.clone();
+        move || format!("{} ({} selected)", title, selected.borrow().len())
+    };
+    let mut buttons = vec![];
+    for action in actions {
+        match action {
+            DataRowsBulkAction::Edit => {
+                // The node editor can change any relation, so it's admin only
+                if !common.admin {
+                    continue;
+                }
+                let button =
+                    style_export::leaf_view_bulk_button(
+                        style_export::LeafViewBulkButtonArgs { text: format!("Edit") },
+                    ).root;
+                button.ref_on("click", {
+                    let eg = pc.eg();
+                    let selected = selection.selected.clone();
+                    let selection_title = selection_title.clone();
+                    move |_| eg.event(|pc| {
+                        goto_replace_ministate(pc, &state().log, &Ministate::NodeEdit(MinistateNodeEdit {
+                            title: selection_title(),
+                            nodes: selected.borrow().clone(),
+                        }));
+                    }).unwrap()
+                });
+                buttons.push(button);
+            },
+            DataRowsBulkAction::AddToList => {
+                // Adding edits the list directly, which only admins can do
+                if !common.admin {
+                    continue;
+                }
+                let button =
+                    style_export::leaf_view_bulk_button(
+                        style_export::LeafViewBulkButtonArgs { text: format!("Add to list") },
+                    ).root;
+                button.ref_own(
+                    |b| link!((_pc = pc), (current_list = state().current_list.clone()), (), (b = b.weak()) {
+                        let b = b.upgrade()?;
+                        b.ref_modify_classes(
+                            &[(&style_export::class_state_disabled().value, current_list.borrow().is_none())],
+                        );
+                    }),
+                );
+                on_thinking(&button, {
+                    let eg = pc.eg();
+                    let id = common.id.clone();
+                    let selected = selection.selected.clone();
+                    async move || {
+                        let nodes = selected.borrow().clone();
+                        match add_to_current_list(nodes, &format!("Add nodes to list via view [{}]", id)).await {
+                            Ok(_) => {
+                                eg.event(|pc| {
+                                    selected.set(pc, vec![]);
+                                }).unwrap();
+                            },
+                            Err(e) => {
+                                state().log.log(&format!("Error adding selected nodes to list: {}", e));
+                            },
+                        }
+                    }
+                });
+                buttons.push(button);
+            },
+            DataRowsBulkAction::Form(f) => {
+                let button =
+                    style_export::leaf_view_bulk_button(
+                        style_export::LeafViewBulkButtonArgs { text: f.name.clone() },
+                    ).root;
+                button.ref_on("click", {
+                    let eg = pc.eg();
+                    let selected = selection.selected.clone();
+                    let f = f.clone();
+                    move |_| eg.event(|pc| {
+                        let nodes = selected.borrow().clone();
+                        goto_replace_ministate(pc, &state().log, &Ministate::Form(MinistateForm {
+                            id: f.form_id.clone(),
+                            title: format!("{} ({} selected)", f.name, nodes.len()),
+                            params: Default::default(),
+                            list_params: [(f.parameter.clone(), nodes)].into_iter().collect(),
+                        }));
+                    }).unwrap()
+                });
+                buttons.push(button);
+            },
+            DataRowsBulkAction::Offline => {
+                if !LocalStorage::get::<bool>(LOCALSTORAGE_OFFLINE_ENABLED).unwrap_or(false) {
+                    continue;
+                }
+                let button =
+                    style_export::leaf_view_bulk_button(
+                        style_export::LeafViewBulkButtonArgs { text: format!("Offline") },
+                    ).root;
+                on_thinking(&button, {
+                    let eg = pc.eg();
+                    let id = common.id.clone();
+                    let node_field = selection.node_field.clone();
+                    let selected = selection.selected.clone();
+                    let selection_title = selection_title.clone();
+                    let offline_view = common.offline_view.clone();
+                    async move || {
+                        let res = async {
+                            ta_return!((), String);
+                            let client_config = state().client_config.get().await.borrow().clone();
+                            let Some(view_def) = client_config.views.get(&id) else {
+                                return Err(format!("No view with id [{}] in config", id));
+                            };
+                            let mut view = offline_view.borrow().clone();
+                            view.title = selection_title();
+                            let nodes = selected.borrow().iter().cloned().collect::<HashSet<_>>();
+                            ensure_offline_selected(eg.clone(), view, view_def, &node_field, &nodes).await?;
+                            return Ok(());
+                        }.await;
+                        match res {
+                            Ok(_) => {
+                                eg.event(|pc| {
+                                    selected.set(pc, vec![]);
+                                }).unwrap();
+                            },
+                            Err(e) => {
+                                state().log.log(&format!("Error triggering offline for selected rows: {}", e));
+                            },
+                        }
+                    }
+                });
+                buttons.push(button);
+            },
+            DataRowsBulkAction::Delete => {
+                if !common.editable {
+                    continue;
+                }
+                let button =
+                    style_export::leaf_view_bulk_button(
+                        style_export::LeafViewBulkButtonArgs { text: format!("Delete") },
+                    ).root;
+                button.ref_on("click", {
+                    let eg = pc.eg();
+                    let common = common.clone();
+                    let param_data = param_data.clone();
+                    let selected = selection.selected.clone();
+                    move |_| {
+                        let modal_res =
+                            style_export::cont_view_modal_confirm_bulk_delete(
+                                style_export::ContViewModalConfirmBulkDeleteArgs { count: selected.borrow().len() },
+                            );
+                        modal_res.button_close.ref_on("click", {
+                            let modal_el = modal_res.root.weak();
+                            let eg = eg.clone();
+                            move |_| eg.event(|_pc| {
+                                let Some(modal_el) = modal_el.upgrade() else {
+                                    return;
+                                };
+                                modal_el.ref_replace(vec![]);
+                            }).unwrap()
+                        });
+                        modal_res.root.ref_on("click", {
+                            let modal_el = modal_res.root.weak();
+                            let eg = eg.clone();
+                            move |_| eg.event(|_pc| {
+                                let Some(modal_el) = modal_el.upgrade() else {
+                                    return;
+                                };
+                                modal_el.ref_replace(vec![]);
+                            }).unwrap()
+                        });
+                        on_thinking(&modal_res.button_ok, {
+                            let eg = eg.clone();
+                            let common = common.clone();
+                            let param_data = param_data.clone();
+                            let selected = selected.clone();
+                            let modal_el = modal_res.root.weak();
+                            async move || {
+                                let res = async {
+                                    ta_return!((), String);
+                                    let nodes = selected.borrow().clone();
+                                    let parameters = param_data.borrow().clone();
+                                    req_post_json(ReqCommit::ViewDelete(ReqCommitViewDelete {
+                                        view_id: common.id.clone(),
+                                        parameters: parameters,
+                                        nodes: nodes,
+                                    })).await?;
+                                    return Ok(());
+                                }.await;
+                                match res {
+                                    Ok(_) => {
+                                        eg.event(|pc| {
+                                            build_page_view_body(pc, &common, &*param_data.borrow(), None, None);
+                                        }).unwrap();
+                                    },
+                                    Err(e) => {
+                                        state().log.log(&format!("Error deleting selected nodes: {}", e));
+                                    },
+                                }
+                                if let Some(modal_el) = modal_el.upgrade() {
+                                    modal_el.ref_replace(vec![]);
+                                }
+                            }
+                        });
+                        state().modal_stack.ref_push(modal_res.root.clone());
+                    }
+                });
+                buttons.push(button);
+            },
+        }
+    }
+    let bar = style_export::cont_view_bulk_bar(style_export::ContViewBulkBarArgs { actions: buttons });
+    bar.button_clear.ref_on("click", {
+        let eg = pc.eg();
+        let selected = selection.selected.clone();
+        move |_| eg.event(|pc| {
+            selected.set(pc, vec![]);
+        }).unwrap()
+    });
+    bar.root.ref_own(
+        |root| link!(
+            (_pc = pc),
+            (selected = selection.selected.clone()),
+            (),
+            (root = root.weak(), count = bar.count.weak()) {
+                let root = root.upgrade()?;
+                let count = count.upgrade()?;
+                let selected = selected.borrow().len();
+                root.ref_modify_classes(&[(&style_export::class_state_hide().value, selected == 0)]);
+                count.ref_text(&format!("{} selected", selected));
+            }
+        ),
+    );
+    return bar.root;
+}
+
 struct BuildViewBodyCommon {
     id: ViewId,
     config_at: WidgetRootDataRows,
@@ -1899,11 +2813,90 @@ struct BuildViewBodyCommon {
     transport_slot: WeakEl,
     have_media: Rc<Cell<bool>>,
     view_ministate_state: MinistateViewState,
+    selection: Option<ViewSelection>,
+    /// Show bulk actions that need full access.
+    admin: bool,
+    /// Show edit controls and bulk actions that need edit access to the view.
+    editable: bool,
+    facets: Vec<ViewFacet>,
+    facets_slot: WeakEl,
+    /// The current view state, for offlining.
+    offline_view: Prim<MinistateView>,
+}
+
+/// Fetch the value counts for the view's facets and build a selector for each.
+fn build_facets(eg: &EventGraph, common: &Rc<BuildViewBodyCommon>, param_data: &HashMap<String, Node>) -> El {
+    let eg = eg.clone();
+    let common = common.clone();
+    let param_data = param_data.clone();
+    return el_async(async move {
+        ta_return!(Vec < El >, String);
+        let selected = common.view_ministate_state.0.borrow().facets.clone();
+        let counts = req_post_json(ReqViewFacets {
+            view_id: common.id.clone(),
+            parameters: param_data.clone(),
+            facets: selected.clone(),
+        }).await?;
+        let mut out = vec![];
+        for facet in &common.facets {
+            let mut options = HashMap::new();
+            options.insert(String::new(), format!("All"));
+            if let Some(counts) = counts.iter().find(|x| x.field == facet.field) {
+                // Counts from a partial read are lower bounds
+                let plus = if counts.truncated {
+                    "+"
+                } else {
+                    ""
+                };
+                for (value, count) in &counts.values {
+                    options.insert(
+                        serde_json::to_string(value).unwrap(),
+                        format!("{} ({}{})", tree_node_to_text(&TreeNode::Scalar(value.clone())), count, plus),
+                    );
+                }
+            }
+            let value = match selected.get(&facet.field) {
+                Some(value) => {
+                    let key = serde_json::to_string(value).unwrap();
+                    options
+                        .entry(key.clone())
+                        .or_insert_with(|| format!("{} (0)", tree_node_to_text(&TreeNode::Scalar(value.clone()))));
+                    key
+                },
+                None => String::new(),
+            };
+            let pair = style_export::leaf_input_pair_enum(style_export::LeafInputPairEnumArgs {
+                id: format!("view_facet_{}", facet.field),
+                title: facet.name.clone(),
+                value: value,
+                options: options,
+            });
+            pair.input.ref_on("change", {
+                let eg = eg.clone();
+                let input = pair.input.weak();
+                let common = common.clone();
+                let param_data = param_data.clone();
+                let field = facet.field.clone();
+                move |_| {
+                    let Some(input) = input.upgrade() else {
+                        return;
+                    };
+                    let v = input.raw().dyn_ref::<HtmlSelectElement>().map(|el| el.value()).unwrap_or_default();
+                    common.view_ministate_state.set_facet(field.clone(), serde_json::from_str::<Node>(&v).ok());
+                    eg.event(|pc| {
+                        build_page_view_body(pc, &common, &param_data, None, None);
+                    }).unwrap();
+                }
+            });
+            out.push(pair.root);
+        }
+        return Ok(out);
+    });
 }
 
 fn build_page_view_body(
     pc: &mut ProcessingContext,
-    common: &BuildViewBodyCommon,
+    common: &Rc<BuildViewBodyCommon>,
     param_data: &HashMap<String, Node>,
     restore_playlist_pos: Option<PlaylistRestorePos>,
     offline: Option<String>,
@@ -1914,7 +2907,29 @@ fn build_page_view_body(
     let Some(transport_slot) = common.transport_slot.upgrade() else {
         return;
     };
+    if offline.is_none() {
+        {
+            let vs = common.view_ministate_state.0.borrow();
+            common.offline_view.set(pc, MinistateView {
+                id: common.id.clone(),
+                title: vs.title.clone(),
+                pos: None,
+                params: param_data.clone(),
+                sort: vs.sort.clone(),
+                facets: vs.facets.clone(),
+            });
+        }
+        if !common.facets.is_empty() {
+            if let Some(facets_slot) = common.facets_slot.upgrade() {
+                facets_slot.ref_clear();
+                facets_slot.ref_push(build_facets(&pc.eg(), common, param_data));
+            }
+        }
+    }
     playlist_clear(pc, &state().playlist, common.shuffle, common.track_end_mode);
+    if let Some(selection) = &common.selection {
+        selection.selected.set(pc, vec![]);
+    }
     body.ref_clear();
     body.ref_push(
         build_widget_root_data_rows(
@@ -1927,6 +2942,8 @@ fn build_page_view_body(
             transport_slot,
             (random() * u64::MAX as f64) as u64,
             offline,
+            common.editable,
+            common.selection.clone(),
             &common.config_at,
             &common.config_query_params,
             &vec![Rc::new(DataStackLevel {
@@ -1944,7 +2961,10 @@ pub fn build_page_view(
     id: ViewId,
     title: String,
     view: ClientView,
+    admin: bool,
     params: HashMap<String, Node>,
+    sort: Option<String>,
+    facets: BTreeMap<String, Node>,
     restore_playlist_pos: Option<PlaylistRestorePos>,
     offline: Option<String>,
 ) -> Result<El, String> {
@@ -1954,13 +2974,23 @@ pub fn build_page_view(
             title: title.clone(),
             pos: restore_playlist_pos.clone(),
             params: params.clone(),
+            sort: sort.clone(),
+            facets: facets.clone(),
             offline: offline.clone(),
         })));
+        let facets_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
         let transport_slot = style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
         let body = style_export::cont_view_root(style_export::ContViewRootArgs {
             elements: vec![],
             element_width: view.root.element_width.clone(),
         });
+        let selection = match (&offline, &view.root.selection) {
+            (None, Some(s)) => Some(ViewSelection {
+                node_field: s.node_field.clone(),
+                selected: Prim::new(vec![]),
+            }),
+            _ => None,
+        };
         let common = Rc::new(BuildViewBodyCommon {
             id: id.clone(),
             view_ministate_state: vs.clone(),
@@ -1971,6 +3001,19 @@ pub fn build_page_view(
             config_query_params: view.query_parameter_keys,
             body: body.body.weak(),
             have_media: Rc::new(Cell::new(false)),
+            selection: selection,
+            admin: admin,
+            editable: view.editable,
+            facets: view.facets.clone(),
+            facets_slot: facets_slot.weak(),
+            offline_view: Prim::new(MinistateView {
+                id: id.clone(),
+                title: title.clone(),
+                pos: None,
+                params: params.clone(),
+                sort: sort,
+                facets: facets,
+            }),
         });
         let param_data = Rc::new(RefCell::new(params));
         let mut param_els = vec![];
@@ -2042,12 +3085,7 @@ pub fn build_page_view(
             });
             state().menu_page_buttons.ref_push(unoffline_button);
         } else {
-            let offline_view = Prim::new(MinistateView {
-                id: id.clone(),
-                title: title.clone(),
-                pos: None,
-                params: param_data.borrow().clone(),
-            });
+            let offline_view = common.offline_view.clone();
             let params_debounce = Rc::new(RefCell::new(None));
             for (k, v) in view.parameter_specs {
                 match v {
@@ -2080,24 +3118,18 @@ pub fn build_page_view(
                             }
                         });
                         pair.input.ref_on("input", {
-                            let id = id.clone();
-                            let title = title.clone();
                             let eg = pc.eg();
                             let k = k.clone();
                             let input = pair.input.weak();
                             let common = common.clone();
                             let params_debounce = params_debounce.clone();
                             let param_data = param_data.clone();
-                            let offline_view = offline_view.clone();
                             move |_| *params_debounce.borrow_mut() = Some(Timeout::new(500, {
-                                let id = id.clone();
-                                let title = title.clone();
                                 let input = input.clone();
                                 let common = common.clone();
                                 let param_data = param_data.clone();
                                 let eg = eg.clone();
                                 let k = k.clone();
-                                let offline_view = offline_view.clone();
                                 move || {
                                     let Some(input) = input.upgrade() else {
                                         return;
@@ -2115,12 +3147,6 @@ pub fn build_page_view(
                                     common.view_ministate_state.set_param(k.clone(), v.clone());
                                     param_data.borrow_mut().insert(k, v);
                                     eg.event(|pc| {
-                                        offline_view.set(pc, MinistateView {
-                                            id: id.clone(),
-                                            title: title.clone(),
-                                            pos: None,
-                                            params: param_data.borrow().clone(),
-                                        });
                                         build_page_view_body(pc, &common, &*param_data.borrow(), None, None);
                                     }).unwrap();
                                 }
@@ -2130,6 +3156,43 @@ pub fn build_page_view(
                     },
                 }
             }
+            if !view.sorts.is_empty() {
+                let mut options = HashMap::new();
+                options.insert(String::new(), format!("Default"));
+                for name in &view.sorts {
+                    options.insert(name.clone(), name.clone());
+                }
+                let pair = style_export::leaf_input_pair_enum(style_export::LeafInputPairEnumArgs {
+                    id: format!("view_sort"),
+                    title: format!("Sort"),
+                    value: vs.0.borrow().sort.clone().unwrap_or_default(),
+                    options: options,
+                });
+                pair.input.ref_on("change", {
+                    let eg = pc.eg();
+                    let input = pair.input.weak();
+                    let common = common.clone();
+                    let param_data = param_data.clone();
+                    move |_| {
+                        let Some(input) = input.upgrade() else {
+                            return;
+                        };
+                        let v = input.raw().dyn_ref::<HtmlSelectElement>().map(|el| el.value()).unwrap_or_default();
+                        common.view_ministate_state.set_sort(if v.is_empty() {
+                            None
+                        } else {
+                            Some(v)
+                        });
+                        eg.event(|pc| {
+                            build_page_view_body(pc, &common, &*param_data.borrow(), None, None);
+                        }).unwrap();
+                    }
+                });
+                param_els.push(pair.root);
+            }
+            if !view.facets.is_empty() {
+                param_els.push(facets_slot);
+            }
             if LocalStorage::get::<bool>(LOCALSTORAGE_OFFLINE_ENABLED).unwrap_or(false) {
                 let offline_button = style_export::leaf_menu_page_button_offline().root;
                 offline_button.ref_on("click", {
@@ -2216,11 +3279,99 @@ pub fn build_page_view(
                 );
                 state().menu_page_buttons.ref_push(offline_button);
             }
+            let save_preset_button = style_export::leaf_menu_page_button_save_preset().root;
+            save_preset_button.ref_own(
+                |b| link!((_pc = pc), (whoami = state().whoami.clone()), (), (b = b.weak()) {
+                    let b = b.upgrade()?;
+                    b.ref_modify_classes(
+                        &[
+                            (
+                                &style_export::class_state_hide().value,
+                                !matches!(&*whoami.borrow(), Some(RespWhoAmI::User(_))),
+                            ),
+                        ],
+                    );
+                }),
+            );
+            save_preset_button.ref_on("click", {
+                let offline_view = offline_view.clone();
+                let eg = pc.eg();
+                let title = title.clone();
+                move |_| {
+                    let modal_res = style_export::cont_view_modal_preset_name(
+                        style_export::ContViewModalPresetNameArgs { default_name: title.clone() },
+                    );
+                    modal_res.button_close.ref_on("click", {
+                        let modal_el = modal_res.root.weak();
+                        let eg = eg.clone();
+                        move |_| eg.event(|_pc| {
+                            let Some(modal_el) = modal_el.upgrade() else {
+                                return;
+                            };
+                            modal_el.ref_replace(vec![]);
+                        }).unwrap()
+                    });
+                    modal_res.root.ref_on("click", {
+                        let modal_el = modal_res.root.weak();
+                        let eg = eg.clone();
+                        move |_| eg.event(|_pc| {
+                            let Some(modal_el) = modal_el.upgrade() else {
+                                return;
+                            };
+                            modal_el.ref_replace(vec![]);
+                        }).unwrap()
+                    });
+                    on_thinking(&modal_res.button_ok, {
+                        let offline_view = offline_view.clone();
+                        let eg = eg.clone();
+                        let modal_el = modal_res.root.weak();
+                        let input = modal_res.input.weak();
+                        move || {
+                            let offline_view = offline_view.clone();
+                            let eg = eg.clone();
+                            let modal_el = modal_el.clone();
+                            let input = input.clone();
+                            async move {
+                                let name = input
+                                    .upgrade()
+                                    .and_then(|el| el.raw().dyn_ref::<HtmlInputElement>().map(|i| i.value()))
+                                    .unwrap_or_default();
+                                let view = offline_view.borrow().clone();
+                                if let Err(e) =
+                                    user_saved_put(eg.clone(), UserSavedItem::Preset(UserSavedPreset {
+                                        name: name,
+                                        view_id: view.id,
+                                        params: view.params,
+                                        sort: view.sort,
+                                        facets: view.facets,
+                                    })).await {
+                                    state().log.log(&format!("Error saving view preset: {}", e));
+                                }
+                                if let Some(modal_el) = modal_el.upgrade() {
+                                    modal_el.ref_replace(vec![]);
+                                }
+                            }
+                        }
+                    });
+                    state().modal_stack.ref_push(modal_res.root.clone());
+                }
+            });
+            state().menu_page_buttons.ref_push(save_preset_button);
         }
+        let bulk = shed!{
+            let Some(selection) = &common.selection else {
+                break None;
+            };
+            let Some(selection_config) = &common.config_at.selection else {
+                break None;
+            };
+            Some(build_bulk_bar(pc, &common, &title, &param_data, selection, &selection_config.actions))
+        };
         build_page_view_body(pc, &common, &*param_data.borrow(), restore_playlist_pos, offline);
         return Ok(style_export::cont_page_view(style_export::ContPageViewArgs {
             transport: Some(transport_slot),
             params: param_els,
+            bulk: bulk,
             elements: body.root,
         }).root);
     }).unwrap();
@@ -1,5 +1,8 @@
 use {
-    chrono::Utc,
+    chrono::{
+        DateTime,
+        Utc,
+    },
     crate::libnonlink::offline::{
         OPFS_OFFLINE_FILES_COMIC_PAGES_DIR,
         get_opfs_url_with_colocated_mime,
@@ -48,6 +51,10 @@ use {
             GENTYPE_CBZDIR,
             GENTYPE_EPUBHTML,
             GENTYPE_VTT,
+            PlayEvent,
+            ReqPlayPositions,
+            ReqPlayReport,
+            RespWhoAmI,
             link::{
                 Prepare,
                 PrepareAudio,
@@ -78,6 +85,7 @@ use {
         time::Duration,
     },
     super::{
+        api::req_post_json,
         ministate::PlaylistRestorePos,
         state::{
             MinistateViewState,
@@ -165,6 +173,9 @@ pub struct PlaylistEntry {
     pub source_file: FileHash,
     pub media_type: PlaylistEntryMediaType,
     pub media: Box<dyn PlaylistMedia>,
+    /// Position saved on the server (ex: from another device), used the first time
+    /// the entry is played.
+    pub resume_time: Cell<Option<f64>>,
 }
 
 /// Callback type for pulling more playlist entries. Returns Some(entries) for a
@@ -194,6 +205,7 @@ pub struct PlaylistState_ {
     pub play_buttons: RefCell<BTreeMap<PlaylistIndex, WeakEl>>,
     pub center_to_playing_bg: RefCell<Option<rooting::ScopeValue>>,
     pub movement_bg: RefCell<Option<rooting::ScopeValue>>,
+    pub play_report: RefCell<Option<PlayReportState>>,
 }
 
 #[derive(Clone)]
@@ -236,6 +248,7 @@ pub fn state_new(pc: &mut ProcessingContext, log: Rc<dyn Log>, env: Env) -> (Pla
             let eg = pc.eg();
             move |_| eg.event(|pc| {
                 let playlist = &state().playlist;
+                play_report_ended(playlist);
                 match playlist.0.track_end_mode.get() {
                     TrackEndMode::Advance => {
                         playlist_next(pc, playlist, None);
@@ -300,6 +313,7 @@ pub fn state_new(pc: &mut ProcessingContext, log: Rc<dyn Log>, env: Env) -> (Pla
         play_buttons: RefCell::new(Default::default()),
         center_to_playing_bg: RefCell::new(None),
         movement_bg: RefCell::new(None),
+        play_report: RefCell::new(None),
     }));
     let media_session = window().navigator().media_session();
 
@@ -504,11 +518,12 @@ pub fn state_new(pc: &mut ProcessingContext, log: Rc<dyn Log>, env: Env) -> (Pla
                 let Some(playing_i) = &*state.0.playing_i.borrow() else {
                     return;
                 };
+                let entry;
                 let time;
                 let max_time;
                 {
                     let playlist = state.0.playlist.borrow();
-                    let entry: &Rc<PlaylistEntry> = playlist.get(&*playing_i).unwrap();
+                    entry = playlist.get(&*playing_i).cloned().unwrap();
                     time = entry.media.pm_get_time();
                     max_time = entry.media.pm_get_max_time();
                 }
@@ -517,6 +532,7 @@ pub fn state_new(pc: &mut ProcessingContext, log: Rc<dyn Log>, env: Env) -> (Pla
                     return;
                 }
                 *last_state.borrow_mut() = Some(new_state);
+                play_report_tick(&state, &entry, playing_i, time, max_time, state.0.playing.get());
                 eg.event(|pc| {
                     state.0.media_time.set(pc, time);
                     state.0.media_max_time.set(pc, max_time);
@@ -800,11 +816,13 @@ pub async fn playlist_extend(
     offline: bool,
 ) {
     *playlist_state.0.view_ministate_state.borrow_mut() = Some(vs);
+    let resume_times = fetch_resume_times(playlist_state, &entries).await;
     for entry in entries {
+        let resume_time = resume_times.get(&entry.source_file).cloned();
         let time = if restore_pos.as_ref().map(|r| r.index == entry.index).unwrap_or(false) {
             restore_pos.as_ref().map(|r| r.time).unwrap_or(0.)
         } else {
-            0.
+            resume_time.unwrap_or(0.)
         };
         let media =
             build_entry_media(
@@ -824,6 +842,7 @@ pub async fn playlist_extend(
             source_file: entry.source_file,
             media_type: entry.media_type,
             media: media,
+            resume_time: Cell::new(resume_time),
         }));
         if let Some(restore_pos) = restore_pos {
             eg.event(|pc| {
@@ -858,6 +877,7 @@ pub fn playlist_clear(pc: &mut ProcessingContext, state: &PlaylistState, shuffle
     *state.0.image_advance_timeout.borrow_mut() = None;
     *state.0.source.borrow_mut() = None;
     *state.0.movement_bg.borrow_mut() = None;
+    *state.0.play_report.borrow_mut() = None;
     state.0.play_buttons.borrow_mut().clear();
 }
 
@@ -874,7 +894,7 @@ pub fn playlist_toggle_play(pc: &mut ProcessingContext, state: &PlaylistState, i
                         state.0.playing.set(pc, false);
                     } else {
                         state.0.playing_i.set(pc, Some(i.clone()));
-                        state.0.playing_time.set(pc, 0.);
+                        state.0.playing_time.set(pc, playlist_take_resume_time(&state, i));
                     }
                 } else {
                     if state.0.playlist.borrow().is_empty() {
@@ -885,7 +905,7 @@ pub fn playlist_toggle_play(pc: &mut ProcessingContext, state: &PlaylistState, i
                         Some(current_i) => *current_i != i,
                         None => true,
                     } {
-                        state.0.playing_time.set(pc, 0.);
+                        state.0.playing_time.set(pc, playlist_take_resume_time(&state, &i));
                     }
                     state.0.playing_i.set(pc, Some(i));
                     state.0.playing.set(pc, true);
@@ -908,7 +928,7 @@ pub fn playlist_next(pc: &mut ProcessingContext, state: &PlaylistState, basis: O
                 eg.event(|pc| {
                     state.0.playing.set(pc, true);
                     state.0.playing_i.set(pc, Some(i.clone()));
-                    state.0.playing_time.set(pc, 0.);
+                    state.0.playing_time.set(pc, playlist_take_resume_time(&state, i));
                 }).unwrap();
                 return;
             }
@@ -932,14 +952,16 @@ pub fn playlist_next(pc: &mut ProcessingContext, state: &PlaylistState, basis: O
                                 .as_ref()
                                 .and_then(|vs| vs.0.borrow().offline.clone())
                                 .is_some();
+                        let resume_times = fetch_resume_times(&state, &entries).await;
                         for entry in entries {
+                            let resume_time = resume_times.get(&entry.source_file).cloned();
                             let media =
                                 build_entry_media(
                                     &state,
                                     entry.media_type,
                                     &entry.source_file,
                                     entry.original_language.clone(),
-                                    0.,
+                                    resume_time.unwrap_or(0.),
                                     offline,
                                 ).await;
                             state.0.playlist.borrow_mut().insert(entry.index.clone(), Rc::new(PlaylistEntry {
@@ -951,6 +973,7 @@ pub fn playlist_next(pc: &mut ProcessingContext, state: &PlaylistState, basis: O
                                 source_file: entry.source_file,
                                 media_type: entry.media_type,
                                 media: media,
+                                resume_time: Cell::new(resume_time),
                             }));
                         }
                         if let Some(i) = state.0.playing_i.get() {
@@ -959,7 +982,7 @@ pub fn playlist_next(pc: &mut ProcessingContext, state: &PlaylistState, basis: O
                                 eg.event(|pc| {
                                     state.0.playing.set(pc, true);
                                     state.0.playing_i.set(pc, Some(next_i.clone()));
-                                    state.0.playing_time.set(pc, 0.);
+                                    state.0.playing_time.set(pc, playlist_take_resume_time(&state, next_i));
                                 }).unwrap();
                                 return;
                             };
@@ -1000,7 +1023,7 @@ pub fn playlist_previous(pc: &mut ProcessingContext, state: &PlaylistState, basi
                 state.0.playlist.borrow().range((Bound::Unbounded, Bound::Excluded(i))).rev().next() {
                 eg.event(|pc| {
                     state.0.playing_i.set(pc, Some(i.clone()));
-                    state.0.playing_time.set(pc, 0.);
+                    state.0.playing_time.set(pc, playlist_take_resume_time(&state, i));
                 }).unwrap();
             }
         }
@@ -1027,3 +1050,163 @@ pub fn playlist_resume(pc: &mut ProcessingContext, state: &PlaylistState) {
 pub fn playlist_seek(pc: &mut ProcessingContext, state: &PlaylistState, time: f64) {
     state.0.playing_time.set(pc, time);
 }
+
+// # Play state sync (resume positions and play history for the user)
+/// Audio shorter than this (ex: music) always starts from the beginning.
+const RESUME_MIN_AUDIO_DURATION: f64 = 20. * 60.;
+
+/// How often to send the position while playing.
+const PLAY_REPORT_INTERVAL_SECS: i64 = 15;
+
+pub struct PlayReportState {
+    index: PlaylistIndex,
+    playing: bool,
+    finished: bool,
+    last_sent: DateTime<Utc>,
+}
+
+fn play_report_enabled(entry: &PlaylistEntry) -> bool {
+    if matches!(entry.media_type, PlaylistEntryMediaType::Image) {
+        return false;
+    }
+    return matches!(&*state().whoami.borrow(), Some(RespWhoAmI::User(_)));
+}
+
+fn play_report_send(
+    state: &PlaylistState,
+    entry: &PlaylistEntry,
+    event: PlayEvent,
+    position: f64,
+    duration: Option<f64>,
+) {
+    let req = ReqPlayReport {
+        file: entry.source_file.clone(),
+        event: event,
+        position: position,
+        duration: duration,
+    };
+    let log = state.0.log.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = req_post_json(req).await {
+            log.log(&format!("Error reporting play state: {}", e));
+        }
+    });
+
+    // Superseded by the reported position
+    entry.resume_time.set(None);
+}
+
+/// Report play state changes for the playing entry, called when the time or
+/// playing state changes.
+fn play_report_tick(
+    state: &PlaylistState,
+    entry: &PlaylistEntry,
+    playing_i: &PlaylistIndex,
+    time: f64,
+    max_time: Option<f64>,
+    playing: bool,
+) {
+    if !play_report_enabled(entry) {
+        return;
+    }
+    let now = Utc::now();
+    let mut report = state.0.play_report.borrow_mut();
+    match &mut *report {
+        Some(report) if &report.index == playing_i => {
+            // Books and comics don't end on their own, treat reaching the last page as
+            // finishing.
+            let at_end =
+                matches!(entry.media_type, PlaylistEntryMediaType::Book | PlaylistEntryMediaType::Comic) &&
+                    max_time.map(|m| time >= m - 1.).unwrap_or(false);
+            if at_end && !report.finished {
+                report.finished = true;
+                play_report_send(state, entry, PlayEvent::Finished, time, max_time);
+            } else if report.playing != playing ||
+                (playing && (now - report.last_sent).num_seconds() >= PLAY_REPORT_INTERVAL_SECS) {
+                play_report_send(state, entry, PlayEvent::Position, time, max_time);
+            } else {
+                return;
+            }
+            report.playing = playing;
+            report.last_sent = now;
+        },
+        _ => {
+            if !playing {
+                return;
+            }
+            *report = Some(PlayReportState {
+                index: playing_i.clone(),
+                playing: true,
+                finished: false,
+                last_sent: now,
+            });
+            play_report_send(state, entry, PlayEvent::Started, time, max_time);
+        },
+    }
+}
+
+/// Report that audio/video played to the end. The next play (ex: when looping)
+/// counts as a new start.
+fn play_report_ended(state: &PlaylistState) {
+    let Some(playing_i) = state.0.playing_i.get() else {
+        return;
+    };
+    let entry = state.0.playlist.borrow().get(&playing_i).cloned().unwrap();
+    if !play_report_enabled(&entry) {
+        return;
+    }
+    let max_time = entry.media.pm_get_max_time();
+    play_report_send(state, &entry, PlayEvent::Finished, max_time.unwrap_or(entry.media.pm_get_time()), max_time);
+    *state.0.play_report.borrow_mut() = None;
+}
+
+/// Get saved positions to resume the entries at, for unfinished entries.
+async fn fetch_resume_times(
+    playlist_state: &PlaylistState,
+    entries: &Vec<PlaylistPushArg>,
+) -> HashMap<FileHash, f64> {
+    if !matches!(&*state().whoami.borrow(), Some(RespWhoAmI::User(_))) {
+        return HashMap::new();
+    }
+    let mut media_types = HashMap::new();
+    for entry in entries {
+        if matches!(entry.media_type, PlaylistEntryMediaType::Image) {
+            continue;
+        }
+        media_types.insert(entry.source_file.clone(), entry.media_type);
+    }
+    if media_types.is_empty() {
+        return HashMap::new();
+    }
+    let positions = match req_post_json(ReqPlayPositions { files: media_types.keys().cloned().collect() }).await {
+        Ok(p) => p,
+        Err(e) => {
+            playlist_state.0.log.log(&format!("Error getting saved play positions: {}", e));
+            return HashMap::new();
+        },
+    };
+    let mut out = HashMap::new();
+    for position in positions {
+        if position.finished || position.position <= 0. {
+            continue;
+        }
+        let Some(media_type) = media_types.get(&position.file) else {
+            continue;
+        };
+        if matches!(media_type, PlaylistEntryMediaType::Audio) &&
+            position.duration.unwrap_or(0.) < RESUME_MIN_AUDIO_DURATION {
+            continue;
+        }
+        out.insert(position.file, position.position);
+    }
+    return out;
+}
+
+/// The time to start the entry at when switching to it - the saved position the
+/// first time, otherwise the beginning.
+fn playlist_take_resume_time(state: &PlaylistState, i: &PlaylistIndex) -> f64 {
+    let Some(entry) = state.0.playlist.borrow().get(i).cloned() else {
+        return 0.;
+    };
+    return entry.resume_time.take().unwrap_or(0.);
+}
@@ -33,7 +33,10 @@ use {
             view::ViewId,
         },
         triple::Node,
-        wire::RespWhoAmI,
+        wire::{
+            RespWhoAmI,
+            UserSavedItem,
+        },
     },
     shared_wasm::{
         log::{
@@ -46,7 +49,10 @@ use {
     },
     std::{
         cell::RefCell,
-        collections::HashMap,
+        collections::{
+            BTreeMap,
+            HashMap,
+        },
         rc::Rc,
     },
     super::{
@@ -58,6 +64,7 @@ use {
         page_form::build_page_form,
         page_history::build_page_history,
         page_node_edit::build_page_node_edit,
+        page_node_graph::build_page_node_graph,
         page_node_view::build_page_node_view,
         page_opfs::build_page_opfs,
         page_settings::build_page_settings,
@@ -95,6 +102,9 @@ pub struct State_ {
     pub offline_list: List<(String, MinistateView)>,
     pub client_config: WaitVal<Prim<Rc<ClientConfig>>>,
     pub whoami: Prim<Option<RespWhoAmI>>,
+    /// Presets, bookmarks, and pinned menu items stored on the server; empty unless
+    /// logged in as a user.
+    pub user_saved: Prim<Vec<UserSavedItem>>,
     pub menu_open: Prim<bool>,
     pub main_title: El,
     pub menu_page_buttons: El,
@@ -149,13 +159,26 @@ pub fn build_ministate(pc: &mut ProcessingContext, s: &Ministate) {
                 let view_id = v.id.clone();
                 let pos = v.pos.clone();
                 let params = v.params.clone();
+                let sort = v.sort.clone();
+                let facets = v.facets.clone();
                 let eg = pc.eg();
                 async move {
                     let client_config = state().client_config.get().await.borrow().clone();
                     let Some(view) = client_config.views.get(&view_id) else {
                         return Err(format!("No view with id [{}] in config", view_id));
                     };
-                    return build_page_view(eg, view_id, title, view.clone(), params, pos, None).map(|x| vec![x]);
+                    return build_page_view(
+                        eg,
+                        view_id,
+                        title,
+                        view.clone(),
+                        client_config.admin,
+                        params,
+                        sort,
+                        facets,
+                        pos,
+                        None,
+                    ).map(|x| vec![x]);
                 }
             }));
         },
@@ -177,7 +200,10 @@ pub fn build_ministate(pc: &mut ProcessingContext, s: &Ministate) {
                         view_id,
                         title,
                         view.clone(),
+                        client_config.admin,
                         params,
+                        None,
+                        Default::default(),
                         pos,
                         Some(key),
                     ).map(|x| vec![x]);
@@ -190,13 +216,14 @@ pub fn build_ministate(pc: &mut ProcessingContext, s: &Ministate) {
                 let title = f.title.clone();
                 let form_id = f.id.clone();
                 let params = f.params.clone();
+                let list_params = f.list_params.clone();
                 let eg = pc.eg();
                 async move {
                     let client_config = state().client_config.get().await.borrow().clone();
                     let Some(form) = client_config.forms.get(&form_id) else {
                         return Err(format!("No menu item with id [{}] in config", form_id));
                     };
-                    return build_page_form(eg, form_id, title, form.clone(), params).map(|x| vec![x]);
+                    return build_page_form(eg, form_id, title, form.clone(), params, list_params).map(|x| vec![x]);
                 }
             }));
         },
@@ -208,6 +235,10 @@ pub fn build_ministate(pc: &mut ProcessingContext, s: &Ministate) {
             playlist_clear(pc, &state().playlist, false, Default::default());
             build_page_node_view(pc, &ms.title, &ms.node);
         },
+        Ministate::NodeGraph(ms) => {
+            playlist_clear(pc, &state().playlist, false, Default::default());
+            build_page_node_graph(pc, &ms.title, &ms.node);
+        },
         Ministate::ListEdit(ms) => {
             playlist_clear(pc, &state().playlist, false, Default::default());
             build_page_list_edit(pc, &ms.title, &ms.node);
@@ -268,52 +299,64 @@ pub struct MinistateViewState_ {
     pub title: String,
     pub pos: Option<PlaylistRestorePos>,
     pub params: HashMap<String, Node>,
+    pub sort: Option<String>,
+    pub facets: BTreeMap<String, Node>,
     pub offline: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct MinistateViewState(pub Rc<RefCell<MinistateViewState_>>);
 
+fn record_view_ministate(s: &MinistateViewState_) {
+    if let Some(key) = &s.offline {
+        record_replace_ministate(&state().log, &Ministate::OfflineView(MinistateOfflineView {
+            id: s.view_id.clone(),
+            title: s.title.clone(),
+            pos: s.pos.clone(),
+            params: s.params.clone(),
+            key: key.clone(),
+        }));
+    } else {
+        record_replace_ministate(&state().log, &Ministate::View(MinistateView {
+            id: s.view_id.clone(),
+            title: s.title.clone(),
+            pos: s.pos.clone(),
+            params: s.params.clone(),
+            sort: s.sort.clone(),
+            facets: s.facets.clone(),
+        }));
+    }
+}
+
 impl MinistateViewState {
     pub fn set_pos(&self, pos: Option<PlaylistRestorePos>) {
         let mut s = self.0.borrow_mut();
         s.pos = pos;
-        if let Some(key) = &s.offline {
-            record_replace_ministate(&state().log, &Ministate::OfflineView(MinistateOfflineView {
-                id: s.view_id.clone(),
-                title: s.title.clone(),
-                pos: s.pos.clone(),
-                params: s.params.clone(),
-                key: key.clone(),
-            }));
-        } else {
-            record_replace_ministate(&state().log, &Ministate::View(MinistateView {
-                id: s.view_id.clone(),
-                title: s.title.clone(),
-                pos: s.pos.clone(),
-                params: s.params.clone(),
-            }));
-        }
+        record_view_ministate(&s);
     }
 
     pub fn set_param(&self, k: String, v: Node) {
         let mut s = self.0.borrow_mut();
         s.params.insert(k, v);
-        if let Some(key) = &s.offline {
-            record_replace_ministate(&state().log, &Ministate::OfflineView(MinistateOfflineView {
-                id: s.view_id.clone(),
-                title: s.title.clone(),
-                pos: s.pos.clone(),
-                params: s.params.clone(),
-                key: key.clone(),
-            }));
-        } else {
-            record_replace_ministate(&state().log, &Ministate::View(MinistateView {
-                id: s.view_id.clone(),
-                title: s.title.clone(),
-                pos: s.pos.clone(),
-                params: s.params.clone(),
-            }));
+        record_view_ministate(&s);
+    }
+
+    pub fn set_sort(&self, sort: Option<String>) {
+        let mut s = self.0.borrow_mut();
+        s.sort = sort;
+        record_view_ministate(&s);
+    }
+
+    pub fn set_facet(&self, field: String, value: Option<Node>) {
+        let mut s = self.0.borrow_mut();
+        match value {
+            Some(value) => {
+                s.facets.insert(field, value);
+            },
+            None => {
+                s.facets.remove(&field);
+            },
         }
+        record_view_ministate(&s);
     }
 }
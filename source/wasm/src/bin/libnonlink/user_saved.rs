@@ -0,0 +1,104 @@
+use {
+    crate::libnonlink::{
+        api::req_post_json,
+        ministate::{
+            Ministate,
+            MinistateNodeView,
+            MinistateView,
+        },
+        state::state,
+    },
+    lunk::EventGraph,
+    shared::interface::{
+        config::{
+            ClientMenuItem,
+            ClientMenuItemDetail,
+        },
+        wire::{
+            ReqUserSavedDelete,
+            ReqUserSavedList,
+            ReqUserSavedPut,
+            UserSavedBookmark,
+            UserSavedItem,
+            UserSavedKey,
+            UserSavedPreset,
+        },
+    },
+};
+
+/// Fetch the user's saved items from the server, replacing the local copy. Only
+/// valid when logged in as a user.
+pub async fn user_saved_refresh(eg: EventGraph) -> Result<(), String> {
+    let items = req_post_json(ReqUserSavedList).await?;
+    eg.event(|pc| {
+        state().user_saved.set(pc, items);
+    }).unwrap();
+    return Ok(());
+}
+
+/// Save an item on the server, replacing any existing item with the same key.
+pub async fn user_saved_put(eg: EventGraph, item: UserSavedItem) -> Result<(), String> {
+    req_post_json(ReqUserSavedPut(item.clone())).await?;
+    eg.event(|pc| {
+        let key = item.key();
+        let mut items = state().user_saved.borrow().clone();
+        if let Some(existing) = items.iter_mut().find(|i| i.key() == key) {
+            *existing = item;
+        } else {
+            items.push(item);
+        }
+        state().user_saved.set(pc, items);
+    }).unwrap();
+    return Ok(());
+}
+
+pub async fn user_saved_delete(eg: EventGraph, key: UserSavedKey) -> Result<(), String> {
+    req_post_json(ReqUserSavedDelete(key.clone())).await?;
+    eg.event(|pc| {
+        let mut items = state().user_saved.borrow().clone();
+        items.retain(|i| i.key() != key);
+        state().user_saved.set(pc, items);
+    }).unwrap();
+    return Ok(());
+}
+
+pub fn preset_ministate(preset: &UserSavedPreset) -> Ministate {
+    return Ministate::View(MinistateView {
+        id: preset.view_id.clone(),
+        title: preset.name.clone(),
+        pos: None,
+        params: preset.params.clone(),
+        sort: preset.sort.clone(),
+        facets: preset.facets.clone(),
+    });
+}
+
+pub fn bookmark_ministate(bookmark: &UserSavedBookmark) -> Ministate {
+    return Ministate::NodeView(MinistateNodeView {
+        title: bookmark.name.clone(),
+        node: bookmark.node.clone(),
+    });
+}
+
+/// All non-section menu items (things that can be pinned), with the titles of the
+/// sections containing them.
+pub fn menu_item_paths(menu: &Vec<ClientMenuItem>) -> Vec<(Vec<String>, ClientMenuItem)> {
+    fn walk(out: &mut Vec<(Vec<String>, ClientMenuItem)>, carry_titles: &Vec<String>, items: &Vec<ClientMenuItem>) {
+        for item in items {
+            match &item.detail {
+                ClientMenuItemDetail::Section(section) => {
+                    let mut sub_carry_titles = carry_titles.clone();
+                    sub_carry_titles.push(item.name.clone());
+                    walk(out, &sub_carry_titles, &section.children);
+                },
+                ClientMenuItemDetail::Page(_) => {
+                    out.push((carry_titles.clone(), item.clone()));
+                },
+            }
+        }
+    }
+
+    let mut out = vec![];
+    walk(&mut out, &vec![], menu);
+    return out;
+}
@@ -1,10 +1,19 @@
 use {
+    chrono::{
+        DateTime,
+        NaiveDate,
+        NaiveDateTime,
+    },
     flowcontrol::exenum,
     shared::{
         interface::{
             config::view::{
                 FieldOrLiteral,
                 FieldOrLiteralString,
+                Widget,
+                WidgetCondition,
+                WidgetIf,
+                WidgetSwitch,
             },
             triple::{
                 FileHash,
@@ -84,9 +93,85 @@ pub fn tree_node_to_text(data_at: &TreeNode) -> String {
     }
 }
 
+/// Parse a date or datetime field for placing rows in time. Datetimes keep the
+/// date and time as written rather than converting time zones.
+pub fn maybe_get_date(config_at: &String, data_stack: &Vec<Rc<DataStackLevel>>) -> Option<NaiveDateTime> {
+    let Some(TreeNode::Scalar(Node::Value(serde_json::Value::String(v)))) =
+        maybe_get_field(config_at, data_stack) else {
+            return None;
+        };
+    let v = v.trim();
+    if let Ok(v) = DateTime::parse_from_rfc3339(v) {
+        return Some(v.naive_local());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"] {
+        if let Ok(v) = NaiveDateTime::parse_from_str(v, format) {
+            return Some(v);
+        }
+    }
+    if let Ok(v) = NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+        return Some(v.and_time(Default::default()));
+    }
+    return None;
+}
+
 pub fn unwrap_value_media_hash(data_at: &Node) -> Result<FileHash, String> {
     match data_at {
         Node::File(v) => return Ok(v.clone()),
         _ => return Err(format!("Media source is not a file: {}", serde_json::to_string(data_at).unwrap())),
     }
 }
+
+pub fn eval_condition(config_at: &WidgetCondition, data_stack: &Vec<Rc<DataStackLevel>>) -> bool {
+    match config_at {
+        WidgetCondition::Exists(config_at) => {
+            match maybe_get_field_or_literal(config_at, data_stack) {
+                None => return false,
+                Some(TreeNode::Scalar(Node::Value(serde_json::Value::Null))) => return false,
+                Some(TreeNode::Scalar(Node::Value(serde_json::Value::String(v)))) => return !v.is_empty(),
+                Some(TreeNode::Array(v)) => return !v.is_empty(),
+                Some(_) => return true,
+            }
+        },
+        WidgetCondition::Equals(config_at) => {
+            let Some(a) = maybe_get_field_or_literal(&config_at.a, data_stack) else {
+                return false;
+            };
+            let Some(b) = maybe_get_field_or_literal(&config_at.b, data_stack) else {
+                return false;
+            };
+            return a == b;
+        },
+        WidgetCondition::Mime(config_at) => {
+            let Some(TreeNode::Scalar(node)) = maybe_get_field_or_literal(&config_at.data, data_stack) else {
+                return false;
+            };
+            let Some(mime) = maybe_get_meta(data_stack, &node).and_then(|m| m.mime.as_ref()) else {
+                return false;
+            };
+            return mime.starts_with(&config_at.prefix);
+        },
+        WidgetCondition::Not(config_at) => return !eval_condition(config_at, data_stack),
+        WidgetCondition::All(config_at) => return config_at.iter().all(|c| eval_condition(c, data_stack)),
+        WidgetCondition::Any(config_at) => return config_at.iter().any(|c| eval_condition(c, data_stack)),
+    }
+}
+
+/// The widget to show for an `if`, if any.
+pub fn if_branch<'a>(config_at: &'a WidgetIf, data_stack: &Vec<Rc<DataStackLevel>>) -> Option<&'a Widget> {
+    if eval_condition(&config_at.condition, data_stack) {
+        return Some(&config_at.then);
+    } else {
+        return config_at.otherwise.as_deref();
+    }
+}
+
+/// The widget to show for a `switch`, if any.
+pub fn switch_branch<'a>(config_at: &'a WidgetSwitch, data_stack: &Vec<Rc<DataStackLevel>>) -> Option<&'a Widget> {
+    for case in &config_at.cases {
+        if eval_condition(&case.condition, data_stack) {
+            return Some(&case.widget);
+        }
+    }
+    return config_at.default.as_deref();
+}
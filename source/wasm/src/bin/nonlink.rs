@@ -69,6 +69,10 @@ use {
             build_ministate,
             state,
         },
+        user_saved::{
+            menu_item_paths,
+            user_saved_refresh,
+        },
     },
     lunk::{
         EventGraph,
@@ -94,6 +98,7 @@ use {
                 ReqGetClientConfig,
                 ReqWhoAmI,
                 RespWhoAmI,
+                UserSavedItem,
             },
         },
     },
@@ -213,6 +218,7 @@ pub fn main() {
             main_body: main_body.clone(),
             client_config: WaitVal::new(),
             whoami: Prim::new(None),
+            user_saved: Prim::new(vec![]),
             log1: log1,
             log: log.clone(),
             current_list: Prim::new(shed!{
@@ -284,9 +290,13 @@ pub fn main() {
                     if want_logged_in() && whoami == RespWhoAmI::Public {
                         redirect_login(&state().env.base_url);
                     }
+                    let is_user = matches!(whoami, RespWhoAmI::User(_));
                     eg.event(|pc| {
                         state().whoami.set(pc, Some(whoami));
                     }).unwrap();
+                    if is_user {
+                        user_saved_refresh(eg.clone()).await?;
+                    }
                     return Ok(());
                 }.await {
                     Ok(_) => { },
@@ -339,7 +349,11 @@ pub fn main() {
                                 let menu_dynamic =
                                     style_export::cont_group(style_export::ContGroupArgs { children: vec![] }).root;
                                 menu_dynamic.ref_own(
-                                    |g| link!((pc = pc), (client_config = client_config), (), (g = g.weak()) {
+                                    |g| link!(
+                                        (pc = pc),
+                                        (client_config = client_config, user_saved = state().user_saved.clone()),
+                                        (),
+                                        (g = g.weak()) {
                                         fn build_menu_item(
                                             pc: &mut ProcessingContext,
                                             config: &ClientConfig,
@@ -383,6 +397,8 @@ pub fn main() {
                                                                                 .iter()
                                                                                 .map(|(k, v)| (k.clone(), v.clone()))
                                                                                 .collect(),
+                                                                            sort: None,
+                                                                            facets: Default::default(),
                                                                         })
                                                                     ),
                                                                 }
@@ -405,6 +421,7 @@ pub fn main() {
                                                                                 .iter()
                                                                                 .map(|(k, v)| (k.clone(), v.clone()))
                                                                                 .collect(),
+                                                                            list_params: Default::default(),
                                                                         })
                                                                     ),
                                                                 }
@@ -458,6 +475,27 @@ pub fn main() {
                                         let g = g.upgrade()?;
                                         let client_config = client_config.borrow().clone();
                                         g.ref_clear();
+                                        let pinned = user_saved.borrow().iter().filter_map(|i| match i {
+                                            UserSavedItem::PinnedMenuItem(id) => Some(id.clone()),
+                                            _ => None,
+                                        }).collect::<Vec<_>>();
+                                        if !pinned.is_empty() {
+                                            let paths = menu_item_paths(&client_config.menu);
+                                            let mut children = vec![];
+                                            for id in pinned {
+                                                let Some((carry_titles, item)) =
+                                                    paths.iter().find(|(_, item)| item.id == id) else {
+                                                    continue;
+                                                };
+                                                children.push(build_menu_item(pc, &client_config, carry_titles, item));
+                                            }
+                                            g.ref_push(
+                                                style_export::cont_menu_group(style_export::ContMenuGroupArgs {
+                                                    title: format!("Pinned"),
+                                                    children: children,
+                                                }).root,
+                                            );
+                                        }
                                         for item in &client_config.menu {
                                             g.ref_push(build_menu_item(pc, &client_config, &vec![], item));
                                         }
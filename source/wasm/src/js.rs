@@ -167,10 +167,14 @@ pub mod style_export {
             Serialize,
         },
         shared::interface::config::view::{
+            CalendarPeriod,
+            ChartType,
             Direction,
             Orientation,
             TextSizeMode,
+            TimelineGroup,
             TransAlign,
+            WidgetEditInput,
         },
         std::collections::HashMap,
         wasm_bindgen::{
@@ -220,6 +224,16 @@ pub mod style_export {
         }
     }
 
+    impl JsExport for f64 {
+        fn from_js(v: &JsValue) -> Self {
+            return v.as_f64().unwrap();
+        }
+
+        fn to_js(&self) -> JsValue {
+            return JsValue::from_f64(*self);
+        }
+    }
+
     impl JsExport for bool {
         fn from_js(v: &JsValue) -> Self {
             return v.as_bool().unwrap();
@@ -398,6 +412,46 @@ pub mod style_export {
         }
     }
 
+    impl JsExport for ChartType {
+        fn from_js(v: &JsValue) -> Self {
+            return <JsValue as JsValueSerdeExt>::into_serde(v).unwrap();
+        }
+
+        fn to_js(&self) -> JsValue {
+            return <JsValue as JsValueSerdeExt>::from_serde(self).unwrap();
+        }
+    }
+
+    impl JsExport for CalendarPeriod {
+        fn from_js(v: &JsValue) -> Self {
+            return <JsValue as JsValueSerdeExt>::into_serde(v).unwrap();
+        }
+
+        fn to_js(&self) -> JsValue {
+            return <JsValue as JsValueSerdeExt>::from_serde(self).unwrap();
+        }
+    }
+
+    impl JsExport for TimelineGroup {
+        fn from_js(v: &JsValue) -> Self {
+            return <JsValue as JsValueSerdeExt>::into_serde(v).unwrap();
+        }
+
+        fn to_js(&self) -> JsValue {
+            return <JsValue as JsValueSerdeExt>::from_serde(self).unwrap();
+        }
+    }
+
+    impl JsExport for WidgetEditInput {
+        fn from_js(v: &JsValue) -> Self {
+            return <JsValue as JsValueSerdeExt>::into_serde(v).unwrap();
+        }
+
+        fn to_js(&self) -> JsValue {
+            return <JsValue as JsValueSerdeExt>::from_serde(self).unwrap();
+        }
+    }
+
     impl JsExport for TextSizeMode {
         fn from_js(v: &JsValue) -> Self {
             return <JsValue as JsValueSerdeExt>::into_serde(v).unwrap();
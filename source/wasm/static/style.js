@@ -219,6 +219,10 @@
   const textIconMoveUp = "\ue316";
   const textIconMoveDown = "\ue313";
   const textIconDeselect = "\ue9d5";
+  const textIconUnchecked = "\ue835";
+  const textIconChecked = "\ue834";
+  const textIconGraph = "\ue9f4";
+  const textIconSavePreset = "\ue866";
 
   // xx Variables
   const varFNormal = "12pt";
@@ -2389,6 +2393,9 @@
         ),
       );
     }
+    if (args.bulk != null) {
+      children.push(args.bulk);
+    }
     children.push(args.elements);
     return {
       root: e(
@@ -2442,6 +2449,18 @@
         }).root,
       };
     };
+  presentation.leafMenuPageButtonSavePreset =
+    /** @type {Presentation["leafMenuPageButtonSavePreset"]} */ (args) => {
+      return {
+        root: leafButton({
+          title: "Save preset",
+          text: "Save preset",
+          icon: textIconSavePreset,
+          rootStyles: [menuPageButtonStyle],
+          innerStyles: [menuPageButtonInnerStyle],
+        }).root,
+      };
+    };
 
   const contViewElementStyle = ss(uniq("cont_view_element_outer"), {
     "": (s) => {
@@ -2600,11 +2619,126 @@
       if (args.height != null) {
         body.style.height = args.height;
       }
+      if (args.select != null) {
+        body.classList.add(contViewElementSelectableStyle);
+        body.appendChild(args.select);
+      }
       return {
         root: out,
       };
     };
 
+  const contViewElementSelectableStyle = ss(
+    uniq("cont_view_element_selectable"),
+    {
+      "": (s) => {
+        s.position = "relative";
+      },
+    },
+  );
+  presentation.leafViewSelectToggle =
+    /** @type {Presentation["leafViewSelectToggle"]} */ (args) => {
+      const out = buildLeafButton({
+        parent: e(
+          "button",
+          { title: "Select" },
+          {
+            styles_: [
+              ss(uniq("leaf_view_select_toggle"), {
+                "": (s) => {
+                  s.position = "absolute";
+                  s.top = "0";
+                  s.right = "0";
+                  s.padding = varPSmall;
+                  s.opacity = varONoninteractive;
+                },
+                ":hover": (s) => {
+                  s.opacity = "1";
+                },
+                [`.${classStateSelected}`]: (s) => {
+                  s.opacity = "1";
+                  s.color = varCSelected;
+                },
+                [`>*>:nth-child(2)`]: (s) => {
+                  s.display = "none";
+                },
+                [`.${classStateSelected}>*>:nth-child(1)`]: (s) => {
+                  s.display = "none";
+                },
+                [`.${classStateSelected}>*>:nth-child(2)`]: (s) => {
+                  s.display = "initial";
+                },
+              }),
+            ],
+          },
+        ),
+        innerStyles: [
+          ss(uniq("leaf_view_select_toggle_inner"), {
+            ">svg": (s) => {
+              s.width = varSButtonSmallIcon;
+              s.height = varSButtonSmallIcon;
+            },
+          }),
+        ],
+        children: [
+          leafIcon({ text: textIconUnchecked, extraStyles: [leafIconStyle] }),
+          leafIcon({ text: textIconChecked, extraStyles: [leafIconStyle] }),
+        ],
+      });
+      return { root: out };
+    };
+
+  presentation.contViewBulkBar =
+    /** @type {Presentation["contViewBulkBar"]} */ (args) => {
+      const count = e("span", {}, {});
+      const buttonClear = leafButton({
+        title: "Clear selection",
+        icon: textIconDeselect,
+        rootStyles: [menuPageButtonStyle],
+        innerStyles: [menuPageButtonInnerStyle],
+      });
+      return {
+        root: e(
+          "div",
+          {},
+          {
+            styles_: [
+              contHboxStyle,
+              ss(uniq("cont_view_bulk_bar"), {
+                "": (s) => {
+                  s.position = "sticky";
+                  s.top = "0";
+                  s.zIndex = "1";
+                  s.flexWrap = "wrap";
+                  s.alignItems = "center";
+                  s.gap = varPSmall;
+                  s.padding = `${varPSmall} ${varPViewHoriz}`;
+                  s.backgroundColor = varCBackground2;
+                },
+                [`.${classStateHide}`]: (s) => {
+                  s.display = "none";
+                },
+              }),
+            ],
+            children_: [count, ...args.actions, buttonClear.root],
+          },
+        ),
+        count: count,
+        buttonClear: buttonClear.root,
+      };
+    };
+  presentation.leafViewBulkButton =
+    /** @type {Presentation["leafViewBulkButton"]} */ (args) => {
+      return {
+        root: leafButton({
+          title: args.text,
+          text: args.text,
+          rootStyles: [menuPageButtonStyle],
+          innerStyles: [menuPageButtonInnerStyle],
+        }).root,
+      };
+    };
+
   presentation.contMediaFullscreen =
     /** @type {Presentation["contMediaFullscreen"]} */ () => {
       const fsButtonStyle = ss(uniq("cont_media_fullscreen_close"), {
@@ -3034,6 +3168,56 @@
         buttonOk: buttonConfirm.root,
       };
     };
+  presentation.contViewModalConfirmBulkDelete =
+    /** @type {Presentation["contViewModalConfirmBulkDelete"]} */ (args) => {
+      const buttonConfirm = presentation.leafButtonBig({
+        title: "Delete",
+        icon: textIconDelete,
+        text: `Delete`,
+        extraStyles: [],
+      });
+      const out = newContModal({
+        title: "Confirm delete",
+        minimal: true,
+        child: e(
+          "div",
+          {},
+          {
+            styles_: [
+              contVboxStyle,
+              ss(uniq("cont_view_modal_confirm_bulk_delete_vbox"), {
+                "": (s) => {
+                  s.flexGrow = "1";
+                },
+              }),
+            ],
+            children_: [
+              e(
+                "span",
+                {
+                  textContent: `Are you sure you'd like to delete ${args.count} selected node(s) and all their relations?`,
+                },
+                {
+                  styles_: [
+                    ss(uniq("cont_view_modal_confirm_bulk_delete"), {
+                      "": (s) => {
+                        s.padding = varP05;
+                      },
+                    }),
+                  ],
+                },
+              ),
+              buttonConfirm.root,
+            ],
+          },
+        ),
+      });
+      return {
+        root: out.root,
+        buttonClose: out.buttonClose,
+        buttonOk: buttonConfirm.root,
+      };
+    };
   presentation.contViewModalShare =
     /** @type {Presentation["contViewModalShare"]} */ (args) => {
       const buttonUnshare = presentation.leafButtonBig({
@@ -3266,6 +3450,201 @@
     return { root: out };
   };
 
+  const parseIsoDate = /** @type { (date: string) => Date } */ (date) => {
+    const [y, m, d] = date.split("-").map((x) => parseInt(x));
+    return new Date(y, m - 1, d);
+  };
+  const contViewCalendarStyle = ss(uniq("cont_view_calendar"), {
+    "": (s) => {
+      s.display = "flex";
+      s.flexDirection = "column";
+      s.gap = varP05;
+      s.width = "100%";
+    },
+    ">h2": (s) => {
+      s.fontSize = varFLinkBig;
+      s.fontWeight = varWSemiLight;
+    },
+  });
+  const contViewCalendarGridStyle = ss(uniq("cont_view_calendar_grid"), {
+    "": (s) => {
+      s.display = "grid";
+      s.gridTemplateColumns = "repeat(7, minmax(0, 1fr))";
+      s.gap = varLThin;
+    },
+    ">span": (s) => {
+      s.color = varCForegroundFade;
+      s.textAlign = "center";
+    },
+  });
+  const contViewCalendarDayStyle = ss(uniq("cont_view_calendar_day"), {
+    "": (s) => {
+      s.display = "flex";
+      s.flexDirection = "column";
+      s.gap = varPSmall;
+      s.minHeight = "2cm";
+      s.minWidth = "0";
+      s.padding = varPSmall;
+      s.backgroundColor = varCBackground2;
+      s.overflow = "hidden";
+    },
+    ">span:first-child": (s) => {
+      s.color = varCForegroundFade;
+    },
+    ".outside": (s) => {
+      s.opacity = varONoninteractiveLight;
+    },
+  });
+  presentation.contViewCalendar =
+    /** @type { Presentation["contViewCalendar"] } */
+    (args) => {
+      const weekdays = [];
+      for (let i = 0; i < 7; i += 1) {
+        // 2024-01-01 is a Monday
+        weekdays.push(
+          e(
+            "span",
+            {
+              textContent: new Date(2024, 0, 1 + i).toLocaleDateString(
+                undefined,
+                { weekday: "short" },
+              ),
+            },
+            {},
+          ),
+        );
+      }
+      const children = [];
+      for (let i = 0; i < args.periods.length; i += 1) {
+        const start = parseIsoDate(args.titles[i]);
+        let title;
+        switch (args.period) {
+          case "month":
+            title = start.toLocaleDateString(undefined, {
+              year: "numeric",
+              month: "long",
+            });
+            break;
+          case "week":
+            title = start.toLocaleDateString(undefined, { dateStyle: "long" });
+            break;
+        }
+        children.push(e("h2", { textContent: title }, {}));
+        children.push(
+          e(
+            "div",
+            {},
+            {
+              styles_: [contViewCalendarGridStyle],
+              children_: [
+                ...weekdays.map(
+                  (x) => /** @type { Element } */ (x.cloneNode(true)),
+                ),
+                ...args.periods[i],
+              ],
+            },
+          ),
+        );
+      }
+      const out = e(
+        "div",
+        {},
+        {
+          styles_: [
+            contViewCalendarStyle,
+            viewTransStyle({
+              parentOrientationType: args.parentOrientationType,
+              parentOrientation: args.parentOrientation,
+              transAlign: args.transAlign,
+            }),
+          ],
+          children_: /** @type { Element[] } */ (children),
+        },
+      );
+      if (args.conSizeMax != null) {
+        out.style.maxWidth = args.conSizeMax;
+      }
+      return { root: out };
+    };
+  presentation.contViewCalendarDay =
+    /** @type { Presentation["contViewCalendarDay"] } */
+    (args) => {
+      const date = parseIsoDate(args.date);
+      const out = e(
+        "div",
+        { title: date.toLocaleDateString(undefined, { dateStyle: "full" }) },
+        {
+          styles_: [contViewCalendarDayStyle],
+          children_: [
+            e("span", { textContent: `${date.getDate()}` }, {}),
+            ...args.children,
+          ],
+        },
+      );
+      if (args.outside) {
+        out.classList.add("outside");
+      }
+      return { root: out };
+    };
+  const contViewTimelineGroupStyle = ss(uniq("cont_view_timeline_group"), {
+    "": (s) => {
+      s.display = "flex";
+      s.flexDirection = "column";
+      s.gap = varPSmall;
+      s.borderColor = varCForegroundFade;
+      s.borderStyle = "solid";
+      s.borderWidth = "0";
+    },
+    ">h3": (s) => {
+      s.color = varCForegroundFade;
+      s.fontWeight = varWSemiLight;
+    },
+  });
+  presentation.contViewTimelineGroup =
+    /** @type { Presentation["contViewTimelineGroup"] } */
+    (args) => {
+      const date = parseIsoDate(args.date);
+      let title;
+      switch (args.group) {
+        case "day":
+          title = date.toLocaleDateString(undefined, { dateStyle: "full" });
+          break;
+        case "week":
+          title = date.toLocaleDateString(undefined, { dateStyle: "long" });
+          break;
+        case "month":
+          title = date.toLocaleDateString(undefined, {
+            year: "numeric",
+            month: "long",
+          });
+          break;
+        case "year":
+          title = date.toLocaleDateString(undefined, { year: "numeric" });
+          break;
+      }
+      const out = e(
+        "div",
+        {},
+        {
+          styles_: [contViewTimelineGroupStyle],
+          children_: [e("h3", { textContent: title }, {}), args.body],
+        },
+      );
+      switch (con(args.orientation)) {
+        case "up":
+        case "down":
+          out.style.borderLeftWidth = varLThin;
+          out.style.paddingLeft = varPSmall;
+          break;
+        case "left":
+        case "right":
+          out.style.borderTopWidth = varLThin;
+          out.style.paddingTop = varPSmall;
+          break;
+      }
+      return { root: out };
+    };
+
   presentation.contViewTable =
     /** @type { Presentation["contViewTable"] } */
     (args) => {
@@ -3991,40 +4370,472 @@
     });
     return { root: out };
   };
-  presentation.leafViewDatetime =
-    /** @type { Presentation["leafViewDatetime"] } */ (args) => {
-      const alignStyle = viewTransStyle({
-        parentOrientationType: args.parentOrientationType,
-        parentOrientation: args.parentOrientation,
-        transAlign: args.transAlign,
-      });
-      const out = (() => {
-        return e(
-          "span",
-          {
-            textContent: (() => {
-              try {
-                return new Date(args.value).toLocaleString();
-              } catch (e) {}
-              return " ";
-            })(),
-          },
-          {
-            styles_: [
-              viewTextBaseStyle,
-              ...viewTextOrientationStyles(args.orientation),
-              alignStyle,
-            ],
-          },
-        );
-      })();
-      if (args.fontSize != null) {
-        out.style.fontSize = args.fontSize;
-      }
-      if (args.color != null) {
-        out.style.color = args.color;
-      }
-      return { root: out };
+  const viewMarkdownStyle = ss(uniq("leaf_view_markdown"), {
+    "": (s) => {
+      s.pointerEvents = "initial";
+      s.overflowWrap = "anywhere";
+      s.flexShrink = "1";
+      s.minWidth = "0";
+    },
+    " > *:first-child": (s) => {
+      s.marginTop = "0";
+    },
+    " > *:last-child": (s) => {
+      s.marginBottom = "0";
+    },
+    " :is(p, ul, ol, blockquote, pre, table)": (s) => {
+      s.marginTop = "0.5em";
+      s.marginBottom = "0.5em";
+    },
+    " :is(ul, ol)": (s) => {
+      s.paddingLeft = "1.5em";
+    },
+    " ul": (s) => {
+      s.listStyleType = "disc";
+    },
+    " ol": (s) => {
+      s.listStyleType = "decimal";
+    },
+    " :is(h1, h2, h3, h4, h5, h6)": (s) => {
+      s.fontWeight = "bold";
+      s.marginTop = "0.8em";
+      s.marginBottom = "0.4em";
+    },
+    " h1": (s) => {
+      s.fontSize = "1.6em";
+    },
+    " h2": (s) => {
+      s.fontSize = "1.4em";
+    },
+    " h3": (s) => {
+      s.fontSize = "1.2em";
+    },
+    " strong": (s) => {
+      s.fontWeight = "bold";
+    },
+    " em": (s) => {
+      s.fontStyle = "italic";
+    },
+    " a": (s) => {
+      s.textDecoration = "underline";
+    },
+    " blockquote": (s) => {
+      s.paddingLeft = "0.8em";
+      s.borderLeft = `0.1em solid ${varCForegroundFade}`;
+      s.color = varCForegroundFade;
+    },
+    " :is(code, pre)": (s) => {
+      s.fontFamily = "monospace";
+      s.backgroundColor = varCBackground2;
+      s.borderRadius = "0.1em";
+    },
+    " code": (s) => {
+      s.padding = "0 0.2em";
+    },
+    " pre": (s) => {
+      s.padding = "0.5em";
+      s.whiteSpace = "pre-wrap";
+    },
+    " pre code": (s) => {
+      s.padding = "0";
+    },
+    " :is(img, video)": (s) => {
+      s.maxWidth = "100%";
+      s.borderRadius = varRMedia;
+    },
+    " audio": (s) => {
+      s.maxWidth = "100%";
+    },
+    " table": (s) => {
+      s.borderCollapse = "collapse";
+    },
+    " td": (s) => {
+      s.padding = "0.2em 0.5em";
+      s.border = `0.05em solid ${varCForegroundFade}`;
+    },
+    " thead td": (s) => {
+      s.fontWeight = "bold";
+    },
+    " hr": (s) => {
+      s.border = "none";
+      s.borderTop = `0.05em solid ${varCForegroundFade}`;
+    },
+  });
+  presentation.leafViewMarkdown =
+    /** @type { Presentation["leafViewMarkdown"] } */ (args) => {
+      const out = e(
+        "div",
+        {},
+        {
+          styles_: [
+            viewMarkdownStyle,
+            viewTransStyle({
+              parentOrientationType: args.parentOrientationType,
+              parentOrientation: args.parentOrientation,
+              transAlign: args.transAlign,
+            }),
+          ],
+          children_: [args.body],
+        },
+      );
+      if (args.fontSize != null) {
+        out.style.fontSize = args.fontSize;
+      }
+      if (args.color != null) {
+        out.style.color = args.color;
+      }
+      if (args.conSizeMax != null) {
+        out.style.maxWidth = args.conSizeMax;
+      }
+      return { root: out };
+    };
+  const viewChartStyle = ss(uniq("leaf_view_chart"), {
+    "": (s) => {
+      s.width = "100%";
+      s.display = "flex";
+      s.flexDirection = "column";
+      s.gap = varPSmall;
+    },
+    ">svg": (s) => {
+      s.width = "100%";
+      s.height = "100%";
+      s.overflow = "visible";
+    },
+    " .axis": (s) => {
+      s.stroke = varCForegroundFade;
+      s.strokeWidth = "1";
+    },
+    " text": (s) => {
+      s.fill = varCForegroundFade;
+      s.fontSize = "10px";
+    },
+  });
+  const viewChartLegendStyle = ss(uniq("leaf_view_chart_legend"), {
+    "": (s) => {
+      s.display = "flex";
+      s.flexWrap = "wrap";
+      s.columnGap = varP05;
+      s.rowGap = varPSmall;
+    },
+    ">span::before": (s) => {
+      s.content = `""`;
+      s.display = "inline-block";
+      s.width = "0.7em";
+      s.height = "0.7em";
+      s.marginRight = "0.3em";
+      s.borderRadius = "0.2em";
+      s.backgroundColor = "var(--swatch)";
+    },
+  });
+  presentation.leafViewChart =
+    /** @type { Presentation["leafViewChart"] } */ (args) => {
+      const svgNs = "http://www.w3.org/2000/svg";
+      const svgE = /** @type {(
+        name: string,
+        attrs: { [k: string]: string | number },
+        children?: Element[]
+      ) => SVGElement} */ (name, attrs, children) => {
+        const out = document.createElementNS(svgNs, name);
+        for (const [k, v] of Object.entries(attrs)) {
+          out.setAttribute(k, `${v}`);
+        }
+        for (const c of children || []) {
+          out.appendChild(c);
+        }
+        return /** @type { SVGElement } */ (out);
+      };
+      const title = /** @type {(text: string) => SVGElement} */ (text) => {
+        const out = svgE("title", {});
+        out.textContent = text;
+        return out;
+      };
+      const color = args.color || varCSelected;
+      const width = 400;
+      const height = 250;
+      const svg = svgE("svg", {
+        viewBox: `0 0 ${width} ${height}`,
+        // Scaling non-uniformly would stretch the axis labels
+        preserveAspectRatio: "xMidYMid meet",
+      });
+      const children = /** @type { Element[] } */ ([svg]);
+      if (args.chartType == "pie") {
+        svg.setAttribute("viewBox", `-1 -1 2 2`);
+        const total = args.values.reduce((a, b) => a + Math.max(0, b), 0);
+        const legend = e("div", {}, { styles_: [viewChartLegendStyle] });
+        let angle = -Math.PI / 2;
+        for (let i = 0; i < args.values.length; i += 1) {
+          const value = Math.max(0, args.values[i]);
+          const swatch = `hsl(${(i * 137.5) % 360}, 55%, 55%)`;
+          const label = `${args.labels[i]}: ${args.values[i]}`;
+          const item = e("span", { textContent: label }, {});
+          item.style.setProperty("--swatch", swatch);
+          legend.appendChild(item);
+          if (total <= 0 || value <= 0) {
+            continue;
+          }
+          const sweep = (value / total) * Math.PI * 2;
+          let shape;
+          if (sweep >= Math.PI * 2 - 0.0001) {
+            shape = svgE("circle", { cx: 0, cy: 0, r: 1 });
+          } else {
+            const end = angle + sweep;
+            shape = svgE("path", {
+              d: [
+                `M 0 0`,
+                `L ${Math.cos(angle)} ${Math.sin(angle)}`,
+                `A 1 1 0 ${sweep > Math.PI ? 1 : 0} 1 ${Math.cos(end)} ${Math.sin(end)}`,
+                `Z`,
+              ].join(" "),
+            });
+          }
+          shape.style.fill = swatch;
+          shape.appendChild(title(label));
+          svg.appendChild(shape);
+          angle += sweep;
+        }
+        children.push(legend);
+      } else {
+        const padLeft = 40;
+        const padBottom = 30;
+        const padTop = 10;
+        const plotWidth = width - padLeft;
+        const plotHeight = height - padBottom - padTop;
+        const max = args.values.reduce((a, b) => Math.max(a, b), 0);
+        const min = args.values.reduce((a, b) => Math.min(a, b), 0);
+        const range = max - min || 1;
+        const y = /** @type {(v: number) => number} */ (v) =>
+          padTop + plotHeight - ((v - min) / range) * plotHeight;
+        const step = plotWidth / Math.max(1, args.values.length);
+        const labelEvery = Math.max(1, Math.ceil(args.values.length / 10));
+
+        // Axes, ticks
+        svg.appendChild(
+          svgE("line", {
+            class: "axis",
+            x1: padLeft,
+            y1: y(0),
+            x2: width,
+            y2: y(0),
+          }),
+        );
+        svg.appendChild(
+          svgE("line", {
+            class: "axis",
+            x1: padLeft,
+            y1: padTop,
+            x2: padLeft,
+            y2: padTop + plotHeight,
+          }),
+        );
+        for (const tick of [min, (min + max) / 2, max]) {
+          const text = svgE("text", {
+            x: padLeft - 4,
+            y: y(tick),
+            "text-anchor": "end",
+            "dominant-baseline": "middle",
+          });
+          text.textContent = `${Math.round(tick * 100) / 100}`;
+          svg.appendChild(text);
+        }
+        for (let i = 0; i < args.labels.length; i += labelEvery) {
+          const text = svgE("text", {
+            x: padLeft + step * (i + 0.5),
+            y: height - padBottom / 2,
+            "text-anchor": "middle",
+            "dominant-baseline": "middle",
+          });
+          text.textContent = args.labels[i];
+          svg.appendChild(text);
+        }
+
+        // Data
+        if (args.chartType == "line") {
+          const points = args.values.map(
+            (v, i) => `${padLeft + step * (i + 0.5)},${y(v)}`,
+          );
+          const line = svgE("polyline", {
+            points: points.join(" "),
+            fill: "none",
+            "stroke-width": 2,
+          });
+          line.style.stroke = color;
+          svg.appendChild(line);
+          for (let i = 0; i < args.values.length; i += 1) {
+            const point = svgE(
+              "circle",
+              { cx: padLeft + step * (i + 0.5), cy: y(args.values[i]), r: 3 },
+              [title(`${args.labels[i]}: ${args.values[i]}`)],
+            );
+            point.style.fill = color;
+            svg.appendChild(point);
+          }
+        } else {
+          // Bar, histogram
+          const gap = args.chartType == "histogram" ? 0 : step * 0.2;
+          for (let i = 0; i < args.values.length; i += 1) {
+            const top = y(Math.max(0, args.values[i]));
+            const bottom = y(Math.min(0, args.values[i]));
+            const bar = svgE(
+              "rect",
+              {
+                x: padLeft + step * i + gap / 2,
+                y: top,
+                width: Math.max(0, step - gap),
+                height: bottom - top,
+              },
+              [title(`${args.labels[i]}: ${args.values[i]}`)],
+            );
+            bar.style.fill = color;
+            svg.appendChild(bar);
+          }
+        }
+      }
+      const out = e(
+        "div",
+        {},
+        {
+          styles_: [
+            viewChartStyle,
+            viewTransStyle({
+              parentOrientationType: args.parentOrientationType,
+              parentOrientation: args.parentOrientation,
+              transAlign: args.transAlign,
+            }),
+          ],
+          children_: children,
+        },
+      );
+      if (args.width != null) {
+        out.style.width = args.width;
+      }
+      svg.style.height = args.height || "6cm";
+      return { root: out };
+    };
+  const leafViewEditableStyle = ss(uniq("leaf_view_editable"), {
+    "": (s) => {
+      s.display = "flex";
+      s.flexDirection = "row";
+      s.alignItems = "center";
+      s.gap = varPSmall;
+      s.minWidth = "0";
+    },
+    ">input": (s) => {
+      s.display = "none";
+      s.minWidth = "0";
+      s.background = "transparent";
+      s.border = "none";
+      s.color = "inherit";
+      s.font = "inherit";
+      s.outline = "none";
+    },
+    ".editing>input": (s) => {
+      s.display = "initial";
+    },
+    ".editing>:not(input)": (s) => {
+      s.display = "none";
+    },
+    [`.${classStateThinking}`]: (s) => {
+      s.opacity = varONoninteractive;
+    },
+  });
+  const leafViewEditableButtonStyle = ss(uniq("leaf_view_editable_button"), {
+    "": (s) => {
+      s.opacity = varONoninteractive;
+    },
+    ":hover": (s) => {
+      s.opacity = "1";
+    },
+  });
+  const leafViewEditableButtonInnerStyle = ss(
+    uniq("leaf_view_editable_button_inner"),
+    {
+      ">svg": (s) => {
+        s.width = varSButtonSmallIcon;
+        s.height = varSButtonSmallIcon;
+      },
+    },
+  );
+  presentation.leafViewEditable =
+    /** @type { Presentation["leafViewEditable"] } */ (args) => {
+      const display = e("div", {}, { children_: [args.body] });
+      const input = e(
+        "input",
+        {
+          type: args.inputType,
+          defaultValue: args.value,
+          title: "Enter to save, Escape to cancel",
+        },
+        { styles_: [leafInputStyle, leafInputBorderStyle] },
+      );
+      const button = leafButton({
+        title: "Edit",
+        icon: textIconEdit,
+        rootStyles: [leafViewEditableButtonStyle],
+        innerStyles: [leafViewEditableButtonInnerStyle],
+      }).root;
+      const root = e(
+        "div",
+        {},
+        {
+          styles_: [leafViewEditableStyle],
+          children_: [display, button, input],
+        },
+      );
+      button.addEventListener("click", (ev) => {
+        ev.stopPropagation();
+        root.classList.add("editing");
+        input.focus();
+      });
+      input.addEventListener("keydown", (ev) => {
+        switch (ev.key) {
+          case "Enter":
+            input.blur();
+            break;
+          case "Escape":
+            input.value = input.defaultValue;
+            input.blur();
+            break;
+        }
+      });
+      input.addEventListener("blur", () => {
+        root.classList.remove("editing");
+      });
+      return { root: root, display: display, input: input };
+    };
+  presentation.leafViewDatetime =
+    /** @type { Presentation["leafViewDatetime"] } */ (args) => {
+      const alignStyle = viewTransStyle({
+        parentOrientationType: args.parentOrientationType,
+        parentOrientation: args.parentOrientation,
+        transAlign: args.transAlign,
+      });
+      const out = (() => {
+        return e(
+          "span",
+          {
+            textContent: (() => {
+              try {
+                return new Date(args.value).toLocaleString();
+              } catch (e) {}
+              return " ";
+            })(),
+          },
+          {
+            styles_: [
+              viewTextBaseStyle,
+              ...viewTextOrientationStyles(args.orientation),
+              alignStyle,
+            ],
+          },
+        );
+      })();
+      if (args.fontSize != null) {
+        out.style.fontSize = args.fontSize;
+      }
+      if (args.color != null) {
+        out.style.color = args.color;
+      }
+      return { root: out };
     };
   presentation.leafViewDate = /** @type { Presentation["leafViewDate"] } */ (
     args,
@@ -4420,8 +5231,10 @@
       ],
     }).root,
   });
-  presentation.contViewModalOfflineName =
-    /** @type {Presentation["contViewModalOfflineName"]} */ (args) => {
+  const newContModalName =
+    /** @type { (args: { title: string, defaultName: string }) => { root: HTMLElement, buttonClose: HTMLElement, buttonOk: HTMLElement, input: HTMLElement } } */ (
+      args,
+    ) => {
       const input = e(
         "input",
         {
@@ -4449,7 +5262,7 @@
         extraStyles: [],
       });
       const out = newContModal({
-        title: "Offline view name",
+        title: args.title,
         minimal: true,
         child: e(
           "div",
@@ -4476,6 +5289,18 @@
         input: input,
       };
     };
+  presentation.contViewModalOfflineName =
+    /** @type {Presentation["contViewModalOfflineName"]} */ (args) =>
+      newContModalName({
+        title: "Offline view name",
+        defaultName: args.defaultName,
+      });
+  presentation.contViewModalPresetName =
+    /** @type {Presentation["contViewModalPresetName"]} */ (args) =>
+      newContModalName({
+        title: "Preset name",
+        defaultName: args.defaultName,
+      });
   presentation.leafFormComment =
     /** @type {Presentation["leafFormComment"]} */ (args) => ({
       root: e(
@@ -4493,6 +5318,97 @@
         },
       ),
     });
+  presentation.contFormSaved = /** @type {Presentation["contFormSaved"]} */ (
+    args,
+  ) => {
+    const body = e(
+      "div",
+      {},
+      {
+        styles_: [
+          contVboxStyle,
+          ss(uniq("cont_form_saved_body"), {
+            "": (s) => {
+              s.gap = varPSmall;
+            },
+            ":empty:before": (s) => {
+              s.display = "span";
+              s.content = JSON.stringify("(none)");
+              s.opacity = varONoninteractive;
+            },
+          }),
+        ],
+      },
+    );
+    return {
+      root: e(
+        "div",
+        {},
+        {
+          styles_: [
+            contVboxStyle,
+            ss(uniq("cont_form_saved"), {
+              "": (s) => {
+                s.gridColumn = "1 / -1";
+                s.marginTop = varPFormCommentTop;
+                s.gap = varPSmall;
+              },
+            }),
+          ],
+          children_: [e("h2", { textContent: args.title }, {}), body],
+        },
+      ),
+      body: body,
+    };
+  };
+  const leafFormSavedEntryTextStyle = ss(uniq("leaf_form_saved_entry_text"), {
+    "": (s) => {
+      s.flexGrow = "1";
+      s.overflow = "hidden";
+      s.textOverflow = "ellipsis";
+      s.whiteSpace = "nowrap";
+    },
+  });
+  presentation.leafFormSavedEntry =
+    /** @type {Presentation["leafFormSavedEntry"]} */ (args) => {
+      const buttonRemove = leafButtonFree({
+        icon: textIconDelete,
+        hint: "Remove",
+        extraStyles: [],
+      }).root;
+      return {
+        root: e(
+          "div",
+          {},
+          {
+            styles_: [
+              contHboxStyle,
+              ss(uniq("leaf_form_saved_entry"), {
+                "": (s) => {
+                  s.alignItems = "center";
+                  s.gap = varPSmall;
+                },
+              }),
+            ],
+            children_: [
+              args.href != null
+                ? e(
+                    "a",
+                    { href: args.href, textContent: args.title },
+                    { styles_: [leafLinkStyle, leafFormSavedEntryTextStyle] },
+                  )
+                : e(
+                    "span",
+                    { textContent: args.title },
+                    { styles_: [leafFormSavedEntryTextStyle] },
+                  ),
+              buttonRemove,
+            ],
+          },
+        ),
+        buttonRemove: buttonRemove,
+      };
+    };
 
   // /////////////////////////////////////////////////////////////////////////////
   // xx Components, styles: page, node view/edit/history
@@ -4764,6 +5680,25 @@
         ),
       };
     };
+  presentation.leafNodeViewGenerationStatus =
+    /** @type {Presentation["leafNodeViewGenerationStatus"]} */ (args) => ({
+      root: e(
+        "p",
+        { textContent: args.value },
+        {
+          styles_: [
+            ss(uniq("leaf_node_view_generation_status"), {
+              "": (s) => {
+                s.opacity = varONoninteractive;
+                s.whiteSpace = "pre-wrap";
+                s.overflowWrap = "anywhere";
+                s.pointerEvents = "initial";
+              },
+            }),
+          ],
+        },
+      ),
+    });
 
   // /////////////////////////////////////////////////////////////////////////////
   // xx Components, styles: page, node view/edit
@@ -4926,6 +5861,17 @@
         download: false,
       });
     };
+  presentation.leafNodeViewToolbarGraphLinkButton =
+    /** @type {Presentation["leafNodeViewToolbarGraphLinkButton"]} */ (
+      args,
+    ) => {
+      return leafButtonFreeLink({
+        icon: textIconGraph,
+        hint: "Graph",
+        url: args.link,
+        download: false,
+      });
+    };
   presentation.leafNodeViewToolbarNodeButton =
     /** @type {Presentation["leafNodeViewToolbarNodeButton"]} */ (args) => {
       return leafButtonFree({
@@ -4934,6 +5880,195 @@
       });
     };
 
+  ///////////////////////////////////////////////////////////////////////////////
+  // xx Components, styles: page, node graph
+  presentation.contPageNodeGraph =
+    /** @type {Presentation["contPageNodeGraph"]} */ (args) => {
+      return {
+        root: presentation.contGroup({
+          children: [
+            presentation.contBarMain({
+              leftChildren: [],
+              leftMidChildren: [],
+              midChildren: [],
+              rightMidChildren: [],
+              rightChildren: args.barChildren,
+            }).root,
+            e(
+              "div",
+              {},
+              {
+                styles_: [classMenuWantStateOpen, contVboxStyle, contBodyStyle],
+                children_: [
+                  e(
+                    "div",
+                    {},
+                    {
+                      styles_: [
+                        contVboxStyle,
+                        ss(uniq("cont_page_node_graph"), {
+                          "": (s) => {
+                            s.padding = `0 ${varPViewHoriz}`;
+                            s.gap = varP05;
+                          },
+                        }),
+                      ],
+                      children_: args.children,
+                    },
+                  ),
+                ],
+              },
+            ),
+          ],
+        }).root,
+      };
+    };
+
+  presentation.leafNodeGraphPredicateToggle =
+    /** @type {Presentation["leafNodeGraphPredicateToggle"]} */ (args) => {
+      return leafButton({
+        title: "Toggle predicate",
+        text: args.text,
+        rootStyles: [
+          ss(uniq("leaf_node_graph_predicate_toggle"), {
+            "": (s) => {
+              s.display = "inline-flex";
+              s.margin = `0 ${varP05} ${varP05} 0`;
+              s.padding = `${varPSmall} ${varP05}`;
+              s.border = `${varLThin} solid ${varCForegroundFade}`;
+              s.borderRadius = varP05;
+              s.color = varCForegroundFade;
+              s.textDecoration = "line-through";
+            },
+            [`.${classStatePressed}`]: (s) => {
+              s.color = varCSelected;
+              s.borderColor = varCSelected;
+              s.textDecoration = "none";
+            },
+          }),
+        ],
+      });
+    };
+
+  presentation.contNodeGraph =
+    /** @type {Presentation["contNodeGraph"]} */ (args) => {
+      const svgNs = "http://www.w3.org/2000/svg";
+      const svg = document.createElementNS(svgNs, "svg");
+      svg.setAttribute("viewBox", "0 0 1 1");
+      svg.setAttribute("preserveAspectRatio", "none");
+      const labels = [];
+      for (let i = 0; i + 3 < args.edges.length; i += 4) {
+        const [x1, y1, x2, y2] = args.edges.slice(i, i + 4);
+        const label = args.edgeLabels[i / 4] || "";
+        const line = document.createElementNS(svgNs, "line");
+        line.setAttribute("x1", `${x1}`);
+        line.setAttribute("y1", `${y1}`);
+        line.setAttribute("x2", `${x2}`);
+        line.setAttribute("y2", `${y2}`);
+        const title = document.createElementNS(svgNs, "title");
+        title.textContent = label;
+        line.appendChild(title);
+        svg.appendChild(line);
+        const labelEl = e("span", { textContent: label }, {});
+        labelEl.style.left = `${((x1 + x2) / 2) * 100}%`;
+        labelEl.style.top = `${((y1 + y2) / 2) * 100}%`;
+        labels.push(labelEl);
+      }
+      return {
+        root: e(
+          "div",
+          {},
+          {
+            styles_: [
+              ss(uniq("cont_node_graph"), {
+                "": (s) => {
+                  s.position = "relative";
+                  s.height = "70vh";
+                  s.minHeight = "20rem";
+                  s.overflow = "hidden";
+                  s.backgroundColor = varCBackground2;
+                  s.borderRadius = varP05;
+                },
+                ">svg": (s) => {
+                  s.position = "absolute";
+                  s.inset = "0";
+                  s.width = "100%";
+                  s.height = "100%";
+                  s.stroke = varCForegroundFade;
+                },
+                ">svg>line": (s) => {
+                  s.strokeWidth = "1";
+                  s.vectorEffect = "non-scaling-stroke";
+                },
+                ">span": (s) => {
+                  s.position = "absolute";
+                  s.transform = "translate(-50%, -50%)";
+                  s.fontSize = "0.8em";
+                  s.color = varCForegroundFade;
+                  s.whiteSpace = "nowrap";
+                  s.pointerEvents = "none";
+                },
+              }),
+            ],
+            children_: [
+              /** @type { HTMLElement } */ (/** @type { unknown } */ (svg)),
+              ...labels,
+              ...args.nodes,
+            ],
+          },
+        ),
+      };
+    };
+
+  presentation.leafNodeGraphNode =
+    /** @type {Presentation["leafNodeGraphNode"]} */ (args) => {
+      const out = e(
+        "button",
+        { textContent: args.text, title: args.text },
+        {
+          styles_: [
+            ss(uniq("leaf_node_graph_node"), {
+              "": (s) => {
+                s.position = "absolute";
+                s.transform = "translate(-50%, -50%)";
+                s.maxWidth = "12rem";
+                s.overflow = "hidden";
+                s.textOverflow = "ellipsis";
+                s.whiteSpace = "nowrap";
+                s.padding = `${varPSmall} ${varP05}`;
+                s.border = `${varLThin} dashed ${varCForegroundFade}`;
+                s.borderRadius = varP05;
+                s.backgroundColor = varCBackground;
+              },
+              ":hover": (s) => {
+                s.backgroundColor = varCButtonHover;
+              },
+              ".expanded": (s) => {
+                s.borderStyle = "solid";
+              },
+              ".pivot": (s) => {
+                s.fontWeight = "bold";
+                s.borderColor = varCModified;
+              },
+              [`.${classStateSelected}`]: (s) => {
+                s.color = varCSelected;
+                s.borderColor = varCSelected;
+              },
+            }),
+          ],
+        },
+      );
+      out.style.left = `${args.x * 100}%`;
+      out.style.top = `${args.y * 100}%`;
+      if (args.pivot) {
+        out.classList.add("pivot");
+      }
+      if (args.expanded) {
+        out.classList.add("expanded");
+      }
+      return { root: out };
+    };
+
   ///////////////////////////////////////////////////////////////////////////////
   // xx Components, styles: page, node edit
   presentation.contPageNodeEdit =
@@ -6665,6 +7800,7 @@
       text: "Set as current list",
     });
     listGroupChildren.push(buttonSetCurrentList);
+    const buttonBookmark = modalMenuButton({ text: "Bookmark" });
     const out = newContModal({
       minimal: true,
       title: "Node",
@@ -6689,6 +7825,7 @@
               url: args.nodeLink,
               extraStyles: [],
             }),
+            buttonBookmark,
             e(
               "div",
               {},
@@ -6715,6 +7852,7 @@
       buttonSetList: buttonSetCurrentList,
       buttonAddToList: buttonAddToList,
       buttonCopy: buttonCopy,
+      buttonBookmark: buttonBookmark,
     };
   };
 
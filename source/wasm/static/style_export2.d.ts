@@ -11,6 +11,10 @@ declare type Orientation =
 declare type OrientationType2 = "grid" | "flex";
 declare type TransAlign = "start" | "middle" | "end";
 declare type TextSizeMode = "wrap" | "ellipsize";
+declare type ChartType = "bar" | "line" | "pie" | "histogram";
+declare type CalendarPeriod = "month" | "week";
+declare type TimelineGroup = "day" | "week" | "month" | "year";
+declare type WidgetEditInput = "text" | "number" | "date";
 // Merge, separated to avoid issues with rust generation
 declare interface Window {
   sunwetPresentation: Presentation;
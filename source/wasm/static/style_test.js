@@ -10,6 +10,9 @@
     menuPageButtons.appendChild(
       presentation.leafMenuPageButtonUnoffline({}).root,
     );
+    menuPageButtons.appendChild(
+      presentation.leafMenuPageButtonSavePreset({}).root,
+    );
     const stagingMenu_ = presentation.contMenuBody({
       pageButtons: menuPageButtons,
       children: [